@@ -1,16 +1,43 @@
 use super::{graph::*, types::*};
 use crate::{
-    analysis::core::alias_analysis::default::{MopAAFact, MopAAResultMap},
+    analysis::core::alias_analysis::{
+        default::{MopAAFact, MopAAResultMap},
+        AAPlace, DEREF_FIELD,
+    },
     def_id::*,
     rap_debug,
 };
+use rustc_abi::FieldIdx;
+use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
     mir::{Operand, Place, ProjectionElem, TerminatorKind},
-    ty,
+    ty::{self, EarlyBinder, GenericArgsRef, Instance, InstanceKind, TypeVisitableExt, TypingEnv},
 };
+use rustc_span::source_map::Spanned;
 use std::collections::HashSet;
 
+/// How the arguments at a call site map to the parameters of the resolved callee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgLayout {
+    /// The arguments are passed as they are.
+    Direct,
+    /// A `Fn*` trait call on a closure: the closure is followed by the untupled arguments.
+    Closure,
+    /// A `Fn*` trait call on a function item: only the untupled arguments are passed.
+    FnItem,
+    /// A closure called through a function pointer: the closure body takes its environment first.
+    ClosurePtr,
+}
+
+/// The function body a call is resolved to.
+#[derive(Debug, Clone, Copy)]
+pub struct CallTarget<'tcx> {
+    pub def_id: DefId,
+    pub args: GenericArgsRef<'tcx>,
+    pub layout: ArgLayout,
+}
+
 impl<'tcx> MopGraph<'tcx> {
     /* alias analysis for a single block */
     pub fn alias_bb(&mut self, bb_index: usize) {
@@ -48,7 +75,7 @@ impl<'tcx> MopGraph<'tcx> {
         let cur_block = self.blocks[bb_index].clone();
         for call in cur_block.calls {
             if let TerminatorKind::Call {
-                func: Operand::Constant(ref constant),
+                ref func,
                 ref args,
                 ref destination,
                 target: _,
@@ -57,6 +84,9 @@ impl<'tcx> MopGraph<'tcx> {
                 fn_span: _,
             } = call.kind
             {
+                let Some(target) = self.resolve_call(func) else {
+                    continue;
+                };
                 let lv = self.projection(false, *destination);
                 let mut merge_vec = Vec::new();
                 merge_vec.push(lv);
@@ -64,83 +94,231 @@ impl<'tcx> MopGraph<'tcx> {
                 if self.values[lv].may_drop {
                     may_drop_flag += 1;
                 }
-                for arg in args {
-                    match arg.node {
-                        Operand::Copy(ref p) => {
-                            let rv = self.projection(true, *p);
-                            merge_vec.push(rv);
-                            if self.values[rv].may_drop {
-                                may_drop_flag += 1;
-                            }
-                        }
-                        Operand::Move(ref p) => {
-                            let rv = self.projection(true, *p);
-                            merge_vec.push(rv);
-                            if self.values[rv].may_drop {
-                                may_drop_flag += 1;
-                            }
-                        }
-                        Operand::Constant(_) => {
-                            merge_vec.push(0);
-                        }
+                for rv in self.call_args(args, target.layout) {
+                    if rv != 0 && self.values[rv].may_drop {
+                        may_drop_flag += 1;
                     }
+                    merge_vec.push(rv);
                 }
-                if let &ty::FnDef(target_id, _) = constant.const_.ty().kind() {
-                    //if may_drop_flag > 1 || Self::should_check(target_id.clone()) == false {
-                    if may_drop_flag > 0 {
-                        if self.tcx.is_mir_available(target_id) {
-                            rap_debug!("target_id {:?}", target_id);
-                            if fn_map.contains_key(&target_id) {
-                                let assignments = fn_map.get(&target_id).unwrap();
-                                for assign in assignments.aliases().iter() {
-                                    if !assign.valuable() {
-                                        continue;
-                                    }
-                                    self.merge(assign, &merge_vec);
-                                }
-                            } else {
-                                /* Fixed-point iteration: this is not perfect */
-                                if recursion_set.contains(&target_id) {
-                                    continue;
+                let target_id = target.def_id;
+                //if may_drop_flag > 1 || Self::should_check(target_id.clone()) == false {
+                if may_drop_flag > 0 {
+                    if self.tcx.is_mir_available(target_id) {
+                        rap_debug!("target_id {:?}", target_id);
+                        // A generic local callee is analyzed with the arguments of this call
+                        // site, so that the trait calls in its body can be resolved.
+                        let instantiate = target_id.is_local()
+                            && self
+                                .tcx
+                                .generics_of(target_id)
+                                .requires_monomorphization(self.tcx)
+                            && !target.args.has_param();
+                        let key = (target_id, target.args);
+                        let cached = if instantiate {
+                            self.instance_map.borrow().get(&key).cloned()
+                        } else {
+                            fn_map.get(&target_id).cloned()
+                        };
+                        let ret_alias = match cached {
+                            Some(ret_alias) => ret_alias,
+                            // Local functions are summarized by `solve_call_graph` before their
+                            // callers, so a recursive call only reaches here for instantiated
+                            // generics and non-local callees. Use the summary computed so far.
+                            None if recursion_set.contains(&target_id) => {
+                                match fn_map.get(&target_id) {
+                                    Some(ret_alias) => ret_alias.clone(),
+                                    None => continue,
                                 }
+                            }
+                            None => {
                                 recursion_set.insert(target_id);
                                 let mut mop_graph = MopGraph::new(self.tcx, target_id);
+                                mop_graph.instance_map = self.instance_map.clone();
+                                if instantiate {
+                                    mop_graph.args = target.args;
+                                }
                                 mop_graph.solve_scc();
                                 mop_graph.check(0, fn_map, recursion_set);
                                 let ret_alias = mop_graph.ret_alias.clone();
-                                for assign in ret_alias.aliases().iter() {
-                                    if !assign.valuable() {
-                                        continue;
-                                    }
-                                    self.merge(assign, &merge_vec);
+                                if instantiate {
+                                    self.instance_map
+                                        .borrow_mut()
+                                        .insert(key, ret_alias.clone());
+                                } else {
+                                    fn_map.insert(target_id, ret_alias.clone());
                                 }
-                                fn_map.insert(target_id, ret_alias);
                                 recursion_set.remove(&target_id);
+                                ret_alias
                             }
-                        } else if self.values[lv].may_drop {
-                            if target_id == call_mut() {
+                        };
+                        for assign in ret_alias.aliases().iter() {
+                            if !assign.valuable() {
                                 continue;
                             }
+                            self.merge(assign, &merge_vec);
+                        }
+                    } else if self.values[lv].may_drop {
+                        if target_id == call_mut() {
+                            continue;
+                        }
 
-                            let mut right_set = Vec::new();
-                            for rv in &merge_vec {
-                                if self.values[*rv].may_drop
-                                    && lv != *rv
-                                    && self.values[lv].is_ptr()
-                                {
-                                    right_set.push(*rv);
-                                }
-                            }
-                            if right_set.len() == 1 {
-                                self.merge_alias(lv, right_set[0], 0);
+                        let mut right_set = Vec::new();
+                        for rv in &merge_vec {
+                            if self.values[*rv].may_drop && lv != *rv && self.values[lv].is_ptr() {
+                                right_set.push(*rv);
                             }
                         }
+                        if right_set.len() == 1 {
+                            self.merge_alias(lv, right_set[0], 0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolve the callee of a call to the function body it executes. Trait methods and `Fn*`
+    /// trait calls are resolved under the typing environment of the caller, and calls through
+    /// function pointers are resolved to the function item or closure the pointer is created
+    /// from. If the callee cannot be resolved, the declaration being called is returned as is.
+    pub fn resolve_call(&self, func: &Operand<'tcx>) -> Option<CallTarget<'tcx>> {
+        match func {
+            Operand::Constant(constant) => match *constant.const_.ty().kind() {
+                ty::FnDef(def_id, args) => Some(self.resolve_fn(def_id, args)),
+                _ => None,
+            },
+            Operand::Copy(place) | Operand::Move(place) => {
+                let (def_id, args) = *self.fn_ptrs.get(&place.as_local()?.as_usize())?;
+                if self.tcx.is_closure_like(def_id) {
+                    Some(CallTarget {
+                        def_id,
+                        args: self.instantiate(args),
+                        layout: ArgLayout::ClosurePtr,
+                    })
+                } else {
+                    Some(self.resolve_fn(def_id, args))
+                }
+            }
+        }
+    }
+
+    fn resolve_fn(&self, def_id: DefId, args: GenericArgsRef<'tcx>) -> CallTarget<'tcx> {
+        let args = self.instantiate(args);
+        let unresolved = CallTarget {
+            def_id,
+            args,
+            layout: ArgLayout::Direct,
+        };
+        let is_fn_trait_call = self
+            .tcx
+            .trait_of_assoc(def_id)
+            .is_some_and(|trait_id| self.tcx.fn_trait_kind_from_def_id(trait_id).is_some());
+        let Ok(Some(instance)) = Instance::try_resolve(self.tcx, self.typing_env(), def_id, args)
+        else {
+            return unresolved;
+        };
+        match instance.def {
+            InstanceKind::Item(item) => CallTarget {
+                def_id: item,
+                args: instance.args,
+                layout: if is_fn_trait_call && self.tcx.is_closure_like(item) {
+                    ArgLayout::Closure
+                } else {
+                    ArgLayout::Direct
+                },
+            },
+            // `FnOnce::call_once` on a closure implementing `FnMut`, or a `Fn*` trait call on a
+            // function item.
+            InstanceKind::ClosureOnceShim { .. } | InstanceKind::FnPtrShim(..) => {
+                match *instance.args.type_at(0).kind() {
+                    ty::Closure(closure, closure_args) => CallTarget {
+                        def_id: closure,
+                        args: closure_args,
+                        layout: ArgLayout::Closure,
+                    },
+                    ty::FnDef(item, item_args) => CallTarget {
+                        layout: ArgLayout::FnItem,
+                        ..self.resolve_fn(item, item_args)
+                    },
+                    _ => unresolved,
+                }
+            }
+            _ => unresolved,
+        }
+    }
+
+    // The value ids of the arguments as seen by the parameters of the callee; constants are 0.
+    fn call_args(&mut self, args: &[Spanned<Operand<'tcx>>], layout: ArgLayout) -> Vec<usize> {
+        let mut ids = Vec::new();
+        if layout == ArgLayout::ClosurePtr {
+            ids.push(0);
+        }
+        let untuple = matches!(layout, ArgLayout::Closure | ArgLayout::FnItem);
+        for (i, arg) in args.iter().enumerate() {
+            if untuple && i == 0 && layout == ArgLayout::FnItem {
+                continue;
+            }
+            if untuple && i == 1 {
+                // The elements are taken from where the tuple is built, so that the untupled
+                // arguments stay apart.
+                let elems = arg
+                    .node
+                    .place()
+                    .and_then(|place| place.as_local())
+                    .and_then(|local| self.tuples.get(&local.as_usize()).cloned());
+                if let Some(elems) = elems {
+                    for elem in elems {
+                        let id = match elem {
+                            Some(place) => self.projection(true, place),
+                            None => 0,
+                        };
+                        ids.push(id);
                     }
+                    continue;
+                }
+                let body = self.tcx.optimized_mir(self.def_id);
+                if let ty::Tuple(tys) = arg.node.ty(body, self.tcx).kind() {
+                    for (j, ty) in tys.iter().enumerate() {
+                        let id = match arg.node.place() {
+                            Some(place) => {
+                                let field =
+                                    self.tcx.mk_place_field(place, FieldIdx::from_usize(j), ty);
+                                self.projection(true, field)
+                            }
+                            None => 0,
+                        };
+                        ids.push(id);
+                    }
+                    continue;
                 }
             }
+            let id = match arg.node.place() {
+                Some(place) => self.projection(true, place),
+                None => 0,
+            };
+            ids.push(id);
+        }
+        ids
+    }
+
+    fn typing_env(&self) -> TypingEnv<'tcx> {
+        if self.args.has_param() {
+            TypingEnv::post_analysis(self.tcx, self.def_id)
+        } else {
+            TypingEnv::fully_monomorphized()
         }
     }
 
+    fn instantiate(&self, args: GenericArgsRef<'tcx>) -> GenericArgsRef<'tcx> {
+        self.tcx
+            .try_instantiate_and_normalize_erasing_regions(
+                self.args,
+                self.typing_env(),
+                EarlyBinder::bind(args),
+            )
+            .unwrap_or(args)
+    }
+
     /*
      * This is the function for field sensitivity
      * If the projection is a deref, we directly return its head alias or alias[0].
@@ -150,12 +328,19 @@ impl<'tcx> MopGraph<'tcx> {
     pub fn projection(&mut self, is_right: bool, place: Place<'tcx>) -> usize {
         let mut local = place.local.as_usize();
         let mut proj_id: usize = local;
-        for proj in place.projection {
+        let mut variant = None;
+        for (base, proj) in place.iter_projections() {
             let new_id = self.values.len();
             match proj {
                 ProjectionElem::Deref => {
-                    proj_id = self.values[proj_id].index;
+                    // A multi-level pointer leads to its pointee; a single-level pointer is
+                    // aliased with the object it points to.
+                    proj_id = match self.values[proj_id].fields.get(&DEREF_FIELD) {
+                        Some(&pointee) => pointee,
+                        None => self.values[proj_id].index,
+                    };
                 }
+                ProjectionElem::Downcast(_, idx) => variant = Some(idx),
                 /*
                  * Objective: 2 = 1.0; 0 = 2.0; => 0 = 1.0.0
                  */
@@ -164,7 +349,13 @@ impl<'tcx> MopGraph<'tcx> {
                         proj_id = self.values[proj_id].index;
                         local = self.values[proj_id].local;
                     }
-                    let field_idx = field.as_usize();
+                    let field_idx = match variant.take() {
+                        Some(variant) => {
+                            let body = self.tcx.optimized_mir(self.def_id);
+                            variant_field(self.tcx, base.ty(body, self.tcx).ty, variant, field)
+                        }
+                        None => field.as_usize(),
+                    };
                     if let std::collections::hash_map::Entry::Vacant(e) =
                         self.values[proj_id].fields.entry(field_idx)
                     {
@@ -180,6 +371,7 @@ impl<'tcx> MopGraph<'tcx> {
                         self.values.push(node);
                     }
                     proj_id = *self.values[proj_id].fields.get(&field_idx).unwrap();
+                    self.add_pointees(proj_id, ty);
                 }
                 _ => {}
             }
@@ -271,7 +463,14 @@ impl<'tcx> MopGraph<'tcx> {
             }
             rv = *self.values[rv].fields.get(index).unwrap();
         }
-        self.merge_alias(lv, rv, 0);
+        // A callee that does not see the pointer levels of its arguments, e.g., a generic one,
+        // relates a multi-level pointer to its pointee.
+        match self.values[lv].fields.get(&DEREF_FIELD) {
+            Some(&pointee) if !self.values[rv].fields.contains_key(&DEREF_FIELD) => {
+                self.merge_alias(pointee, rv, 0)
+            }
+            _ => self.merge_alias(lv, rv, 0),
+        }
     }
 
     //merge the result of current path to the final result.
@@ -285,6 +484,7 @@ impl<'tcx> MopGraph<'tcx> {
                     return;
                 }
                 let f_node: Vec<usize> = results_nodes.iter().map(|v| v.father).collect();
+                let parents = self.parents();
 
                 for idx in 1..self.values.len() {
                     if !self.union_is_same(idx, node.index) {
@@ -338,11 +538,27 @@ impl<'tcx> MopGraph<'tcx> {
                             right_node.may_drop,
                             right_node.need_drop,
                         );
-                        new_alias.fact.lhs_fields = self.get_field_seq(left_node);
-                        new_alias.fact.rhs_fields = self.get_field_seq(right_node);
+                        new_alias.fact.lhs_fields = self.get_field_seq(&parents, left_node);
+                        new_alias.fact.rhs_fields = self.get_field_seq(&parents, right_node);
+                        // A pointee lies at another level than its pointer, so the replaced node
+                        // keeps the path through the pointee.
+                        let path = self.get_field_seq(&parents, &results_nodes[idx]);
+                        if replace.is_some() && path.contains(&DEREF_FIELD) {
+                            match results_nodes[idx].local {
+                                0 => new_alias.fact.lhs_fields.extend(path),
+                                _ => new_alias.fact.rhs_fields.extend(path),
+                            }
+                        }
                         if new_alias.lhs_no() == 0 && new_alias.rhs_no() == 0 {
                             return;
                         }
+                        // A place is not an alias of itself or of its own fields.
+                        if new_alias.lhs_no() == new_alias.rhs_no()
+                            && (new_alias.lhs_fields().starts_with(new_alias.rhs_fields())
+                                || new_alias.rhs_fields().starts_with(new_alias.lhs_fields()))
+                        {
+                            continue;
+                        }
                         self.ret_alias.add_alias(new_alias);
                     }
                 }
@@ -350,13 +566,50 @@ impl<'tcx> MopGraph<'tcx> {
         }
     }
 
-    pub fn get_field_seq(&self, value: &ValueNode) -> Vec<usize> {
+    /// Record the alias relations among the values of the current path after visiting a block,
+    /// if `record_states` is set.
+    pub fn record_state(&mut self, bb_index: usize) {
+        if !self.record_states {
+            return;
+        }
+        let parents = self.parents();
+        let mut classes: FxHashMap<usize, Vec<AAPlace>> = FxHashMap::default();
+        for index in 0..self.values.len() {
+            let fields = self.get_field_seq(&parents, &self.values[index]);
+            let place = AAPlace::new(self.values[index].local, fields);
+            let root = self.union_find(index);
+            classes.entry(root).or_default().push(place);
+        }
+        let state = self.alias_states.entry(bb_index).or_default();
+        for class in classes.into_values() {
+            state.add_class(class);
+        }
+    }
+
+    /// Map each field node to its parent node and its field id.
+    pub fn parents(&self) -> FxHashMap<usize, (usize, usize)> {
+        let mut parents = FxHashMap::default();
+        for value in &self.values {
+            for (field, child) in &value.fields {
+                parents.insert(*child, (value.index, *field));
+            }
+        }
+        parents
+    }
+
+    /// The field ids from the local of the value down to the value.
+    pub fn get_field_seq(
+        &self,
+        parents: &FxHashMap<usize, (usize, usize)>,
+        value: &ValueNode,
+    ) -> Vec<usize> {
         let mut field_id_seq = vec![];
-        let mut node_ref = value;
-        while node_ref.field_id != usize::MAX {
-            field_id_seq.push(node_ref.field_id);
-            node_ref = &self.values[value.father];
+        let mut node = value.index;
+        while let Some((parent, field)) = parents.get(&node) {
+            field_id_seq.push(*field);
+            node = *parent;
         }
+        field_id_seq.reverse();
         field_id_seq
     }
 
@@ -0,0 +1,140 @@
+use super::{graph::MopGraph, *};
+use crate::{analysis::core::callgraph::default::sccs, rap_warn};
+use rustc_middle::mir::TerminatorKind;
+use std::collections::HashMap;
+
+/// Number of rounds a recursive call-graph SCC is iterated before its summaries are widened.
+pub const WIDENING_ROUNDS: usize = 5;
+/// Number of rounds after which the iteration of a recursive call-graph SCC is given up.
+/// Widening makes the summaries converge well before this limit is reached.
+pub const FIXPOINT_LIMIT: usize = 20;
+/// Length of the field sequences kept in the facts of a widened summary.
+pub const WIDENING_DEPTH: usize = 1;
+
+impl MopAAResult {
+    /// Add the facts of `other` to this summary. Return true if the summary has changed.
+    pub fn join(&mut self, other: &MopAAResult) -> bool {
+        let len = self.len();
+        self.alias_set.extend(other.aliases().iter().cloned());
+        self.len() != len
+    }
+
+    /// Truncate the field sequences of all facts to `depth`. An alias between two values implies
+    /// the alias between their fields, so the widened summary over-approximates the original one,
+    /// while the number of facts it may contain is bounded by the number of arguments.
+    /// Return true if the summary has changed.
+    pub fn widen(&mut self, depth: usize) -> bool {
+        let changed = self
+            .alias_set
+            .iter()
+            .any(|alias| alias.lhs_fields().len() > depth || alias.rhs_fields().len() > depth);
+        let alias_set = std::mem::take(&mut self.alias_set);
+        self.alias_set = alias_set
+            .into_iter()
+            .map(|mut alias| {
+                alias.fact.lhs_fields.truncate(depth);
+                alias.fact.rhs_fields.truncate(depth);
+                alias
+            })
+            .collect();
+        changed
+    }
+}
+
+impl<'tcx> AliasAnalyzer<'tcx> {
+    /// Compute the alias summaries of all local functions. Functions are visited by the strongly
+    /// connected components of the call graph in reverse topological order, so that the summaries
+    /// of the callees are available when a caller is analyzed.
+    pub fn solve_call_graph(&mut self) {
+        let fns: Vec<DefId> = self
+            .tcx
+            .mir_keys(())
+            .iter()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| self.is_analyzable(*def_id))
+            .collect();
+        let name = self.name();
+        let mut callees: HashMap<DefId, Vec<DefId>> = HashMap::new();
+        for def_id in fns.iter() {
+            let fn_callees = guarded(name, *def_id, || self.local_callees(*def_id));
+            callees.insert(*def_id, fn_callees.unwrap_or_default());
+        }
+        for scc in sccs(&fns, |def_id| callees[&def_id].clone()) {
+            let recursive = scc.len() > 1 || callees[&scc[0]].contains(&scc[0]);
+            if recursive {
+                self.solve_recursive_scc(&scc);
+            } else {
+                self.query_mop(scc[0]);
+            }
+        }
+    }
+
+    fn is_analyzable(&self, def_id: DefId) -> bool {
+        /* filter const mir */
+        self.tcx
+            .hir_body_const_context(def_id.expect_local())
+            .is_none()
+            && self.tcx.is_mir_available(def_id)
+    }
+
+    /// The local functions called by `def_id`, resolved in the same way as `alias_bbcall` does.
+    /// The graph built for `def_id` is kept for its summary.
+    fn local_callees(&mut self, def_id: DefId) -> Vec<DefId> {
+        let mop_graph = MopGraph::new(self.tcx, def_id);
+        let mut callees = Vec::new();
+        for block in &mop_graph.blocks {
+            for call in &block.calls {
+                if let TerminatorKind::Call { ref func, .. } = call.kind {
+                    if let Some(target) = mop_graph.resolve_call(func) {
+                        if target.def_id.is_local() && !callees.contains(&target.def_id) {
+                            callees.push(target.def_id);
+                        }
+                    }
+                }
+            }
+        }
+        self.graphs.insert(def_id, mop_graph);
+        callees
+    }
+
+    /// Iterate the summaries of a recursive SCC until they are stable. Every round analyzes all
+    /// functions of the SCC against the summaries of the previous round, starting from empty
+    /// summaries. The summaries are joined across rounds, and they are widened after `WIDENING_ROUNDS` rounds.
+    fn solve_recursive_scc(&mut self, scc: &[DefId]) {
+        rap_debug!(
+            "Solve recursive functions: {:?}",
+            scc.iter()
+                .map(|def_id| get_fn_name(self.tcx, *def_id))
+                .collect::<Vec<_>>()
+        );
+        for def_id in scc {
+            let arg_size = self.tcx.optimized_mir(*def_id).arg_count;
+            self.fn_map.insert(*def_id, MopAAResult::new(arg_size));
+        }
+        for round in 1..=FIXPOINT_LIMIT {
+            let widen = round > WIDENING_ROUNDS;
+            let mut changed = false;
+            for def_id in scc {
+                let mut ret_alias = self.summarize(*def_id);
+                ret_alias.sort_alias_index();
+                let summary = self.fn_map.get_mut(def_id).unwrap();
+                if widen {
+                    changed |= summary.widen(WIDENING_DEPTH);
+                    ret_alias.widen(WIDENING_DEPTH);
+                }
+                changed |= summary.join(&ret_alias);
+            }
+            if !changed {
+                rap_debug!("Fixed point reached after {} rounds.", round);
+                return;
+            }
+        }
+        rap_warn!(
+            "Alias summaries of {:?} did not reach a fixed point after {} rounds.",
+            scc.iter()
+                .map(|def_id| get_fn_name(self.tcx, *def_id))
+                .collect::<Vec<_>>(),
+            FIXPOINT_LIMIT
+        );
+    }
+}
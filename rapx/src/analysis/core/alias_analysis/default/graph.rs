@@ -1,18 +1,24 @@
 use crate::{
-    analysis::core::alias_analysis::default::{types::*, MopAAResult},
+    analysis::core::{
+        alias_analysis::{
+            default::{types::*, MopAAResult, MopInstanceMap},
+            AAState,
+        },
+        range_analysis::feasibility::{prune_infeasible_paths, PathChecker},
+    },
     rap_debug,
     utils::source::*,
 };
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::{
     mir::{
-        BasicBlock, Const, Operand, Place, Rvalue, StatementKind, Terminator, TerminatorKind,
-        UnwindAction,
+        AggregateKind, BasicBlock, CastKind, Const, Operand, Place, Rvalue, StatementKind,
+        Terminator, TerminatorKind, UnwindAction,
     },
-    ty::{TyCtxt, TypingEnv},
+    ty::{self, adjustment::PointerCoercion, GenericArgs, GenericArgsRef, Ty, TyCtxt, TypingEnv},
 };
 use rustc_span::{def_id::DefId, Span};
-use std::{cell::RefCell, cmp::min, vec::Vec};
+use std::{cell::RefCell, cmp::min, rc::Rc, vec::Vec};
 
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum AssignType {
@@ -160,6 +166,23 @@ pub struct MopGraph<'tcx> {
     >,
     pub disc_map: FxHashMap<usize, usize>,
     pub terms: Vec<TerminatorKind<'tcx>>,
+    // the generic arguments the body is instantiated with; they are the identity unless the graph
+    // is built for a monomorphized call site.
+    pub args: GenericArgsRef<'tcx>,
+    // the summaries of generic callees analyzed with the arguments of their call sites, shared
+    // with the graphs of the callees.
+    pub instance_map: Rc<RefCell<MopInstanceMap<'tcx>>>,
+    // the function items or closures that function pointer locals are created from.
+    pub fn_ptrs: FxHashMap<usize, (DefId, GenericArgsRef<'tcx>)>,
+    // the places the elements of tuple locals are built from; `None` for constants.
+    pub tuples: FxHashMap<usize, Vec<Option<Place<'tcx>>>>,
+    // whether the alias relations after each visited block are recorded.
+    pub record_states: bool,
+    // the alias relations after each visited block, joined over all paths reaching the block.
+    pub alias_states: FxHashMap<usize, AAState>,
+    // the blocks on the path being visited, used to skip the branches proved infeasible.
+    pub path: Vec<usize>,
+    pub path_checker: Option<PathChecker<'tcx>>,
 }
 
 impl<'tcx> MopGraph<'tcx> {
@@ -192,6 +215,8 @@ impl<'tcx> MopGraph<'tcx> {
         let mut scc_indices = Vec::<usize>::new();
         let mut disc_map = FxHashMap::default();
         let mut terms = Vec::new();
+        let mut fn_ptrs = FxHashMap::default();
+        let mut tuples = FxHashMap::default();
 
         // handle each basicblock
         for i in 0..basicblocks.len() {
@@ -208,6 +233,29 @@ impl<'tcx> MopGraph<'tcx> {
                     let lv_local = assign.0.local.as_usize(); // assign.0 is a Place
                     let lv = assign.0;
                     cur_bb.modified_value.insert(lv_local);
+                    // Record the callee of a function pointer for indirect calls.
+                    if let Rvalue::Cast(
+                        CastKind::PointerCoercion(
+                            PointerCoercion::ReifyFnPointer | PointerCoercion::ClosureFnPointer(_),
+                            _,
+                        ),
+                        ref x,
+                        _,
+                    ) = assign.1
+                    {
+                        if let ty::FnDef(def_id, args) | ty::Closure(def_id, args) =
+                            *x.ty(locals, tcx).kind()
+                        {
+                            fn_ptrs.insert(lv_local, (def_id, args));
+                        }
+                    }
+                    // Record the elements of tuples, which may be the arguments of closure calls.
+                    if let Rvalue::Aggregate(ref agg_kind, ref x) = assign.1 {
+                        if matches!(**agg_kind, AggregateKind::Tuple) && lv.projection.is_empty() {
+                            let elems = x.iter().map(|each_x| each_x.place()).collect();
+                            tuples.insert(lv_local, elems);
+                        }
+                    }
                     match assign.1 {
                         // assign.1 is a Rvalue
                         Rvalue::Use(ref x) => {
@@ -220,6 +268,9 @@ impl<'tcx> MopGraph<'tcx> {
                                             Assignment::new(lv, rv, AssignType::Copy, span);
                                         cur_bb.assignments.push(assign);
                                     }
+                                    if let Some(target) = fn_ptrs.get(&rv_local).copied() {
+                                        fn_ptrs.insert(lv_local, target);
+                                    }
                                 }
                                 Operand::Move(ref p) => {
                                     let rv_local = p.local.as_usize();
@@ -229,6 +280,9 @@ impl<'tcx> MopGraph<'tcx> {
                                             Assignment::new(lv, rv, AssignType::Move, span);
                                         cur_bb.assignments.push(assign);
                                     }
+                                    if let Some(target) = fn_ptrs.get(&rv_local).copied() {
+                                        fn_ptrs.insert(lv_local, target);
+                                    }
                                 }
                                 Operand::Constant(ref constant) => {
                                     /* We should check the correctness due to the update of rustc
@@ -253,9 +307,21 @@ impl<'tcx> MopGraph<'tcx> {
                                 }
                             }
                         }
-                        Rvalue::Ref(_, _, ref p)
-                        | Rvalue::RawPtr(_, ref p)
-                        | Rvalue::CopyForDeref(ref p) => {
+                        Rvalue::Ref(_, _, ref p) | Rvalue::RawPtr(_, ref p) => {
+                            let rv_local = p.local.as_usize();
+                            if values[lv_local].may_drop && values[rv_local].may_drop {
+                                let rv = *p;
+                                // A multi-level pointer is not aliased with the object it points
+                                // to but keeps the object as its pointee, i.e., `*lv = rv`.
+                                let lv = match multi_level_pointee(tcx, lv.ty(locals, tcx).ty) {
+                                    Some(_) => tcx.mk_place_deref(lv),
+                                    None => lv,
+                                };
+                                let assign = Assignment::new(lv, rv, AssignType::Copy, span);
+                                cur_bb.assignments.push(assign);
+                            }
+                        }
+                        Rvalue::CopyForDeref(ref p) => {
                             let rv_local = p.local.as_usize();
                             if values[lv_local].may_drop && values[rv_local].may_drop {
                                 let rv = *p;
@@ -266,8 +332,9 @@ impl<'tcx> MopGraph<'tcx> {
                         Rvalue::ShallowInitBox(ref x, _) => {
                             /*
                              * Original ShllowInitBox is a two-level pointer: lvl0 -> lvl1 -> lvl2
-                             * Since our alias analysis does not consider multi-level pointer,
-                             * We simplify it as: lvl0
+                             * The derefs of a box are lowered to derefs of its NonNull field, so
+                             * lvl1 is modelled as field 0 of lvl0, and lvl2 as the pointee of that
+                             * NonNull if lvl2 is a pointer itself.
                              */
                             if !values[lv_local].fields.contains_key(&0) {
                                 let mut lvl0 = ValueNode::new(values.len(), lv_local, false, true);
@@ -386,7 +453,8 @@ impl<'tcx> MopGraph<'tcx> {
                     }
                     cur_bb.calls.push(terminator.clone());
                 }
-                TerminatorKind::TailCall { .. } => todo!(),
+                // a tail call leaves the function like a return.
+                TerminatorKind::TailCall { .. } => {}
                 TerminatorKind::Assert {
                     cond: _,
                     expected: _,
@@ -442,7 +510,7 @@ impl<'tcx> MopGraph<'tcx> {
             blocks.push(cur_bb);
         }
 
-        MopGraph {
+        let mut graph = MopGraph {
             def_id,
             tcx,
             span: body.span,
@@ -457,7 +525,50 @@ impl<'tcx> MopGraph<'tcx> {
             child_scc: FxHashMap::default(),
             disc_map,
             terms,
+            args: GenericArgs::identity_for_item(tcx, def_id),
+            instance_map: Rc::default(),
+            fn_ptrs,
+            tuples,
+            record_states: false,
+            alias_states: FxHashMap::default(),
+            path: Vec::new(),
+            path_checker: prune_infeasible_paths().then(|| PathChecker::new(tcx, def_id)),
+        };
+        for (local, local_decl) in locals.iter_enumerated() {
+            graph.add_pointees(local.as_usize(), local_decl.ty);
         }
+        graph
+    }
+
+    /// Create the pointee nodes of a multi-level pointer as the children of the pointer node; see
+    /// `pointee_chain`.
+    pub fn add_pointees(&mut self, node: usize, ty: Ty<'tcx>) {
+        let mut node = node;
+        for (field_id, ty) in pointee_chain(self.tcx, ty) {
+            node = self.add_child(node, field_id, ty);
+        }
+    }
+
+    fn add_child(&mut self, node: usize, field_id: usize, ty: Ty<'tcx>) -> usize {
+        if let Some(&child) = self.values[node].fields.get(&field_id) {
+            return child;
+        }
+        let ty_env = TypingEnv::post_analysis(self.tcx, self.def_id);
+        let need_drop = ty.needs_drop(self.tcx, ty_env);
+        let may_drop = !is_not_drop(self.tcx, ty);
+        let mut child = ValueNode::new(
+            self.values.len(),
+            self.values[node].local,
+            need_drop,
+            need_drop || may_drop,
+        );
+        child.kind = kind(ty);
+        child.field_id = field_id;
+        let index = child.index;
+        self.values[node].fields.insert(field_id, index);
+        self.alias_set.push(index);
+        self.values.push(child);
+        index
     }
 
     pub fn tarjan(
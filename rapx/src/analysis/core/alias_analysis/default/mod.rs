@@ -1,15 +1,24 @@
 pub mod alias;
+pub mod fixpoint;
 pub mod graph;
 pub mod mop;
 pub mod types;
 
-use super::{AAFact, AAResult, AAResultMap, AliasAnalysis};
-use crate::{analysis::Analysis, def_id::*, rap_debug, rap_trace, utils::source::*};
+use super::{AAFact, AAPlace, AAResult, AAResultMap, AAState, AliasAnalysis};
+use crate::{
+    analysis::Analysis,
+    def_id::*,
+    rap_debug, rap_trace,
+    utils::{boundary::guarded, source::*},
+};
 use graph::MopGraph;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
-use rustc_middle::ty::TyCtxt;
-use std::{collections::HashSet, convert::From, fmt};
+use rustc_middle::{
+    mir::{Location, Place},
+    ty::{GenericArgsRef, TyCtxt},
+};
+use std::{cell::RefCell, collections::HashSet, convert::From, fmt, rc::Rc};
 
 pub const VISIT_LIMIT: usize = 1000;
 
@@ -143,10 +152,22 @@ impl MopAAResult {
 
 //struct to cache the results for analyzed functions.
 pub type MopAAResultMap = FxHashMap<DefId, MopAAResult>;
+// the results of generic functions analyzed with the generic arguments of their call sites.
+pub type MopInstanceMap<'tcx> = FxHashMap<(DefId, GenericArgsRef<'tcx>), MopAAResult>;
 
 pub struct AliasAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub fn_map: FxHashMap<DefId, MopAAResult>,
+    pub instance_map: Rc<RefCell<MopInstanceMap<'tcx>>>,
+    /// Whether the alias relations after each basic block of the local functions are recorded
+    /// for `get_alias_state` and the queries built on it. They are recorded by default, so any
+    /// consumer of the `AliasAnalysis` queries gets them; a client only using the summaries may
+    /// turn it off before `run` to save the cost.
+    pub record_states: bool,
+    // the alias relations after each basic block of the local functions.
+    pub alias_states: FxHashMap<DefId, FxHashMap<usize, AAState>>,
+    // the graphs built to find the callees of the local functions, kept until they are summarized.
+    graphs: FxHashMap<DefId, MopGraph<'tcx>>,
 }
 
 impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
@@ -156,10 +177,7 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
 
     fn run(&mut self) {
         rap_debug!("Start alias analysis via MoP.");
-        let mir_keys = self.tcx.mir_keys(());
-        for local_def_id in mir_keys {
-            self.query_mop(local_def_id.to_def_id());
-        }
+        self.solve_call_graph();
         // Meaning of output: 0 for ret value; 1,2,3,... for corresponding args.
         for (fn_id, fn_alias) in &mut self.fn_map {
             let fn_name = get_fn_name(self.tcx, *fn_id);
@@ -172,11 +190,14 @@ impl<'tcx> Analysis for AliasAnalyzer<'tcx> {
     }
 
     fn reset(&mut self) {
-        todo!();
+        self.fn_map.clear();
+        self.instance_map.borrow_mut().clear();
+        self.alias_states.clear();
+        self.graphs.clear();
     }
 }
 
-impl<'tcx> AliasAnalysis for AliasAnalyzer<'tcx> {
+impl<'tcx> AliasAnalysis<'tcx> for AliasAnalyzer<'tcx> {
     fn get_fn_alias(&self, def_id: DefId) -> Option<AAResult> {
         self.fn_map.get(&def_id).cloned().map(Into::into)
     }
@@ -187,6 +208,48 @@ impl<'tcx> AliasAnalysis for AliasAnalyzer<'tcx> {
             .map(|(k, v)| (*k, AAResult::from(v.clone())))
             .collect()
     }
+
+    /// The state of a location is the one at the end of its basic block, whatever the statement
+    /// index is. If the block has not been visited, e.g., due to `VISIT_LIMIT`, the states of all
+    /// visited blocks are joined. The states are empty if `record_states` was turned off.
+    fn get_alias_state(&self, def_id: DefId, location: Location) -> AAState {
+        let Some(states) = self.alias_states.get(&def_id) else {
+            return AAState::new();
+        };
+        match states.get(&location.block.as_usize()) {
+            Some(state) => state.clone(),
+            None => states.values().fold(AAState::new(), |mut joined, state| {
+                joined.join(state);
+                joined
+            }),
+        }
+    }
+
+    fn may_alias(
+        &self,
+        def_id: DefId,
+        location: Location,
+        lhs: Place<'tcx>,
+        rhs: Place<'tcx>,
+    ) -> bool {
+        self.get_alias_state(def_id, location).may_alias(
+            &AAPlace::from_place(self.tcx, def_id, lhs),
+            &AAPlace::from_place(self.tcx, def_id, rhs),
+        )
+    }
+
+    fn points_to(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace> {
+        let body = self.tcx.optimized_mir(def_id);
+        if place.ty(body, self.tcx).ty.builtin_deref(true).is_none() {
+            return HashSet::new();
+        }
+        self.alias_set(def_id, location, self.tcx.mk_place_deref(place))
+    }
+
+    fn alias_set(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace> {
+        self.get_alias_state(def_id, location)
+            .alias_set(&AAPlace::from_place(self.tcx, def_id, place))
+    }
 }
 
 impl<'tcx> AliasAnalyzer<'tcx> {
@@ -194,6 +257,10 @@ impl<'tcx> AliasAnalyzer<'tcx> {
         Self {
             tcx,
             fn_map: FxHashMap::default(),
+            instance_map: Rc::default(),
+            record_states: true,
+            alias_states: FxHashMap::default(),
+            graphs: FxHashMap::default(),
         }
     }
 
@@ -222,17 +289,35 @@ impl<'tcx> AliasAnalyzer<'tcx> {
         }
 
         if self.tcx.is_mir_available(def_id) {
-            let mut mop_graph = MopGraph::new(self.tcx, def_id);
+            let ret_alias = self.summarize(def_id);
+            self.fn_map.insert(def_id, ret_alias);
+        } else {
+            rap_trace!("Mir is not available at {}", self.tcx.def_path_str(def_id));
+        }
+    }
+
+    /// Analyze the body of `def_id` against the summaries in `fn_map` and return its summary.
+    /// A function that cannot be analyzed gets an empty summary.
+    fn summarize(&mut self, def_id: DefId) -> MopAAResult {
+        guarded(self.name(), def_id, || {
+            let mut mop_graph = match self.graphs.remove(&def_id) {
+                Some(mop_graph) => mop_graph,
+                None => MopGraph::new(self.tcx, def_id),
+            };
+            mop_graph.instance_map = self.instance_map.clone();
+            mop_graph.record_states = self.record_states;
             mop_graph.solve_scc();
             let mut recursion_set = HashSet::default();
             mop_graph.check(0, &mut self.fn_map, &mut recursion_set);
             if mop_graph.visit_times > VISIT_LIMIT {
                 rap_trace!("Over visited: {:?}", def_id);
             }
-            self.fn_map.insert(def_id, mop_graph.ret_alias);
-        } else {
-            rap_trace!("Mir is not available at {}", self.tcx.def_path_str(def_id));
-        }
+            if self.record_states {
+                self.alias_states.insert(def_id, mop_graph.alias_states);
+            }
+            mop_graph.ret_alias
+        })
+        .unwrap_or_else(|| MopAAResult::new(self.tcx.optimized_mir(def_id).arg_count))
     }
 
     pub fn get_all_fn_alias_raw(&mut self) -> MopAAResultMap {
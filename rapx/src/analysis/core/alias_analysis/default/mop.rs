@@ -1,4 +1,5 @@
 use super::{graph::*, *};
+use crate::analysis::core::range_analysis::feasibility::is_infeasible_branch;
 use rustc_data_structures::fx::FxHashSet;
 use rustc_hir::def_id::DefId;
 use rustc_middle::{
@@ -21,12 +22,17 @@ impl<'tcx> MopGraph<'tcx> {
         fn_map: &mut MopAAResultMap,
         recursion_set: &mut HashSet<DefId>,
     ) {
+        if is_infeasible_branch(self.path_checker.as_ref(), &mut self.path, bb_index) {
+            return;
+        }
+        let path_len = self.path.len();
         /* duplicate the status before visiting a path; */
         let backup_values = self.values.clone(); // duplicate the status when visiting different paths;
         let backup_constant = self.constant.clone();
         let backup_alias_set = self.alias_set.clone();
         self.check(bb_index, fn_map, recursion_set);
         /* restore after visit */
+        self.path.truncate(path_len);
         self.alias_set = backup_alias_set;
         self.values = backup_values;
         self.constant = backup_constant;
@@ -39,6 +45,10 @@ impl<'tcx> MopGraph<'tcx> {
         fn_map: &mut MopAAResultMap,
         recursion_set: &mut HashSet<DefId>,
     ) {
+        if is_infeasible_branch(self.path_checker.as_ref(), &mut self.path, bb_index) {
+            return;
+        }
+        let path_len = self.path.len();
         /* duplicate the status before visiting a path; */
         let backup_values = self.values.clone(); // duplicate the status when visiting different paths;
         let backup_constant = self.constant.clone();
@@ -47,6 +57,7 @@ impl<'tcx> MopGraph<'tcx> {
         self.constant.insert(path_discr_id, path_discr_val);
         self.check(bb_index, fn_map, recursion_set);
         /* restore after visit */
+        self.path.truncate(path_len);
         self.alias_set = backup_alias_set;
         self.values = backup_values;
         self.constant = backup_constant;
@@ -63,9 +74,12 @@ impl<'tcx> MopGraph<'tcx> {
         if self.visit_times > VISIT_LIMIT {
             return;
         }
+        self.path.push(bb_index);
+        let path_len = self.path.len();
         let cur_block = self.blocks[self.scc_indices[bb_index]].clone();
         self.alias_bb(self.scc_indices[bb_index]);
         self.alias_bbcall(self.scc_indices[bb_index], fn_map, recursion_set);
+        self.record_state(self.scc_indices[bb_index]);
 
         if self.child_scc.get(&self.scc_indices[bb_index]).is_some() {
             let init_index = self.scc_indices[bb_index];
@@ -73,6 +87,7 @@ impl<'tcx> MopGraph<'tcx> {
                 self.child_scc.get(&init_index).unwrap().clone();
 
             for enum_index in cur_targets.all_targets() {
+                self.path.truncate(path_len);
                 let backup_values = self.values.clone();
                 let backup_constant = self.constant.clone();
 
@@ -178,6 +193,7 @@ impl<'tcx> MopGraph<'tcx> {
                 for i in block_node.scc_sub_blocks.clone() {
                     self.alias_bb(i);
                     self.alias_bbcall(i, fn_map, recursion_set);
+                    self.record_state(i);
                 }
                 /* Reach a leaf node, check bugs */
                 match block_node.next.len() {
@@ -188,6 +204,7 @@ impl<'tcx> MopGraph<'tcx> {
                          * We cannot use [0] for FxHashSet.
                          */
                         for next in block_node.next {
+                            self.path.truncate(path_len);
                             self.check(next, fn_map, recursion_set);
                         }
                     }
@@ -229,6 +246,7 @@ impl<'tcx> MopGraph<'tcx> {
         let backup_fn_map = fn_map.clone();
         let backup_recursion_set = recursion_set.clone();
         for scc_each in order {
+            self.path.truncate(path_len);
             self.alias_set = backup_alias_set.clone();
             self.values = backup_values.clone();
             self.constant = backup_constant.clone();
@@ -239,6 +257,7 @@ impl<'tcx> MopGraph<'tcx> {
                 for idx in scc_each {
                     self.alias_bb(idx);
                     self.alias_bbcall(idx, fn_map, recursion_set);
+                    self.record_state(idx);
                 }
             }
 
@@ -1,5 +1,9 @@
+use rustc_abi::{FieldIdx, VariantIdx};
 use rustc_middle::ty;
 use rustc_middle::ty::{Ty, TyCtxt};
+use rustc_span::sym;
+
+use crate::analysis::core::alias_analysis::DEREF_FIELD;
 
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum TyKind {
@@ -50,3 +54,96 @@ pub fn is_not_drop<'tcx>(tcx: TyCtxt<'tcx>, current_ty: Ty<'tcx>) -> bool {
         _ => false,
     }
 }
+
+/// Return the type a value of `ty` points to if `ty` is a reference, a raw pointer or a `NonNull`.
+/// A `Box` is not included here because its derefs are lowered to derefs of its `NonNull` field.
+pub fn pointee_ty<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+    match ty.kind() {
+        ty::Ref(_, pointee, _) | ty::RawPtr(pointee, _) => Some(*pointee),
+        ty::Adt(adt, args) if tcx.is_diagnostic_item(sym::NonNull, adt.did()) => {
+            Some(args.type_at(0))
+        }
+        _ => None,
+    }
+}
+
+/// Return true if `ty` is a box or a type with a pointee.
+pub fn is_pointer<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> bool {
+    ty.is_box() || pointee_ty(tcx, ty).is_some()
+}
+
+/// Return the pointee type of `ty` if `ty` is a multi-level pointer, i.e., a pointer to a pointer
+/// or to a box.
+pub fn multi_level_pointee<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+    pointee_ty(tcx, ty).filter(|pointee| is_pointer(tcx, *pointee))
+}
+
+/// Return the type of the first field of a struct, e.g., the `Unique` of a box or the `NonNull`
+/// of a `Unique`.
+pub fn first_field_ty<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+    match ty.kind() {
+        ty::Adt(adt, args) if adt.is_struct() => adt
+            .non_enum_variant()
+            .fields
+            .iter()
+            .next()
+            .map(|field| field.ty(tcx, args)),
+        _ => None,
+    }
+}
+
+/// Return the children a node of `ty` needs to reach the pointees of a multi-level pointer, as
+/// pairs of field ids and types from the node down: its pointee, the pointee's pointee, and so on,
+/// as `DEREF_FIELD` children. A box reaches the object it owns through the `NonNull` in its
+/// `Unique` field.
+pub fn pointee_chain<'tcx>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Vec<(usize, Ty<'tcx>)> {
+    let mut chain = Vec::new();
+    let mut ty = ty;
+    loop {
+        if let Some(boxed) = ty.boxed_ty() {
+            if !is_pointer(tcx, boxed) {
+                break;
+            }
+            for _ in 0..2 {
+                let Some(field_ty) = first_field_ty(tcx, ty) else {
+                    return chain;
+                };
+                chain.push((0, field_ty));
+                ty = field_ty;
+            }
+            continue;
+        }
+        match multi_level_pointee(tcx, ty) {
+            Some(pointee) => {
+                chain.push((DEREF_FIELD, pointee));
+                ty = pointee;
+            }
+            None => break,
+        }
+    }
+    chain
+}
+
+/// Return the index of the field `field` of the variant `variant` of `ty`. The variants of a
+/// coroutine are its suspension points, and their fields are the locals held across them; a local
+/// held across several suspension points is stored once, so the field is numbered by the saved
+/// local after the upvars. The fields of an enum variant keep their own index.
+pub fn variant_field<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ty: Ty<'tcx>,
+    variant: VariantIdx,
+    field: FieldIdx,
+) -> usize {
+    if let ty::Coroutine(def_id, args) = ty.kind() {
+        if let Ok(layout) = tcx.coroutine_layout(*def_id, args) {
+            if let Some(saved) = layout
+                .variant_fields
+                .get(variant)
+                .and_then(|fields| fields.get(field))
+            {
+                return args.as_coroutine().upvar_tys().len() + saved.as_usize();
+            }
+        }
+    }
+    field.as_usize()
+}
@@ -1,11 +1,18 @@
 pub mod default;
+pub mod steensgaard;
 use crate::utils::source::get_fn_name_byid;
 
 use super::super::Analysis;
+use default::{types::multi_level_pointee, AliasAnalyzer, MopAAResultMap};
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Location, Place, PlaceTy, ProjectionElem},
+    ty::TyCtxt,
+};
 use rustc_span::def_id::LOCAL_CRATE;
-use std::{collections::HashSet, fmt};
+use std::{collections::HashSet, env, fmt};
+use steensgaard::SteensgaardAnalyzer;
 
 /// The data structure to store aliases for a set of functions.
 pub type AAResultMap = FxHashMap<DefId, AAResult>;
@@ -14,7 +21,7 @@ pub type AAResultMap = FxHashMap<DefId, AAResult>;
 pub struct AAResultMapWrapper(pub AAResultMap);
 
 /// This trait provides features related to alias analysis.
-pub trait AliasAnalysis: Analysis {
+pub trait AliasAnalysis<'tcx>: Analysis {
     /// Return the aliases among the function arguments and return value of a specific function.
     fn get_fn_alias(&self, def_id: DefId) -> Option<AAResult>;
     /// Return the aliases among the function arguments and return value for all functions.
@@ -28,6 +35,171 @@ pub trait AliasAnalysis: Analysis {
             .map(|(k, v)| (*k, v.clone()))
             .collect()
     }
+    /// Return the alias relations that may hold at a program point of a local function.
+    ///
+    /// The states are kept at block granularity at best: MoP returns the state at the end of the
+    /// basic block of `location` and ignores the statement index, and Steensgaard, which is
+    /// flow-insensitive, returns the same state for every location of the function. A query at
+    /// a statement in the middle of a block may thus see the aliases created later in the block.
+    fn get_alias_state(&self, def_id: DefId, location: Location) -> AAState;
+    /// Return true if two places may alias at a program point of a local function, i.e., in the
+    /// state returned by `get_alias_state`, with the same block-level granularity.
+    fn may_alias(
+        &self,
+        def_id: DefId,
+        location: Location,
+        lhs: Place<'tcx>,
+        rhs: Place<'tcx>,
+    ) -> bool;
+    /// Return the places that may be the object `place` points to at a program point of a local
+    /// function, with the granularity of `get_alias_state`. A single-level pointer shares its
+    /// place with the object it points to, so the result of such a pointer is the same as
+    /// `alias_set`.
+    fn points_to(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace>;
+    /// Return the places that may alias `place` at a program point of a local function, including
+    /// `place` itself, with the granularity of `get_alias_state`.
+    fn alias_set(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace>;
+}
+
+/// A place of a function body in the form used by alias analysis: a local followed by a sequence
+/// of field indices, where `DEREF_FIELD` stands for the pointee of a multi-level pointer.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AAPlace {
+    pub local: usize,
+    pub fields: Vec<usize>,
+}
+
+impl AAPlace {
+    pub fn new(local: usize, fields: Vec<usize>) -> Self {
+        Self { local, fields }
+    }
+
+    /// Convert a MIR place of `def_id`. A deref of a single-level pointer is dropped, as the
+    /// pointer and its pointee are the same place in the analysis.
+    pub fn from_place<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId, place: Place<'tcx>) -> Self {
+        let body = tcx.optimized_mir(def_id);
+        let mut fields = Vec::new();
+        let mut place_ty = PlaceTy::from_ty(body.local_decls[place.local].ty);
+        for elem in place.projection {
+            match elem {
+                ProjectionElem::Deref => {
+                    if multi_level_pointee(tcx, place_ty.ty).is_some() {
+                        fields.push(DEREF_FIELD);
+                    }
+                }
+                ProjectionElem::Field(field, _) => fields.push(field.as_usize()),
+                _ => {}
+            }
+            place_ty = place_ty.projection_ty(tcx, elem);
+        }
+        Self::new(place.local.as_usize(), fields)
+    }
+
+    /// If `self` is `prefix` followed by some fields, return these fields.
+    fn strip_prefix(&self, prefix: &AAPlace) -> Option<&[usize]> {
+        if self.local != prefix.local {
+            return None;
+        }
+        self.fields.strip_prefix(prefix.fields.as_slice())
+    }
+}
+
+impl fmt::Display for AAPlace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "_{}", aa_place_desc_str(self.local, &self.fields, true))
+    }
+}
+
+/// The alias relations that may hold at a program point. Each class is a sorted list of places
+/// that may alias each other. A place in no class only aliases itself, and the fields of two
+/// aliased places are aliased as well.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AAState {
+    classes: HashSet<Vec<AAPlace>>,
+}
+
+impl AAState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn classes(&self) -> &HashSet<Vec<AAPlace>> {
+        &self.classes
+    }
+
+    pub fn add_class(&mut self, mut class: Vec<AAPlace>) {
+        if class.len() < 2 {
+            return;
+        }
+        class.sort();
+        class.dedup();
+        self.classes.insert(class);
+    }
+
+    /// Add the relations of another state, e.g., of another path reaching the same point.
+    pub fn join(&mut self, other: &AAState) {
+        self.classes.extend(other.classes.iter().cloned());
+    }
+
+    pub fn may_alias(&self, lhs: &AAPlace, rhs: &AAPlace) -> bool {
+        lhs == rhs || self.alias_set(lhs).contains(rhs)
+    }
+
+    pub fn alias_set(&self, place: &AAPlace) -> HashSet<AAPlace> {
+        let mut result = HashSet::from([place.clone()]);
+        for class in &self.classes {
+            for member in class {
+                let Some(suffix) = place.strip_prefix(member) else {
+                    continue;
+                };
+                for other in class {
+                    let mut fields = other.fields.clone();
+                    fields.extend_from_slice(suffix);
+                    result.insert(AAPlace::new(other.local, fields));
+                }
+            }
+        }
+        result
+    }
+}
+
+/// The alias analysis algorithms. `-alias=<name>` selects the algorithm of `-alias`, and the
+/// `ALIAS_BACKEND` environment variable selects the one of the detectors built on top of alias
+/// analysis, e.g., SafeDrop and Senryx.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AliasBackend {
+    /// Path-sensitive meet-over-paths analysis, see `default::AliasAnalyzer`.
+    Mop,
+    /// Flow-insensitive unification-based analysis, see `steensgaard::SteensgaardAnalyzer`.
+    Steensgaard,
+}
+
+impl AliasBackend {
+    pub fn from_env() -> Self {
+        match env::var("ALIAS_BACKEND") {
+            Ok(backend) if backend.eq_ignore_ascii_case("steensgaard") => Self::Steensgaard,
+            _ => Self::Mop,
+        }
+    }
+
+    /// Run the algorithm on the crate and return the summaries of all functions, with the drop
+    /// flags of the arguments and return values used by SafeDrop.
+    pub fn run(self, tcx: TyCtxt<'_>) -> MopAAResultMap {
+        match self {
+            Self::Mop => {
+                let mut analyzer = AliasAnalyzer::new(tcx);
+                // only the summaries are returned.
+                analyzer.record_states = false;
+                analyzer.run();
+                analyzer.get_all_fn_alias_raw()
+            }
+            Self::Steensgaard => {
+                let mut analyzer = SteensgaardAnalyzer::new(tcx);
+                analyzer.run();
+                analyzer.get_all_fn_alias_raw()
+            }
+        }
+    }
 }
 
 /// To store the alias relationships among arguments and return values.
@@ -104,6 +276,13 @@ impl fmt::Display for AAResultMapWrapper {
     }
 }
 
+/// The field index that stands for the pointee of a multi-level pointer in a field sequence.
+///
+/// A pointer whose pointee is itself a pointer, e.g., `&mut &mut T`, `*mut *mut T` or the
+/// `NonNull` inside `Box<Box<T>>`, keeps the object it points to under this index, so that the
+/// pointer, its pointee and the pointee's pointee are different places. It is displayed as `*`.
+pub const DEREF_FIELD: usize = usize::MAX - 1;
+
 /// AAFact is used to store the alias relationships between two places.
 /// The result is field-sensitive.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -166,7 +345,11 @@ fn aa_place_desc_str(no: usize, fields: &[usize], field_sensitive: bool) -> Stri
     }
     for num in fields.iter() {
         result.push('.');
-        result.push_str(&num.to_string());
+        if *num == DEREF_FIELD {
+            result.push('*');
+        } else {
+            result.push_str(&num.to_string());
+        }
     }
     result
 }
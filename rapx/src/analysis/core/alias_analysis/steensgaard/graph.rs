@@ -0,0 +1,498 @@
+use crate::analysis::core::alias_analysis::{
+    default::{
+        alias::ArgLayout,
+        types::{is_not_drop, multi_level_pointee},
+    },
+    AAFact, AAPlace, AAResult, AAState, DEREF_FIELD,
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        AggregateKind, Body, CastKind, Operand, Place, PlaceTy, ProjectionElem, Rvalue,
+        StatementKind, TerminatorKind,
+    },
+    ty::{
+        self, adjustment::PointerCoercion, GenericArgsRef, Instance, InstanceKind, TyCtxt,
+        TypingEnv,
+    },
+};
+use std::collections::VecDeque;
+
+/// A call site of the function under analysis. The destination and the arguments are the nodes
+/// of the places they refer to, or `None` for constants and values that cannot hold a pointer.
+#[derive(Debug, Clone)]
+pub struct CallSite {
+    pub callee: DefId,
+    pub destination: Option<usize>,
+    pub args: Vec<Option<usize>>,
+}
+
+impl CallSite {
+    /// Return true if the call may create an alias, i.e., a pointer is passed or returned.
+    pub fn has_pointer(&self) -> bool {
+        self.destination.is_some() || self.args.iter().any(Option::is_some)
+    }
+}
+
+/// The unification graph of a function. Every MIR local starts as its own node, and fields and
+/// pointees are created on demand as children of a node, up to `max_depth` levels. Nodes that
+/// may hold the same value are unified into one equivalence class, whose children are unified
+/// recursively as well.
+///
+/// The graph follows the place model of MoP: a single-level pointer and its pointee are the same
+/// node, and a multi-level pointer keeps its pointee under `DEREF_FIELD`.
+pub struct SteensgaardGraph<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub def_id: DefId,
+    pub body: &'tcx Body<'tcx>,
+    parent: Vec<usize>,
+    depth: Vec<usize>,
+    children: Vec<FxHashMap<usize, usize>>,
+    // the place each node is created for.
+    places: Vec<AAPlace>,
+    may_drop: Vec<bool>,
+    max_depth: usize,
+    // the function items or closures that function pointer locals are created from.
+    fn_ptrs: FxHashMap<usize, (DefId, GenericArgsRef<'tcx>)>,
+    pub calls: Vec<CallSite>,
+}
+
+impl<'tcx> SteensgaardGraph<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId, max_depth: usize) -> Self {
+        let body = tcx.optimized_mir(def_id);
+        let typing_env = TypingEnv::post_analysis(tcx, def_id);
+        let may_drop = body
+            .local_decls
+            .iter()
+            .map(|decl| decl.ty.needs_drop(tcx, typing_env) || !is_not_drop(tcx, decl.ty))
+            .collect::<Vec<_>>();
+        let locals = body.local_decls.len();
+        let mut graph = Self {
+            tcx,
+            def_id,
+            body,
+            parent: (0..locals).collect(),
+            depth: vec![0; locals],
+            children: vec![FxHashMap::default(); locals],
+            places: (0..locals)
+                .map(|local| AAPlace::new(local, Vec::new()))
+                .collect(),
+            may_drop,
+            max_depth,
+            fn_ptrs: FxHashMap::default(),
+            calls: Vec::new(),
+        };
+        graph.build();
+        graph
+    }
+
+    pub fn arg_size(&self) -> usize {
+        self.body.arg_count
+    }
+
+    pub fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut node = node;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    /// Unify the classes of two nodes together with their children.
+    pub fn union(&mut self, lhs: usize, rhs: usize) {
+        let mut worklist = vec![(lhs, rhs)];
+        while let Some((lhs, rhs)) = worklist.pop() {
+            let (lhs, rhs) = (self.find(lhs), self.find(rhs));
+            if lhs == rhs {
+                continue;
+            }
+            // Keep the shallower node as the root, so that the depth limit stays conservative.
+            let (root, other) = if self.depth[lhs] <= self.depth[rhs] {
+                (lhs, rhs)
+            } else {
+                (rhs, lhs)
+            };
+            self.parent[other] = root;
+            for (field, child) in std::mem::take(&mut self.children[other]) {
+                match self.children[root].get(&field) {
+                    Some(root_child) => worklist.push((*root_child, child)),
+                    None => {
+                        self.children[root].insert(field, child);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Return the child of a node for a field or for `DEREF_FIELD`. Beyond the depth limit, all
+    /// children are collapsed into the node itself.
+    pub fn child(&mut self, node: usize, field: usize) -> usize {
+        let root = self.find(node);
+        if let Some(child) = self.children[root].get(&field) {
+            return *child;
+        }
+        if self.depth[root] >= self.max_depth {
+            return root;
+        }
+        let child = self.parent.len();
+        self.parent.push(child);
+        self.depth.push(self.depth[root] + 1);
+        self.children.push(FxHashMap::default());
+        self.children[root].insert(field, child);
+        let mut place = self.places[root].clone();
+        place.fields.push(field);
+        self.places.push(place);
+        child
+    }
+
+    /// Return the node of a field sequence, e.g., the place of a fact in a callee summary.
+    pub fn path_node(&mut self, node: usize, fields: &[usize]) -> usize {
+        fields
+            .iter()
+            .fold(node, |node, field| self.child(node, *field))
+    }
+
+    pub fn place_node(&mut self, place: Place<'tcx>) -> usize {
+        let mut node = place.local.as_usize();
+        let mut place_ty = PlaceTy::from_ty(self.body.local_decls[place.local].ty);
+        for elem in place.projection {
+            match elem {
+                ProjectionElem::Deref => {
+                    if multi_level_pointee(self.tcx, place_ty.ty).is_some() {
+                        node = self.child(node, DEREF_FIELD);
+                    }
+                }
+                ProjectionElem::Field(field, _) => {
+                    node = self.child(node, field.as_usize());
+                }
+                _ => {}
+            }
+            place_ty = place_ty.projection_ty(self.tcx, elem);
+        }
+        node
+    }
+
+    fn operand_node(&mut self, operand: &Operand<'tcx>) -> Option<usize> {
+        let place = operand.place()?;
+        if !self.may_drop[place.local.as_usize()] {
+            return None;
+        }
+        Some(self.place_node(place))
+    }
+
+    /// Unify the places of all assignments of the body regardless of their order, and record the
+    /// call sites to be resolved against the callee summaries.
+    fn build(&mut self) {
+        let body = self.body;
+        self.collect_fn_ptrs();
+        for block in body.basic_blocks.iter() {
+            for stmt in &block.statements {
+                let StatementKind::Assign(box (lv, ref rvalue)) = stmt.kind else {
+                    continue;
+                };
+                if !self.may_drop[lv.local.as_usize()] {
+                    continue;
+                }
+                match rvalue {
+                    Rvalue::Use(operand)
+                    | Rvalue::Cast(_, operand, _)
+                    | Rvalue::ShallowInitBox(operand, _) => {
+                        if let Some(place) = operand.place() {
+                            self.assign(lv, place);
+                        }
+                    }
+                    Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => {
+                        // A pointer to a pointer keeps the referenced place as its pointee.
+                        let lv_ty = self.body.local_decls[lv.local].ty;
+                        if lv.projection.is_empty()
+                            && multi_level_pointee(self.tcx, lv_ty).is_some()
+                        {
+                            self.assign(self.tcx.mk_place_deref(lv), *place);
+                        } else {
+                            self.assign(lv, *place);
+                        }
+                    }
+                    Rvalue::CopyForDeref(place) => {
+                        self.assign(lv, *place);
+                    }
+                    Rvalue::Aggregate(box kind, operands) => {
+                        let lv_node = self.place_node(lv);
+                        for (i, operand) in operands.iter().enumerate() {
+                            let Some(place) = operand.place() else {
+                                continue;
+                            };
+                            if !self.may_drop[place.local.as_usize()] {
+                                continue;
+                            }
+                            let rv_node = self.place_node(place);
+                            let field_node = match kind {
+                                AggregateKind::Array(_) => lv_node,
+                                _ => self.child(lv_node, i),
+                            };
+                            self.union(field_node, rv_node);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if let TerminatorKind::Call {
+                ref func,
+                ref args,
+                ref destination,
+                ..
+            } = block.terminator().kind
+            {
+                let Some((callee, layout)) = self.resolve_call(func) else {
+                    continue;
+                };
+                let destination = self.operand_node(&Operand::Copy(*destination));
+                let mut arg_nodes = Vec::new();
+                if layout == ArgLayout::ClosurePtr {
+                    // The environment of the closure is not passed through the pointer.
+                    arg_nodes.push(None);
+                }
+                let untuple = layout == ArgLayout::Closure;
+                for (i, arg) in args.iter().enumerate() {
+                    if untuple && i == 1 {
+                        if let ty::Tuple(tys) = arg.node.ty(body, self.tcx).kind() {
+                            let node = self.operand_node(&arg.node);
+                            for j in 0..tys.len() {
+                                arg_nodes.push(node.map(|node| self.child(node, j)));
+                            }
+                            continue;
+                        }
+                    }
+                    arg_nodes.push(self.operand_node(&arg.node));
+                }
+                self.calls.push(CallSite {
+                    callee,
+                    destination,
+                    args: arg_nodes,
+                });
+            }
+        }
+    }
+
+    fn assign(&mut self, lv: Place<'tcx>, rv: Place<'tcx>) {
+        if !self.may_drop[rv.local.as_usize()] {
+            return;
+        }
+        let lv = self.place_node(lv);
+        let rv = self.place_node(rv);
+        self.union(lv, rv);
+    }
+
+    /// Record the function items and closures that function pointer locals are created from,
+    /// regardless of the order of the statements.
+    fn collect_fn_ptrs(&mut self) {
+        let body = self.body;
+        loop {
+            let len = self.fn_ptrs.len();
+            for block in body.basic_blocks.iter() {
+                for stmt in &block.statements {
+                    let StatementKind::Assign(box (lv, ref rvalue)) = stmt.kind else {
+                        continue;
+                    };
+                    let Some(lv) = lv.as_local() else {
+                        continue;
+                    };
+                    let target = match rvalue {
+                        Rvalue::Cast(
+                            CastKind::PointerCoercion(
+                                PointerCoercion::ReifyFnPointer
+                                | PointerCoercion::ClosureFnPointer(_),
+                                _,
+                            ),
+                            operand,
+                            _,
+                        ) => match *operand.ty(body, self.tcx).kind() {
+                            ty::FnDef(def_id, args) | ty::Closure(def_id, args) => {
+                                Some((def_id, args))
+                            }
+                            _ => None,
+                        },
+                        Rvalue::Use(Operand::Copy(place) | Operand::Move(place)) => place
+                            .as_local()
+                            .and_then(|rv| self.fn_ptrs.get(&rv.as_usize()).copied()),
+                        _ => None,
+                    };
+                    if let Some(target) = target {
+                        self.fn_ptrs.entry(lv.as_usize()).or_insert(target);
+                    }
+                }
+            }
+            if self.fn_ptrs.len() == len {
+                break;
+            }
+        }
+    }
+
+    /// Resolve the callee of a call to the function body it executes and the way the arguments
+    /// map to its parameters. Calls through function pointers are resolved to the function item
+    /// or closure the pointer is created from.
+    fn resolve_call(&self, func: &Operand<'tcx>) -> Option<(DefId, ArgLayout)> {
+        match func {
+            Operand::Constant(constant) => match *constant.const_.ty().kind() {
+                ty::FnDef(def_id, args) => Some(self.resolve_fn(def_id, args)),
+                _ => None,
+            },
+            Operand::Copy(place) | Operand::Move(place) => {
+                let (def_id, args) = *self.fn_ptrs.get(&place.as_local()?.as_usize())?;
+                if self.tcx.is_closure_like(def_id) {
+                    Some((def_id, ArgLayout::ClosurePtr))
+                } else {
+                    Some(self.resolve_fn(def_id, args))
+                }
+            }
+        }
+    }
+
+    fn resolve_fn(&self, def_id: DefId, args: GenericArgsRef<'tcx>) -> (DefId, ArgLayout) {
+        let typing_env = TypingEnv::post_analysis(self.tcx, self.def_id);
+        match Instance::try_resolve(self.tcx, typing_env, def_id, args) {
+            Ok(Some(Instance {
+                def: InstanceKind::Item(item),
+                ..
+            })) => {
+                let is_fn_trait_call = self
+                    .tcx
+                    .trait_of_assoc(def_id)
+                    .is_some_and(|trait_id| self.tcx.fn_trait_kind_from_def_id(trait_id).is_some());
+                if is_fn_trait_call && self.tcx.is_closure_like(item) {
+                    (item, ArgLayout::Closure)
+                } else {
+                    (item, ArgLayout::Direct)
+                }
+            }
+            _ => (def_id, ArgLayout::Direct),
+        }
+    }
+
+    /// Apply the summary of a callee to a call site.
+    pub fn apply_summary(&mut self, call: &CallSite, summary: &AAResult) {
+        for fact in summary.aliases() {
+            let (Some(lhs), Some(rhs)) = (
+                self.call_place(call, fact.lhs_no(), fact.lhs_fields()),
+                self.call_place(call, fact.rhs_no(), fact.rhs_fields()),
+            ) else {
+                continue;
+            };
+            self.union(lhs, rhs);
+        }
+    }
+
+    /// Without the body of the callee, the returned pointer is assumed to alias the only argument
+    /// that may hold a pointer, as MoP does.
+    pub fn apply_unknown_call(&mut self, call: &CallSite) {
+        let Some(destination) = call.destination else {
+            return;
+        };
+        let candidates = call.args.iter().flatten().copied().collect::<Vec<_>>();
+        if candidates.len() == 1 {
+            self.union(destination, candidates[0]);
+        }
+    }
+
+    fn call_place(&mut self, call: &CallSite, no: usize, fields: &[usize]) -> Option<usize> {
+        let node = if no == 0 {
+            call.destination?
+        } else {
+            (*call.args.get(no - 1)?)?
+        };
+        Some(self.path_node(node, fields))
+    }
+
+    /// Return the alias relations among all places of the body. The analysis is flow-insensitive,
+    /// so they hold at every program point.
+    pub fn state(&mut self) -> AAState {
+        let mut classes: FxHashMap<usize, Vec<AAPlace>> = FxHashMap::default();
+        for node in 0..self.places.len() {
+            let root = self.find(node);
+            classes
+                .entry(root)
+                .or_default()
+                .push(self.places[node].clone());
+        }
+        let mut state = AAState::new();
+        for class in classes.into_values() {
+            state.add_class(class);
+        }
+        state
+    }
+
+    /// Extract the aliases among the return value and the arguments. Every class reached from two
+    /// of them yields a fact, unless the fact follows from a fact on shorter field sequences.
+    pub fn summary(&mut self) -> AAResult {
+        let arg_size = self.arg_size();
+        let mut places: FxHashMap<usize, Vec<(usize, Vec<usize>)>> = FxHashMap::default();
+        for local in 0..=arg_size {
+            if !self.may_drop[local] {
+                continue;
+            }
+            let mut visited = FxHashSet::default();
+            let mut queue = VecDeque::from([(local, Vec::new())]);
+            while let Some((node, fields)) = queue.pop_front() {
+                let root = self.find(node);
+                places
+                    .entry(root)
+                    .or_default()
+                    .push((local, fields.clone()));
+                if !visited.insert(root) {
+                    continue;
+                }
+                let mut children = self.children[root]
+                    .iter()
+                    .map(|(field, child)| (*field, *child))
+                    .collect::<Vec<_>>();
+                children.sort();
+                for (field, child) in children {
+                    let mut child_fields = fields.clone();
+                    child_fields.push(field);
+                    queue.push_back((child, child_fields));
+                }
+            }
+        }
+
+        let mut facts = Vec::new();
+        for entries in places.values() {
+            for (i, (lhs_no, lhs_fields)) in entries.iter().enumerate() {
+                for (rhs_no, rhs_fields) in &entries[i + 1..] {
+                    if lhs_no == rhs_no {
+                        continue;
+                    }
+                    facts.push(AAFact {
+                        lhs_no: *lhs_no,
+                        lhs_fields: lhs_fields.clone(),
+                        rhs_no: *rhs_no,
+                        rhs_fields: rhs_fields.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut result = AAResult::new(arg_size);
+        for fact in facts.iter() {
+            let implied = facts.iter().any(|other| {
+                other.lhs_no == fact.lhs_no
+                    && other.rhs_no == fact.rhs_no
+                    && other.lhs_fields.len() < fact.lhs_fields.len()
+                    && fact.lhs_fields.starts_with(&other.lhs_fields)
+                    && fact.rhs_fields.starts_with(&other.rhs_fields)
+                    && fact.lhs_fields[other.lhs_fields.len()..]
+                        == fact.rhs_fields[other.rhs_fields.len()..]
+            });
+            if !implied {
+                result.add_alias(fact.clone());
+            }
+        }
+        result.sort_alias_index();
+        result
+    }
+}
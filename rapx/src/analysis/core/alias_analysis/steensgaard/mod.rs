@@ -0,0 +1,232 @@
+pub mod graph;
+
+use super::{
+    default::{
+        fixpoint::FIXPOINT_LIMIT, types::is_not_drop, MopAAFact, MopAAResult, MopAAResultMap,
+    },
+    AAPlace, AAResult, AAResultMap, AAState, AliasAnalysis,
+};
+use crate::{
+    analysis::Analysis,
+    rap_debug, rap_trace, rap_warn,
+    utils::{boundary::guarded, source::*},
+};
+use graph::SteensgaardGraph;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Local, Location, Place},
+    ty::{TyCtxt, TypingEnv},
+};
+use std::{collections::HashSet, env};
+
+/// The field depth used if `ALIAS_DEPTH` is not set.
+pub const DEFAULT_FIELD_DEPTH: usize = 5;
+
+/// A flow-insensitive, unification-based (Steensgaard-style) alias analysis.
+///
+/// Each function body is visited once, and the places that may hold the same value are unified
+/// regardless of the order of the statements. Summaries of callees are applied at call sites in
+/// the same way as MoP, and the local functions are revisited until no summary changes. The
+/// results are less precise than MoP, but the cost is almost linear to the size of the bodies.
+pub struct SteensgaardAnalyzer<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub fn_map: FxHashMap<DefId, AAResult>,
+    // the alias relations of the local functions, which hold at all their program points.
+    pub alias_states: FxHashMap<DefId, AAState>,
+    field_depth: usize,
+}
+
+impl<'tcx> Analysis for SteensgaardAnalyzer<'tcx> {
+    fn name(&self) -> &'static str {
+        "Alias Analysis (Steensgaard)"
+    }
+
+    fn run(&mut self) {
+        rap_debug!(
+            "Start alias analysis via Steensgaard, field depth {}.",
+            self.field_depth
+        );
+        let fns: Vec<DefId> = self
+            .tcx
+            .mir_keys(())
+            .iter()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| {
+                self.tcx
+                    .hir_body_const_context(def_id.expect_local())
+                    .is_none()
+                    && self.tcx.is_mir_available(*def_id)
+            })
+            .collect();
+        // The summaries only grow and the field depth is bounded, so the iteration terminates, but
+        // it is still given up after `FIXPOINT_LIMIT` rounds as MoP does.
+        for round in 1..=FIXPOINT_LIMIT {
+            let mut changed = false;
+            for def_id in &fns {
+                let Some(summary) = guarded(self.name(), *def_id, || {
+                    self.summarize(*def_id, &mut HashSet::default())
+                }) else {
+                    continue;
+                };
+                let old = self
+                    .fn_map
+                    .entry(*def_id)
+                    .or_insert_with(|| AAResult::new(summary.arg_size()));
+                for fact in summary.aliases() {
+                    if !old.aliases().contains(fact) {
+                        old.add_alias(fact.clone());
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                rap_debug!("Steensgaard summaries are stable after {} rounds.", round);
+                break;
+            }
+            if round == FIXPOINT_LIMIT {
+                rap_warn!(
+                    "Steensgaard summaries did not reach a fixed point after {} rounds.",
+                    FIXPOINT_LIMIT
+                );
+            }
+        }
+        for (fn_id, fn_alias) in &self.fn_map {
+            if fn_alias.len() > 0 {
+                rap_debug!(
+                    "Alias found in {:?}: {}",
+                    get_fn_name(self.tcx, *fn_id),
+                    fn_alias
+                );
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.fn_map.clear();
+        self.alias_states.clear();
+    }
+}
+
+impl<'tcx> AliasAnalysis<'tcx> for SteensgaardAnalyzer<'tcx> {
+    fn get_fn_alias(&self, def_id: DefId) -> Option<AAResult> {
+        self.fn_map.get(&def_id).cloned()
+    }
+
+    fn get_all_fn_alias(&self) -> AAResultMap {
+        self.fn_map.clone()
+    }
+
+    /// The relations hold at all program points of the function, so the location is ignored.
+    fn get_alias_state(&self, def_id: DefId, _location: Location) -> AAState {
+        self.alias_states.get(&def_id).cloned().unwrap_or_default()
+    }
+
+    fn may_alias(
+        &self,
+        def_id: DefId,
+        location: Location,
+        lhs: Place<'tcx>,
+        rhs: Place<'tcx>,
+    ) -> bool {
+        self.get_alias_state(def_id, location).may_alias(
+            &AAPlace::from_place(self.tcx, def_id, lhs),
+            &AAPlace::from_place(self.tcx, def_id, rhs),
+        )
+    }
+
+    fn points_to(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace> {
+        let body = self.tcx.optimized_mir(def_id);
+        if place.ty(body, self.tcx).ty.builtin_deref(true).is_none() {
+            return HashSet::new();
+        }
+        self.alias_set(def_id, location, self.tcx.mk_place_deref(place))
+    }
+
+    fn alias_set(&self, def_id: DefId, location: Location, place: Place<'tcx>) -> HashSet<AAPlace> {
+        self.get_alias_state(def_id, location)
+            .alias_set(&AAPlace::from_place(self.tcx, def_id, place))
+    }
+}
+
+impl<'tcx> SteensgaardAnalyzer<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        let field_depth = env::var("ALIAS_DEPTH")
+            .ok()
+            .and_then(|depth| depth.parse().ok())
+            .unwrap_or(DEFAULT_FIELD_DEPTH);
+        Self {
+            tcx,
+            fn_map: FxHashMap::default(),
+            alias_states: FxHashMap::default(),
+            field_depth,
+        }
+    }
+
+    /// Analyze the body of `def_id` and return its summary. Callees without a summary are
+    /// analyzed on demand; a callee that is being analyzed contributes nothing in this round.
+    fn summarize(&mut self, def_id: DefId, recursion_set: &mut HashSet<DefId>) -> AAResult {
+        rap_trace!("Steensgaard: {:?}", get_fn_name(self.tcx, def_id));
+        recursion_set.insert(def_id);
+        let mut graph = SteensgaardGraph::new(self.tcx, def_id, self.field_depth);
+        for call in std::mem::take(&mut graph.calls) {
+            if !call.has_pointer() {
+                continue;
+            }
+            if !self.tcx.is_mir_available(call.callee) {
+                graph.apply_unknown_call(&call);
+                continue;
+            }
+            let summary = match self.fn_map.get(&call.callee) {
+                Some(summary) => summary.clone(),
+                None if recursion_set.contains(&call.callee) => continue,
+                None => {
+                    let summary = self.summarize(call.callee, recursion_set);
+                    self.fn_map.insert(call.callee, summary.clone());
+                    summary
+                }
+            };
+            graph.apply_summary(&call, &summary);
+        }
+        recursion_set.remove(&def_id);
+        if def_id.is_local() {
+            self.alias_states.insert(def_id, graph.state());
+        }
+        graph.summary()
+    }
+
+    /// Return the summaries in the form consumed by SafeDrop. The drop flags of a fact are those
+    /// of the argument or return value it refers to.
+    pub fn get_all_fn_alias_raw(&mut self) -> MopAAResultMap {
+        self.fn_map
+            .iter()
+            .map(|(def_id, result)| (*def_id, self.to_mop_result(*def_id, result)))
+            .collect()
+    }
+
+    fn to_mop_result(&self, def_id: DefId, result: &AAResult) -> MopAAResult {
+        let body = self.tcx.optimized_mir(def_id);
+        let typing_env = TypingEnv::post_analysis(self.tcx, def_id);
+        let drop_flags = |no: usize| {
+            let ty = body.local_decls[Local::from_usize(no)].ty;
+            let need_drop = ty.needs_drop(self.tcx, typing_env);
+            (need_drop || !is_not_drop(self.tcx, ty), need_drop)
+        };
+        let mut mop_result = MopAAResult::new(result.arg_size());
+        for fact in result.aliases() {
+            let (lhs_may_drop, lhs_need_drop) = drop_flags(fact.lhs_no());
+            let (rhs_may_drop, rhs_need_drop) = drop_flags(fact.rhs_no());
+            let mut alias = MopAAFact::new(
+                fact.lhs_no(),
+                lhs_may_drop,
+                lhs_need_drop,
+                fact.rhs_no(),
+                rhs_may_drop,
+                rhs_need_drop,
+            );
+            alias.fact = fact.clone();
+            mop_result.add_alias(alias);
+        }
+        mop_result
+    }
+}
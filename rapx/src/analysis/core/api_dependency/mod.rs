@@ -103,7 +103,7 @@ impl<'tcx> Analysis for ApiDependencyAnalyzer<'tcx> {
     }
 
     fn reset(&mut self) {
-        todo!();
+        self.api_graph = ApiDependencyGraph::new(self.tcx);
     }
 }
 
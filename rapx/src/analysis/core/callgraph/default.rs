@@ -1,3 +1,4 @@
+use rustc_data_structures::graph::{scc::Sccs, vec_graph::VecGraph};
 use rustc_hir::{def::DefKind, def_id::DefId};
 use rustc_middle::{
     mir::{self, Body},
@@ -9,7 +10,9 @@ use std::{collections::HashMap, hash::Hash};
 use super::visitor::CallGraphVisitor;
 use crate::{
     analysis::core::callgraph::{CallGraph, CallGraphAnalysis},
-    rap_debug, rap_info, Analysis,
+    rap_debug, rap_info,
+    utils::boundary::guarded,
+    Analysis,
 };
 
 pub struct CallGraphAnalyzer<'tcx> {
@@ -19,7 +22,7 @@ pub struct CallGraphAnalyzer<'tcx> {
 
 impl<'tcx> Analysis for CallGraphAnalyzer<'tcx> {
     fn name(&self) -> &'static str {
-        "Default call graph analysis algorithm."
+        "Call Graph Analysis"
     }
 
     fn run(&mut self) {
@@ -28,7 +31,7 @@ impl<'tcx> Analysis for CallGraphAnalyzer<'tcx> {
     }
 
     fn reset(&mut self) {
-        todo!();
+        self.graph = CallGraphInfo::new();
     }
 }
 
@@ -96,9 +99,10 @@ impl<'tcx> CallGraphAnalyzer<'tcx> {
                         }
                     };
 
+                    let name = self.name();
                     let mut call_graph_visitor =
                         CallGraphVisitor::new(self.tcx, def_id.into(), body, &mut self.graph);
-                    call_graph_visitor.visit();
+                    guarded(name, def_id, || call_graph_visitor.visit());
                 }
             }
         }
@@ -254,6 +258,35 @@ impl<'tcx> CallGraphInfo<'tcx> {
         analysis_order
     }
 
+    /// Return the strongly connected components of the call graph in reverse topological order,
+    /// i.e., every component comes after the components it calls.
+    pub fn get_sccs(&self) -> Vec<Vec<DefId>> {
+        let mut node_ids: Vec<usize> = self.functions.keys().copied().collect();
+        node_ids.sort();
+        sccs(&node_ids, |node_id| {
+            self.fn_calls
+                .get(&node_id)
+                .into_iter()
+                .flatten()
+                .map(|(callee_id, _terminator)| *callee_id)
+                .collect()
+        })
+        .into_iter()
+        .map(|scc| scc.iter().map(|id| self.functions[id].def_id).collect())
+        .collect()
+    }
+
+    /// Test if `def_id` calls itself directly.
+    pub fn is_self_recursive(&self, def_id: DefId) -> bool {
+        self.functions.iter().any(|(id, node)| {
+            node.def_id == def_id
+                && self
+                    .fn_calls
+                    .get(id)
+                    .is_some_and(|callees| callees.iter().any(|(callee, _)| callee == id))
+        })
+    }
+
     /// Helper function to perform a recursive depth-first search.
     fn dfs_post_order(
         &self,
@@ -277,3 +310,27 @@ impl<'tcx> CallGraphInfo<'tcx> {
         post_order_ids.push(node_id);
     }
 }
+
+/// Return the strongly connected components of the graph over `nodes` whose edges are given by
+/// `successors`, in reverse topological order, i.e., every component comes after the components
+/// it reaches. The nodes of a component keep their order in `nodes`, and successors that are not
+/// in `nodes` are ignored.
+pub fn sccs<T: Copy + Eq + Hash>(nodes: &[T], successors: impl Fn(T) -> Vec<T>) -> Vec<Vec<T>> {
+    let index: HashMap<T, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+    let mut edges = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        for succ in successors(*node) {
+            if let Some(&j) = index.get(&succ) {
+                edges.push((i, j));
+            }
+        }
+    }
+    let graph: VecGraph<usize> = VecGraph::new(nodes.len(), edges);
+    // The components are numbered in post order, i.e., callees first.
+    let sccs: Sccs<usize, usize> = Sccs::new(&graph);
+    let mut components = vec![Vec::new(); sccs.num_sccs()];
+    for (i, node) in nodes.iter().enumerate() {
+        components[sccs.scc(i)].push(*node);
+    }
+    components
+}
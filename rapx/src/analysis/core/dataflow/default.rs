@@ -1,9 +1,12 @@
 use super::graph::*;
+use super::inter::CallSites;
 use crate::analysis::core::dataflow::*;
+use crate::utils::boundary::guarded;
 
 pub struct DataFlowAnalyzer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub graphs: HashMap<DefId, Graph>,
+    pub call_sites: CallSites,
     pub debug: bool,
 }
 
@@ -42,6 +45,18 @@ impl<'tcx> DataFlowAnalysis for DataFlowAnalyzer<'tcx> {
         }
         result
     }
+
+    fn has_interproc_flow_between(&self, src: (DefId, Local), dst: (DefId, Local)) -> bool {
+        self.collect_interproc_reachable(src).contains(&dst)
+    }
+
+    fn collect_interproc_reachable(&self, src: (DefId, Local)) -> HashSet<(DefId, Local)> {
+        DataFlowAnalyzer::collect_interproc_reachable(self, src)
+    }
+
+    fn get_fn_interproc_arg2ret(&self, def_id: DefId) -> Arg2Ret {
+        self.interproc_param_return_deps(def_id)
+    }
 }
 
 impl<'tcx> Analysis for DataFlowAnalyzer<'tcx> {
@@ -58,6 +73,7 @@ impl<'tcx> Analysis for DataFlowAnalyzer<'tcx> {
 
     fn reset(&mut self) {
         self.graphs.clear();
+        self.call_sites.clear();
     }
 }
 
@@ -66,6 +82,7 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
         Self {
             tcx: tcx,
             graphs: HashMap::new(),
+            call_sites: CallSites::default(),
             debug,
         }
     }
@@ -83,10 +100,14 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
             if matches!(def_kind, DefKind::Fn) || matches!(def_kind, DefKind::AssocFn) {
                 if self.tcx.hir_maybe_body_owned_by(local_def_id).is_some() {
                     let def_id = local_def_id.to_def_id();
-                    self.build_graph(def_id);
+                    guarded(self.name(), def_id, || self.build_graph(def_id));
                 }
             }
         }
+        let callers: Vec<DefId> = self.graphs.keys().copied().collect();
+        for caller in callers {
+            guarded(self.name(), caller, || self.stitch_call_sites(caller));
+        }
     }
 
     pub fn build_graph(&mut self, def_id: DefId) {
@@ -96,12 +117,12 @@ impl<'tcx> DataFlowAnalyzer<'tcx> {
         let body: &Body = self.tcx.optimized_mir(def_id);
         let mut graph = Graph::new(def_id, body.span, body.arg_count, body.local_decls.len());
         let basic_blocks = &body.basic_blocks;
-        for basic_block_data in basic_blocks.iter() {
+        for (block, basic_block_data) in basic_blocks.iter_enumerated() {
             for statement in basic_block_data.statements.iter() {
-                graph.add_statm_to_graph(&statement);
+                graph.add_statm_to_graph(statement);
             }
             if let Some(terminator) = &basic_block_data.terminator {
-                graph.add_terminator_to_graph(&terminator);
+                graph.add_terminator_to_graph(block, terminator);
             }
         }
         for closure_id in graph.closures.iter() {
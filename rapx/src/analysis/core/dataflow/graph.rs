@@ -5,8 +5,8 @@ use rustc_hir::def_id::DefId;
 use rustc_index::IndexVec;
 use rustc_middle::{
     mir::{
-        AggregateKind, BorrowKind, Const, Local, Operand, Place, PlaceElem, Rvalue, Statement,
-        StatementKind, Terminator, TerminatorKind,
+        AggregateKind, BasicBlock, BorrowKind, Const, Local, Operand, Place, PlaceElem, Rvalue,
+        Statement, StatementKind, Terminator, TerminatorKind,
     },
     ty::TyKind,
 };
@@ -35,6 +35,21 @@ pub struct Graph {
     pub edges: GraphEdges,
     pub n_locals: usize,
     pub closures: HashSet<DefId>,
+    /// The calls to the functions known statically, in the order they are added.
+    pub calls: Vec<GraphCall>,
+}
+
+/// A call to a function known statically, recorded as the graph is built.
+#[derive(Clone, Debug)]
+pub struct GraphCall {
+    /// The node of the destination.
+    pub dest: Local,
+    /// The batch of the edges from the arguments to `dest`.
+    pub seq: usize,
+    /// The nodes of the arguments, in order.
+    pub args: Vec<Local>,
+    /// The block terminated by the call.
+    pub block: BasicBlock,
 }
 
 impl From<Graph> for DataFlowGraph {
@@ -58,6 +73,7 @@ impl Graph {
             edges: GraphEdges::new(),
             n_locals,
             closures: HashSet::new(),
+            calls: Vec::new(),
         }
     }
 
@@ -261,7 +277,7 @@ impl Graph {
         }
     }
 
-    pub fn add_terminator_to_graph(&mut self, terminator: &Terminator) {
+    pub fn add_terminator_to_graph(&mut self, block: BasicBlock, terminator: &Terminator) {
         if let TerminatorKind::Call {
             func,
             args,
@@ -283,6 +299,19 @@ impl Graph {
                                 self.add_operand(&op.node, dst);
                             }
                             self.nodes[dst].ops[seq] = NodeOp::Call(*def_id);
+                            let args = self.nodes[dst]
+                                .in_edges
+                                .iter()
+                                .map(|edge_idx| &self.edges[*edge_idx])
+                                .filter(|edge| edge.seq == seq)
+                                .map(|edge| edge.src)
+                                .collect();
+                            self.calls.push(GraphCall {
+                                dest: dst,
+                                seq,
+                                args,
+                                block,
+                            });
                         }
                     }
                 }
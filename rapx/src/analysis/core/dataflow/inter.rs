@@ -0,0 +1,397 @@
+//! The inter-procedural dataflow graph.
+//!
+//! The graphs of the functions are stitched at the calls to the functions having a graph, i.e.,
+//! the local functions and closures: the arguments of a call flow into the parameters of the
+//! callee, and its return value flows back to the destination of the call. The callee is resolved
+//! with the generic arguments of the call, so a call to a trait method is stitched to the method of
+//! the impl. A stitched call replaces the edges from the arguments to the destination in the graph
+//! of the caller, which assume that any argument may flow to the return value.
+//!
+//! The traversal is field-sensitive: a value put into a field of a struct or a tuple only flows
+//! out of the same field, in the caller or in a callee receiving the aggregate. It is also
+//! context-sensitive: a value flowing out of a callee returns to the call it entered by. Both are
+//! limited to `DEPTH` nested fields and calls; beyond the limit, a value flows out of any field
+//! and returns to any call.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Local, TerminatorKind},
+    ty::{Instance, TypingEnv},
+};
+use rustc_span::Span;
+
+use super::{
+    default::DataFlowAnalyzer,
+    graph::{Graph, GraphCall},
+    AggKind, Arg2Ret, EdgeOp, NodeOp,
+};
+
+const DEPTH: usize = 3;
+
+/// An argument of a call and the parameter of the callee it is bound to.
+#[derive(Clone, Debug)]
+pub struct Binding {
+    /// The node of the argument in the graph of the caller.
+    pub arg: Local,
+    /// The field of the argument bound to the parameter, for the arguments of a closure, which are
+    /// passed in a tuple.
+    pub field: Option<usize>,
+    pub param: Local,
+}
+
+/// A call from a function to another one having a dataflow graph.
+#[derive(Clone, Debug)]
+pub struct CallSite {
+    pub caller: DefId,
+    /// The resolved callee.
+    pub callee: DefId,
+    /// The destination of the call in the graph of the caller.
+    pub dest: Local,
+    /// The batch of the edges from the arguments to `dest`.
+    pub seq: usize,
+    pub bindings: Vec<Binding>,
+    pub span: Span,
+}
+
+/// The call sites of all the functions, indexed for the traversal.
+#[derive(Clone, Debug, Default)]
+pub struct CallSites {
+    pub sites: Vec<CallSite>,
+    // the call sites and the bindings of the arguments, by the node of the argument.
+    by_arg: HashMap<(DefId, Local), Vec<(usize, usize)>>,
+    by_callee: HashMap<DefId, Vec<usize>>,
+    // the destinations and the batches of the stitched calls.
+    stitched: HashSet<(DefId, Local, usize)>,
+}
+
+impl CallSites {
+    pub fn push(&mut self, site: CallSite) {
+        let idx = self.sites.len();
+        for (binding_idx, binding) in site.bindings.iter().enumerate() {
+            self.by_arg
+                .entry((site.caller, binding.arg))
+                .or_default()
+                .push((idx, binding_idx));
+        }
+        self.by_callee.entry(site.callee).or_default().push(idx);
+        self.stitched.insert((site.caller, site.dest, site.seq));
+        self.sites.push(site);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// A value in a function, the fields it was put into, and the calls it entered.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct State {
+    def_id: DefId,
+    local: Local,
+    fields: Vec<usize>,
+    calls: Vec<usize>,
+}
+
+// Take the field out of the fields a value was put into; a value not known to be in a field flows
+// out of all of them.
+fn project(fields: &[usize], field: usize) -> Option<Vec<usize>> {
+    match fields.split_last() {
+        Some((last, rest)) if *last == field => Some(rest.to_vec()),
+        Some(_) => None,
+        None => Some(Vec::new()),
+    }
+}
+
+impl<'tcx> DataFlowAnalyzer<'tcx> {
+    // The callee of a call recorded by the graph of the caller, resolved with the generic
+    // arguments of the call.
+    fn resolve_call(&self, caller: DefId, call: &GraphCall) -> Option<DefId> {
+        let body = self.tcx.optimized_mir(caller);
+        let TerminatorKind::Call { func, .. } = &body.basic_blocks[call.block].terminator().kind
+        else {
+            return None;
+        };
+        let (def_id, args) = func.const_fn_def()?;
+        let typing_env = TypingEnv::post_analysis(self.tcx, caller);
+        match Instance::try_resolve(self.tcx, typing_env, def_id, args) {
+            Ok(Some(instance)) => Some(instance.def_id()),
+            _ => Some(def_id),
+        }
+    }
+
+    /// Record the calls of the function to the functions having a graph.
+    pub fn stitch_call_sites(&mut self, caller: DefId) {
+        let Some(graph) = self.graphs.get(&caller) else {
+            return;
+        };
+        let body = self.tcx.optimized_mir(caller);
+        let mut sites = Vec::new();
+        for call in graph.calls.iter() {
+            let Some(callee) = self.resolve_call(caller, call) else {
+                continue;
+            };
+            let Some(callee_graph) = self.graphs.get(&callee) else {
+                continue;
+            };
+            let called = match graph.nodes[call.dest].ops.get(call.seq) {
+                Some(NodeOp::Call(def_id)) => *def_id,
+                _ => continue,
+            };
+            let bindings = bind_args(
+                &call.args,
+                callee_graph,
+                callee != called && self.tcx.is_closure_like(callee),
+            );
+            sites.push(CallSite {
+                caller,
+                callee,
+                dest: call.dest,
+                seq: call.seq,
+                bindings,
+                span: body.basic_blocks[call.block].terminator().source_info.span,
+            });
+        }
+        for site in sites {
+            self.call_sites.push(site);
+        }
+    }
+
+    /// Build the graph of the function and of the local functions it calls, and stitch its calls
+    /// to them.
+    pub fn build_graph_with_callees(&mut self, def_id: DefId) {
+        self.build_graph(def_id);
+        let callees: Vec<DefId> = self.graphs[&def_id]
+            .calls
+            .iter()
+            .filter_map(|call| self.resolve_call(def_id, call))
+            .filter(|callee| {
+                callee.as_local().is_some_and(|local_def_id| {
+                    self.tcx.hir_maybe_body_owned_by(local_def_id).is_some()
+                        && self.tcx.is_mir_available(*callee)
+                })
+            })
+            .collect();
+        for callee in callees {
+            self.build_graph(callee);
+        }
+        if !self
+            .call_sites
+            .sites
+            .iter()
+            .any(|site| site.caller == def_id)
+        {
+            self.stitch_call_sites(def_id);
+        }
+    }
+
+    /// The locals of the function equal to the local, also through the calls to the functions
+    /// returning one of their parameters unchanged, e.g., `fn id(x: usize) -> usize { x }`.
+    pub fn collect_interproc_equivalent_locals(
+        &self,
+        def_id: DefId,
+        local: Local,
+    ) -> HashSet<Local> {
+        let graph = &self.graphs[&def_id];
+        let mut equivalent = graph.collect_equivalent_locals(local, true);
+        let sites: Vec<&CallSite> = self
+            .call_sites
+            .sites
+            .iter()
+            .filter(|site| site.caller == def_id)
+            .collect();
+        // the argument and the destination of the calls returning the argument.
+        let pairs: Vec<(Local, Local)> = sites
+            .iter()
+            .flat_map(|site| {
+                let returned =
+                    self.graphs[&site.callee].collect_equivalent_locals(Local::from_usize(0), true);
+                site.bindings
+                    .iter()
+                    .filter(move |binding| {
+                        binding.field.is_none() && returned.contains(&binding.param)
+                    })
+                    .map(|binding| (binding.arg, site.dest))
+            })
+            .collect();
+        loop {
+            let len = equivalent.len();
+            for (arg, dest) in pairs.iter() {
+                if equivalent.contains(arg) != equivalent.contains(dest) {
+                    equivalent.extend(graph.collect_equivalent_locals(*arg, true));
+                    equivalent.extend(graph.collect_equivalent_locals(*dest, true));
+                }
+            }
+            if equivalent.len() == len {
+                return equivalent;
+            }
+        }
+    }
+
+    // The states a value flows to in one step.
+    fn successors(&self, state: &State) -> Vec<State> {
+        let mut successors = Vec::new();
+        let Some(graph) = self.graphs.get(&state.def_id) else {
+            return successors;
+        };
+        for edge_idx in graph.nodes[state.local].out_edges.iter() {
+            let edge = &graph.edges[*edge_idx];
+            if self
+                .call_sites
+                .stitched
+                .contains(&(state.def_id, edge.dst, edge.seq))
+            {
+                continue;
+            }
+            let fields = match (&edge.op, graph.nodes[edge.dst].ops.get(edge.seq)) {
+                (EdgeOp::Field(field), _) => match project(&state.fields, *field) {
+                    Some(fields) => fields,
+                    None => continue,
+                },
+                (_, Some(NodeOp::Aggregate(AggKind::Adt(_) | AggKind::Tuple))) => {
+                    let field = graph.nodes[edge.dst]
+                        .in_edges
+                        .iter()
+                        .filter(|idx| graph.edges[**idx].seq == edge.seq)
+                        .position(|idx| idx == edge_idx)
+                        .unwrap_or_default();
+                    let mut fields = state.fields.clone();
+                    match fields.len() < DEPTH {
+                        true => fields.push(field),
+                        false => fields.clear(),
+                    }
+                    fields
+                }
+                (EdgeOp::Deref | EdgeOp::Downcast(_), _)
+                | (_, Some(NodeOp::Use | NodeOp::Ref | NodeOp::CopyForDeref | NodeOp::RawPtr)) => {
+                    state.fields.clone()
+                }
+                // a marker of a projection.
+                (_, Some(NodeOp::Nop)) if graph.is_marker(edge.dst) => state.fields.clone(),
+                // the value is computed from the whole of the operand.
+                _ => Vec::new(),
+            };
+            successors.push(State {
+                def_id: state.def_id,
+                local: edge.dst,
+                fields,
+                calls: state.calls.clone(),
+            });
+        }
+
+        // into the callees.
+        if let Some(bindings) = self.call_sites.by_arg.get(&(state.def_id, state.local)) {
+            for (site_idx, binding_idx) in bindings.iter() {
+                let site = &self.call_sites.sites[*site_idx];
+                let binding = &site.bindings[*binding_idx];
+                let fields = match binding.field {
+                    Some(field) => match project(&state.fields, field) {
+                        Some(fields) => fields,
+                        None => continue,
+                    },
+                    None => state.fields.clone(),
+                };
+                let mut calls = state.calls.clone();
+                if calls.len() == DEPTH {
+                    calls.remove(0);
+                }
+                calls.push(*site_idx);
+                successors.push(State {
+                    def_id: site.callee,
+                    local: binding.param,
+                    fields,
+                    calls,
+                });
+            }
+        }
+
+        // back to the call entered by, or to all the calls.
+        if state.local == Local::from_usize(0) {
+            let mut calls = state.calls.clone();
+            let sites = match calls.pop() {
+                Some(site_idx) => vec![site_idx],
+                None => self
+                    .call_sites
+                    .by_callee
+                    .get(&state.def_id)
+                    .cloned()
+                    .unwrap_or_default(),
+            };
+            for site_idx in sites {
+                let site = &self.call_sites.sites[site_idx];
+                successors.push(State {
+                    def_id: site.caller,
+                    local: site.dest,
+                    fields: state.fields.clone(),
+                    calls: calls.clone(),
+                });
+            }
+        }
+        successors
+    }
+
+    /// Whether each parameter of the function flows to its return value through the calls.
+    pub fn interproc_param_return_deps(&self, def_id: DefId) -> Arg2Ret {
+        let ret = (def_id, Local::from_usize(0));
+        let argc = self.graphs.get(&def_id).map_or(0, |graph| graph.argc);
+        (0..argc + 1)
+            .map(|idx| {
+                self.collect_interproc_reachable((def_id, Local::from_usize(idx)))
+                    .contains(&ret)
+            })
+            .collect()
+    }
+
+    /// The locals of all the functions that the local of the function flows to, itself included.
+    pub fn collect_interproc_reachable(&self, src: (DefId, Local)) -> HashSet<(DefId, Local)> {
+        let mut reachable = HashSet::new();
+        let start = State {
+            def_id: src.0,
+            local: src.1,
+            fields: Vec::new(),
+            calls: Vec::new(),
+        };
+        let mut seen = HashSet::new();
+        let mut worklist = VecDeque::from([start]);
+        while let Some(state) = worklist.pop_front() {
+            if !seen.insert(state.clone()) {
+                continue;
+            }
+            reachable.insert((state.def_id, state.local));
+            worklist.extend(self.successors(&state));
+        }
+        reachable
+    }
+}
+
+// Bind the arguments to the parameters. A closure called through the `Fn*` traits receives itself
+// and a tuple of the arguments, which are spread to its parameters.
+fn bind_args(args: &[Local], callee_graph: &Graph, spread: bool) -> Vec<Binding> {
+    let param = |idx: usize| Local::from_usize(idx + 1);
+    match (spread, args) {
+        (true, [closure, tuple]) => {
+            let mut bindings = vec![Binding {
+                arg: *closure,
+                field: None,
+                param: param(0),
+            }];
+            bindings.extend((1..callee_graph.argc).map(|idx| Binding {
+                arg: *tuple,
+                field: Some(idx - 1),
+                param: param(idx),
+            }));
+            bindings
+        }
+        _ => args
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx < callee_graph.argc)
+            .map(|(idx, arg)| Binding {
+                arg: *arg,
+                field: None,
+                param: param(idx),
+            })
+            .collect(),
+    }
+}
@@ -1,6 +1,7 @@
 pub mod debug;
 pub mod default;
 pub mod graph;
+pub mod inter;
 
 use std::{
     collections::{HashMap, HashSet},
@@ -76,6 +77,19 @@ pub trait DataFlowAnalysis: Analysis {
 
     /// The function returns the dataflow between the arguments and return value for all functions
     fn get_all_arg2ret(&self) -> Arg2RetMap;
+
+    /// If there is a dataflow from `src` to `dst`, two locals of any functions, the function
+    /// returns true; the dataflow goes through the calls to the local functions, from the arguments
+    /// to the parameters and from the return values to the destinations.
+    fn has_interproc_flow_between(&self, src: (DefId, Local), dst: (DefId, Local)) -> bool;
+
+    /// The function returns the locals of all functions that `src` flows to through the calls.
+    fn collect_interproc_reachable(&self, src: (DefId, Local)) -> HashSet<(DefId, Local)>;
+
+    /// The function returns an IndexVec of whether the returned Local depends on the parameter
+    /// Local, following the dataflow into the callees instead of assuming that they return their
+    /// arguments.
+    fn get_fn_interproc_arg2ret(&self, def_id: DefId) -> Arg2Ret;
 }
 
 impl fmt::Display for Arg2RetWrapper {
@@ -12,7 +12,7 @@ use rustc_middle::{
 use rustc_span::def_id::DefId;
 use std::{collections::HashMap, ops::ControlFlow};
 
-use super::*;
+use super::{instance::TyHeap, *};
 use crate::rap_debug;
 
 pub struct OwnedHeapAnalyzer<'tcx> {
@@ -21,6 +21,7 @@ pub struct OwnedHeapAnalyzer<'tcx> {
     fn_set: HashSet<DefId>,
     ty_map: HashMap<Ty<'tcx>, String>,
     adt_recorder: HashSet<DefId>,
+    instances: HashMap<(Ty<'tcx>, Option<VariantIdx>), TyHeap>,
 }
 
 impl<'tcx> Analysis for OwnedHeapAnalyzer<'tcx> {
@@ -31,7 +32,11 @@ impl<'tcx> Analysis for OwnedHeapAnalyzer<'tcx> {
         self.start();
     }
     fn reset(&mut self) {
-        todo!();
+        self.adt_heap.clear();
+        self.fn_set.clear();
+        self.ty_map.clear();
+        self.adt_recorder.clear();
+        self.instances.clear();
     }
 }
 
@@ -41,19 +46,6 @@ impl<'tcx> OwnedHeapAnalysis for OwnedHeapAnalyzer<'tcx> {
     }
 }
 
-// This function is aiming at resolving problems due to 'TyContext' not implementing 'Clone' trait,
-// thus we call function 'copy_ty_context' to simulate 'self.clone()'.
-#[inline(always)]
-pub(crate) fn copy_ty_context(tc: &TyContext) -> TyContext {
-    match tc {
-        TyContext::LocalDecl { local, source_info } => TyContext::LocalDecl {
-            local: local.clone(),
-            source_info: source_info.clone(),
-        },
-        _ => unreachable!(),
-    }
-}
-
 impl<'tcx> OwnedHeapAnalyzer<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>) -> Self {
         Self {
@@ -62,9 +54,14 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
             fn_set: HashSet::new(),
             ty_map: HashMap::new(),
             adt_recorder: HashSet::new(),
+            instances: HashMap::new(),
         }
     }
 
+    pub fn tcx(&self) -> TyCtxt<'tcx> {
+        self.tcx
+    }
+
     pub fn ty_map(&self) -> &HashMap<Ty<'tcx>, String> {
         &self.ty_map
     }
@@ -89,6 +86,14 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
         &mut self.adt_recorder
     }
 
+    pub fn instances(&self) -> &HashMap<(Ty<'tcx>, Option<VariantIdx>), TyHeap> {
+        &self.instances
+    }
+
+    pub fn instances_mut(&mut self) -> &mut HashMap<(Ty<'tcx>, Option<VariantIdx>), TyHeap> {
+        &mut self.instances
+    }
+
     pub fn adt_heap(&self) -> &OHAResultMap {
         &self.adt_heap
     }
@@ -130,16 +135,6 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
     //
     // Those 2 parts can accelerate heap-heap inference in the data-flow analysis.
     pub fn start(&mut self) {
-        #[inline(always)]
-        fn start_channel<M>(mut method: M, v_did: &Vec<DefId>)
-        where
-            M: FnMut(DefId) -> (),
-        {
-            for did in v_did {
-                method(*did);
-            }
-        }
-
         #[inline(always)]
         fn show_heap(ref_type_analysis: &mut OwnedHeapAnalyzer) {
             for elem in ref_type_analysis.adt_heap() {
@@ -172,14 +167,66 @@ impl<'tcx> OwnedHeapAnalyzer<'tcx> {
 
         let dids: Vec<DefId> = self.adt_recorder.iter().map(|did| *did).collect();
 
-        start_channel(|did| self.extract_raw_generic(did), &dids);
-        start_channel(|did| self.extract_raw_generic_prop(did), &dids);
-        start_channel(|did| self.extract_phantom_unit(did), &dids);
-        start_channel(|did| self.extract_heap_prop(did), &dids);
+        self.analyze_adts(&dids);
 
         show_heap(self);
     }
 
+    // Run the sub-phases over the ADTs, each phase over all of them before the next one.
+    pub(crate) fn analyze_adts(&mut self, dids: &[DefId]) {
+        for did in dids {
+            self.extract_raw_generic(*did);
+        }
+        for did in dids {
+            self.extract_raw_generic_prop(*did);
+        }
+        for did in dids {
+            self.extract_phantom_unit(*did);
+        }
+        for did in dids {
+            self.extract_heap_prop(*did);
+        }
+    }
+
+    // Record the ADTs in the type, and in the types of their fields and generic arguments.
+    pub(crate) fn record_ty(&mut self, ty: Ty<'tcx>) {
+        match ty.kind() {
+            TyKind::Adt(adtdef, substs) => {
+                if self.ty_map().get(&ty).is_some() {
+                    return;
+                }
+                self.ty_map_mut().insert(ty, format!("{:?}", ty));
+                self.adt_recorder_mut().insert(adtdef.did());
+
+                for field in adtdef.all_fields() {
+                    self.record_ty(field.ty(self.tcx, substs))
+                }
+
+                for ty in substs.types() {
+                    self.record_ty(ty);
+                }
+            }
+            TyKind::Array(ty, ..) => {
+                self.record_ty(*ty);
+            }
+            TyKind::Slice(ty) => {
+                self.record_ty(*ty);
+            }
+            TyKind::RawPtr(ty, _) => {
+                self.record_ty(*ty);
+            }
+            TyKind::Ref(_, ty, ..) => {
+                self.record_ty(*ty);
+            }
+            TyKind::Tuple(tuple_fields) => {
+                for field in tuple_fields.iter() {
+                    self.record_ty(field);
+                }
+            }
+            _ => {}
+        }
+    }
+
     // Extract params in adt types, the 'param' means one generic parameter acting like 'T', 'A', etc...
     // In the sub-visitor RawGeneric, it will visit the given type recursively, and extract all params.
     //
@@ -412,42 +459,8 @@ impl<'tcx> Visitor<'tcx> for OwnedHeapAnalyzer<'tcx> {
         }
     }
 
-    fn visit_ty(&mut self, ty: Ty<'tcx>, ty_context: TyContext) {
-        match ty.kind() {
-            TyKind::Adt(adtdef, substs) => {
-                if self.ty_map().get(&ty).is_some() {
-                    return;
-                }
-                self.ty_map_mut().insert(ty, format!("{:?}", ty));
-                self.adt_recorder_mut().insert(adtdef.did());
-
-                for field in adtdef.all_fields() {
-                    self.visit_ty(field.ty(self.tcx, substs), copy_ty_context(&ty_context))
-                }
-
-                for ty in substs.types() {
-                    self.visit_ty(ty, copy_ty_context(&ty_context));
-                }
-            }
-            TyKind::Array(ty, ..) => {
-                self.visit_ty(*ty, ty_context);
-            }
-            TyKind::Slice(ty) => {
-                self.visit_ty(*ty, ty_context);
-            }
-            TyKind::RawPtr(ty, _) => {
-                self.visit_ty(*ty, ty_context);
-            }
-            TyKind::Ref(_, ty, ..) => {
-                self.visit_ty(*ty, ty_context);
-            }
-            TyKind::Tuple(tuple_fields) => {
-                for field in tuple_fields.iter() {
-                    self.visit_ty(field, copy_ty_context(&ty_context));
-                }
-            }
-            _ => return,
-        }
+    fn visit_ty(&mut self, ty: Ty<'tcx>, _ty_context: TyContext) {
+        self.record_ty(ty);
     }
 
     fn visit_local_decl(&mut self, local: Local, local_decl: &LocalDecl<'tcx>) {
@@ -534,7 +547,10 @@ impl<'tcx, 'a> TypeVisitor<TyCtxt<'tcx>> for IsolatedParamPropagation<'tcx, 'a>
                     return ControlFlow::Break(());
                 }
 
-                let get_ans = self.heap().get(&adtdef.did()).unwrap();
+                // the ADT has not been analyzed.
+                let Some(get_ans) = self.heap().get(&adtdef.did()) else {
+                    return ControlFlow::Break(());
+                };
                 if get_ans.len() == 0 {
                     return ControlFlow::Break(());
                 }
@@ -586,7 +602,9 @@ impl<'tcx, 'a> TypeVisitor<TyCtxt<'tcx>> for HeapPropagation<'tcx, 'a> {
                     return ControlFlow::Break(());
                 }
 
-                let get_ans = self.heap_res().get(&adtdef.did()).unwrap();
+                let Some(get_ans) = self.heap_res().get(&adtdef.did()) else {
+                    return ControlFlow::Break(());
+                };
                 if get_ans.len() == 0 {
                     return ControlFlow::Break(());
                 }
@@ -667,7 +685,9 @@ impl<'tcx, 'a> TypeVisitor<TyCtxt<'tcx>> for DefaultOwnership<'tcx, 'a> {
                     return ControlFlow::Continue(());
                 }
 
-                let get_ans = self.heap().get(&adtdef.did()).unwrap();
+                let Some(get_ans) = self.heap().get(&adtdef.did()) else {
+                    return ControlFlow::Break(());
+                };
 
                 // handle the secene of Zero Sized Types
                 if get_ans.len() == 0 {
@@ -0,0 +1,311 @@
+//! The owned heap of concrete types.
+//!
+//! The results of `OHAResultMap` are per ADT: a variant that owns no heap by itself may still own
+//! heap through its type parameters. Given a type whose generic arguments are known, e.g.,
+//! `Option<Vec<u8>>`, the query walks the fields of the type with the arguments substituted down
+//! to the ADTs owning the heap allocations, e.g., `Unique`, and tells whether the type owns heap
+//! and through which chains of fields. A type parameter, an opaque type or a trait object left in
+//! the type makes the answer `OwnedHeap::Unknown` unless another field owns heap. A value in
+//! `ManuallyDrop` is not released automatically, so it owns no heap.
+
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use rustc_abi::VariantIdx;
+use rustc_middle::ty::{
+    EarlyBinder, InstanceKind::Item, Ty, TyCtxt, TyKind, TypeVisitableExt, TypingEnv,
+};
+use rustc_span::def_id::DefId;
+
+use super::{default::OwnedHeapAnalyzer, OHAResultMap, OwnedHeap};
+use crate::utils::source::get_fn_name_byid;
+
+/// A step from a value to a part of it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathStep {
+    /// A field of a struct, a union, a tuple or the captures of a closure, or a field of the given
+    /// variant of an enum.
+    Field {
+        variant: Option<(VariantIdx, String)>,
+        index: usize,
+        name: String,
+    },
+    /// The elements of an array or a slice.
+    Element,
+}
+
+/// A chain of fields from a value to a heap allocation it owns.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HeapPath {
+    pub steps: Vec<PathStep>,
+    /// The ADT owning the allocation, e.g., `Unique`.
+    pub unit: DefId,
+}
+
+impl Display for HeapPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut path = String::new();
+        for step in self.steps.iter() {
+            match step {
+                PathStep::Field { variant, name, .. } => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    if let Some((_, variant)) = variant {
+                        path.push_str(variant);
+                        path.push('.');
+                    }
+                    path.push_str(name);
+                }
+                PathStep::Element => path.push_str("[_]"),
+            }
+        }
+        if path.is_empty() {
+            path.push_str("self");
+        }
+        write!(f, "{}: {}", path, get_fn_name_byid(&self.unit))
+    }
+}
+
+/// Whether a type owns heap, and the chains of fields to the allocations it owns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TyHeap {
+    pub heap: OwnedHeap,
+    pub paths: Vec<HeapPath>,
+}
+
+impl TyHeap {
+    fn new(heap: OwnedHeap) -> Self {
+        Self {
+            heap,
+            paths: Vec::new(),
+        }
+    }
+
+    // Add the result of a part of the value reached by the step.
+    fn merge(&mut self, part: TyHeap, step: PathStep) {
+        self.heap = match (self.heap, part.heap) {
+            (OwnedHeap::True, _) | (_, OwnedHeap::True) => OwnedHeap::True,
+            (OwnedHeap::Unknown, _) | (_, OwnedHeap::Unknown) => OwnedHeap::Unknown,
+            _ => OwnedHeap::False,
+        };
+        for mut path in part.paths {
+            path.steps.insert(0, step.clone());
+            self.paths.push(path);
+        }
+    }
+}
+
+/// The query over the results of the ADTs; an ADT without a result owns heap if one of its fields
+/// does, and is unknown otherwise.
+pub struct HeapQuery<'tcx, 'a> {
+    tcx: TyCtxt<'tcx>,
+    adt_heap: &'a OHAResultMap,
+    // the types being evaluated, to stop at recursive types.
+    visiting: HashSet<Ty<'tcx>>,
+}
+
+impl<'tcx, 'a> HeapQuery<'tcx, 'a> {
+    pub fn new(tcx: TyCtxt<'tcx>, adt_heap: &'a OHAResultMap) -> Self {
+        Self {
+            tcx,
+            adt_heap,
+            visiting: HashSet::new(),
+        }
+    }
+
+    pub fn ty_heap(&mut self, ty: Ty<'tcx>) -> TyHeap {
+        self.ty_heap_of_variant(ty, None)
+    }
+
+    /// The result of a value of the type known to be of the variant, e.g., after a downcast.
+    pub fn ty_heap_of_variant(&mut self, ty: Ty<'tcx>, variant: Option<VariantIdx>) -> TyHeap {
+        let ty = self.normalize(ty);
+        if !self.visiting.insert(ty) {
+            return TyHeap::new(OwnedHeap::False);
+        }
+        let res = self.eval(ty, variant);
+        self.visiting.remove(&ty);
+        res
+    }
+
+    fn normalize(&self, ty: Ty<'tcx>) -> Ty<'tcx> {
+        if !ty.has_aliases() {
+            return ty;
+        }
+        self.tcx
+            .try_normalize_erasing_regions(TypingEnv::fully_monomorphized(), ty)
+            .unwrap_or(ty)
+    }
+
+    fn eval(&mut self, ty: Ty<'tcx>, variant: Option<VariantIdx>) -> TyHeap {
+        match ty.kind() {
+            TyKind::Adt(adtdef, _) if adtdef.is_manually_drop() => TyHeap::new(OwnedHeap::False),
+            TyKind::Adt(adtdef, substs) => {
+                let mut res = TyHeap::new(OwnedHeap::False);
+                for (vidx, variant_def) in adtdef.variants().iter_enumerated() {
+                    if variant.is_some_and(|variant| variant != vidx) {
+                        continue;
+                    }
+                    let variant_name = adtdef
+                        .is_enum()
+                        .then(|| (vidx, variant_def.name.to_string()));
+                    for (index, field) in variant_def.fields.iter().enumerate() {
+                        let part = self.ty_heap(field.ty(self.tcx, substs));
+                        let step = PathStep::Field {
+                            variant: variant_name.clone(),
+                            index,
+                            name: field.name.to_string(),
+                        };
+                        res.merge(part, step);
+                    }
+                }
+                if res.heap == OwnedHeap::True {
+                    return res;
+                }
+                // no field owns heap: the ADT owns the allocation by itself, e.g., `Unique`.
+                match self.adt_heap.get(&adtdef.did()) {
+                    Some(units) => {
+                        let owning = units.iter().enumerate().any(|(vidx, unit)| {
+                            variant.is_none_or(|variant| variant.as_usize() == vidx)
+                                && unit.0 == OwnedHeap::True
+                        });
+                        if owning {
+                            res.heap = OwnedHeap::True;
+                            res.paths = vec![HeapPath {
+                                steps: Vec::new(),
+                                unit: adtdef.did(),
+                            }];
+                        }
+                    }
+                    None => res.heap = OwnedHeap::Unknown,
+                }
+                res
+            }
+            TyKind::Tuple(tys) => {
+                let mut res = TyHeap::new(OwnedHeap::False);
+                for (index, field_ty) in tys.iter().enumerate() {
+                    let part = self.ty_heap(field_ty);
+                    let step = PathStep::Field {
+                        variant: None,
+                        index,
+                        name: index.to_string(),
+                    };
+                    res.merge(part, step);
+                }
+                res
+            }
+            TyKind::Closure(_, args) => {
+                let upvars = args.as_closure().tupled_upvars_ty();
+                self.ty_heap(upvars)
+            }
+            TyKind::Array(elem_ty, _) | TyKind::Slice(elem_ty) => {
+                let mut res = TyHeap::new(OwnedHeap::False);
+                let part = self.ty_heap(*elem_ty);
+                res.merge(part, PathStep::Element);
+                res
+            }
+            TyKind::Bool
+            | TyKind::Char
+            | TyKind::Int(..)
+            | TyKind::Uint(..)
+            | TyKind::Float(..)
+            | TyKind::Str
+            | TyKind::Never
+            | TyKind::RawPtr(..)
+            | TyKind::Ref(..)
+            | TyKind::FnDef(..)
+            | TyKind::FnPtr(..) => TyHeap::new(OwnedHeap::False),
+            // type parameters, opaque types, trait objects, coroutines, etc.
+            _ => TyHeap::new(OwnedHeap::Unknown),
+        }
+    }
+}
+
+impl<'tcx> OwnedHeapAnalyzer<'tcx> {
+    /// Analyze the ADTs in the type that have not been analyzed, e.g., the ADTs of other crates
+    /// that only appear in the type.
+    pub fn analyze_adts_of(&mut self, ty: Ty<'tcx>) {
+        self.record_ty(ty);
+        let dids: Vec<DefId> = self
+            .adt_recorder()
+            .iter()
+            .filter(|did| !self.adt_heap().contains_key(did))
+            .copied()
+            .collect();
+        self.analyze_adts(&dids);
+    }
+
+    /// Analyze the ADT of the `DefId` if it has not been analyzed, e.g., an ADT of another crate.
+    pub fn analyze_adt(&mut self, did: DefId) {
+        if self.adt_heap().contains_key(&did) {
+            return;
+        }
+        let ty = EarlyBinder::skip_binder(self.tcx().type_of(did));
+        self.analyze_adts_of(ty);
+    }
+
+    /// Whether the type owns heap, e.g., `True` for `Option<Vec<u8>>` and `False` for
+    /// `Option<u8>`, and the chains of fields to the allocations it owns.
+    pub fn ty_heap(&mut self, ty: Ty<'tcx>) -> TyHeap {
+        self.ty_heap_of_variant(ty, None)
+    }
+
+    /// The result of a value of the type known to be of the variant, e.g., after a downcast.
+    pub fn ty_heap_of_variant(&mut self, ty: Ty<'tcx>, variant: Option<VariantIdx>) -> TyHeap {
+        if let Some(res) = self.instances().get(&(ty, variant)) {
+            return res.clone();
+        }
+        self.analyze_adts_of(ty);
+        let res = HeapQuery::new(self.tcx(), self.adt_heap()).ty_heap_of_variant(ty, variant);
+        self.instances_mut().insert((ty, variant), res.clone());
+        res
+    }
+
+    /// The result of each field of the variant of the ADT, or of each element of the tuple, with
+    /// the generic arguments of the type.
+    pub fn field_heaps(&mut self, ty: Ty<'tcx>, variant: Option<VariantIdx>) -> Vec<TyHeap> {
+        let field_tys: Vec<Ty<'tcx>> = match ty.kind() {
+            TyKind::Adt(adtdef, substs) => {
+                let vidx = match (adtdef.is_enum(), variant) {
+                    (true, Some(vidx)) => vidx,
+                    (true, None) => return Vec::new(),
+                    (false, _) => VariantIdx::from_usize(0),
+                };
+                adtdef.variants()[vidx]
+                    .fields
+                    .iter()
+                    .map(|field| field.ty(self.tcx(), substs))
+                    .collect()
+            }
+            TyKind::Tuple(tys) => tys.iter().collect(),
+            _ => Vec::new(),
+        };
+        field_tys
+            .into_iter()
+            .map(|field_ty| self.ty_heap(field_ty))
+            .collect()
+    }
+
+    /// The results of the ADTs with all their generic arguments known among the types of the
+    /// locals of the functions of the crate.
+    pub fn local_instances(&mut self) -> HashMap<Ty<'tcx>, TyHeap> {
+        let tcx = self.tcx();
+        let mut instances = HashMap::new();
+        for local_def_id in tcx.mir_keys(()) {
+            let body = tcx.instance_mir(Item(local_def_id.to_def_id()));
+            for local_decl in body.local_decls.iter() {
+                let ty = local_decl.ty;
+                if !matches!(ty.kind(), TyKind::Adt(..)) || ty.has_param() {
+                    continue;
+                }
+                if let Entry::Vacant(entry) = instances.entry(ty) {
+                    entry.insert(self.ty_heap(ty));
+                }
+            }
+        }
+        instances
+    }
+}
@@ -1,6 +1,9 @@
 pub mod default;
+pub mod instance;
 
 use rustc_middle::ty::{Ty, TyKind};
+
+use self::instance::TyHeap;
 use rustc_span::def_id::DefId;
 
 use std::{
@@ -81,6 +84,32 @@ impl OHAResultMapWrapper {
         format!("{:?}, <{}>", heap, bit_str)
     }
 }
+/// The results of concrete types, e.g., `Option<Vec<u8>>`, with the chains of fields to the heap
+/// allocations they own.
+pub struct TyHeapMapWrapper<'tcx>(pub HashMap<Ty<'tcx>, TyHeap>);
+
+impl<'tcx> Display for TyHeapMapWrapper<'tcx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Print owned heap of instantiated types ===")?;
+        let mut instances: Vec<(String, &TyHeap)> = self
+            .0
+            .iter()
+            .map(|(ty, res)| (format!("{:?}", ty), res))
+            .collect();
+        instances.sort_by(|a, b| a.0.cmp(&b.0));
+        for (ty, res) in instances {
+            let paths = res
+                .paths
+                .iter()
+                .map(|path| path.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(f, "Instance: {}: {:?}, [{}]", ty, res.heap, paths)?;
+        }
+        Ok(())
+    }
+}
+
 /// This trait provides features for owned heap analysis, which is used to determine if a type owns
 /// memory on heap. Owned heap should be automatically released by default.
 pub trait OwnedHeapAnalysis: Analysis {
@@ -92,7 +121,9 @@ pub trait OwnedHeapAnalysis: Analysis {
     fn is_heapowner<'tcx>(hares: OHAResultMap, ty: Ty<'tcx>) -> Result<bool, &'static str> {
         match ty.kind() {
             TyKind::Adt(adtdef, ..) => {
-                let heapinfo = hares.get(&adtdef.0 .0.did).unwrap();
+                let Some(heapinfo) = hares.get(&adtdef.0 .0.did) else {
+                    return Err("The ADT has not been analyzed");
+                };
                 for item in heapinfo {
                     if item.0 == OwnedHeap::True {
                         return Ok(true);
@@ -109,7 +140,9 @@ pub trait OwnedHeapAnalysis: Analysis {
     fn maybe_heapowner<'tcx>(hares: OHAResultMap, ty: Ty<'tcx>) -> Result<bool, &'static str> {
         match ty.kind() {
             TyKind::Adt(adtdef, ..) => {
-                let heapinfo = hares.get(&adtdef.0 .0.did).unwrap();
+                let Some(heapinfo) = hares.get(&adtdef.0 .0.did) else {
+                    return Err("The ADT has not been analyzed");
+                };
                 for item in heapinfo {
                     if item.0 == OwnedHeap::False && item.1.contains(&true) {
                         return Ok(true);
@@ -10,6 +10,8 @@ use crate::{
                     domain::{ConstConvert, IntervalArithmetic, VarNodes},
                     ConstraintGraph::ConstraintGraph,
                 },
+                feasibility::PathChecker,
+                summary::{call_string_depth, join_return_range, FnSummary, FnSummaryMap},
                 Range, RangeAnalysis,
             },
             ssa_transform::*,
@@ -17,6 +19,7 @@ use crate::{
         Analysis,
     },
     rap_debug, rap_info,
+    utils::boundary::guarded,
 };
 
 use rustc_data_structures::fx::FxHashMap;
@@ -32,21 +35,24 @@ use std::{
     rc::Rc,
 };
 
-use super::{PathConstraint, PathConstraintMap, RAResult, RAResultMap, RAVecResultMap};
+use super::{
+    PathConstraint, PathConstraintMap, PathFeasibility, PathFeasibilityMap, RAResult, RAResultMap,
+    RAVecResultMap,
+};
 pub struct RangeAnalyzer<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub tcx: TyCtxt<'tcx>,
     pub debug: bool,
-    pub ssa_def_id: Option<DefId>,
-    pub essa_def_id: Option<DefId>,
     pub final_vars: RAResultMap<'tcx, T>,
     pub ssa_places_mapping: FxHashMap<DefId, HashMap<Place<'tcx>, HashSet<Place<'tcx>>>>,
     pub fn_constraintgraph_mapping: FxHashMap<DefId, ConstraintGraph<'tcx, T>>,
     pub callgraph: CallGraphInfo<'tcx>,
-    pub body_map: FxHashMap<DefId, Body<'tcx>>,
+    pub ssa_bodies: FxHashMap<DefId, &'tcx SsaBody<'tcx>>,
     pub cg_map: FxHashMap<DefId, Rc<RefCell<ConstraintGraph<'tcx, T>>>>,
     pub vars_map: FxHashMap<DefId, Vec<RefCell<VarNodes<'tcx, T>>>>,
     pub final_vars_vec: RAVecResultMap<'tcx, T>,
     pub path_constraints: PathConstraintMap<'tcx>,
+    pub path_feasibility: PathFeasibilityMap,
+    pub summaries: Rc<RefCell<FnSummaryMap<'tcx, T>>>,
 }
 impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> Analysis for RangeAnalyzer<'tcx, T>
 where
@@ -101,6 +107,22 @@ where
     fn get_all_path_constraints(&self) -> PathConstraintMap<'tcx> {
         self.path_constraints.clone()
     }
+
+    fn get_fn_path_feasibility(&self, def_id: DefId) -> Option<PathFeasibility> {
+        self.path_feasibility.get(&def_id).cloned()
+    }
+
+    fn get_all_path_feasibility(&self) -> PathFeasibilityMap {
+        self.path_feasibility.clone()
+    }
+
+    fn get_fn_summary(&self, def_id: DefId) -> Option<FnSummary<'tcx, T>> {
+        self.summaries.borrow().get(&def_id).cloned()
+    }
+
+    fn get_all_fn_summaries(&self) -> FnSummaryMap<'tcx, T> {
+        self.summaries.borrow().clone()
+    }
 }
 
 impl<'tcx, T> RangeAnalyzer<'tcx, T>
@@ -108,52 +130,27 @@ where
     T: IntervalArithmetic + ConstConvert + Debug,
 {
     pub fn new(tcx: TyCtxt<'tcx>, debug: bool) -> Self {
-        let mut ssa_id = None;
-        let mut essa_id = None;
-
-        if let Some(ssa_def_id) = tcx.hir_crate_items(()).free_items().find(|id| {
-            let hir_id = id.hir_id();
-            if let Some(ident_name) = tcx.hir_opt_name(hir_id) {
-                ident_name.to_string() == "SSAstmt"
-            } else {
-                false
-            }
-        }) {
-            ssa_id = Some(ssa_def_id.owner_id.to_def_id());
-            if let Some(essa_def_id) = tcx.hir_crate_items(()).free_items().find(|id| {
-                let hir_id = id.hir_id();
-                if let Some(ident_name) = tcx.hir_opt_name(hir_id) {
-                    ident_name.to_string() == "ESSAstmt"
-                } else {
-                    false
-                }
-            }) {
-                essa_id = Some(essa_def_id.owner_id.to_def_id());
-            }
-        }
         Self {
             tcx: tcx,
             debug,
-            ssa_def_id: ssa_id,
-            essa_def_id: essa_id,
             final_vars: FxHashMap::default(),
             ssa_places_mapping: FxHashMap::default(),
             fn_constraintgraph_mapping: FxHashMap::default(),
             callgraph: CallGraphInfo::new(),
-            body_map: FxHashMap::default(),
+            ssa_bodies: FxHashMap::default(),
             cg_map: FxHashMap::default(),
             vars_map: FxHashMap::default(),
             final_vars_vec: FxHashMap::default(),
             path_constraints: FxHashMap::default(),
+            path_feasibility: FxHashMap::default(),
+            summaries: Rc::new(RefCell::new(FnSummaryMap::default())),
         }
     }
 
-    fn build_constraintgraph(&mut self, body_mut_ref: &'tcx Body<'tcx>, def_id: DefId) {
-        let ssa_def_id = self.ssa_def_id.expect("SSA definition ID is not set");
-        let essa_def_id = self.essa_def_id.expect("ESSA definition ID is not set");
+    fn build_constraintgraph(&mut self, ssa_body: &SsaBody<'tcx>, def_id: DefId) {
         let mut cg: ConstraintGraph<'tcx, T> =
-            ConstraintGraph::new(def_id, essa_def_id, ssa_def_id);
-        cg.build_graph(body_mut_ref);
+            ConstraintGraph::new(def_id, ssa_body.node_kinds().clone());
+        cg.build_graph(ssa_body.body);
         cg.build_nuutila(false);
         // cg.rap_print_vars();
         // cg.rap_print_final_vars();
@@ -166,8 +163,6 @@ where
     }
 
     fn only_caller_range_analysis(&mut self) {
-        let ssa_def_id = self.ssa_def_id.expect("SSA definition ID is not set");
-        let essa_def_id = self.essa_def_id.expect("ESSA definition ID is not set");
         // ====================================================================
         // PHASE 1: Build all ConstraintGraphs and the complete CallGraph first.
         // ====================================================================
@@ -177,33 +172,44 @@ where
                 let def_id = local_def_id.to_def_id();
 
                 if self.tcx.is_mir_available(def_id) {
-                    let mut body = self.tcx.optimized_mir(def_id).clone();
-                    let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
-                    // Run SSA/ESSA passes
-                    let mut passrunner = PassRunner::new(self.tcx);
-                    passrunner.run_pass(body_mut_ref, ssa_def_id, essa_def_id);
-                    self.body_map.insert(def_id, body);
-                    // Print the MIR after SSA/ESSA passes
-                    if self.debug {
-                        print_diff(self.tcx, body_mut_ref, def_id.into());
-                        print_mir_graph(self.tcx, body_mut_ref, def_id.into());
-                    }
+                    let built = guarded(self.name(), def_id, || {
+                        // Run SSA/ESSA passes
+                        let ssa_body = SsaBody::of(self.tcx, def_id);
+                        // Print the MIR after SSA/ESSA passes
+                        if self.debug {
+                            print_diff(self.tcx, ssa_body.body, def_id);
+                            print_mir_graph(self.tcx, ssa_body.body, def_id);
+                        }
 
-                    self.ssa_places_mapping
-                        .insert(def_id, passrunner.places_map.clone());
-                    // rap_debug!("ssa_places_mapping: {:?}", self.ssa_places_mapping);
-                    // Build and store the constraint graph
-                    self.build_constraintgraph(body_mut_ref, def_id);
-                    // Visit for call graph construction
-                    let mut call_graph_visitor =
-                        CallGraphVisitor::new(self.tcx, def_id, body_mut_ref, &mut self.callgraph);
-                    call_graph_visitor.visit();
+                        self.ssa_places_mapping
+                            .insert(def_id, ssa_body.places_map.clone());
+                        // Build and store the constraint graph
+                        self.build_constraintgraph(ssa_body, def_id);
+                        // Visit for call graph construction
+                        let mut call_graph_visitor = CallGraphVisitor::new(
+                            self.tcx,
+                            def_id,
+                            ssa_body.body,
+                            &mut self.callgraph,
+                        );
+                        self.ssa_bodies.insert(def_id, ssa_body);
+                        call_graph_visitor.visit();
+                    });
+                    if built.is_none() {
+                        // The calls to the function are treated as unknown.
+                        self.ssa_places_mapping.remove(&def_id);
+                        self.cg_map.remove(&def_id);
+                        self.vars_map.remove(&def_id);
+                        self.ssa_bodies.remove(&def_id);
+                    }
                 }
             }
         }
         rap_debug!("PHASE 1 Complete. CallGraph built.");
         // self.callgraph.print_call_graph(); // Optional: for debugging
 
+        self.compute_summaries();
+
         // ====================================================================
         // PHASE 2: Analyze only the call chain start functions.
         // ====================================================================
@@ -240,8 +246,10 @@ where
                 self.tcx.def_path_str(def_id)
             );
             if let Some(cg_cell) = self.cg_map.get(&def_id) {
-                let mut cg = cg_cell.borrow_mut();
-                cg.find_intervals(&self.cg_map, &mut self.vars_map);
+                guarded(self.name(), def_id, || {
+                    let mut cg = cg_cell.borrow_mut();
+                    cg.find_intervals(&self.cg_map, &mut self.vars_map);
+                });
             } else {
                 rap_debug!(
                     "Warning: No ConstraintGraph found for DefId {:?} during analysis of call chain starts.",
@@ -283,6 +291,100 @@ where
 
         rap_debug!("PHASE 2 Complete. Interval analysis finished for call chain start functions.");
     }
+    // Summarize the functions bottom-up over the SCCs of the call graph, where the callees are
+    // represented by their summaries. The functions of a recursive SCC are iterated until their
+    // return ranges are stable.
+    fn compute_summaries(&mut self) {
+        rap_debug!("Computing function summaries...");
+        for cg_cell in self.cg_map.values() {
+            let mut cg = cg_cell.borrow_mut();
+            cg.context.summaries = self.summaries.clone();
+            cg.context.depth = 0;
+        }
+        for scc in self.callgraph.get_sccs() {
+            let scc: Vec<DefId> = scc
+                .into_iter()
+                .filter(|def_id| self.cg_map.contains_key(def_id))
+                .collect();
+            if scc.is_empty() {
+                continue;
+            }
+            let recursive = scc.len() > 1 || self.callgraph.is_self_recursive(scc[0]);
+            if recursive {
+                for &def_id in &scc {
+                    self.summaries
+                        .borrow_mut()
+                        .insert(def_id, FnSummary::bottom());
+                }
+            }
+            let mut round = 0;
+            loop {
+                let mut changed = false;
+                for &def_id in &scc {
+                    let Some(mut summary) = guarded(self.name(), def_id, || self.summarize(def_id))
+                    else {
+                        self.summaries.borrow_mut().remove(&def_id);
+                        continue;
+                    };
+                    let mut summaries = self.summaries.borrow_mut();
+                    if let Some(old) = summaries.get(&def_id) {
+                        summary.ret_range =
+                            join_return_range(&old.ret_range, &summary.ret_range, round);
+                        changed |= old.ret_range != summary.ret_range;
+                    }
+                    summaries.insert(def_id, summary);
+                }
+                round += 1;
+                if !recursive || !changed {
+                    break;
+                }
+            }
+        }
+        // Restore the graphs for the analysis of the call chains.
+        for (def_id, cg_cell) in self.cg_map.iter() {
+            let mut cg = cg_cell.borrow_mut();
+            let varnodes_vec = self.vars_map.get_mut(def_id).unwrap();
+            cg.reset_vars(varnodes_vec);
+            varnodes_vec.truncate(1);
+            cg.context.depth = call_string_depth();
+        }
+    }
+
+    fn summarize(&mut self, def_id: DefId) -> FnSummary<'tcx, T> {
+        let cg_cell = self.cg_map[&def_id].clone();
+        let mut cg = cg_cell.borrow_mut();
+        cg.reset_vars(self.vars_map.get_mut(&def_id).unwrap());
+        cg.find_intervals(&self.cg_map, &mut self.vars_map);
+        let ret_range = cg
+            .get_vars()
+            .get(&Place::return_place())
+            .map(|node| node.get_range().clone())
+            .unwrap_or(Range::default(T::min_value()));
+        let summary = FnSummary::new(
+            self.tcx,
+            self.tcx.optimized_mir(def_id),
+            cg.return_expr(),
+            ret_range,
+            &self.summaries.borrow(),
+        );
+        rap_debug!(
+            "Summary of {}: {} in {}",
+            self.tcx.def_path_str(def_id),
+            summary.ret,
+            summary.ret_range
+        );
+        summary
+    }
+
+    fn check_path_feasibility(&mut self, def_id: DefId, paths: &[Vec<usize>]) {
+        let checker = PathChecker::new(self.tcx, def_id);
+        let feasibility = paths
+            .iter()
+            .map(|path| (path.clone(), checker.check(path)))
+            .collect();
+        self.path_feasibility.insert(def_id, feasibility);
+    }
+
     pub fn start_path_constraints_analysis_for_defid(
         &mut self,
         def_id: DefId,
@@ -312,6 +414,7 @@ where
                 self.tcx.def_path_str(def_id),
                 result
             );
+            self.check_path_feasibility(def_id, &paths);
             self.path_constraints.insert(def_id, result.clone());
             Some(result)
         } else {
@@ -322,37 +425,9 @@ where
         for local_def_id in self.tcx.iter_local_def_id() {
             if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
                 let def_id = local_def_id.to_def_id();
-
-                if self.tcx.is_mir_available(def_id) {
-                    let mut body = self.tcx.optimized_mir(def_id).clone();
-                    let body_mut_ref = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
-
-                    let mut cg: ConstraintGraph<'tcx, T> = ConstraintGraph::new_without_ssa(def_id);
-                    let mut graph = MopGraph::new(self.tcx, def_id);
-                    graph.solve_scc();
-                    // rap_info!("child_scc: {:?}\n", graph.child_scc);
-                    // rap_info!("scc_indices: {:?}\n", graph.scc_indices);
-                    // rap_info!("blocks: {:?}\n", graph.blocks);
-                    let paths: Vec<Vec<usize>> = graph.get_all_branch_sub_blocks_paths();
-                    let result = cg.start_analyze_path_constraints(body_mut_ref, &paths);
-                    rap_debug!(
-                        "Paths for function {}: {:?}",
-                        self.tcx.def_path_str(def_id),
-                        paths
-                    );
-                    let switchbbs = cg.switchbbs.clone();
-                    rap_debug!(
-                        "Switch basicblocks for function {}: {:?}",
-                        self.tcx.def_path_str(def_id),
-                        switchbbs
-                    );
-                    rap_debug!(
-                        "Path Constraints Analysis Result for function {}: {:?}",
-                        self.tcx.def_path_str(def_id),
-                        result
-                    );
-                    self.path_constraints.insert(def_id, result);
-                }
+                guarded(self.name(), def_id, || {
+                    self.start_path_constraints_analysis_for_defid(def_id)
+                });
             }
         }
     }
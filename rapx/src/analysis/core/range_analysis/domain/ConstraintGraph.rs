@@ -6,9 +6,11 @@
 #![allow(non_snake_case)]
 
 use super::domain::*;
+use crate::analysis::core::range_analysis::summary::CallContext;
 use crate::analysis::core::range_analysis::{Range, RangeType};
 
 use crate::analysis::core::range_analysis::domain::SymbolicExpr::*;
+use crate::analysis::core::ssa_transform::{sigma_cmp, SsaNodeKind};
 use crate::rap_debug;
 use crate::rap_info;
 use crate::rap_trace;
@@ -49,8 +51,7 @@ pub struct ConstraintGraph<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     constant_vector: Vec<T>, // Vector for constants from an SCC
 
     pub inst_rand_place_set: Vec<Place<'tcx>>,
-    pub essa: DefId,
-    pub ssa: DefId,
+    pub ssa_nodes: HashMap<Location, SsaNodeKind>, // The phi and sigma statements of the SSA body
     // values_switchmap: ValuesSwitchMap<'tcx, T>, // Store intervals for switch branches
     pub index: i32,
     pub dfs: HashMap<&'tcx Place<'tcx>, i32>,
@@ -65,6 +66,7 @@ pub struct ConstraintGraph<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub rerurn_places: HashSet<&'tcx Place<'tcx>>,
     pub switchbbs: HashMap<BasicBlock, (Place<'tcx>, Place<'tcx>)>,
     pub const_func_place: HashMap<&'tcx Place<'tcx>, usize>,
+    pub context: CallContext<'tcx, T>, // The call string and the function summaries for calls
 }
 
 impl<'tcx, T> ConstraintGraph<'tcx, T>
@@ -74,7 +76,7 @@ where
     pub fn convert_const(c: &Const) -> Option<T> {
         T::from_const(c)
     }
-    pub fn new(self_def_id: DefId, essa: DefId, ssa: DefId) -> Self {
+    pub fn new(self_def_id: DefId, ssa_nodes: HashMap<Location, SsaNodeKind>) -> Self {
         Self {
             self_def_id,
             vars: VarNodes::new(),
@@ -87,8 +89,7 @@ where
             // values_switchmap: ValuesSwitchMap::new(),
             constant_vector: Vec::new(),
             inst_rand_place_set: Vec::new(),
-            essa,
-            ssa,
+            ssa_nodes,
             index: 0,
             dfs: HashMap::new(),
             root: HashMap::new(),
@@ -102,6 +103,7 @@ where
             rerurn_places: HashSet::new(),
             switchbbs: HashMap::new(),
             const_func_place: HashMap::new(),
+            context: CallContext::new(),
         }
     }
     pub fn new_without_ssa(self_def_id: DefId) -> Self {
@@ -117,8 +119,7 @@ where
             // values_switchmap: ValuesSwitchMap::new(),
             constant_vector: Vec::new(),
             inst_rand_place_set: Vec::new(),
-            essa: self_def_id, // Assuming essa is the same as self_def_id
-            ssa: self_def_id,  // Assuming ssa is the same as self_def_id
+            ssa_nodes: HashMap::new(),
             index: 0,
             dfs: HashMap::new(),
             root: HashMap::new(),
@@ -132,6 +133,7 @@ where
             rerurn_places: HashSet::new(),
             switchbbs: HashMap::new(),
             const_func_place: HashMap::new(),
+            context: CallContext::new(),
         }
     }
     pub fn build_final_vars(
@@ -303,8 +305,14 @@ where
             let block_data = &body[block];
             // Traverse statements
 
-            for statement in block_data.statements.iter() {
-                self.build_operations(statement, block);
+            for (statement_index, statement) in block_data.statements.iter().enumerate() {
+                self.build_operations(
+                    statement,
+                    Location {
+                        block,
+                        statement_index,
+                    },
+                );
             }
             self.build_terminator(block, block_data.terminator.as_ref().unwrap());
         }
@@ -671,61 +679,54 @@ where
             }
         }
     }
-    pub fn build_operations(&mut self, inst: &'tcx Statement<'tcx>, block: BasicBlock) {
+    pub fn build_operations(&mut self, inst: &'tcx Statement<'tcx>, location: Location) {
+        let block = location.block;
         match &inst.kind {
-            StatementKind::Assign(box (sink, rvalue)) => {
-                match rvalue {
-                    Rvalue::BinaryOp(op, box (op1, op2)) => match op {
-                        BinOp::Add
-                        | BinOp::Sub
-                        | BinOp::Mul
-                        | BinOp::Div
-                        | BinOp::Rem
-                        | BinOp::AddUnchecked => {
-                            self.add_binary_op(sink, inst, op1, op2, *op);
-                        }
-                        BinOp::AddWithOverflow => {
-                            self.add_binary_op(sink, inst, op1, op2, *op);
-                        }
-                        BinOp::SubUnchecked => {
-                            self.add_binary_op(sink, inst, op1, op2, *op);
-                        }
-                        BinOp::SubWithOverflow => {
-                            self.add_binary_op(sink, inst, op1, op2, *op);
-                        }
-                        BinOp::MulUnchecked => {
-                            self.add_binary_op(sink, inst, op1, op2, *op);
-                        }
-                        BinOp::MulWithOverflow => {
-                            self.add_binary_op(sink, inst, op1, op2, *op);
-                        }
-
-                        _ => {}
-                    },
-                    Rvalue::UnaryOp(unop, operand) => {
-                        self.add_unary_op(sink, inst, operand, *unop);
+            StatementKind::Assign(box (sink, rvalue)) => match rvalue {
+                Rvalue::BinaryOp(op, box (op1, op2)) => match op {
+                    BinOp::Add
+                    | BinOp::Sub
+                    | BinOp::Mul
+                    | BinOp::Div
+                    | BinOp::Rem
+                    | BinOp::AddUnchecked => {
+                        self.add_binary_op(sink, inst, op1, op2, *op);
                     }
-                    Rvalue::Aggregate(kind, operends) => {
-                        match **kind {
-                            AggregateKind::Adt(def_id, _, _, _, _) => {
-                                if def_id == self.essa {
-                                    self.add_essa_op(sink, inst, operends, block);
-                                    // rap_trace!("Adt{:?}\n", operends);
-                                }
-                                if def_id == self.ssa {
-                                    self.add_ssa_op(sink, inst, operends);
-                                    // rap_trace!("Adt{:?}\n", operends);
-                                }
-                            }
-                            _ => {}
-                        }
+                    BinOp::AddWithOverflow => {
+                        self.add_binary_op(sink, inst, op1, op2, *op);
+                    }
+                    BinOp::SubUnchecked => {
+                        self.add_binary_op(sink, inst, op1, op2, *op);
                     }
-                    Rvalue::Use(operend) => {
-                        self.add_use_op(sink, inst, operend);
+                    BinOp::SubWithOverflow => {
+                        self.add_binary_op(sink, inst, op1, op2, *op);
                     }
+                    BinOp::MulUnchecked => {
+                        self.add_binary_op(sink, inst, op1, op2, *op);
+                    }
+                    BinOp::MulWithOverflow => {
+                        self.add_binary_op(sink, inst, op1, op2, *op);
+                    }
+
                     _ => {}
+                },
+                Rvalue::UnaryOp(unop, operand) => {
+                    self.add_unary_op(sink, inst, operand, *unop);
                 }
-            }
+                Rvalue::Aggregate(_, operends) => match self.ssa_nodes.get(&location) {
+                    Some(SsaNodeKind::Sigma) => {
+                        self.add_essa_op(sink, inst, operends, block);
+                    }
+                    Some(SsaNodeKind::Phi) => {
+                        self.add_ssa_op(sink, inst, operends);
+                    }
+                    None => {}
+                },
+                Rvalue::Use(operend) => {
+                    self.add_use_op(sink, inst, operend);
+                }
+                _ => {}
+            },
             _ => {}
         }
     }
@@ -802,6 +803,7 @@ where
                 terminator, // Pass the allocated dummy statement
                 arg_operands,
                 *func_def_id.unwrap(), // Use the DefId if available
+                (self.self_def_id, block),
             );
             rap_debug!("call_op: {:?}\n", call_op);
             let bop_index = self.oprs.len();
@@ -1165,7 +1167,7 @@ where
         let estimated_interval = match op_kind {
             BasicOpKind::Call(call_op) => {
                 // For a call, use the special inter-procedural eval.
-                call_op.eval_call(&self.vars, cg_map, vars_map)
+                call_op.eval_call(&self.vars, &self.context, cg_map, vars_map)
             }
             _ => {
                 // For all other operations, use the simple, generic eval.
@@ -1230,7 +1232,7 @@ where
         let estimated_interval = match op_kind {
             BasicOpKind::Call(call_op) => {
                 // For a call, use the special inter-procedural eval.
-                call_op.eval_call(&self.vars, cg_map, vars_map)
+                call_op.eval_call(&self.vars, &self.context, cg_map, vars_map)
             }
             _ => {
                 // For all other operations, use the simple, generic eval.
@@ -1386,7 +1388,7 @@ where
                     let new_range = op_kind.eval(&self.vars);
                     let new_range = match op_kind {
                         BasicOpKind::Call(call_op) => {
-                            call_op.eval_call(&self.vars, cg_map, vars_map)
+                            call_op.eval_call(&self.vars, &self.context, cg_map, vars_map)
                         }
                         _ => {
                             // For all other operations, use the simple, generic eval.
@@ -1427,7 +1429,7 @@ where
                 op
             );
             if let BasicOpKind::Call(call_op) = &self.oprs[*op] {
-                let new_range = call_op.eval_call(&self.vars, cg_map, vars_map);
+                let new_range = call_op.eval_call(&self.vars, &self.context, cg_map, vars_map);
                 rap_trace!("Setting range for {:?} to {:?}\n", sink, new_range);
                 self.vars.get_mut(sink).unwrap().set_range(new_range);
             }
@@ -1520,21 +1522,28 @@ where
     }
     pub fn merge_return_places(&mut self) {
         rap_trace!("====Merging return places====\n");
+        if self.rerurn_places.is_empty() {
+            return;
+        }
+        // `_0` may also be assigned by an operation, e.g., the call in a tail position.
+        let mut merged_range = match self.defmap.get(&Place::return_place()) {
+            Some(_) => self.vars[&Place::return_place()].get_range().clone(),
+            None => Range::default(T::min_value()),
+        };
         for &place in self.rerurn_places.iter() {
             rap_debug!("merging return place {:?}\n", place);
-            let mut merged_range = Range::default(T::min_value());
             if let Some(opset) = self.vars.get(place) {
                 merged_range = merged_range.unionwith(opset.get_range());
             }
-            if let Some(return_node) = self.vars.get_mut(&Place::return_place()) {
-                rap_debug!("Assigning final merged range {} to _0", merged_range);
-                return_node.set_range(merged_range);
-            } else {
-                // This case is unlikely for functions that return a value, as `_0`
-                // should have been created during the initial graph build.
-                // We add a trace message for robustness.
-                rap_trace!("Warning: RETURN_PLACE (_0) not found in self.vars. Cannot assign merged return range.");
-            }
+        }
+        if let Some(return_node) = self.vars.get_mut(&Place::return_place()) {
+            rap_debug!("Assigning final merged range {} to _0", merged_range);
+            return_node.set_range(merged_range);
+        } else {
+            // This case is unlikely for functions that return a value, as `_0`
+            // should have been created during the initial graph build.
+            // We add a trace message for robustness.
+            rap_trace!("Warning: RETURN_PLACE (_0) not found in self.vars. Cannot assign merged return range.");
         }
     }
 
@@ -1672,6 +1681,27 @@ where
         self.get_symbolic_expression_recursive(place, &mut memo, &mut in_progress)
     }
 
+    /// The symbolic expression of the return value, which joins all the places returned.
+    pub fn return_expr(&self) -> SymbolicExpr<'tcx> {
+        let mut places: Vec<&'tcx Place<'tcx>> = self.rerurn_places.iter().copied().collect();
+        places.sort_by_key(|place| place.local);
+        if let Some((&place, _)) = self.defmap.get_key_value(&Place::return_place()) {
+            places.push(place);
+        }
+        let mut exprs: Vec<SymbolicExpr<'tcx>> = places
+            .into_iter()
+            .map(|place| {
+                self.get_symbolicexpression(place)
+                    .unwrap_or(SymbolicExpr::Unknown(UnknownReason::CannotParse))
+            })
+            .collect();
+        if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            SymbolicExpr::Ssa(exprs)
+        }
+    }
+
     fn get_symbolic_expression_recursive(
         &self,
         place: &'tcx Place<'tcx>,
@@ -1900,14 +1930,24 @@ where
                         )
                     }
                     super::domain::IntervalType::Basic(basic_interval) => {
-                        if let Some(vbm) = self.values_branchmap.get(essa_op.get_source()) {
-                            rap_trace!("Warning: EssaOp with BasicInterval constraint. Cannot directly reconstruct original BinOp and constraint_operand from EssaOp's internal state. Returning Unknown for constraint part.",);
-                            // Fallback if we cannot precisely reconstruct
+                        // The σ node is `(source, bound, cmp)`, see `Replacer::essa_assign_statement`.
+                        let StatementKind::Assign(box (_, Rvalue::Aggregate(_, operands))) =
+                            &essa_op.inst.kind
+                        else {
+                            rap_trace!("Warning: EssaOp with BasicInterval constraint is not a lowered sigma node. Returning Unknown for constraint part.",);
                             return Some(SymbolicExpr::Unknown(UnknownReason::CannotParse));
-                        } else {
-                            rap_trace!("Warning: EssaOp with BasicInterval constraint, but source not found in values_branchmap. Returning Unknown for constraint part.",);
+                        };
+                        let Some(bin_op) = operands.raw.get(2).and_then(sigma_cmp) else {
+                            rap_trace!("Warning: EssaOp with BasicInterval constraint has no comparison. Returning Unknown for constraint part.",);
                             return Some(SymbolicExpr::Unknown(UnknownReason::CannotParse));
-                        }
+                        };
+                        let constraint_operand = match &operands.raw[1] {
+                            Operand::Copy(place) | Operand::Move(place) => {
+                                VarorConst::Place(*place)
+                            }
+                            Operand::Constant(c) => VarorConst::Constant(c.const_),
+                        };
+                        (constraint_operand, bin_op)
                     }
                 };
 
@@ -1921,7 +1961,25 @@ where
                 rap_trace!("Encountered unexpected ControlDep operation defining a place. Returning Unknown.");
                 Some(SymbolicExpr::Unknown(UnknownReason::CannotParse))
             }
-            BasicOpKind::Call(call_op) => todo!(),
+            BasicOpKind::Call(call_op) => {
+                let mut args = Vec::new();
+                for arg in call_op.args.iter() {
+                    let arg_expr = match arg {
+                        Operand::Copy(place) | Operand::Move(place) => {
+                            let Some((&place, _)) = self.vars.get_key_value(place) else {
+                                return Some(SymbolicExpr::Unknown(UnknownReason::CannotParse));
+                            };
+                            self.get_symbolic_expression_recursive(place, memo, in_progress)?
+                        }
+                        Operand::Constant(c) => SymbolicExpr::Constant(c.const_),
+                    };
+                    args.push(arg_expr);
+                }
+                Some(SymbolicExpr::Call {
+                    def_id: call_op.def_id,
+                    args,
+                })
+            }
         }
     }
     pub fn start_analyze_path_constraints(
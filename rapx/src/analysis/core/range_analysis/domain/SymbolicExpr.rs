@@ -19,6 +19,7 @@ use std::ops::{Add, Mul, Sub};
 use crate::{
     analysis::core::range_analysis::domain::domain::{ConstConvert, IntervalArithmetic},
     rap_trace,
+    utils::source::get_fn_name_byid,
 };
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UnknownReason {
@@ -79,6 +80,11 @@ pub enum SymbolicExpr<'tcx> {
         bin_op: BinOp,
     },
     Discriminant(Box<SymbolicExpr<'tcx>>),
+    /// The value returned by a direct call to `def_id`.
+    Call {
+        def_id: DefId,
+        args: Vec<SymbolicExpr<'tcx>>,
+    },
     NullaryOp(NullOp<'tcx>, Ty<'tcx>),
     ThreadLocalRef(DefId),
     Unknown(UnknownReason),
@@ -184,6 +190,10 @@ impl<'tcx> fmt::Display for SymbolicExpr<'tcx> {
                 )
             }
             SymbolicExpr::Discriminant(expr) => write!(f, "discriminant({})", expr),
+            SymbolicExpr::Call { def_id, args } => {
+                let parts: Vec<String> = args.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}({})", get_fn_name_byid(def_id), parts.join(", "))
+            }
             SymbolicExpr::NullaryOp(op, ty) => write!(f, "{:?}({})", op, ty),
             SymbolicExpr::ThreadLocalRef(def_id) => write!(f, "tls_{:?}", def_id),
             SymbolicExpr::Unknown(reason) => write!(f, "{{{:?}}}", reason),
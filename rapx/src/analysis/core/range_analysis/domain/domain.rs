@@ -6,9 +6,10 @@
 #![allow(non_snake_case)]
 
 use crate::analysis::core::range_analysis::domain::ConstraintGraph::ConstraintGraph;
+use crate::analysis::core::range_analysis::summary::CallContext;
 use crate::analysis::core::range_analysis::{Range, RangeType};
 use crate::{rap_debug, rap_trace};
-use num_traits::{Bounded, CheckedAdd, CheckedSub, One, ToPrimitive, Zero};
+use num_traits::{Bounded, CheckedAdd, CheckedMul, CheckedSub, One, ToPrimitive, Zero};
 use rustc_abi::Size;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::DefId;
@@ -86,6 +87,7 @@ pub trait IntervalArithmetic:
     + One
     + CheckedAdd
     + CheckedSub
+    + CheckedMul
     + Add<Output = Self>
     + Sub<Output = Self>
     + Mul<Output = Self>
@@ -102,6 +104,7 @@ impl<T> IntervalArithmetic for T where
         + Copy
         + CheckedAdd
         + CheckedSub
+        + CheckedMul
         + Add<Output = T>
         + Sub<Output = T>
         + Mul<Output = T>
@@ -394,6 +397,7 @@ pub struct CallOp<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
     pub inst: &'tcx Terminator<'tcx>,
     pub args: Vec<Operand<'tcx>>,
     pub def_id: DefId,
+    pub call_site: (DefId, BasicBlock), // The caller and the block of the call
 }
 
 impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
@@ -406,6 +410,7 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
         inst: &'tcx Terminator<'tcx>,
         args: Vec<Operand<'tcx>>,
         def_id: DefId,
+        call_site: (DefId, BasicBlock),
     ) -> Self {
         Self {
             intersect,
@@ -413,18 +418,51 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
             inst,
             args,
             def_id,
+            call_site,
         }
     }
 
     pub fn eval(&self, caller_vars: &VarNodes<'tcx, T>) -> Range<T> {
         return Range::default(T::min_value());
     }
+    // The ranges of the arguments in the caller, where the unknown ones are unbounded.
+    fn arg_ranges(&self, caller_vars: &VarNodes<'tcx, T>) -> Vec<Range<T>> {
+        let top = Range::new(T::min_value(), T::max_value(), RangeType::Regular);
+        self.args
+            .iter()
+            .map(|arg| match arg {
+                Operand::Copy(place) | Operand::Move(place) => caller_vars
+                    .get(place)
+                    .map(|node| node.get_range().clone())
+                    .filter(|range| range.is_regular())
+                    .unwrap_or_else(|| top.clone()),
+                Operand::Constant(c) => Self::convert_const(&c.const_)
+                    .map(|value| Range::new(value, value, RangeType::Regular))
+                    .unwrap_or_else(|| top.clone()),
+            })
+            .collect()
+    }
     pub fn eval_call(
         &self,
         caller_vars: &VarNodes<'tcx, T>,
+        context: &CallContext<'tcx, T>,
         cg_map: &FxHashMap<DefId, Rc<RefCell<ConstraintGraph<'tcx, T>>>>,
         vars_map: &mut FxHashMap<DefId, Vec<RefCell<VarNodes<'tcx, T>>>>,
     ) -> Range<T> {
+        // 0. Instantiate the summary of the callee. It is all we use once the call string
+        //    reaches the depth limit.
+        let summarized = context.instantiate(self.def_id, &self.arg_ranges(caller_vars));
+        if let Some(range) = &summarized {
+            if !context.is_within_depth() {
+                rap_debug!(
+                    "Instantiating the summary of {:?} at {:?}: {}",
+                    self.def_id,
+                    self.call_site,
+                    range
+                );
+                return range.clone();
+            }
+        }
         // 1. Find the callee's ConstraintGraph in the map.
         if let Some(rc_callee_cg_cell) = cg_map.get(&self.def_id) {
             rap_debug!(
@@ -509,7 +547,12 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
                 //    NOTE: This is a simplification. A full implementation would use memoization
                 //    or a bottom-up analysis order to avoid re-analyzing functions repeatedly.
                 //    For now, we re-run it to ensure argument values are propagated.
+                let call_string = std::mem::replace(
+                    &mut callee_cg.context.call_string,
+                    context.extend(self.call_site),
+                );
                 callee_cg.find_intervals(cg_map, vars_map);
+                callee_cg.context.call_string = call_string;
 
                 // 5. Retrieve the return value.
                 //    The return value is stored in `_0` (RETURN_PLACE).
@@ -520,6 +563,9 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
                 // The `rerurn_places` set in the callee's graph tracks these.
                 if let Some(return_node) = callee_cg.vars.get_mut(&Place::return_place()) {
                     return_range = return_node.get_range().clone();
+                    if let Some(range) = &summarized {
+                        return_range = return_range.intersectwith(range);
+                    }
                     rap_debug!(" final return range {} ", return_range);
                     return return_range;
                 }
@@ -532,12 +578,16 @@ impl<'tcx, T: IntervalArithmetic + ConstConvert + Debug> CallOp<'tcx, T> {
                 callee_cg.reset_vars(callee_varnodes_vec);
             } else {
                 // Recursive call detected or graph is already borrowed.
-                // Conservatively return a full range.
+                // Use the summary if any, or conservatively return a full range.
                 rap_trace!(
-                    "Recursive call or existing borrow for {:?}, returning top.",
+                    "Recursive call or existing borrow for {:?}, using its summary.",
                     self.def_id
                 );
-                return Range::new(T::min_value(), T::max_value(), RangeType::Regular);
+                return summarized.unwrap_or(Range::new(
+                    T::min_value(),
+                    T::max_value(),
+                    RangeType::Regular,
+                ));
             }
         }
 
@@ -170,12 +170,16 @@ where
     }
 
     pub fn mul(&self, other: &Range<T>) -> Range<T> {
-        let candidates = vec![
-            self.get_lower().clone() * other.get_lower().clone(),
-            self.get_lower().clone() * other.get_upper().clone(),
-            self.get_upper().clone() * other.get_lower().clone(),
-            self.get_upper().clone() * other.get_upper().clone(),
+        let candidates = [
+            self.get_lower().checked_mul(&other.get_lower()),
+            self.get_lower().checked_mul(&other.get_upper()),
+            self.get_upper().checked_mul(&other.get_lower()),
+            self.get_upper().checked_mul(&other.get_upper()),
         ];
+        // An overflowing product may wrap to any value.
+        let Some(candidates) = candidates.into_iter().collect::<Option<Vec<T>>>() else {
+            return Range::new(T::min_value(), T::max_value(), RangeType::Regular);
+        };
         let min = candidates
             .iter()
             .cloned()
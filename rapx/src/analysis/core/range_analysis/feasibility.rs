@@ -0,0 +1,602 @@
+use crate::rap_trace;
+use rustc_abi::{FieldIdx, Size};
+use rustc_data_structures::{fx::FxHashMap, fx::FxHashSet, graph::scc::Sccs};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        AggregateKind, BasicBlock, BinOp, Body, BorrowKind, CastKind, Local, Operand, Place,
+        ProjectionElem, Rvalue, StatementKind, TerminatorKind, UnOp,
+    },
+    ty::{Ty, TyCtxt, TyKind, TypingEnv},
+};
+use std::{cell::RefCell, env, fmt};
+use z3::{
+    ast::{self, Ast},
+    Config, Context, SatResult, Solver,
+};
+
+/// The verdict of the solver on the constraints collected along a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feasibility {
+    Feasible,
+    Infeasible,
+    /// The solver timed out or the path does not belong to the body.
+    Unknown,
+}
+
+impl fmt::Display for Feasibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Feasibility::Feasible => write!(f, "feasible"),
+            Feasibility::Infeasible => write!(f, "infeasible"),
+            Feasibility::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Whether SafeDrop, MoP and Senryx skip the paths proved infeasible; set by `PRUNE_PATHS`.
+pub fn prune_infeasible_paths() -> bool {
+    env::var("PRUNE_PATHS")
+        .map(|val| matches!(val.to_lowercase().as_str(), "true" | "1"))
+        .unwrap_or(false)
+}
+
+/// Whether the path being visited is proved infeasible if it continues to `bb`. Without a
+/// checker, i.e., when the paths are not pruned, every branch is feasible.
+pub fn is_infeasible_branch(
+    checker: Option<&PathChecker<'_>>,
+    path: &mut Vec<usize>,
+    bb: usize,
+) -> bool {
+    let Some(checker) = checker else {
+        return false;
+    };
+    path.push(bb);
+    let infeasible = checker.is_infeasible(path);
+    path.pop();
+    infeasible
+}
+
+const SOLVER_TIMEOUT_MSEC: u64 = 1000;
+
+thread_local! {
+    // One context for the solvers of all functions, so that they can keep their assertions
+    // between queries.
+    static Z3_CONTEXT: &'static Context = {
+        let mut cfg = Config::new();
+        cfg.set_timeout_msec(SOLVER_TIMEOUT_MSEC);
+        Box::leak(Box::new(Context::new(&cfg)))
+    };
+}
+
+/// Checks the satisfiability of the branch conditions along a path of basic blocks.
+///
+/// The path is executed symbolically: integer, bool and char places are encoded as Z3 bit-vectors
+/// whose width follows their MIR type, and every `SwitchInt` or `Assert` edge taken by the path
+/// adds a constraint over them. Anything the encoding cannot follow (dereferences, borrowed
+/// locals, call results, unsupported types) is a fresh unconstrained value, so a path is only
+/// reported infeasible when it is infeasible for every possible input.
+///
+/// Paths may skip blocks, e.g., those produced by SCC condensation. When two consecutive blocks
+/// are not connected by an edge, all knowledge is dropped; when a block lies on a cycle, the
+/// locals written within the cycle are forgotten on entry, so one visit stands for any iteration.
+pub struct PathChecker<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    def_id: DefId,
+    body: &'tcx Body<'tcx>,
+    typing_env: TypingEnv<'tcx>,
+    // the locals whose value may change through a pointer; they are never tracked.
+    escaped: FxHashSet<Local>,
+    // for the blocks on a cycle, the locals written within the cycle.
+    loop_writes: FxHashMap<usize, FxHashSet<Local>>,
+    prefix: RefCell<Prefix<'tcx>>,
+}
+
+impl<'tcx> PathChecker<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        let body = tcx.optimized_mir(def_id);
+        let typing_env = TypingEnv::post_analysis(tcx, def_id);
+
+        let mut escaped = FxHashSet::default();
+        let mut writes: Vec<FxHashSet<Local>> = Vec::with_capacity(body.basic_blocks.len());
+        for data in body.basic_blocks.iter() {
+            let mut written = FxHashSet::default();
+            for stmt in &data.statements {
+                match &stmt.kind {
+                    StatementKind::Assign(box (place, rvalue)) => {
+                        written.insert(place.local);
+                        match rvalue {
+                            Rvalue::Ref(_, BorrowKind::Shared, borrowed)
+                            | Rvalue::Ref(_, BorrowKind::Fake(_), borrowed) => {
+                                // shared borrows can only mutate through interior mutability.
+                                let ty = borrowed.ty(body, tcx).ty;
+                                if !ty.is_freeze(tcx, typing_env) {
+                                    escaped.insert(borrowed.local);
+                                }
+                            }
+                            Rvalue::Ref(_, _, borrowed) | Rvalue::RawPtr(_, borrowed) => {
+                                escaped.insert(borrowed.local);
+                            }
+                            _ => {}
+                        }
+                    }
+                    StatementKind::SetDiscriminant { place, .. } | StatementKind::Deinit(place) => {
+                        written.insert(place.local);
+                    }
+                    _ => {}
+                }
+            }
+            if let TerminatorKind::Call { destination, .. } = &data.terminator().kind {
+                written.insert(destination.local);
+            }
+            writes.push(written);
+        }
+
+        let sccs: Sccs<BasicBlock, usize> = Sccs::new(&body.basic_blocks);
+        let mut scc_blocks: FxHashMap<usize, Vec<usize>> = FxHashMap::default();
+        for (bb, _) in body.basic_blocks.iter_enumerated() {
+            scc_blocks
+                .entry(sccs.scc(bb))
+                .or_default()
+                .push(bb.as_usize());
+        }
+        let mut loop_writes = FxHashMap::default();
+        for blocks in scc_blocks.values() {
+            let first = BasicBlock::from_usize(blocks[0]);
+            let self_loop = body.basic_blocks[first]
+                .terminator()
+                .successors()
+                .any(|succ| succ == first);
+            if blocks.len() == 1 && !self_loop {
+                continue;
+            }
+            let written: FxHashSet<Local> = blocks
+                .iter()
+                .flat_map(|bb| writes[*bb].iter().copied())
+                .collect();
+            for bb in blocks {
+                loop_writes.insert(*bb, written.clone());
+            }
+        }
+
+        PathChecker {
+            tcx,
+            def_id,
+            body,
+            typing_env,
+            escaped,
+            loop_writes,
+            prefix: RefCell::new(Prefix {
+                solver: Z3_CONTEXT.with(|ctx| Solver::new(ctx)),
+                frames: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn is_infeasible(&self, path: &[usize]) -> bool {
+        self.check(path) == Feasibility::Infeasible
+    }
+
+    /// Checks `path` incrementally: the blocks it shares with the previously checked path stay
+    /// asserted in the solver, and only the remaining ones are encoded.
+    pub fn check(&self, path: &[usize]) -> Feasibility {
+        if path.iter().any(|bb| *bb >= self.body.basic_blocks.len()) {
+            return Feasibility::Unknown;
+        }
+        let mut prefix = self.prefix.borrow_mut();
+        let common = prefix
+            .frames
+            .iter()
+            .zip(path)
+            .take_while(|(frame, bb)| frame.bb == **bb)
+            .count();
+        prefix.truncate(common);
+        for i in common..path.len() {
+            let from = i.checked_sub(1).map(|prev| path[prev]);
+            prefix.extend(self, from, path[i]);
+        }
+        let result = prefix.check();
+        rap_trace!(
+            "Path {:?} of {}: {}",
+            path,
+            self.tcx.def_path_str(self.def_id),
+            result
+        );
+        result
+    }
+
+    /// The bit-vector width of a type and whether it is signed.
+    fn width(&self, ty: Ty<'tcx>) -> Option<(u32, bool)> {
+        let ptr_bits = self.tcx.data_layout.pointer_size().bits() as u32;
+        match ty.kind() {
+            TyKind::Bool => Some((1, false)),
+            TyKind::Char => Some((32, false)),
+            TyKind::Int(int_ty) => Some((
+                int_ty.bit_width().map(|w| w as u32).unwrap_or(ptr_bits),
+                true,
+            )),
+            TyKind::Uint(uint_ty) => Some((
+                uint_ty.bit_width().map(|w| w as u32).unwrap_or(ptr_bits),
+                false,
+            )),
+            _ => None,
+        }
+    }
+
+    fn is_trackable(&self, place: &Place<'tcx>) -> bool {
+        !self.escaped.contains(&place.local)
+            && place.projection.iter().all(|elem| {
+                matches!(
+                    elem,
+                    ProjectionElem::Field(..) | ProjectionElem::Downcast(..)
+                )
+            })
+    }
+
+    fn place_ty(&self, place: &Place<'tcx>) -> Ty<'tcx> {
+        place.ty(self.body, self.tcx).ty
+    }
+}
+
+// The blocks of the last checked path, each asserted in its own solver scope.
+struct Prefix<'tcx> {
+    solver: Solver<'static>,
+    frames: Vec<Frame<'tcx>>,
+}
+
+// The symbolic state after a block of the prefix.
+struct Frame<'tcx> {
+    bb: usize,
+    values: FxHashMap<Place<'tcx>, ast::BV<'static>>,
+    discrs: FxHashMap<Place<'tcx>, ast::BV<'static>>,
+    // whether any constraint has been asserted up to this block.
+    constrained: bool,
+}
+
+impl<'tcx> Prefix<'tcx> {
+    fn truncate(&mut self, len: usize) {
+        if len < self.frames.len() {
+            self.solver.pop((self.frames.len() - len) as u32);
+            self.frames.truncate(len);
+        }
+    }
+
+    fn extend(&mut self, checker: &PathChecker<'tcx>, from: Option<usize>, bb: usize) {
+        let (values, discrs, constrained) = match self.frames.last() {
+            Some(frame) => (
+                frame.values.clone(),
+                frame.discrs.clone(),
+                frame.constrained,
+            ),
+            None => (FxHashMap::default(), FxHashMap::default(), false),
+        };
+        let mut encoder = PathEncoder {
+            checker,
+            ctx: self.solver.get_context(),
+            values,
+            discrs,
+            conds: Vec::new(),
+        };
+        if let Some(from) = from {
+            encoder.edge(from, bb);
+        }
+        encoder.block(bb);
+        self.solver.push();
+        for cond in &encoder.conds {
+            self.solver.assert(cond);
+        }
+        self.frames.push(Frame {
+            bb,
+            values: encoder.values,
+            discrs: encoder.discrs,
+            constrained: constrained || !encoder.conds.is_empty(),
+        });
+    }
+
+    fn check(&self) -> Feasibility {
+        if !self.frames.last().is_some_and(|frame| frame.constrained) {
+            return Feasibility::Feasible;
+        }
+        match self.solver.check() {
+            SatResult::Sat => Feasibility::Feasible,
+            SatResult::Unsat => Feasibility::Infeasible,
+            SatResult::Unknown => Feasibility::Unknown,
+        }
+    }
+}
+
+struct PathEncoder<'a, 'ctx, 'tcx> {
+    checker: &'a PathChecker<'tcx>,
+    ctx: &'ctx Context,
+    values: FxHashMap<Place<'tcx>, ast::BV<'ctx>>,
+    discrs: FxHashMap<Place<'tcx>, ast::BV<'ctx>>,
+    conds: Vec<ast::Bool<'ctx>>,
+}
+
+impl<'a, 'ctx, 'tcx> PathEncoder<'a, 'ctx, 'tcx> {
+    fn block(&mut self, bb: usize) {
+        if let Some(written) = self.checker.loop_writes.get(&bb) {
+            self.values
+                .retain(|place, _| !written.contains(&place.local));
+            self.discrs
+                .retain(|place, _| !written.contains(&place.local));
+        }
+        let data = &self.checker.body.basic_blocks[BasicBlock::from_usize(bb)];
+        for stmt in &data.statements {
+            match &stmt.kind {
+                StatementKind::Assign(box (place, rvalue)) => self.assign(place, rvalue),
+                StatementKind::SetDiscriminant {
+                    place,
+                    variant_index,
+                } => {
+                    self.forget(place.local);
+                    let ty = self.checker.place_ty(place);
+                    if let Some(discr) = self.discriminant(ty, *variant_index) {
+                        self.discrs.insert(**place, discr);
+                    }
+                }
+                StatementKind::Deinit(place) => self.forget(place.local),
+                _ => {}
+            }
+        }
+    }
+
+    // the constraints of the terminator of `from` when the path continues to `to`.
+    fn edge(&mut self, from: usize, to: usize) {
+        let next = BasicBlock::from_usize(to);
+        let terminator = self.checker.body.basic_blocks[BasicBlock::from_usize(from)].terminator();
+        if !terminator.successors().any(|succ| succ == next) {
+            // the blocks in between are unknown.
+            self.values.clear();
+            self.discrs.clear();
+            return;
+        }
+        match &terminator.kind {
+            TerminatorKind::SwitchInt { discr, targets } => {
+                let Some(value) = self.operand(discr) else {
+                    return;
+                };
+                let width = value.get_size();
+                let matched: Vec<ast::Bool<'ctx>> = targets
+                    .iter()
+                    .filter(|(_, target)| *target == next)
+                    .map(|(val, _)| value._eq(&self.constant(val, width)))
+                    .collect();
+                if targets.otherwise() == next {
+                    if !matched.is_empty() {
+                        return;
+                    }
+                    for (val, _) in targets.iter() {
+                        self.conds.push(value._eq(&self.constant(val, width)).not());
+                    }
+                } else if !matched.is_empty() {
+                    let refs: Vec<&ast::Bool<'ctx>> = matched.iter().collect();
+                    self.conds.push(ast::Bool::or(self.ctx, &refs));
+                }
+            }
+            TerminatorKind::Assert {
+                cond,
+                expected,
+                target,
+                ..
+            } if *target == next => {
+                if let Some(value) = self.operand(cond) {
+                    let expected = self.constant(*expected as u128, value.get_size());
+                    self.conds.push(value._eq(&expected));
+                }
+            }
+            TerminatorKind::Call { destination, .. } => self.forget(destination.local),
+            _ => {}
+        }
+    }
+
+    fn forget(&mut self, local: Local) {
+        self.values.retain(|place, _| place.local != local);
+        self.discrs.retain(|place, _| place.local != local);
+    }
+
+    fn constant(&self, val: u128, width: u32) -> ast::BV<'ctx> {
+        let val = Size::from_bits(width).truncate(val);
+        ast::BV::from_str(self.ctx, width, &val.to_string()).unwrap()
+    }
+
+    fn bool_to_bv(&self, cond: ast::Bool<'ctx>) -> ast::BV<'ctx> {
+        cond.ite(&self.constant(1, 1), &self.constant(0, 1))
+    }
+
+    fn discriminant(
+        &self,
+        ty: Ty<'tcx>,
+        variant_index: rustc_abi::VariantIdx,
+    ) -> Option<ast::BV<'ctx>> {
+        let tcx = self.checker.tcx;
+        let (width, _) = self.checker.width(ty.discriminant_ty(tcx))?;
+        let discr = ty.discriminant_for_variant(tcx, variant_index)?;
+        Some(self.constant(discr.val, width))
+    }
+
+    fn place(&mut self, place: &Place<'tcx>) -> Option<ast::BV<'ctx>> {
+        let (width, _) = self.checker.width(self.checker.place_ty(place))?;
+        if !self.checker.is_trackable(place) {
+            return Some(ast::BV::fresh_const(self.ctx, "v", width));
+        }
+        let value = self
+            .values
+            .entry(*place)
+            .or_insert_with(|| ast::BV::fresh_const(self.ctx, "v", width));
+        Some(value.clone())
+    }
+
+    fn operand(&mut self, operand: &Operand<'tcx>) -> Option<ast::BV<'ctx>> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.place(place),
+            Operand::Constant(constant) => {
+                let (width, _) = self.checker.width(constant.ty())?;
+                let bits = constant
+                    .const_
+                    .try_eval_bits(self.checker.tcx, self.checker.typing_env)?;
+                Some(self.constant(bits, width))
+            }
+        }
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        operand.ty(self.checker.body, self.checker.tcx)
+    }
+
+    fn assign(&mut self, dest: &Place<'tcx>, rvalue: &Rvalue<'tcx>) {
+        self.forget(dest.local);
+        if !self.checker.is_trackable(dest) {
+            return;
+        }
+        let tcx = self.checker.tcx;
+        match rvalue {
+            Rvalue::BinaryOp(op, box (lhs, rhs))
+                if matches!(
+                    op,
+                    BinOp::AddWithOverflow | BinOp::SubWithOverflow | BinOp::MulWithOverflow
+                ) =>
+            {
+                // the result is a tuple of the wrapped value and the overflow flag.
+                let TyKind::Tuple(fields) = self.checker.place_ty(dest).kind() else {
+                    return;
+                };
+                let Some((_, signed)) = self.checker.width(self.operand_ty(lhs)) else {
+                    return;
+                };
+                let (Some(l), Some(r)) = (self.operand(lhs), self.operand(rhs)) else {
+                    return;
+                };
+                let (value, no_overflow) = match op {
+                    BinOp::AddWithOverflow => (
+                        l.bvadd(&r),
+                        if signed {
+                            ast::Bool::and(
+                                self.ctx,
+                                &[&l.bvadd_no_overflow(&r, true), &l.bvadd_no_underflow(&r)],
+                            )
+                        } else {
+                            l.bvadd_no_overflow(&r, false)
+                        },
+                    ),
+                    BinOp::SubWithOverflow => (
+                        l.bvsub(&r),
+                        if signed {
+                            ast::Bool::and(
+                                self.ctx,
+                                &[&l.bvsub_no_overflow(&r), &l.bvsub_no_underflow(&r, true)],
+                            )
+                        } else {
+                            l.bvsub_no_underflow(&r, false)
+                        },
+                    ),
+                    _ => (
+                        l.bvmul(&r),
+                        if signed {
+                            ast::Bool::and(
+                                self.ctx,
+                                &[&l.bvmul_no_overflow(&r, true), &l.bvmul_no_underflow(&r)],
+                            )
+                        } else {
+                            l.bvmul_no_overflow(&r, false)
+                        },
+                    ),
+                };
+                let overflow = self.bool_to_bv(no_overflow.not());
+                let result = tcx.mk_place_field(*dest, FieldIdx::from_usize(0), fields[0]);
+                let flag = tcx.mk_place_field(*dest, FieldIdx::from_usize(1), fields[1]);
+                self.values.insert(result, value);
+                self.values.insert(flag, overflow);
+            }
+            Rvalue::Discriminant(place) => {
+                let Some((width, _)) = self.checker.width(self.checker.place_ty(dest)) else {
+                    return;
+                };
+                let discr = if self.checker.is_trackable(place) {
+                    self.discrs
+                        .entry(*place)
+                        .or_insert_with(|| ast::BV::fresh_const(self.ctx, "d", width))
+                        .clone()
+                } else {
+                    ast::BV::fresh_const(self.ctx, "d", width)
+                };
+                self.values.insert(*dest, discr);
+            }
+            Rvalue::Aggregate(box AggregateKind::Adt(_, variant_index, ..), _) => {
+                let ty = self.checker.place_ty(dest);
+                if ty.is_enum() {
+                    if let Some(discr) = self.discriminant(ty, *variant_index) {
+                        self.discrs.insert(*dest, discr);
+                    }
+                }
+            }
+            _ => {
+                if let Some(value) = self.rvalue(dest, rvalue) {
+                    self.values.insert(*dest, value);
+                }
+            }
+        }
+    }
+
+    fn rvalue(&mut self, dest: &Place<'tcx>, rvalue: &Rvalue<'tcx>) -> Option<ast::BV<'ctx>> {
+        let (width, _) = self.checker.width(self.checker.place_ty(dest))?;
+        match rvalue {
+            Rvalue::Use(operand) => self.operand(operand),
+            Rvalue::UnaryOp(UnOp::Not, operand) => Some(self.operand(operand)?.bvnot()),
+            Rvalue::UnaryOp(UnOp::Neg, operand) => Some(self.operand(operand)?.bvneg()),
+            Rvalue::Cast(CastKind::IntToInt, operand, _) => {
+                let (from, signed) = self.checker.width(self.operand_ty(operand))?;
+                let value = self.operand(operand)?;
+                Some(if from > width {
+                    value.extract(width - 1, 0)
+                } else if from < width && signed {
+                    value.sign_ext(width - from)
+                } else if from < width {
+                    value.zero_ext(width - from)
+                } else {
+                    value
+                })
+            }
+            Rvalue::BinaryOp(op, box (lhs, rhs)) => {
+                let (lwidth, signed) = self.checker.width(self.operand_ty(lhs))?;
+                let l = self.operand(lhs)?;
+                let mut r = self.operand(rhs)?;
+                let rwidth = r.get_size();
+                // shift amounts may have a different type from the shifted value.
+                if rwidth > lwidth {
+                    r = r.extract(lwidth - 1, 0);
+                } else if rwidth < lwidth {
+                    r = r.zero_ext(lwidth - rwidth);
+                }
+                let value = match op {
+                    BinOp::Add | BinOp::AddUnchecked => l.bvadd(&r),
+                    BinOp::Sub | BinOp::SubUnchecked => l.bvsub(&r),
+                    BinOp::Mul | BinOp::MulUnchecked => l.bvmul(&r),
+                    BinOp::Div if signed => l.bvsdiv(&r),
+                    BinOp::Div => l.bvudiv(&r),
+                    BinOp::Rem if signed => l.bvsrem(&r),
+                    BinOp::Rem => l.bvurem(&r),
+                    BinOp::BitAnd => l.bvand(&r),
+                    BinOp::BitOr => l.bvor(&r),
+                    BinOp::BitXor => l.bvxor(&r),
+                    BinOp::Shl | BinOp::ShlUnchecked => l.bvshl(&r),
+                    BinOp::Shr | BinOp::ShrUnchecked if signed => l.bvashr(&r),
+                    BinOp::Shr | BinOp::ShrUnchecked => l.bvlshr(&r),
+                    BinOp::Eq => self.bool_to_bv(l._eq(&r)),
+                    BinOp::Ne => self.bool_to_bv(l._eq(&r).not()),
+                    BinOp::Lt if signed => self.bool_to_bv(l.bvslt(&r)),
+                    BinOp::Lt => self.bool_to_bv(l.bvult(&r)),
+                    BinOp::Le if signed => self.bool_to_bv(l.bvsle(&r)),
+                    BinOp::Le => self.bool_to_bv(l.bvule(&r)),
+                    BinOp::Gt if signed => self.bool_to_bv(l.bvsgt(&r)),
+                    BinOp::Gt => self.bool_to_bv(l.bvugt(&r)),
+                    BinOp::Ge if signed => self.bool_to_bv(l.bvsge(&r)),
+                    BinOp::Ge => self.bool_to_bv(l.bvuge(&r)),
+                    _ => return None,
+                };
+                (value.get_size() == width).then_some(value)
+            }
+            _ => None,
+        }
+    }
+}
@@ -3,9 +3,15 @@
 #![allow(dead_code)]
 pub mod default;
 pub mod domain;
+pub mod feasibility;
+pub mod summary;
 use crate::{
     analysis::{
-        core::range_analysis::domain::domain::{ConstConvert, IntervalArithmetic},
+        core::range_analysis::{
+            domain::domain::{ConstConvert, IntervalArithmetic},
+            feasibility::Feasibility,
+            summary::{FnSummary, FnSummaryMap},
+        },
         Analysis,
     },
     utils::source::get_fn_name_byid,
@@ -30,11 +36,17 @@ pub type RAVecResultMap<'tcx, T> = FxHashMap<DefId, Vec<HashMap<Place<'tcx>, Ran
 pub type PathConstraint<'tcx> = HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>>;
 pub type PathConstraintMap<'tcx> =
     FxHashMap<DefId, HashMap<Vec<usize>, Vec<(Place<'tcx>, Place<'tcx>, BinOp)>>>;
+pub type PathFeasibility = HashMap<Vec<usize>, Feasibility>;
+pub type PathFeasibilityMap = FxHashMap<DefId, PathFeasibility>;
 pub struct RAResultWrapper<'tcx, T: Clone + PartialOrd>(pub RAResult<'tcx, T>);
 pub struct RAResultMapWrapper<'tcx, T: Clone + PartialOrd>(pub RAResultMap<'tcx, T>);
 pub struct RAVecResultMapWrapper<'tcx, T: Clone + PartialOrd>(pub RAVecResultMap<'tcx, T>);
 pub struct PathConstraintWrapper<'tcx>(pub PathConstraint<'tcx>);
 pub struct PathConstraintMapWrapper<'tcx>(pub PathConstraintMap<'tcx>);
+pub struct PathFeasibilityMapWrapper(pub PathFeasibilityMap);
+pub struct FnSummaryMapWrapper<'tcx, T: IntervalArithmetic + ConstConvert + Debug>(
+    pub FnSummaryMap<'tcx, T>,
+);
 
 /// The core trait for performing range analysis over Rust MIR.
 ///
@@ -66,6 +78,20 @@ pub trait RangeAnalysis<'tcx, T: IntervalArithmetic + ConstConvert + Debug>: Ana
 
     /// The function returns path constraints for all functions in the crate.
     fn get_all_path_constraints(&self) -> PathConstraintMap<'tcx>;
+
+    /// The function returns whether the path constraints of each path of the specified function
+    /// are satisfiable, as decided by Z3 over their bit-vector encoding.
+    fn get_fn_path_feasibility(&self, def_id: DefId) -> Option<PathFeasibility>;
+
+    /// The function returns the feasibility of the paths of all functions in the crate.
+    fn get_all_path_feasibility(&self) -> PathFeasibilityMap;
+
+    /// The function returns the summary of the specified function, i.e., its return value as a
+    /// symbolic expression of the parameters and the range of it for arbitrary arguments.
+    fn get_fn_summary(&self, def_id: DefId) -> Option<FnSummary<'tcx, T>>;
+
+    /// The function returns the summaries of all functions in the crate.
+    fn get_all_fn_summaries(&self) -> FnSummaryMap<'tcx, T>;
 }
 
 impl<'tcx, T> Display for RAResultWrapper<'tcx, T>
@@ -166,6 +192,48 @@ where
         Ok(())
     }
 }
+impl Display for PathFeasibilityMapWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Print path feasibility ===")?;
+        let mut sorted: Vec<_> = self
+            .0
+            .iter()
+            .map(|(def_id, paths)| (get_fn_name_byid(def_id), paths))
+            .collect();
+        sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (fn_name, paths) in sorted {
+            writeln!(f, "Function: {:?}:", fn_name)?;
+            let mut paths: Vec<_> = paths.iter().collect();
+            paths.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (path, feasibility) in paths {
+                writeln!(f, "  Path {:?}: {}", path, feasibility)?;
+            }
+        }
+        Ok(())
+    }
+}
+impl<'tcx, T> Display for FnSummaryMapWrapper<'tcx, T>
+where
+    T: IntervalArithmetic + ConstConvert + Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Print function summaries ===")?;
+        let mut sorted: Vec<_> = self
+            .0
+            .iter()
+            .map(|(def_id, summary)| (get_fn_name_byid(def_id), summary))
+            .collect();
+        sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (fn_name, summary) in sorted {
+            writeln!(
+                f,
+                "Function: {:?} => return {} in {}",
+                fn_name, summary.ret, summary.ret_range
+            )?;
+        }
+        Ok(())
+    }
+}
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum RangeType {
     Unknown,
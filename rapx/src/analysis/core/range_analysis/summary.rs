@@ -0,0 +1,435 @@
+use crate::analysis::core::range_analysis::{
+    domain::{
+        domain::{ConstConvert, IntervalArithmetic},
+        SymbolicExpr::{SymbolicExpr, VarorConst},
+    },
+    Range, RangeType,
+};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{BasicBlock, BinOp, Body},
+    ty::{Ty, TyCtxt, TyKind},
+};
+use std::{
+    cell::{Cell, RefCell},
+    env,
+    fmt::Debug,
+    rc::Rc,
+};
+
+/// The call-string depth used if `RANGE_CALL_DEPTH` is not set.
+pub const DEFAULT_CALL_STRING_DEPTH: usize = 2;
+/// The rounds of fixed-point iteration over a recursive SCC before the changing bounds of its
+/// return ranges are widened.
+pub const WIDENING_ROUNDS: usize = 3;
+/// The number of callee summaries unfolded when a summary is instantiated. The calls beyond it
+/// contribute the return ranges of their callees only.
+const INSTANTIATION_FUEL: usize = 32;
+
+/// The call sites from the entry of the analysis to the current function, outermost first. Each
+/// site is the caller and the block of its `Call` terminator.
+pub type CallString = Vec<(DefId, BasicBlock)>;
+pub type FnSummaryMap<'tcx, T> = FxHashMap<DefId, FnSummary<'tcx, T>>;
+
+/// The summary of a function, which is computed once and instantiated at every call site.
+#[derive(Debug, Clone)]
+pub struct FnSummary<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
+    /// The return value in terms of the parameters `_1`, `_2`, ...
+    pub ret: SymbolicExpr<'tcx>,
+    /// The range of the return value for arbitrary arguments. It is unknown if the function has
+    /// not been summarized yet, i.e., no value is known to be returned.
+    pub ret_range: Range<T>,
+    /// The ranges of the types of the parameters, which bound the arguments.
+    pub params: Vec<Range<T>>,
+}
+
+impl<'tcx, T> FnSummary<'tcx, T>
+where
+    T: IntervalArithmetic + ConstConvert + Debug,
+{
+    /// Summarize the function of `body` returning `ret`, where `range` is the return range found
+    /// by the intra-procedural analysis. The calls in `ret` are evaluated with the current
+    /// summaries, and the parameters and the return value are bounded by their types.
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        body: &Body<'tcx>,
+        ret: SymbolicExpr<'tcx>,
+        range: Range<T>,
+        summaries: &FnSummaryMap<'tcx, T>,
+    ) -> Self {
+        let params: Vec<Range<T>> = body
+            .args_iter()
+            .map(|arg| ty_range(tcx, body.local_decls[arg].ty))
+            .collect();
+        let fuel = Cell::new(INSTANTIATION_FUEL);
+        let instantiation = Instantiation {
+            args: &params,
+            summaries,
+            fuel: &fuel,
+        };
+        let mut ret_range = meet(&instantiation.eval(&ret), &ty_range(tcx, body.return_ty()));
+        if range.is_regular() {
+            ret_range = meet(&ret_range, &range);
+        }
+        if ret_range.is_empty() {
+            ret_range = Range::default(T::min_value());
+        }
+        Self {
+            ret,
+            ret_range,
+            params,
+        }
+    }
+
+    /// The initial summary of the functions in a recursive SCC.
+    pub fn bottom() -> Self {
+        Self {
+            ret: SymbolicExpr::Ssa(Vec::new()),
+            ret_range: Range::default(T::min_value()),
+            params: Vec::new(),
+        }
+    }
+
+    /// The return range for the given argument ranges. The result is empty if no value can be
+    /// returned for them.
+    pub fn instantiate(&self, args: &[Range<T>], summaries: &FnSummaryMap<'tcx, T>) -> Range<T> {
+        let args = self.bound_args(args);
+        let fuel = Cell::new(INSTANTIATION_FUEL);
+        let instantiation = Instantiation {
+            args: &args,
+            summaries,
+            fuel: &fuel,
+        };
+        instantiation.apply(self)
+    }
+}
+
+impl<'tcx, T> FnSummary<'tcx, T>
+where
+    T: IntervalArithmetic + ConstConvert + Debug,
+{
+    // The arguments bounded by the types of the parameters; an argument of an unknown range takes
+    // the range of its type.
+    fn bound_args(&self, args: &[Range<T>]) -> Vec<Range<T>> {
+        (0..args.len().max(self.params.len()))
+            .map(|index| match (args.get(index), self.params.get(index)) {
+                (Some(arg), Some(param)) if arg.is_regular() || arg.is_empty() => meet(arg, param),
+                (_, Some(param)) => param.clone(),
+                (Some(arg), None) => arg.clone(),
+                (None, None) => top(),
+            })
+            .collect()
+    }
+}
+
+/// The state of the inter-procedural analysis shared by the constraint graph of a function.
+#[derive(Debug, Clone)]
+pub struct CallContext<'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
+    pub summaries: Rc<RefCell<FnSummaryMap<'tcx, T>>>,
+    /// The call sites through which the function is being analyzed.
+    pub call_string: CallString,
+    /// The callees are re-analyzed with the argument ranges of each call site while the call
+    /// string is shorter than `depth`. Beyond it, their summaries are instantiated instead.
+    pub depth: usize,
+}
+
+impl<'tcx, T> CallContext<'tcx, T>
+where
+    T: IntervalArithmetic + ConstConvert + Debug,
+{
+    pub fn new() -> Self {
+        Self {
+            summaries: Rc::new(RefCell::new(FnSummaryMap::default())),
+            call_string: CallString::new(),
+            depth: call_string_depth(),
+        }
+    }
+
+    /// Test if the callees at the end of the current call string should be re-analyzed.
+    pub fn is_within_depth(&self) -> bool {
+        self.call_string.len() < self.depth
+    }
+
+    /// The call string of a callee invoked at `call_site`.
+    pub fn extend(&self, call_site: (DefId, BasicBlock)) -> CallString {
+        let mut call_string = self.call_string.clone();
+        call_string.push(call_site);
+        call_string
+    }
+
+    /// Instantiate the summary of `def_id` at a call site, or None if it has no summary.
+    pub fn instantiate(&self, def_id: DefId, args: &[Range<T>]) -> Option<Range<T>> {
+        let summaries = self.summaries.borrow();
+        let range = summaries.get(&def_id)?.instantiate(args, &summaries);
+        if range.is_empty() {
+            Some(Range::default(T::min_value()))
+        } else {
+            Some(range)
+        }
+    }
+}
+
+impl<'tcx, T> Default for CallContext<'tcx, T>
+where
+    T: IntervalArithmetic + ConstConvert + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn call_string_depth() -> usize {
+    env::var("RANGE_CALL_DEPTH")
+        .ok()
+        .and_then(|depth| depth.parse().ok())
+        .unwrap_or(DEFAULT_CALL_STRING_DEPTH)
+}
+
+/// Join the return range of a new round into that of the previous one. After `WIDENING_ROUNDS`
+/// rounds, the bounds still growing are moved to the extremes.
+pub fn join_return_range<T>(old: &Range<T>, new: &Range<T>, round: usize) -> Range<T>
+where
+    T: IntervalArithmetic + ConstConvert + Debug,
+{
+    if old.is_unknown() || new.is_unknown() {
+        return if old.is_unknown() { new } else { old }.clone();
+    }
+    let joined = old.unionwith(new);
+    if round < WIDENING_ROUNDS {
+        return joined;
+    }
+    let lower = if joined.get_lower() < old.get_lower() {
+        T::min_value()
+    } else {
+        joined.get_lower()
+    };
+    let upper = if joined.get_upper() > old.get_upper() {
+        T::max_value()
+    } else {
+        joined.get_upper()
+    };
+    Range::new(lower, upper, RangeType::Regular)
+}
+
+// Evaluates symbolic expressions over the ranges of the arguments. Empty ranges stand for the
+// values that cannot be produced, e.g., a σ node whose branch is infeasible.
+struct Instantiation<'a, 'tcx, T: IntervalArithmetic + ConstConvert + Debug> {
+    args: &'a [Range<T>],
+    summaries: &'a FnSummaryMap<'tcx, T>,
+    fuel: &'a Cell<usize>,
+}
+
+impl<'a, 'tcx, T> Instantiation<'a, 'tcx, T>
+where
+    T: IntervalArithmetic + ConstConvert + Debug,
+{
+    fn apply(&self, summary: &FnSummary<'tcx, T>) -> Range<T> {
+        if summary.ret_range.is_unknown() {
+            return empty();
+        }
+        meet(&self.eval(&summary.ret), &summary.ret_range)
+    }
+
+    fn eval(&self, expr: &SymbolicExpr<'tcx>) -> Range<T> {
+        match expr {
+            SymbolicExpr::Argument(place) => {
+                let index = place.local.as_usize().checked_sub(1);
+                match index.and_then(|index| self.args.get(index)) {
+                    Some(range) if place.projection.is_empty() && range.is_regular() => {
+                        range.clone()
+                    }
+                    Some(range) if range.is_empty() => empty(),
+                    _ => top(),
+                }
+            }
+            SymbolicExpr::Constant(c) => T::from_const(c)
+                .map(|value| Range::new(value, value, RangeType::Regular))
+                .unwrap_or_else(top),
+            SymbolicExpr::BinaryOp { op, left, right } => {
+                let left = self.eval(left);
+                let right = self.eval(right);
+                if left.is_empty() || right.is_empty() {
+                    return empty();
+                }
+                match op {
+                    BinOp::Add | BinOp::AddUnchecked | BinOp::AddWithOverflow => {
+                        checked(&left, &right, |a, b| a.checked_add(b), false)
+                    }
+                    BinOp::Sub | BinOp::SubUnchecked | BinOp::SubWithOverflow => {
+                        checked(&left, &right, |a, b| a.checked_sub(b), true)
+                    }
+                    BinOp::Mul | BinOp::MulUnchecked | BinOp::MulWithOverflow => {
+                        let bounds = [
+                            (left.get_lower(), right.get_lower()),
+                            (left.get_lower(), right.get_upper()),
+                            (left.get_upper(), right.get_lower()),
+                            (left.get_upper(), right.get_upper()),
+                        ];
+                        let mut products = Vec::new();
+                        for (a, b) in bounds {
+                            match a.checked_mul(&b) {
+                                Some(product) => products.push(product),
+                                None => return top(),
+                            }
+                        }
+                        let mut range = Range::new(products[0], products[0], RangeType::Regular);
+                        for product in products {
+                            range =
+                                range.unionwith(&Range::new(product, product, RangeType::Regular));
+                        }
+                        range
+                    }
+                    _ => top(),
+                }
+            }
+            SymbolicExpr::Ssa(operands) => operands
+                .iter()
+                .fold(empty(), |range, operand| join(&range, &self.eval(operand))),
+            SymbolicExpr::Essa {
+                operand,
+                constraint_operand,
+                bin_op,
+            } => {
+                let range = self.eval(operand);
+                let VarorConst::Constant(c) = constraint_operand else {
+                    return range;
+                };
+                let Some(bound) = T::from_const(c) else {
+                    return range;
+                };
+                let constraint = match bin_op {
+                    BinOp::Lt => match bound.checked_sub(&T::one()) {
+                        Some(upper) => Range::new(T::min_value(), upper, RangeType::Regular),
+                        None => empty(),
+                    },
+                    BinOp::Le => Range::new(T::min_value(), bound, RangeType::Regular),
+                    BinOp::Gt => match bound.checked_add(&T::one()) {
+                        Some(lower) => Range::new(lower, T::max_value(), RangeType::Regular),
+                        None => empty(),
+                    },
+                    BinOp::Ge => Range::new(bound, T::max_value(), RangeType::Regular),
+                    BinOp::Eq => Range::new(bound, bound, RangeType::Regular),
+                    _ => return range,
+                };
+                meet(&range, &constraint)
+            }
+            SymbolicExpr::Call { def_id, args } => {
+                let args: Vec<Range<T>> = args.iter().map(|arg| self.eval(arg)).collect();
+                if args.iter().any(Range::is_empty) {
+                    return empty();
+                }
+                let Some(summary) = self.summaries.get(def_id) else {
+                    return top();
+                };
+                if summary.ret_range.is_unknown() {
+                    return empty();
+                }
+                if self.fuel.get() == 0 {
+                    return summary.ret_range.clone();
+                }
+                self.fuel.set(self.fuel.get() - 1);
+                let args = summary.bound_args(&args);
+                let callee = Instantiation {
+                    args: &args,
+                    summaries: self.summaries,
+                    fuel: self.fuel,
+                };
+                callee.apply(summary)
+            }
+            _ => top(),
+        }
+    }
+}
+
+/// The range of the values of an integer type, as far as `T` can represent them, or the whole
+/// range of `T` for other types.
+pub fn ty_range<'tcx, T: IntervalArithmetic>(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> Range<T> {
+    let ptr_bits = tcx.data_layout.pointer_size().bits();
+    let (bits, signed) = match ty.kind() {
+        TyKind::Int(int_ty) => (int_ty.bit_width().unwrap_or(ptr_bits), true),
+        TyKind::Uint(uint_ty) => (uint_ty.bit_width().unwrap_or(ptr_bits), false),
+        _ => return top(),
+    };
+    // 2^(bits - 1), or None if `T` cannot represent it.
+    let half = (1..bits).try_fold(T::one(), |power, _| power.checked_add(&power));
+    match (half, signed) {
+        (Some(half), true) => Range::new(
+            T::zero().checked_sub(&half).unwrap_or(T::min_value()),
+            half - T::one(),
+            RangeType::Regular,
+        ),
+        (Some(half), false) => Range::new(
+            T::zero(),
+            half.checked_add(&(half - T::one()))
+                .unwrap_or(T::max_value()),
+            RangeType::Regular,
+        ),
+        (None, true) => top(),
+        (None, false) => Range::new(T::zero(), T::max_value(), RangeType::Regular),
+    }
+}
+
+fn top<T: IntervalArithmetic>() -> Range<T> {
+    Range::new(T::min_value(), T::max_value(), RangeType::Regular)
+}
+
+fn empty<T: IntervalArithmetic>() -> Range<T> {
+    Range::new(T::zero(), T::zero(), RangeType::Empty)
+}
+
+fn meet<T: IntervalArithmetic>(a: &Range<T>, b: &Range<T>) -> Range<T> {
+    if a.is_empty() || b.is_empty() {
+        return empty();
+    }
+    let lower = if a.get_lower() > b.get_lower() {
+        a.get_lower()
+    } else {
+        b.get_lower()
+    };
+    let upper = if a.get_upper() < b.get_upper() {
+        a.get_upper()
+    } else {
+        b.get_upper()
+    };
+    if lower > upper {
+        empty()
+    } else {
+        Range::new(lower, upper, RangeType::Regular)
+    }
+}
+
+fn join<T: IntervalArithmetic>(a: &Range<T>, b: &Range<T>) -> Range<T> {
+    if a.is_empty() {
+        b.clone()
+    } else if b.is_empty() {
+        a.clone()
+    } else {
+        a.unionwith(b)
+    }
+}
+
+// Apply a checked operation to the bounds, pairing the lower bound of `left` with the upper bound
+// of `right` if `crossed`. Any overflow makes the result unbounded.
+fn checked<T: IntervalArithmetic>(
+    left: &Range<T>,
+    right: &Range<T>,
+    op: impl Fn(&T, &T) -> Option<T>,
+    crossed: bool,
+) -> Range<T> {
+    let (lower, upper) = if crossed {
+        (
+            op(&left.get_lower(), &right.get_upper()),
+            op(&left.get_upper(), &right.get_lower()),
+        )
+    } else {
+        (
+            op(&left.get_lower(), &right.get_lower()),
+            op(&left.get_upper(), &right.get_upper()),
+        )
+    };
+    match (lower, upper) {
+        (Some(lower), Some(upper)) => Range::new(lower, upper, RangeType::Regular),
+        _ => top(),
+    }
+}
@@ -5,8 +5,8 @@ use super::SSATransformer::SSATransformer;
 use rustc_abi::FieldIdx;
 use rustc_hir::def_id::DefIdMap;
 use rustc_index::IndexVec;
+use rustc_middle::mir::*;
 use rustc_middle::ty::TyCtxt;
-use rustc_middle::{mir::*, ty::GenericArgs};
 use std::collections::{HashMap, HashSet, VecDeque};
 // use stable_mir::mir::FieldIdx;
 // use stable_mir::ty::ConstantKind;
@@ -70,16 +70,7 @@ impl<'tcx> Replacer<'tcx> {
                     SourceInfo::outermost(body.span),
                     StatementKind::Assign(Box::new((
                         Place::from(var),
-                        Rvalue::Aggregate(
-                            Box::new(AggregateKind::Adt(
-                                self.ssatransformer.phi_def_id.clone(),
-                                rustc_abi::VariantIdx::from_u32(0),
-                                GenericArgs::empty(),
-                                None,
-                                None,
-                            )),
-                            operands,
-                        ),
+                        Rvalue::Aggregate(Box::new(AggregateKind::Tuple), operands),
                     ))),
                 );
                 // let phi_stmt = Statement {
@@ -94,6 +85,7 @@ impl<'tcx> Replacer<'tcx> {
                     .insert(0, phi_stmt);
             }
 
+            self.ssatransformer.phi_counts.insert(block, vars.len());
             for i in 0..vars.len() {
                 let phi_in_body = body.basic_blocks.as_mut()[block]
                     .statements
@@ -279,13 +271,7 @@ impl<'tcx> Replacer<'tcx> {
                                 Operand::Copy(p1) | Operand::Move(p1),
                                 Operand::Copy(p2) | Operand::Move(p2),
                             ) => {
-                                let ADT = AggregateKind::Adt(
-                                    self.ssatransformer.essa_def_id.clone(),
-                                    rustc_abi::VariantIdx::from_u32(0),
-                                    GenericArgs::empty(),
-                                    None,
-                                    None,
-                                );
+                                let ADT = AggregateKind::Tuple;
                                 let place1 = Place::from(p1);
                                 let place2 = Place::from(p2);
                                 let rvalue1;
@@ -337,6 +323,7 @@ impl<'tcx> Replacer<'tcx> {
                                 );
                                 block_data.statements.insert(0, assign_stmt2);
                                 block_data.statements.insert(0, assign_stmt1);
+                                self.ssatransformer.record_sigmas(*bb, *switch_block, 2);
 
                                 for i in 0..2 {
                                     let essa_in_body = block_data.statements.get_mut(i).unwrap();
@@ -362,19 +349,14 @@ impl<'tcx> Replacer<'tcx> {
                         } else {
                             operand.push(cmp_operand.clone());
                         }
-                        let ADT = AggregateKind::Adt(
-                            self.ssatransformer.essa_def_id.clone(),
-                            rustc_abi::VariantIdx::from_u32(0),
-                            GenericArgs::empty(),
-                            None,
-                            None,
-                        );
+                        let ADT = AggregateKind::Tuple;
                         rvalue = Rvalue::Aggregate(Box::new(ADT.clone()), operand);
                         let assign_stmt = Statement::new(
                             SourceInfo::outermost(body.span),
                             StatementKind::Assign(Box::new((place, rvalue))),
                         );
                         block_data.statements.insert(0, assign_stmt);
+                        self.ssatransformer.record_sigmas(*bb, *switch_block, 1);
 
                         for i in 0..1 {
                             let essa_in_body = block_data.statements.get_mut(i).unwrap();
@@ -26,7 +26,6 @@ use std::collections::{HashMap, HashSet};
 // use rustc_middle::mir::*;
 // use rustc_index::IndexVec;
 // use super::Replacer::*;
-pub struct PhiPlaceholder;
 pub struct SSATransformer<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub body: Body<'tcx>,
@@ -42,44 +41,16 @@ pub struct SSATransformer<'tcx> {
     pub phi_index: HashMap<*const Statement<'tcx>, usize>,
     pub phi_statements: HashMap<*const Statement<'tcx>, bool>,
     pub essa_statements: HashMap<*const Statement<'tcx>, bool>,
-    pub phi_def_id: DefId,
-    pub essa_def_id: DefId,
+    // the number of phi statements at the beginning of each block, behind its essa statements.
+    pub phi_counts: HashMap<BasicBlock, usize>,
+    // the switch block of each essa statement at the beginning of each block, in order.
+    pub sigma_switches: HashMap<BasicBlock, Vec<BasicBlock>>,
     pub places_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>>,
     pub ssa_locals_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>>,
 }
 
 impl<'tcx> SSATransformer<'tcx> {
-    fn find_phi_placeholder(tcx: TyCtxt<'_>, crate_name: &str) -> Option<DefId> {
-        let sym_crate = Symbol::intern(crate_name);
-        let krate = tcx
-            .crates(())
-            .iter()
-            .find(|&&c| tcx.crate_name(c) == sym_crate)?;
-        let root_def_id = DefId {
-            krate: *krate,
-            index: CRATE_DEF_INDEX,
-        };
-        // print!("Phid\n");
-
-        for item in tcx.module_children(root_def_id) {
-            // println!("Module child: {:?}", item.ident.name.as_str());
-
-            if item.ident.name.as_str() == "PhiPlaceholder" {
-                if let Some(def_id) = item.res.opt_def_id() {
-                    return Some(def_id);
-                }
-            }
-        }
-        // print!("Phid\n");
-        return Some(root_def_id);
-    }
-    pub fn new(
-        tcx: TyCtxt<'tcx>,
-        body: &Body<'tcx>,
-        ssa_def_id: DefId,
-        essa_def_id: DefId,
-        arg_count: usize,
-    ) -> Self {
+    pub fn new(tcx: TyCtxt<'tcx>, body: &Body<'tcx>, arg_count: usize) -> Self {
         let cfg: HashMap<BasicBlock, Vec<BasicBlock>> = Self::extract_cfg_from_predecessors(&body);
 
         let dominators: Dominators<BasicBlock> = body.basic_blocks.dominators().clone();
@@ -128,8 +99,8 @@ impl<'tcx> SSATransformer<'tcx> {
             phi_index: HashMap::default(),
             phi_statements: HashMap::default(),
             essa_statements: HashMap::default(),
-            phi_def_id: ssa_def_id,
-            essa_def_id: essa_def_id,
+            phi_counts: HashMap::default(),
+            sigma_switches: HashMap::default(),
             places_map: HashMap::default(),
             ssa_locals_map: HashMap::default(),
         }
@@ -313,6 +284,12 @@ impl<'tcx> SSATransformer<'tcx> {
         }
     }
 
+    /// Record `count` essa statements inserted at the beginning of `bb` for the branch of `switch_block`.
+    pub fn record_sigmas(&mut self, bb: BasicBlock, switch_block: BasicBlock, count: usize) {
+        let switches = self.sigma_switches.entry(bb).or_default();
+        switches.splice(0..0, std::iter::repeat_n(switch_block, count));
+    }
+
     pub fn is_phi_statement(&self, statement: &Statement<'tcx>) -> bool {
         let phi_stmt = statement as *const Statement<'tcx>;
         if self.phi_statements.contains_key(&phi_stmt) {
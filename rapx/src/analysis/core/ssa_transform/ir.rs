@@ -0,0 +1,276 @@
+use super::PassRunner;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        visit::{MutatingUseContext, PlaceContext, Visitor},
+        BasicBlock, BinOp, Body, Local, Location, Operand, Place, Rvalue, StatementKind,
+    },
+    ty::{GlobalCtxt, TyCtxt},
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// The kind of a statement inserted by the SSA transformation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SsaNodeKind {
+    Phi,
+    Sigma,
+}
+
+/// A φ node `dest = φ(sources)` at the beginning of a join block.
+#[derive(Debug, Clone)]
+pub struct PhiNode<'tcx> {
+    pub location: Location,
+    pub dest: Local,
+    /// The merged values, one per incoming edge of the block.
+    pub sources: Vec<Operand<'tcx>>,
+}
+
+/// A σ node of e-SSA at the beginning of a branch target. It renames `source` in the branch,
+/// where `source cmp bound` is known to hold.
+#[derive(Debug, Clone)]
+pub struct SigmaNode<'tcx> {
+    pub location: Location,
+    pub dest: Local,
+    /// The block whose `SwitchInt` terminator leads to this branch.
+    pub switch_block: BasicBlock,
+    pub source: Operand<'tcx>,
+    pub bound: Operand<'tcx>,
+    /// None if the branch condition is not a comparison.
+    pub cmp: Option<BinOp>,
+}
+
+/// The SSA/e-SSA form of a function body, built from its optimized MIR without changing the
+/// crate under analysis.
+///
+/// `body` is a renamed copy of the MIR, where every φ and σ node is lowered to a tuple aggregate
+/// at its `location`, so that the analyses visiting MIR statements can consume it directly. The
+/// locals introduced by renaming are declared with the types of the places they replace, and
+/// `origin` maps them back to those places.
+///
+/// A body is built once per function and compilation session, see `SsaBody::of`.
+pub struct SsaBody<'tcx> {
+    pub def_id: DefId,
+    pub body: &'tcx Body<'tcx>,
+    pub phis: Vec<PhiNode<'tcx>>,
+    pub sigmas: Vec<SigmaNode<'tcx>>,
+    /// Each original place and the SSA places it has been renamed to.
+    pub places_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>>,
+    nodes: HashMap<Location, SsaNodeKind>,
+    origins: HashMap<Local, Place<'tcx>>,
+    defs: HashMap<Local, Vec<Location>>,
+    uses: HashMap<Local, Vec<Location>>,
+}
+
+thread_local! {
+    // The SSA bodies built so far, with the address of the global context they borrow from. They
+    // are leaked like the arena allocations of that context, and dropped from the cache when the
+    // context changes.
+    static SSA_BODIES: RefCell<(usize, FxHashMap<DefId, *const ()>)> =
+        RefCell::new((0, FxHashMap::default()));
+}
+
+impl<'tcx> SsaBody<'tcx> {
+    /// The SSA body of `def_id`. It is built on the first request and shared by all the later
+    /// ones, so that the analyses see the same renamed locals.
+    pub fn of(tcx: TyCtxt<'tcx>, def_id: DefId) -> &'tcx SsaBody<'tcx> {
+        let gcx = &**tcx as *const GlobalCtxt<'tcx> as usize;
+        let cached = SSA_BODIES.with_borrow_mut(|(owner, bodies)| {
+            if *owner != gcx {
+                *owner = gcx;
+                bodies.clear();
+            }
+            bodies.get(&def_id).copied()
+        });
+        if let Some(ssa_body) = cached {
+            // SAFETY: the body was leaked below for the same global context, which outlives 'tcx.
+            return unsafe { &*(ssa_body as *const SsaBody<'tcx>) };
+        }
+        let ssa_body: &'tcx SsaBody<'tcx> = Box::leak(Box::new(SsaBody::new(tcx, def_id)));
+        SSA_BODIES.with_borrow_mut(|(_, bodies)| {
+            bodies.insert(def_id, ssa_body as *const SsaBody<'tcx> as *const ())
+        });
+        ssa_body
+    }
+
+    fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        let mut body = tcx.optimized_mir(def_id).clone();
+        let mut passrunner = PassRunner::new(tcx);
+        passrunner.run_pass(&mut body);
+        let body: &'tcx Body<'tcx> = tcx.arena.alloc(body);
+
+        let mut ssa_body = SsaBody {
+            def_id,
+            body,
+            phis: Vec::new(),
+            sigmas: Vec::new(),
+            places_map: passrunner.places_map.clone(),
+            nodes: HashMap::new(),
+            origins: HashMap::new(),
+            defs: HashMap::new(),
+            uses: HashMap::new(),
+        };
+        for (place, ssa_places) in passrunner
+            .places_map
+            .iter()
+            .chain(passrunner.ssa_locals_map.iter())
+        {
+            for ssa_place in ssa_places {
+                ssa_body.origins.insert(ssa_place.local, *place);
+            }
+        }
+        ssa_body.collect_nodes(&passrunner);
+        let mut collector = DefUseCollector {
+            defs: &mut ssa_body.defs,
+            uses: &mut ssa_body.uses,
+        };
+        for (bb, data) in body.basic_blocks.iter_enumerated() {
+            collector.visit_basic_block_data(bb, data);
+        }
+        ssa_body
+    }
+
+    // The σ nodes of a block come first, followed by its φ nodes.
+    fn collect_nodes(&mut self, passrunner: &PassRunner<'tcx>) {
+        for (block, data) in self.body.basic_blocks.iter_enumerated() {
+            let switches = passrunner
+                .sigma_switches
+                .get(&block)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let phi_count = passrunner.phi_counts.get(&block).copied().unwrap_or(0);
+            for (index, statement) in data
+                .statements
+                .iter()
+                .enumerate()
+                .take(switches.len() + phi_count)
+            {
+                let StatementKind::Assign(box (dest, Rvalue::Aggregate(_, operands))) =
+                    &statement.kind
+                else {
+                    continue;
+                };
+                let location = Location {
+                    block,
+                    statement_index: index,
+                };
+                let operands = operands.raw.clone();
+                if let Some(switch_block) = switches.get(index) {
+                    self.nodes.insert(location, SsaNodeKind::Sigma);
+                    self.sigmas.push(SigmaNode {
+                        location,
+                        dest: dest.local,
+                        switch_block: *switch_block,
+                        source: operands[0].clone(),
+                        bound: operands[1].clone(),
+                        cmp: sigma_cmp(&operands[2]),
+                    });
+                } else {
+                    self.nodes.insert(location, SsaNodeKind::Phi);
+                    self.phis.push(PhiNode {
+                        location,
+                        dest: dest.local,
+                        sources: operands,
+                    });
+                }
+            }
+        }
+    }
+
+    /// The kind of the SSA node at `location`, or None for the statements of the original body.
+    pub fn node_kind(&self, location: Location) -> Option<SsaNodeKind> {
+        self.nodes.get(&location).copied()
+    }
+
+    pub fn node_kinds(&self) -> &HashMap<Location, SsaNodeKind> {
+        &self.nodes
+    }
+
+    /// The place of the original body that `local` stands for.
+    pub fn origin(&self, local: Local) -> Place<'tcx> {
+        self.origins
+            .get(&local)
+            .copied()
+            .unwrap_or_else(|| Place::from(local))
+    }
+
+    /// The locations assigning `local`. The arguments are defined at the function entry and have
+    /// none, and the return place is the only local that may have several.
+    pub fn defs(&self, local: Local) -> &[Location] {
+        self.defs.get(&local).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// The locations reading `local`.
+    pub fn uses(&self, local: Local) -> &[Location] {
+        self.uses.get(&local).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for SsaBody<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for phi in &self.phis {
+            let sources: Vec<String> = phi.sources.iter().map(|op| format!("{:?}", op)).collect();
+            writeln!(
+                f,
+                "{:?}: {:?} = phi({}) for {:?}",
+                phi.location,
+                phi.dest,
+                sources.join(", "),
+                self.origin(phi.dest)
+            )?;
+        }
+        for sigma in &self.sigmas {
+            writeln!(
+                f,
+                "{:?}: {:?} = sigma({:?}, {:?} {:?}) from {:?} for {:?}",
+                sigma.location,
+                sigma.dest,
+                sigma.source,
+                sigma.cmp,
+                sigma.bound,
+                sigma.switch_block,
+                self.origin(sigma.dest)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode the comparison operand of a lowered σ node, see `Replacer::essa_assign_statement`.
+pub fn sigma_cmp(operand: &Operand<'_>) -> Option<BinOp> {
+    let code = operand
+        .constant()?
+        .const_
+        .try_to_scalar_int()?
+        .to_bits_unchecked();
+    match code {
+        1 => Some(BinOp::Lt),
+        2 => Some(BinOp::Le),
+        3 => Some(BinOp::Ge),
+        4 => Some(BinOp::Gt),
+        5 => Some(BinOp::Eq),
+        6 => Some(BinOp::Ne),
+        _ => None,
+    }
+}
+
+struct DefUseCollector<'a> {
+    defs: &'a mut HashMap<Local, Vec<Location>>,
+    uses: &'a mut HashMap<Local, Vec<Location>>,
+}
+
+impl<'tcx> Visitor<'tcx> for DefUseCollector<'_> {
+    fn visit_local(&mut self, local: Local, context: PlaceContext, location: Location) {
+        match context {
+            PlaceContext::MutatingUse(MutatingUseContext::Store | MutatingUseContext::Call) => {
+                self.defs.entry(local).or_default().push(location);
+            }
+            PlaceContext::NonUse(_) => {}
+            _ => self.uses.entry(local).or_default().push(location),
+        }
+    }
+}
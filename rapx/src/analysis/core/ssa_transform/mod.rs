@@ -7,8 +7,11 @@
 
 pub mod Replacer;
 pub mod SSATransformer;
+pub mod ir;
 
-use crate::{rap_info, rap_warn};
+pub use ir::{sigma_cmp, PhiNode, SigmaNode, SsaBody, SsaNodeKind};
+
+use crate::{rap_debug, rap_info, rap_warn};
 use rustc_hir::{
     def::DefKind,
     def_id::{DefId, LocalDefId},
@@ -38,66 +41,36 @@ impl<'tcx> SSATrans<'tcx> {
     }
 
     pub fn start(&mut self) {
-        for local_def_id in self.tcx.iter_local_def_id() {
-            if matches!(self.tcx.def_kind(local_def_id), DefKind::Fn) {
-                if self.tcx.hir_maybe_body_owned_by(local_def_id).is_some() {
-                    if let Some(def_id) = self
-                        .tcx
-                        .hir_body_owners()
-                        .find(|id| self.tcx.def_path_str(*id) == "main")
-                    {
-                        if let Some(ssa_def_id) =
-                            self.tcx.hir_crate_items(()).free_items().find(|id| {
-                                let hir_id = id.hir_id();
-                                if let Some(ident_name) = self.tcx.hir_opt_name(hir_id) {
-                                    ident_name.to_string() == "SSAstmt"
-                                } else {
-                                    false
-                                }
-                            })
-                        {
-                            let ssa_def_id = ssa_def_id.owner_id.to_def_id();
-                            if let Some(essa_def_id) =
-                                self.tcx.hir_crate_items(()).free_items().find(|id| {
-                                    let hir_id = id.hir_id();
-                                    if let Some(ident_name) = self.tcx.hir_opt_name(hir_id) {
-                                        ident_name.to_string() == "ESSAstmt"
-                                    } else {
-                                        false
-                                    }
-                                })
-                            {
-                                let essa_def_id = essa_def_id.owner_id.to_def_id();
-                                self.analyze_mir(self.tcx, def_id, ssa_def_id, essa_def_id);
-                            }
-                        }
-                    }
-                }
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !matches!(self.tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+                || !self.tcx.is_mir_available(def_id)
+            {
+                continue;
             }
-        }
-    }
-    fn analyze_mir(
-        &mut self,
-        tcx: TyCtxt<'tcx>,
-        def_id: LocalDefId,
-        ssa_def_id: DefId,
-        essa_def_id: DefId,
-    ) {
-        let mut body = tcx.optimized_mir(def_id).clone();
-        {
-            let body_mut_ref: &mut Body<'tcx> = unsafe { &mut *(&mut body as *mut Body<'tcx>) };
-            let mut passrunner = PassRunner::new(tcx);
-            passrunner.run_pass(body_mut_ref, ssa_def_id, essa_def_id);
-            // passrunner.print_diff(body_mut_ref);
-            let essa_mir_string = passrunner.get_final_ssa_as_string(body_mut_ref);
-            // rap_info!("final SSA {:?}\n", &essa_mir_string);
-            rap_info!("ssa lvalue check {:?}", lvalue_check(&essa_mir_string));
+            let ssa_body = SsaBody::of(self.tcx, def_id);
+            let name = self.tcx.def_path_str(def_id);
+            if !ssa_body.phis.is_empty() || !ssa_body.sigmas.is_empty() {
+                rap_debug!("SSA form of {:?}:\n{}", name, ssa_body);
+            }
+            if self.debug {
+                print_diff(self.tcx, ssa_body.body, def_id);
+            }
+            let essa_mir_string = PassRunner::new(self.tcx).get_final_ssa_as_string(ssa_body.body);
+            rap_info!(
+                "{}: ssa lvalue check {:?}",
+                name,
+                lvalue_check(&essa_mir_string)
+            );
         }
     }
 }
 pub struct PassRunner<'tcx> {
     tcx: TyCtxt<'tcx>,
     pub places_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>>,
+    pub ssa_locals_map: HashMap<Place<'tcx>, HashSet<Place<'tcx>>>,
+    pub phi_counts: HashMap<BasicBlock, usize>,
+    pub sigma_switches: HashMap<BasicBlock, Vec<BasicBlock>>,
 }
 pub fn lvalue_check(mir_string: &str) -> bool {
     let re = regex::Regex::new(r"_(\d+)\s*=").unwrap();
@@ -201,6 +174,9 @@ impl<'tcx> PassRunner<'tcx> {
         Self {
             tcx,
             places_map: HashMap::default(),
+            ssa_locals_map: HashMap::default(),
+            phi_counts: HashMap::default(),
+            sigma_switches: HashMap::default(),
         }
     }
 
@@ -212,10 +188,9 @@ impl<'tcx> PassRunner<'tcx> {
         after_mir
     }
 
-    pub fn run_pass(&mut self, body: &mut Body<'tcx>, ssa_def_id: DefId, essa_def_id: DefId) {
+    pub fn run_pass(&mut self, body: &mut Body<'tcx>) {
         let arg_count = body.arg_count;
-        let ssatransformer =
-            SSATransformer::SSATransformer::new(self.tcx, body, ssa_def_id, essa_def_id, arg_count);
+        let ssatransformer = SSATransformer::SSATransformer::new(self.tcx, body, arg_count);
         let mut replacer = Replacer::Replacer {
             tcx: self.tcx,
             ssatransformer,
@@ -224,6 +199,36 @@ impl<'tcx> PassRunner<'tcx> {
         replacer.insert_phi_statment(body);
         replacer.insert_essa_statement(body);
         replacer.rename_variables(body);
-        self.places_map = replacer.ssatransformer.places_map.clone();
+        let ssatransformer = replacer.ssatransformer;
+        Self::declare_locals(self.tcx, body, &ssatransformer);
+        self.places_map = ssatransformer.places_map;
+        self.ssa_locals_map = ssatransformer.ssa_locals_map;
+        self.phi_counts = ssatransformer.phi_counts;
+        self.sigma_switches = ssatransformer.sigma_switches;
+    }
+
+    // Each renamed local takes the type of the place it replaces, so that `place.ty(body, tcx)`
+    // works on the SSA body as on the original one.
+    fn declare_locals(
+        tcx: TyCtxt<'tcx>,
+        body: &mut Body<'tcx>,
+        ssatransformer: &SSATransformer::SSATransformer<'tcx>,
+    ) {
+        let mut origins = HashMap::new();
+        for (place, ssa_places) in ssatransformer
+            .places_map
+            .iter()
+            .chain(ssatransformer.ssa_locals_map.iter())
+        {
+            for ssa_place in ssa_places {
+                origins.insert(ssa_place.local, *place);
+            }
+        }
+        for index in body.local_decls.len()..ssatransformer.local_index {
+            let origin = origins[&Local::from_usize(index)];
+            let ty = origin.ty(&body.local_decls, tcx).ty;
+            let span = body.local_decls[origin.local].source_info.span;
+            body.local_decls.push(LocalDecl::new(ty, span));
+        }
     }
 }
@@ -0,0 +1,437 @@
+use super::spec::{FfiSpec, Ownership};
+use crate::analysis::utils::def_path::inherent_self_ty;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, Local, Operand, Place, PlaceElem, Rvalue, StatementKind, TerminatorKind,
+        RETURN_PLACE,
+    },
+    ty::{GenericArgsRef, Ty, TyCtxt},
+};
+use rustc_span::{sym, Span};
+use std::{collections::HashMap, fmt};
+
+/// How a raw pointer obtained its heap object, which decides how it must be released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocKind {
+    Box,
+    CString,
+    Vec,
+    /// Allocated by a foreign function whose result is owned by the caller.
+    Foreign,
+}
+
+impl fmt::Display for AllocKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AllocKind::Box => write!(f, "`Box::into_raw`"),
+            AllocKind::CString => write!(f, "`CString::into_raw`"),
+            AllocKind::Vec => write!(f, "`Vec::into_raw_parts`"),
+            AllocKind::Foreign => write!(f, "a foreign allocation"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RawObj<'tcx> {
+    pub kind: AllocKind,
+    // the pointee type of the raw pointer.
+    pub ty: Ty<'tcx>,
+    pub span: Span,
+    // the first foreign call that borrows the object.
+    pub given_to_c: Option<Span>,
+    // the pointer is returned or stored somewhere, so it may be reclaimed elsewhere.
+    pub escaped: bool,
+}
+
+/// The ownership state of a raw heap object at a program point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Own {
+    /// Not created yet on this path.
+    Unborn,
+    /// Owned by the raw pointer and not reclaimed yet.
+    Raw,
+    /// Reclaimed by a Rust constructor or freed by C at the given span.
+    Reclaimed(Span),
+    /// Ownership is transferred to a foreign function at the given span.
+    Consumed(Span),
+    /// Differs on the incoming paths.
+    Unknown,
+}
+
+impl Own {
+    fn join(self, other: Own) -> Own {
+        match (self, other) {
+            (Own::Unborn, x) | (x, Own::Unborn) => x,
+            (x, y) if x == y => x,
+            _ => Own::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FfiBugKind {
+    Leak,
+    DoubleReclaim(Span),
+    ReclaimAfterConsume(Span),
+    Mismatch(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct FfiBug {
+    pub kind: FfiBugKind,
+    pub span: Span,
+    pub alloc: AllocKind,
+    pub origin: Span,
+}
+
+impl fmt::Display for FfiBug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FfiBugKind::Leak => write!(
+                f,
+                "Heap object from {} is given to C but never reclaimed.",
+                self.alloc
+            ),
+            FfiBugKind::DoubleReclaim(_) => {
+                write!(f, "Pointer from {} is reclaimed twice.", self.alloc)
+            }
+            FfiBugKind::ReclaimAfterConsume(_) => write!(
+                f,
+                "Pointer from {} is reclaimed after its ownership is transferred to C.",
+                self.alloc
+            ),
+            FfiBugKind::Mismatch(reclaim) => {
+                write!(f, "Pointer from {} is released by {}.", self.alloc, reclaim)
+            }
+        }
+    }
+}
+
+const VISIT_LIMIT: usize = 100;
+
+/// Track the ownership of raw heap objects created by `into_raw`-style APIs or returned by
+/// foreign functions, through the foreign calls and the APIs that reclaim them.
+pub struct FnAnalysis<'a, 'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub body: &'a Body<'tcx>,
+    pub spec: &'a FfiSpec,
+    pub objs: Vec<RawObj<'tcx>>,
+    // the call sites that create raw objects.
+    sites: HashMap<BasicBlock, usize>,
+    // the locals that hold a pointer to a raw object.
+    aliases: HashMap<Local, usize>,
+    // the locals that hold the result of `Vec::into_raw_parts`.
+    parts: HashMap<Local, usize>,
+    pub bugs: Vec<FfiBug>,
+}
+
+impl<'a, 'tcx> FnAnalysis<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId, spec: &'a FfiSpec) -> Self {
+        let mut analysis = Self {
+            tcx,
+            body: tcx.optimized_mir(def_id),
+            spec,
+            objs: Vec::new(),
+            sites: HashMap::new(),
+            aliases: HashMap::new(),
+            parts: HashMap::new(),
+            bugs: Vec::new(),
+        };
+        analysis.collect_objs();
+        analysis
+    }
+
+    // Find the raw objects and the locals pointing to them, and whether they escape.
+    fn collect_objs(&mut self) {
+        let body = self.body;
+        for bb in body.basic_blocks.reverse_postorder() {
+            let data = &body.basic_blocks[*bb];
+            if data.is_cleanup {
+                continue;
+            }
+            for stmt in data.statements.iter() {
+                if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind {
+                    self.collect_assign(place, rvalue);
+                }
+            }
+            let terminator = data.terminator();
+            let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &terminator.kind
+            else {
+                continue;
+            };
+            let Some((callee, generics)) = func.const_fn_def() else {
+                continue;
+            };
+            let span = terminator.source_info.span;
+            if let Some(kind) = self.alloc_kind(callee) {
+                let Some(local) = destination.as_local() else {
+                    continue;
+                };
+                let ty = match kind {
+                    AllocKind::Vec => match generics.types().next() {
+                        Some(ty) => ty,
+                        None => continue,
+                    },
+                    _ => match self.body.local_decls[local].ty.builtin_deref(true) {
+                        Some(ty) => ty,
+                        None => continue,
+                    },
+                };
+                let obj = self.objs.len();
+                self.objs.push(RawObj {
+                    kind,
+                    ty,
+                    span,
+                    given_to_c: None,
+                    escaped: false,
+                });
+                self.sites.insert(*bb, obj);
+                match kind {
+                    AllocKind::Vec => self.parts.insert(local, obj),
+                    _ => self.aliases.insert(local, obj),
+                };
+                continue;
+            }
+            if self.reclaim_kind(callee).is_some() {
+                continue;
+            }
+            let foreign = self.tcx.is_foreign_item(callee);
+            for arg in args.iter() {
+                let Some(obj) = self.operand_obj(&arg.node) else {
+                    continue;
+                };
+                if !foreign {
+                    // A Rust callee may store the pointer or reclaim it.
+                    self.objs[obj].escaped = true;
+                } else if self.spec.get(self.tcx.item_name(callee).as_str()) == Ownership::Borrows {
+                    self.objs[obj].given_to_c.get_or_insert(span);
+                }
+            }
+        }
+    }
+
+    fn collect_assign(&mut self, place: &Place<'tcx>, rvalue: &Rvalue<'tcx>) {
+        let obj = match rvalue {
+            Rvalue::Use(operand) | Rvalue::Cast(_, operand, _) => match operand {
+                Operand::Copy(src) | Operand::Move(src) => match src.as_ref().projection {
+                    [] => self.aliases.get(&src.local).copied(),
+                    [PlaceElem::Field(field, _)] if field.as_usize() == 0 => {
+                        self.parts.get(&src.local).copied()
+                    }
+                    _ => None,
+                },
+                Operand::Constant(_) => None,
+            },
+            Rvalue::Aggregate(_, operands) => {
+                for operand in operands.iter() {
+                    if let Some(obj) = self.operand_obj(operand) {
+                        self.objs[obj].escaped = true;
+                    }
+                }
+                None
+            }
+            _ => None,
+        };
+        let Some(obj) = obj else {
+            return;
+        };
+        match place.as_local() {
+            Some(local) if local != RETURN_PLACE => {
+                self.aliases.insert(local, obj);
+            }
+            _ => self.objs[obj].escaped = true,
+        }
+    }
+
+    pub fn run(&mut self) {
+        if self.objs.is_empty() {
+            return;
+        }
+        let body = self.body;
+        let mut states: Vec<Option<Vec<Own>>> = vec![None; body.basic_blocks.len()];
+        let mut visits = vec![0; body.basic_blocks.len()];
+        states[0] = Some(vec![Own::Unborn; self.objs.len()]);
+        let mut worklist = vec![BasicBlock::from_usize(0)];
+        while let Some(bb) = worklist.pop() {
+            visits[bb.as_usize()] += 1;
+            let Some(mut state) = states[bb.as_usize()].clone() else {
+                continue;
+            };
+            self.transfer(bb, &mut state, false);
+            for succ in body.basic_blocks[bb].terminator().successors() {
+                if body.basic_blocks[succ].is_cleanup || visits[succ.as_usize()] > VISIT_LIMIT {
+                    continue;
+                }
+                let joined = match &states[succ.as_usize()] {
+                    Some(old) => old
+                        .iter()
+                        .zip(state.iter())
+                        .map(|(a, b)| a.join(*b))
+                        .collect(),
+                    None => state.clone(),
+                };
+                if states[succ.as_usize()].as_ref() != Some(&joined) {
+                    states[succ.as_usize()] = Some(joined);
+                    worklist.push(succ);
+                }
+            }
+        }
+        for (bb, state) in states.into_iter().enumerate() {
+            if let Some(mut state) = state {
+                self.transfer(BasicBlock::from_usize(bb), &mut state, true);
+            }
+        }
+    }
+
+    fn transfer(&mut self, bb: BasicBlock, state: &mut [Own], report: bool) {
+        let terminator = self.body.basic_blocks[bb].terminator();
+        let span = terminator.source_info.span;
+        match &terminator.kind {
+            TerminatorKind::Call { func, args, .. } => {
+                if let Some(obj) = self.sites.get(&bb) {
+                    state[*obj] = Own::Raw;
+                    return;
+                }
+                let Some((callee, generics)) = func.const_fn_def() else {
+                    return;
+                };
+                if let Some((kind, name)) = self.reclaim_kind(callee) {
+                    let Some(obj) = args.first().and_then(|arg| self.operand_obj(&arg.node)) else {
+                        return;
+                    };
+                    if report {
+                        self.check_reclaim(obj, kind, name, generics, state[obj], span);
+                    }
+                    state[obj] = Own::Reclaimed(span);
+                } else if self.tcx.is_foreign_item(callee)
+                    && self.spec.get(self.tcx.item_name(callee).as_str()) == Ownership::Consumes
+                {
+                    for arg in args.iter() {
+                        if let Some(obj) = self.operand_obj(&arg.node) {
+                            state[obj] = Own::Consumed(span);
+                        }
+                    }
+                }
+            }
+            TerminatorKind::Return if report => {
+                for (obj, own) in state.iter().enumerate() {
+                    let info = &self.objs[obj];
+                    if *own != Own::Raw || info.escaped || info.kind == AllocKind::Foreign {
+                        continue;
+                    }
+                    if let Some(given) = info.given_to_c {
+                        self.push_bug(FfiBugKind::Leak, obj, given);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_reclaim(
+        &mut self,
+        obj: usize,
+        kind: AllocKind,
+        name: String,
+        generics: GenericArgsRef<'tcx>,
+        own: Own,
+        span: Span,
+    ) {
+        let info = &self.objs[obj];
+        let ty_matches = match kind {
+            AllocKind::Box | AllocKind::Vec => {
+                generics.types().next().is_none_or(|ty| ty == info.ty)
+            }
+            _ => true,
+        };
+        if kind != info.kind || !ty_matches {
+            self.push_bug(FfiBugKind::Mismatch(name), obj, span);
+            return;
+        }
+        match own {
+            Own::Reclaimed(prev) => self.push_bug(FfiBugKind::DoubleReclaim(prev), obj, span),
+            Own::Consumed(consumed) => {
+                self.push_bug(FfiBugKind::ReclaimAfterConsume(consumed), obj, span)
+            }
+            _ => {}
+        }
+    }
+
+    fn push_bug(&mut self, kind: FfiBugKind, obj: usize, span: Span) {
+        if self.bugs.iter().any(|bug| bug.span == span) {
+            return;
+        }
+        self.bugs.push(FfiBug {
+            kind,
+            span,
+            alloc: self.objs[obj].kind,
+            origin: self.objs[obj].span,
+        });
+    }
+
+    // The std type whose inherent method the callee is, e.g., `Box` for `Box::into_raw`.
+    fn std_owner(&self, callee: DefId) -> Option<AllocKind> {
+        let tcx = self.tcx;
+        let adt = Some(inherent_self_ty(tcx, callee)?.ty_adt_def()?.did());
+        if adt == tcx.lang_items().owned_box() {
+            Some(AllocKind::Box)
+        } else if adt == tcx.get_diagnostic_item(sym::cstring_type) {
+            Some(AllocKind::CString)
+        } else if adt == tcx.get_diagnostic_item(sym::Vec) {
+            Some(AllocKind::Vec)
+        } else {
+            None
+        }
+    }
+
+    fn alloc_kind(&self, callee: DefId) -> Option<AllocKind> {
+        let name = self.tcx.item_name(callee);
+        match (name.as_str(), self.std_owner(callee)) {
+            ("into_raw", Some(kind @ (AllocKind::Box | AllocKind::CString))) => Some(kind),
+            ("into_raw_parts", Some(AllocKind::Vec)) => Some(AllocKind::Vec),
+            _ if self.tcx.is_foreign_item(callee)
+                && self.spec.get(name.as_str()) == Ownership::ReturnsOwned =>
+            {
+                Some(AllocKind::Foreign)
+            }
+            _ => None,
+        }
+    }
+
+    // The kind of heap objects a call releases, and how the call is displayed.
+    fn reclaim_kind(&self, callee: DefId) -> Option<(AllocKind, String)> {
+        let name = self.tcx.item_name(callee);
+        match (name.as_str(), self.std_owner(callee)) {
+            ("from_raw", Some(AllocKind::Box)) => {
+                Some((AllocKind::Box, "`Box::from_raw`".to_string()))
+            }
+            ("from_raw", Some(AllocKind::CString)) => {
+                Some((AllocKind::CString, "`CString::from_raw`".to_string()))
+            }
+            ("from_raw_parts", Some(AllocKind::Vec)) => {
+                Some((AllocKind::Vec, "`Vec::from_raw_parts`".to_string()))
+            }
+            _ if self.tcx.is_foreign_item(callee)
+                && self.spec.get(name.as_str()) == Ownership::Frees =>
+            {
+                Some((AllocKind::Foreign, format!("`{}`", name)))
+            }
+            _ => None,
+        }
+    }
+
+    fn operand_obj(&self, operand: &Operand<'tcx>) -> Option<usize> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => place
+                .as_local()
+                .and_then(|local| self.aliases.get(&local).copied()),
+            Operand::Constant(_) => None,
+        }
+    }
+}
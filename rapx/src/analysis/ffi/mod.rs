@@ -0,0 +1,113 @@
+pub mod body;
+pub mod spec;
+
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::ty::TyCtxt;
+
+use crate::{
+    rap_debug,
+    utils::{
+        boundary::guarded,
+        log::{report_fn_bugs, BugReport},
+    },
+};
+use body::{FfiBug, FfiBugKind, FnAnalysis};
+use spec::FfiSpec;
+
+/// Detect ownership bugs of heap objects that cross FFI boundaries.
+///
+/// Raw heap objects are created by `Box::into_raw`, `CString::into_raw`, `Vec::into_raw_parts`
+/// and foreign functions returning owned memory. The checker follows them through foreign calls,
+/// whose ownership semantics are given by `FfiSpec`, and reports objects given to C that are
+/// never reclaimed, objects reclaimed twice or after C took them over, and objects released by an
+/// API that does not match how they were allocated.
+pub struct FfiCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub spec: FfiSpec,
+    pub bugs: Vec<(DefId, Vec<FfiBug>)>,
+}
+
+impl<'tcx> FfiCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            spec: FfiSpec::load(),
+            bugs: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "FFI Check"
+    }
+
+    pub fn start(&mut self) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !self.is_target(def_id) {
+                continue;
+            }
+            rap_debug!("FFI ownership analysis for {:?}", def_id);
+            let Some(bugs) = guarded(self.name(), def_id, || {
+                let mut analysis = FnAnalysis::new(self.tcx, def_id, &self.spec);
+                analysis.run();
+                analysis.bugs
+            }) else {
+                continue;
+            };
+            if !bugs.is_empty() {
+                self.bugs.push((def_id, bugs));
+            }
+        }
+        self.report_bugs();
+    }
+
+    fn is_target(&self, def_id: DefId) -> bool {
+        matches!(
+            self.tcx.def_kind(def_id),
+            DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+        ) && self
+            .tcx
+            .hir_body_const_context(def_id.expect_local())
+            .is_none()
+            && self.tcx.is_mir_available(def_id)
+    }
+
+    fn report_bugs(&self) {
+        for (def_id, bugs) in self.bugs.iter() {
+            let reports: Vec<BugReport> = bugs
+                .iter()
+                .map(|bug| {
+                    let label = match bug.kind {
+                        FfiBugKind::Leak => "Given to C here, and never reclaimed afterwards.",
+                        _ => "Released here.",
+                    };
+                    let mut notes = vec![(bug.origin, "Raw pointer created here.".to_string())];
+                    match bug.kind {
+                        FfiBugKind::DoubleReclaim(prev) => {
+                            notes.push((prev, "Already reclaimed here.".to_string()));
+                        }
+                        FfiBugKind::ReclaimAfterConsume(consumed) => {
+                            notes.push((consumed, "Ownership transferred to C here.".to_string()));
+                        }
+                        _ => {}
+                    }
+                    BugReport {
+                        title: bug.to_string(),
+                        span: bug.span,
+                        label: label.to_string(),
+                        notes,
+                        help: None,
+                    }
+                })
+                .collect();
+            report_fn_bugs(
+                self.tcx.optimized_mir(*def_id).span,
+                &format!(
+                    "FFI ownership bug detected in function {:?}",
+                    self.tcx.def_path_str(*def_id)
+                ),
+                &reports,
+            );
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::rap_warn;
+use std::collections::HashMap;
+
+/// The ownership semantics of a foreign function for the pointers passed to or returned by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ownership {
+    /// The function takes over the pointed-to objects; the caller must not reclaim them.
+    Consumes,
+    /// The function only uses the objects during the call.
+    Borrows,
+    /// The function returns an object allocated by C, which must be released by C.
+    ReturnsOwned,
+    /// The function is a C deallocator, e.g., `free`.
+    Frees,
+}
+
+impl Ownership {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "consumes" => Some(Ownership::Consumes),
+            "borrows" => Some(Ownership::Borrows),
+            "returns_owned" => Some(Ownership::ReturnsOwned),
+            "frees" => Some(Ownership::Frees),
+            _ => None,
+        }
+    }
+}
+
+/// The ownership semantics of foreign functions, keyed by function name.
+///
+/// The built-in specification covers common libc functions. It can be extended or overridden by
+/// a TOML file given in the `RAP_FFI_CONFIG` environment variable, which maps function names to
+/// one of `consumes`, `borrows`, `returns_owned` and `frees`, e.g.,
+///
+/// ```toml
+/// register_callback_data = "consumes"
+/// make_message = "returns_owned"
+/// ```
+///
+/// Foreign functions that are not specified are assumed to borrow their arguments.
+#[derive(Debug, Clone, Default)]
+pub struct FfiSpec {
+    pub functions: HashMap<String, Ownership>,
+}
+
+impl FfiSpec {
+    pub fn load() -> Self {
+        let mut spec = FfiSpec::default();
+        spec.extend(include_str!("data/ffi_spec.toml"), "built-in");
+        if let Ok(path) = std::env::var("RAP_FFI_CONFIG") {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => spec.extend(&content, &path),
+                Err(e) => rap_warn!("Failed to read FFI config {}: {}", path, e),
+            }
+        }
+        spec
+    }
+
+    fn extend(&mut self, content: &str, source: &str) {
+        let functions: toml::Table = match content.parse() {
+            Ok(table) => table,
+            Err(e) => {
+                rap_warn!("Failed to parse FFI config {}: {}", source, e);
+                return;
+            }
+        };
+        for (name, value) in functions.iter() {
+            match value.as_str().and_then(Ownership::parse) {
+                Some(ownership) => {
+                    self.functions.insert(name.clone(), ownership);
+                }
+                None => rap_warn!(
+                    "Unknown ownership semantics {} of function {} in FFI config {}.",
+                    value,
+                    name,
+                    source
+                ),
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Ownership {
+        self.functions
+            .get(name)
+            .copied()
+            .unwrap_or(Ownership::Borrows)
+    }
+}
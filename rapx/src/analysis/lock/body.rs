@@ -0,0 +1,641 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use rustc_abi::FieldIdx;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, Local, Operand, Place, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{self, CoroutineArgs, CoroutineArgsExt, GenericArgsRef, Instance, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::{Span, Symbol};
+
+use crate::analysis::safedrop::graph::{DropHeaps, SafeDropGraph};
+use crate::analysis::utils::def_path::plain_path;
+
+// The functions that block on I/O or a timer. Generic arguments are stripped from the paths
+// before matching.
+const BLOCKING_PREFIXES: [&str; 2] = ["std::fs::", "std::thread::sleep"];
+// The types whose methods block on I/O or a process. A method, including a trait method, is
+// matched by the self type it is resolved to, e.g., `<File as Write>::write_all` by `File`, so
+// writing into a `Vec<u8>` is not blocking.
+const BLOCKING_TYPES: [&str; 15] = [
+    "std::fs::File",
+    "std::fs::OpenOptions",
+    "std::fs::ReadDir",
+    "std::io::Stdin",
+    "std::io::StdinLock",
+    "std::io::Stdout",
+    "std::io::StdoutLock",
+    "std::io::Stderr",
+    "std::net::TcpListener",
+    "std::net::TcpStream",
+    "std::net::UdpSocket",
+    "std::os::unix::net::UnixDatagram",
+    "std::os::unix::net::UnixListener",
+    "std::os::unix::net::UnixStream",
+    "std::process::Child",
+];
+// The buffers whose methods block if those of the value they wrap do.
+const BLOCKING_WRAPPERS: [&str; 3] = [
+    "std::io::BufReader",
+    "std::io::BufWriter",
+    "std::io::LineWriter",
+];
+// The methods that block on another thread or a process, with the path of their self type.
+const BLOCKING_CALLS: [&str; 8] = [
+    "std::process::Command::output",
+    "std::process::Command::status",
+    "std::sync::mpsc::Receiver::recv",
+    "std::sync::mpsc::Receiver::recv_timeout",
+    "std::sync::mpsc::SyncSender::send",
+    "std::sync::Condvar::wait",
+    "std::sync::Barrier::wait",
+    "std::thread::JoinHandle::join",
+];
+// The callees that allocate on the heap.
+const ALLOC_CALLS: [&str; 12] = [
+    "alloc::alloc::exchange_malloc",
+    "std::boxed::Box::new",
+    "std::vec::Vec::push",
+    "std::vec::Vec::with_capacity",
+    "std::vec::Vec::extend_from_slice",
+    "std::string::String::push",
+    "std::string::String::push_str",
+    "std::string::String::with_capacity",
+    "std::collections::HashMap::insert",
+    "std::fmt::format",
+    "std::string::ToString::to_string",
+    "std::borrow::ToOwned::to_owned",
+];
+
+const GUARDS: [&str; 4] = [
+    "MutexGuard",
+    "RwLockReadGuard",
+    "RwLockWriteGuard",
+    "RefCellRefMut",
+];
+const LOCKS: [&str; 3] = ["Mutex", "RwLock", "RefCell"];
+
+fn is_diagnostic_adt(tcx: TyCtxt<'_>, ty: Ty<'_>, names: &[&str]) -> bool {
+    match ty.kind() {
+        ty::Adt(adt_def, _) => tcx
+            .get_diagnostic_name(adt_def.did())
+            .is_some_and(|name| names.contains(&name.as_str())),
+        _ => false,
+    }
+}
+
+pub fn is_guard(tcx: TyCtxt<'_>, ty: Ty<'_>) -> bool {
+    is_diagnostic_adt(tcx, ty, &GUARDS)
+}
+
+fn is_lock(tcx: TyCtxt<'_>, ty: Ty<'_>) -> bool {
+    is_diagnostic_adt(tcx, ty, &LOCKS)
+}
+
+/// The lock a guard is acquired from. Locks stored in statics or fields are identified across
+/// functions, and the other ones only within the function acquiring them. A field stands for the
+/// locks of all the instances of the struct, so acquiring `a.m` and `b.m` in the opposite orders
+/// in two functions is not reported as inconsistent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockId {
+    Static(DefId),
+    Field(DefId, FieldIdx),
+    Local(Local),
+}
+
+impl LockId {
+    pub fn is_global(&self) -> bool {
+        !matches!(self, LockId::Local(_))
+    }
+
+    pub fn name(&self, tcx: TyCtxt<'_>) -> String {
+        match self {
+            LockId::Static(def_id) => tcx.def_path_str(*def_id),
+            LockId::Field(adt, field) => {
+                let adt_def = tcx.adt_def(*adt);
+                let field_name = adt_def
+                    .non_enum_variant()
+                    .fields
+                    .get(*field)
+                    .map(|field_def| field_def.name)
+                    .unwrap_or(Symbol::intern("?"));
+                format!("{}.{}", tcx.def_path_str(*adt), field_name)
+            }
+            LockId::Local(local) => format!("{:?}", local),
+        }
+    }
+}
+
+/// A guard acquired in the function, i.e., a call returning a guard.
+#[derive(Debug, Clone)]
+pub struct Acquisition {
+    pub lock: LockId,
+    pub block: BasicBlock,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LockBugKind {
+    BlockingCall(String),
+    AllocLoop,
+    UnknownCall,
+    Await,
+    SecondLock(String),
+}
+
+impl fmt::Display for LockBugKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockBugKind::BlockingCall(callee) => {
+                write!(f, "Lock guard held across the blocking call `{}`", callee)
+            }
+            LockBugKind::AllocLoop => write!(f, "Lock guard held across an allocating loop"),
+            LockBugKind::UnknownCall => {
+                write!(f, "Lock guard held across a call to an unknown closure")
+            }
+            LockBugKind::Await => write!(f, "Lock guard held across an await point"),
+            LockBugKind::SecondLock(lock) => {
+                write!(f, "Lock `{}` acquired while holding another guard", lock)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LockBug {
+    pub kind: LockBugKind,
+    /// Where the guard held is acquired.
+    pub guard: Span,
+    pub span: Span,
+}
+
+/// A lock acquired, directly or by a callee, while a guard of another lock is held.
+#[derive(Debug, Clone)]
+pub struct LockOrder {
+    pub held: LockId,
+    pub acquired: LockId,
+    pub guard: Span,
+    pub span: Span,
+}
+
+/// A call made while guards of global locks are held, whose callee may acquire more locks.
+#[derive(Debug, Clone)]
+pub struct HeldCall {
+    pub held: Vec<(LockId, Span)>,
+    pub callee: DefId,
+    pub span: Span,
+}
+
+// The guards held: the local holding the guard and the index of its acquisition.
+type Held = FxHashSet<(Local, usize)>;
+
+/// Track the guards of a function from their acquisition to their drop.
+pub struct FnLockAnalysis<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub def_id: DefId,
+    body: &'tcx Body<'tcx>,
+    graph: SafeDropGraph<'tcx>,
+    pub acquisitions: Vec<Acquisition>,
+    pub bugs: Vec<LockBug>,
+    pub orders: Vec<LockOrder>,
+    pub held_calls: Vec<HeldCall>,
+}
+
+impl<'tcx> FnLockAnalysis<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        let body = tcx.optimized_mir(def_id);
+        // the drops and the loops of the function are taken from the SafeDrop graph.
+        let mut graph = SafeDropGraph::new(body, tcx, def_id, DropHeaps::default());
+        graph.solve_scc();
+        Self {
+            tcx,
+            def_id,
+            body,
+            graph,
+            acquisitions: Vec::new(),
+            bugs: Vec::new(),
+            orders: Vec::new(),
+            held_calls: Vec::new(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        self.find_acquisitions();
+        if !self.acquisitions.is_empty() {
+            let held = self.held_guards();
+            self.check_held(&held);
+        }
+        self.check_awaits();
+        let mut seen = FxHashSet::default();
+        self.bugs.retain(|bug| seen.insert(bug.clone()));
+    }
+
+    fn find_acquisitions(&mut self) {
+        for (bb, data) in self.body.basic_blocks.iter_enumerated() {
+            if data.is_cleanup {
+                continue;
+            }
+            let terminator = data.terminator();
+            let TerminatorKind::Call {
+                args, destination, ..
+            } = &terminator.kind
+            else {
+                continue;
+            };
+            if !destination.projection.is_empty()
+                || !is_guard(self.tcx, self.body.local_decls[destination.local].ty)
+            {
+                continue;
+            }
+            let lock = args
+                .first()
+                .and_then(|arg| self.lock_of_operand(&arg.node, 0))
+                .unwrap_or(LockId::Local(destination.local));
+            self.acquisitions.push(Acquisition {
+                lock,
+                block: bb,
+                span: terminator.source_info.span,
+            });
+        }
+    }
+
+    fn lock_of_operand(&self, operand: &Operand<'tcx>, depth: usize) -> Option<LockId> {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => self.lock_of_place(place, depth),
+            Operand::Constant(constant) => constant.check_static_ptr(self.tcx).map(LockId::Static),
+        }
+    }
+
+    // Follow the references, `deref()` and `unwrap()` back to the lock, i.e., a static, a field
+    // of a struct, or a local holding the lock. Only the locals defined once are followed.
+    fn lock_of_place(&self, place: &Place<'tcx>, depth: usize) -> Option<LockId> {
+        if depth > 8 {
+            return None;
+        }
+        for (base, elem) in place.iter_projections().rev() {
+            if let ProjectionElem::Field(field, _) = elem {
+                if let ty::Adt(adt_def, _) = base.ty(self.body, self.tcx).ty.kind() {
+                    if adt_def.is_struct() {
+                        return Some(LockId::Field(adt_def.did(), field));
+                    }
+                }
+                return None;
+            }
+        }
+        let local = place.local;
+        if is_lock(self.tcx, self.body.local_decls[local].ty) {
+            return Some(LockId::Local(local));
+        }
+        // the rvalues assigned to the local, or the first arguments of the calls returning it.
+        let mut definitions: Vec<Result<&Rvalue<'tcx>, Option<&Operand<'tcx>>>> = Vec::new();
+        for data in self.body.basic_blocks.iter() {
+            for statement in data.statements.iter() {
+                match &statement.kind {
+                    StatementKind::Assign(assign) if assign.0.local == local => {
+                        if !assign.0.projection.is_empty() {
+                            return None;
+                        }
+                        definitions.push(Ok(&assign.1));
+                    }
+                    _ => {}
+                }
+            }
+            if let TerminatorKind::Call {
+                args, destination, ..
+            } = &data.terminator().kind
+            {
+                if destination.local == local {
+                    definitions.push(Err(args.first().map(|arg| &arg.node)));
+                }
+            }
+        }
+        let [definition] = definitions[..] else {
+            return None;
+        };
+        match definition {
+            Ok(Rvalue::Use(operand)) => self.lock_of_operand(operand, depth + 1),
+            Ok(Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place)) => {
+                self.lock_of_place(place, depth + 1)
+            }
+            Ok(_) => None,
+            Err(arg) => arg.and_then(|arg| self.lock_of_operand(arg, depth + 1)),
+        }
+    }
+
+    // The guards released by the block, i.e., dropped or moved into a call.
+    fn released(&self, bb: BasicBlock) -> Vec<Local> {
+        let mut released = Vec::new();
+        for drop in self.graph.blocks[bb.as_usize()].drops.iter() {
+            match &drop.kind {
+                TerminatorKind::Drop { place, .. } if place.projection.is_empty() => {
+                    released.push(place.local)
+                }
+                TerminatorKind::Call { args, .. } => {
+                    for arg in args.iter() {
+                        if let Operand::Move(place) = &arg.node {
+                            released.push(place.local);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let TerminatorKind::Call { args, .. } = &self.body.basic_blocks[bb].terminator().kind {
+            for arg in args.iter() {
+                if let Operand::Move(place) = &arg.node {
+                    if place.projection.is_empty() {
+                        released.push(place.local);
+                    }
+                }
+            }
+        }
+        released
+    }
+
+    // The guards held before the terminator of each block, by a forward may-analysis over the
+    // blocks that are not cleanups.
+    fn held_guards(&self) -> FxHashMap<BasicBlock, Held> {
+        let body = self.body;
+        let acquired: FxHashMap<BasicBlock, usize> = self
+            .acquisitions
+            .iter()
+            .enumerate()
+            .map(|(idx, acquisition)| (acquisition.block, idx))
+            .collect();
+        let mut entry: FxHashMap<BasicBlock, Held> = FxHashMap::default();
+        let mut before_terminator: FxHashMap<BasicBlock, Held> = FxHashMap::default();
+        let mut worklist = VecDeque::from([BasicBlock::from_usize(0)]);
+        entry.insert(BasicBlock::from_usize(0), Held::default());
+        while let Some(bb) = worklist.pop_front() {
+            let data = &body.basic_blocks[bb];
+            let mut held = entry[&bb].clone();
+            for statement in data.statements.iter() {
+                match &statement.kind {
+                    StatementKind::Assign(assign) if assign.0.projection.is_empty() => {
+                        let dest = assign.0.local;
+                        let moved: Vec<(Local, usize)> = match &assign.1 {
+                            Rvalue::Use(Operand::Move(src)) if src.projection.is_empty() => held
+                                .iter()
+                                .filter(|(local, _)| *local == src.local)
+                                .copied()
+                                .collect(),
+                            _ => Vec::new(),
+                        };
+                        held.retain(|(local, _)| *local != dest);
+                        for (src, idx) in moved {
+                            held.remove(&(src, idx));
+                            held.insert((dest, idx));
+                        }
+                    }
+                    StatementKind::StorageDead(local) => held.retain(|(held, _)| held != local),
+                    _ => {}
+                }
+            }
+            before_terminator.insert(bb, held.clone());
+            let released = self.released(bb);
+            held.retain(|(local, _)| !released.contains(local));
+            let terminator = data.terminator();
+            if let (Some(idx), TerminatorKind::Call { destination, .. }) =
+                (acquired.get(&bb), &terminator.kind)
+            {
+                held.insert((destination.local, *idx));
+            }
+            for succ in terminator.successors() {
+                if body.basic_blocks[succ].is_cleanup {
+                    continue;
+                }
+                let succ_entry = entry.entry(succ).or_default();
+                let len = succ_entry.len();
+                succ_entry.extend(held.iter().copied());
+                let changed = succ_entry.len() > len || !before_terminator.contains_key(&succ);
+                if changed && !worklist.contains(&succ) {
+                    worklist.push_back(succ);
+                }
+            }
+        }
+        before_terminator
+    }
+
+    fn in_loop(&self, bb: BasicBlock) -> bool {
+        let scc = self.graph.scc_indices[bb.as_usize()];
+        !self.graph.blocks[scc].scc_sub_blocks.is_empty()
+            || self.body.basic_blocks[bb]
+                .terminator()
+                .successors()
+                .any(|succ| succ == bb)
+    }
+
+    // The callee is a closure, or a function, only known at runtime.
+    fn is_unknown_call(&self, func: &Operand<'tcx>) -> bool {
+        let Some((callee, args)) = func.const_fn_def() else {
+            return true;
+        };
+        let is_fn_trait = self
+            .tcx
+            .trait_of_assoc(callee)
+            .is_some_and(|trait_id| self.tcx.fn_trait_kind_from_def_id(trait_id).is_some());
+        is_fn_trait
+            && matches!(
+                args.type_at(0).peel_refs().kind(),
+                ty::Param(_) | ty::Dynamic(..) | ty::FnPtr(..) | ty::Alias(..)
+            )
+    }
+
+    // The self type of the method the callee resolves to, with the buffers peeled off, or `None`
+    // if the callee is not a method.
+    fn self_ty(&self, callee: DefId, args: GenericArgsRef<'tcx>) -> Option<Ty<'tcx>> {
+        let tcx = self.tcx;
+        let typing_env = TypingEnv::post_analysis(tcx, self.def_id);
+        let (resolved, args) = match Instance::try_resolve(tcx, typing_env, callee, args) {
+            Ok(Some(instance)) => (instance.def_id(), instance.args),
+            _ => (callee, args),
+        };
+        let mut self_ty = match tcx.impl_of_assoc(resolved) {
+            Some(impl_id) => tcx.type_of(impl_id).instantiate(tcx, args),
+            None if tcx.trait_of_assoc(resolved).is_some() => args.type_at(0),
+            None => return None,
+        };
+        loop {
+            self_ty = self_ty.peel_refs();
+            match self_ty.kind() {
+                ty::Adt(adt_def, adt_args)
+                    if BLOCKING_WRAPPERS.contains(&plain_path(tcx, adt_def.did()).as_str()) =>
+                {
+                    self_ty = adt_args.type_at(0);
+                }
+                _ => return Some(self_ty),
+            }
+        }
+    }
+
+    fn is_blocking(&self, callee: DefId, args: GenericArgsRef<'tcx>) -> bool {
+        let tcx = self.tcx;
+        let Some(self_ty) = self.self_ty(callee, args) else {
+            let path = plain_path(tcx, callee);
+            return BLOCKING_PREFIXES
+                .iter()
+                .any(|prefix| path.starts_with(prefix));
+        };
+        let ty::Adt(adt_def, _) = self_ty.kind() else {
+            return false;
+        };
+        let ty_path = plain_path(tcx, adt_def.did());
+        let method_path = format!("{}::{}", ty_path, tcx.item_name(callee));
+        BLOCKING_TYPES.contains(&ty_path.as_str()) || BLOCKING_CALLS.contains(&method_path.as_str())
+    }
+
+    fn check_held(&mut self, held: &FxHashMap<BasicBlock, Held>) {
+        let tcx = self.tcx;
+        let mut bugs = Vec::new();
+        let mut orders = Vec::new();
+        let mut held_calls = Vec::new();
+        let acquired: FxHashMap<BasicBlock, usize> = self
+            .acquisitions
+            .iter()
+            .enumerate()
+            .map(|(idx, acquisition)| (acquisition.block, idx))
+            .collect();
+        for (bb, guards) in held.iter() {
+            if guards.is_empty() {
+                continue;
+            }
+            let terminator = self.body.basic_blocks[*bb].terminator();
+            let TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+                continue;
+            };
+            let span = terminator.source_info.span;
+            // a guard moved into the call, e.g., by `Condvar::wait(guard)`, is released by it.
+            let moved: Vec<Local> = args
+                .iter()
+                .filter_map(|arg| match &arg.node {
+                    Operand::Move(place) if place.projection.is_empty() => Some(place.local),
+                    _ => None,
+                })
+                .collect();
+            let held_acquisitions: Vec<&Acquisition> = guards
+                .iter()
+                .filter(|(local, _)| !moved.contains(local))
+                .map(|(_, idx)| &self.acquisitions[*idx])
+                .collect();
+            if held_acquisitions.is_empty() {
+                continue;
+            }
+            let mut report = |kind: LockBugKind| {
+                for acquisition in held_acquisitions.iter() {
+                    bugs.push(LockBug {
+                        kind: kind.clone(),
+                        guard: acquisition.span,
+                        span,
+                    });
+                }
+            };
+
+            if let Some(idx) = acquired.get(bb) {
+                let lock = self.acquisitions[*idx].lock;
+                report(LockBugKind::SecondLock(lock.name(tcx)));
+                for acquisition in held_acquisitions.iter() {
+                    if acquisition.lock != lock {
+                        orders.push(LockOrder {
+                            held: acquisition.lock,
+                            acquired: lock,
+                            guard: acquisition.span,
+                            span,
+                        });
+                    }
+                }
+                continue;
+            }
+            if self.is_unknown_call(func) {
+                report(LockBugKind::UnknownCall);
+                continue;
+            }
+            let Some((callee, callee_args)) = func.const_fn_def() else {
+                continue;
+            };
+            let path = plain_path(tcx, callee);
+            if self.is_blocking(callee, callee_args) {
+                report(LockBugKind::BlockingCall(path));
+                continue;
+            }
+            // a loop entered with the guard held, i.e., the guard is not acquired in the loop.
+            if ALLOC_CALLS.contains(&path.as_str()) && self.in_loop(*bb) {
+                let scc = self.graph.scc_indices[bb.as_usize()];
+                for acquisition in held_acquisitions.iter() {
+                    if self.graph.scc_indices[acquisition.block.as_usize()] != scc {
+                        bugs.push(LockBug {
+                            kind: LockBugKind::AllocLoop,
+                            guard: acquisition.span,
+                            span,
+                        });
+                    }
+                }
+                continue;
+            }
+            if callee.is_local() {
+                let held_global: Vec<(LockId, Span)> = held_acquisitions
+                    .iter()
+                    .filter(|acquisition| acquisition.lock.is_global())
+                    .map(|acquisition| (acquisition.lock, acquisition.span))
+                    .collect();
+                if !held_global.is_empty() {
+                    held_calls.push(HeldCall {
+                        held: held_global,
+                        callee,
+                        span,
+                    });
+                }
+            }
+        }
+        // report each allocating loop once per guard.
+        let mut loops = FxHashSet::default();
+        bugs.retain(|bug: &LockBug| {
+            bug.kind != LockBugKind::AllocLoop || loops.insert((bug.guard, self.loop_of(bug.span)))
+        });
+        self.bugs.extend(bugs);
+        self.orders.extend(orders);
+        self.held_calls.extend(held_calls);
+    }
+
+    // The loop of a call, i.e., the SCC of its block.
+    fn loop_of(&self, span: Span) -> usize {
+        self.body
+            .basic_blocks
+            .iter_enumerated()
+            .find(|(_, data)| data.terminator().source_info.span == span)
+            .map(|(bb, _)| self.graph.scc_indices[bb.as_usize()])
+            .unwrap_or(usize::MAX)
+    }
+
+    // A guard saved in a coroutine is held across the suspension points of the variants it is
+    // saved in, i.e., the `.await`s.
+    fn check_awaits(&mut self) {
+        if !self.tcx.is_coroutine(self.def_id) {
+            return;
+        }
+        let Some(layout) = self.tcx.mir_coroutine_witnesses(self.def_id) else {
+            return;
+        };
+        for (saved, field) in layout.field_tys.iter_enumerated() {
+            if !is_guard(self.tcx, field.ty) {
+                continue;
+            }
+            for (variant, fields) in layout.variant_fields.iter_enumerated() {
+                if variant.as_usize() < CoroutineArgs::RESERVED_VARIANTS
+                    || !fields.iter().any(|field| *field == saved)
+                {
+                    continue;
+                }
+                self.bugs.push(LockBug {
+                    kind: LockBugKind::Await,
+                    guard: field.source_info.span,
+                    span: layout.variant_source_info[variant].span,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,226 @@
+pub mod body;
+
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::ty::TyCtxt;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    analysis::core::callgraph::{default::CallGraphAnalyzer, CallGraphAnalysis},
+    rap_debug, rap_warn,
+    utils::{
+        boundary::guarded,
+        log::{print_bug_report, report_fn_bugs, BugReport},
+    },
+};
+use body::{FnLockAnalysis, LockBug, LockId, LockOrder};
+
+/// Detect lock guards held across expensive operations, and locks acquired in inconsistent
+/// orders.
+///
+/// The guards of `Mutex`, `RwLock` and `RefCell` are tracked from the calls returning them to their
+/// drops, which are taken from the SafeDrop graph of the function. A guard is reported if it is
+/// held across a blocking call, a loop that allocates, a call to an unknown closure, an `.await`,
+/// or the acquisition of another lock. The locks stored in statics or struct fields acquired while
+/// holding another one, directly or by the callees found in the call graph, give the order of the
+/// two locks; two locks acquired in both orders may deadlock.
+pub struct LockCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub bugs: Vec<(DefId, Vec<LockBug>)>,
+    pub orders: Vec<(DefId, LockOrder)>,
+}
+
+impl<'tcx> LockCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            bugs: Vec::new(),
+            orders: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "Lock Check"
+    }
+
+    pub fn start(&mut self) {
+        let mut callgraph_analyzer = CallGraphAnalyzer::new(self.tcx);
+        callgraph_analyzer.start();
+        let callgraph = callgraph_analyzer.get_callgraph();
+
+        // the global locks each function acquires itself, and the calls made holding them.
+        let mut acquired: HashMap<DefId, HashSet<LockId>> = HashMap::new();
+        let mut held_calls = Vec::new();
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !self.is_target(def_id) {
+                continue;
+            }
+            rap_debug!("Lock check for {:?}", def_id);
+            let Some(analysis) = guarded(self.name(), def_id, || {
+                let mut analysis = FnLockAnalysis::new(self.tcx, def_id);
+                analysis.run();
+                (
+                    analysis.acquisitions,
+                    analysis.bugs,
+                    analysis.orders,
+                    analysis.held_calls,
+                )
+            }) else {
+                continue;
+            };
+            let (acquisitions, bugs, orders, calls) = analysis;
+            acquired.insert(
+                def_id,
+                acquisitions
+                    .iter()
+                    .map(|acquisition| acquisition.lock)
+                    .filter(LockId::is_global)
+                    .collect(),
+            );
+            if !bugs.is_empty() {
+                self.bugs.push((def_id, bugs));
+            }
+            self.orders
+                .extend(orders.into_iter().map(|order| (def_id, order)));
+            held_calls.extend(calls.into_iter().map(|call| (def_id, call)));
+        }
+
+        // the locks a function may acquire, including those of its callees.
+        let mut may_acquire = acquired;
+        loop {
+            let mut changed = false;
+            for (caller, callees) in callgraph.fn_calls.iter() {
+                let mut locks: HashSet<LockId> = HashSet::new();
+                for callee in callees.iter() {
+                    if let Some(callee_locks) = may_acquire.get(callee) {
+                        locks.extend(callee_locks.iter().copied());
+                    }
+                }
+                let caller_locks = may_acquire.entry(*caller).or_default();
+                let len = caller_locks.len();
+                caller_locks.extend(locks);
+                changed |= caller_locks.len() > len;
+            }
+            if !changed {
+                break;
+            }
+        }
+        for (caller, call) in held_calls {
+            let Some(locks) = may_acquire.get(&call.callee) else {
+                continue;
+            };
+            for lock in locks.iter() {
+                for (held, guard) in call.held.iter() {
+                    if held != lock {
+                        self.orders.push((
+                            caller,
+                            LockOrder {
+                                held: *held,
+                                acquired: *lock,
+                                guard: *guard,
+                                span: call.span,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+
+        self.report_bugs();
+        self.report_deadlocks();
+    }
+
+    fn is_target(&self, def_id: DefId) -> bool {
+        matches!(
+            self.tcx.def_kind(def_id),
+            DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+        ) && self
+            .tcx
+            .hir_body_const_context(def_id.expect_local())
+            .is_none()
+            && self.tcx.is_mir_available(def_id)
+    }
+
+    // a coroutine, e.g., the body of an async fn, is reported with the function defining it.
+    fn fn_name(&self, def_id: DefId) -> String {
+        let def_id = match self.tcx.is_coroutine(def_id) {
+            true => self.tcx.typeck_root_def_id(def_id),
+            false => def_id,
+        };
+        self.tcx.def_path_str(def_id)
+    }
+
+    fn report_bugs(&self) {
+        for (def_id, bugs) in self.bugs.iter() {
+            let reports: Vec<BugReport> = bugs
+                .iter()
+                .map(|bug| BugReport {
+                    title: bug.kind.to_string(),
+                    span: bug.span,
+                    label: "The guard is still held here.".to_string(),
+                    notes: vec![(bug.guard, "The guard is acquired here.".to_string())],
+                    help: Some("Drop the guard before this point.".to_string()),
+                })
+                .collect();
+            report_fn_bugs(
+                self.tcx.optimized_mir(*def_id).span,
+                &format!(
+                    "Lock guard held too long in function {:?}",
+                    self.fn_name(*def_id)
+                ),
+                &reports,
+            );
+        }
+    }
+
+    // Report two locks acquired in both orders, once per pair.
+    fn report_deadlocks(&self) {
+        let mut first_orders: HashMap<(LockId, LockId), &(DefId, LockOrder)> = HashMap::new();
+        for order in self.orders.iter() {
+            if order.1.held.is_global() && order.1.acquired.is_global() {
+                first_orders
+                    .entry((order.1.held, order.1.acquired))
+                    .or_insert(order);
+            }
+        }
+        let mut reported = HashSet::new();
+        for order in self.orders.iter() {
+            let (held, acquired) = (order.1.held, order.1.acquired);
+            let Some(reversed) = first_orders.get(&(acquired, held)) else {
+                continue;
+            };
+            if !reported.insert((held, acquired)) || !reported.insert((acquired, held)) {
+                continue;
+            }
+            let order = first_orders[&(held, acquired)];
+            rap_warn!(
+                "Potential deadlock: locks `{}` and `{}` are acquired in inconsistent orders in functions {:?} and {:?}",
+                held.name(self.tcx),
+                acquired.name(self.tcx),
+                self.fn_name(order.0),
+                self.fn_name(reversed.0),
+            );
+            for (def_id, order) in [order, *reversed] {
+                self.report_order(*def_id, order);
+            }
+        }
+    }
+
+    fn report_order(&self, def_id: DefId, order: &LockOrder) {
+        let report = BugReport {
+            title: format!(
+                "Lock `{}` acquired while holding `{}`",
+                order.acquired.name(self.tcx),
+                order.held.name(self.tcx)
+            ),
+            span: order.span,
+            label: format!("`{}` is acquired here.", order.acquired.name(self.tcx)),
+            notes: vec![(
+                order.guard,
+                format!("`{}` is held from here.", order.held.name(self.tcx)),
+            )],
+            help: None,
+        };
+        print_bug_report(self.tcx.optimized_mir(def_id).span, &report);
+    }
+}
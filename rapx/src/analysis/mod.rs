@@ -1,10 +1,16 @@
 pub mod core;
+pub mod ffi;
+pub mod lock;
 pub mod opt;
 pub mod rcanary;
 pub mod safedrop;
 pub mod scan;
+pub mod sendsync;
 pub mod senryx;
+pub mod taint;
 pub mod test;
+pub mod transmute;
+pub mod uninit;
 pub mod unsafety_isolation;
 pub mod utils;
 
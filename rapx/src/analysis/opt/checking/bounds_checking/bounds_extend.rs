@@ -8,7 +8,10 @@ use crate::{
         core::dataflow::{graph::*, *},
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use annotate_snippets::{Level, Renderer, Snippet};
 
@@ -93,6 +96,7 @@ fn report_extend_bug(graph: &Graph, span: Span) {
                 .span(relative_pos_range(graph.span, span))
                 .label("Checked here."),
         );
+    record_finding("Unnecessary bound checkings detected", span);
     let message = Level::Warning
         .title("Unnecessary bound checkings detected")
         .snippet(snippet)
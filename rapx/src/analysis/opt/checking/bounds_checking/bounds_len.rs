@@ -12,7 +12,10 @@ use crate::{
         core::dataflow::{graph::*, *},
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use annotate_snippets::{Level, Renderer, Snippet};
 
@@ -304,6 +307,7 @@ fn report_upperbound_bug(graph: &Graph, upperbound_node_idx: Local, index_record
                 .label("Checked here."),
         );
     }
+    record_finding("Unnecessary bounds checkings detected", upperbound_span);
     let message = Level::Warning
         .title("Unnecessary bounds checkings detected")
         .snippet(snippet)
@@ -7,6 +7,7 @@ use rustc_span::Span;
 
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::utils::def_path::DefPath;
+use crate::utils::findings::record_finding;
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_first_line, span_to_line_number,
     span_to_source_code, span_to_trimmed_span,
@@ -142,6 +143,7 @@ fn report_loop_push_bug(loop_span: Span, push_record: &Vec<Span>) {
                 .label("Push happens here."),
         );
     }
+    record_finding("Unnecessary bounds checkings detected", loop_span);
     let message = Level::Warning
         .title("Unnecessary bounds checkings detected")
         .snippet(snippet);
@@ -8,9 +8,12 @@ use std::collections::HashSet;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
-        opt::OptCheck,
+        opt::{fix::Fix, OptCheck},
+    },
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
 };
 use annotate_snippets::{Level, Renderer, Snippet};
 
@@ -59,6 +62,10 @@ impl OptCheck for EncodingCheck {
             + self.string_lowercase.cnt()
             + self.string_push.cnt()
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        self.string_lowercase.fixes()
+    }
 }
 
 fn report_encoding_bug(graph: &Graph, span: Span) {
@@ -73,6 +80,7 @@ fn report_encoding_bug(graph: &Graph, span: Span) {
                 .span(relative_pos_range(graph.span, span))
                 .label("Checked here."),
         );
+    record_finding("Unnecessary encoding checkings detected", span);
     let message = Level::Warning
         .title("Unnecessary encoding checkings detected")
         .snippet(snippet)
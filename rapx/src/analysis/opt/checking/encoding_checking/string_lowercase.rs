@@ -2,15 +2,19 @@ use annotate_snippets::{Level, Renderer, Snippet};
 
 use once_cell::sync::OnceCell;
 
-use rustc_hir::{intravisit, Expr, ExprKind};
+use rustc_ast::LitKind;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{intravisit, BinOpKind, Expr, ExprKind, HirId};
 use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::TypeckResults;
+use rustc_span::source_map::get_source_map;
 use rustc_span::Span;
 
 use crate::analysis::core::dataflow::graph::Graph;
-use crate::analysis::opt::OptCheck;
+use crate::analysis::opt::{fix::Fix, OptCheck};
 use crate::analysis::utils::def_path::DefPath;
 
+use crate::utils::findings::record_finding;
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code,
 };
@@ -29,14 +33,99 @@ impl DefPaths {
     }
 }
 
+const TITLE: &str = "Unnecessary encoding checkings detected.";
+
 struct LowercaseFinder<'tcx> {
     typeck_results: &'tcx TypeckResults<'tcx>,
     record: Vec<Span>,
+    fixes: Vec<Fix>,
+    // the calls already fixed by the comparisons containing them.
+    fixed: FxHashSet<HirId>,
+}
+
+// The source code of the expression, parenthesized if it is not a primary expression.
+fn operand_source(ex: &Expr<'_>) -> Option<String> {
+    if ex.span.from_expansion() {
+        return None;
+    }
+    let source = get_source_map()?.span_to_snippet(ex.span).ok()?;
+    match ex.kind {
+        ExprKind::Path(_)
+        | ExprKind::Lit(_)
+        | ExprKind::Call(..)
+        | ExprKind::MethodCall(..)
+        | ExprKind::Field(..)
+        | ExprKind::Index(..) => Some(source),
+        _ => Some(format!("({})", source)),
+    }
+}
+
+impl<'tcx> LowercaseFinder<'tcx> {
+    // The receiver of `to_lowercase()` if the expression is such a call.
+    fn lowercased(&self, ex: &'tcx Expr<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+        let ExprKind::MethodCall(_, receiver, ..) = ex.kind else {
+            return None;
+        };
+        let def_id = self.typeck_results.type_dependent_def_id(ex.hir_id)?;
+        let target_def_id = DEFPATHS.get().unwrap().string_to_lowercase.last_def_id();
+        (def_id == target_def_id).then_some(receiver)
+    }
+
+    // `a.to_lowercase() == b.to_lowercase()`, or a comparison with a lowercase literal, is
+    // `a.eq_ignore_ascii_case(b)` for the ASCII strings the check assumes. Unlike the lowercase
+    // string, the result of the comparison does not leave the expression, so the fix is applied.
+    fn comparison_fix(&self, ex: &'tcx Expr<'tcx>) -> Option<(Fix, Vec<HirId>)> {
+        let ExprKind::Binary(op, lhs, rhs) = ex.kind else {
+            return None;
+        };
+        if !matches!(op.node, BinOpKind::Eq | BinOpKind::Ne) || ex.span.from_expansion() {
+            return None;
+        }
+        let (lowered, other) = match (self.lowercased(lhs), self.lowercased(rhs)) {
+            (Some(receiver), _) => ((lhs, receiver), rhs),
+            (None, Some(receiver)) => ((rhs, receiver), lhs),
+            (None, None) => return None,
+        };
+        let mut fixed = vec![lowered.0.hir_id];
+        let receiver = operand_source(lowered.1)?;
+        let arg = match self.lowercased(other) {
+            Some(other_receiver) => {
+                fixed.push(other.hir_id);
+                let source = operand_source(other_receiver)?;
+                if self.typeck_results.expr_ty(other_receiver).is_ref() {
+                    source
+                } else {
+                    format!("&{}", source)
+                }
+            }
+            None => match other.kind {
+                ExprKind::Lit(lit)
+                    if matches!(lit.node, LitKind::Str(sym, _)
+                        if !sym.as_str().chars().any(char::is_uppercase)) =>
+                {
+                    operand_source(other)?
+                }
+                _ => return None,
+            },
+        };
+        let negation = if op.node == BinOpKind::Ne { "!" } else { "" };
+        let fix = Fix::new(
+            TITLE,
+            "Use eq_ignore_ascii_case instead.",
+            ex.span,
+            format!("{}{}.eq_ignore_ascii_case({})", negation, receiver, arg),
+        );
+        Some((fix, fixed))
+    }
 }
 
 impl<'tcx> intravisit::Visitor<'tcx> for LowercaseFinder<'tcx> {
     fn visit_expr(&mut self, ex: &'tcx Expr<'tcx>) {
-        if let ExprKind::MethodCall(.., span) = ex.kind {
+        if let Some((fix, fixed)) = self.comparison_fix(ex) {
+            self.fixes.push(fix);
+            self.fixed.extend(fixed);
+        }
+        if let ExprKind::MethodCall(segment, .., span) = ex.kind {
             let def_id = self
                 .typeck_results
                 .type_dependent_def_id(ex.hir_id)
@@ -44,6 +133,17 @@ impl<'tcx> intravisit::Visitor<'tcx> for LowercaseFinder<'tcx> {
             let target_def_id = (&DEFPATHS.get().unwrap()).string_to_lowercase.last_def_id();
             if def_id == target_def_id {
                 self.record.push(span);
+                if !self.fixed.contains(&ex.hir_id) && !segment.ident.span.from_expansion() {
+                    self.fixes.push(
+                        Fix::new(
+                            TITLE,
+                            "Use to_ascii_lowercase instead.",
+                            segment.ident.span,
+                            "to_ascii_lowercase".to_string(),
+                        )
+                        .maybe_incorrect(),
+                    );
+                }
             }
         }
         intravisit::walk_expr(self, ex);
@@ -52,11 +152,15 @@ impl<'tcx> intravisit::Visitor<'tcx> for LowercaseFinder<'tcx> {
 
 pub struct StringLowercaseCheck {
     record: Vec<Span>,
+    fixes: Vec<Fix>,
 }
 
 impl OptCheck for StringLowercaseCheck {
     fn new() -> Self {
-        Self { record: Vec::new() }
+        Self {
+            record: Vec::new(),
+            fixes: Vec::new(),
+        }
     }
 
     fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
@@ -67,9 +171,12 @@ impl OptCheck for StringLowercaseCheck {
         let mut contains_finder = LowercaseFinder {
             typeck_results,
             record: Vec::new(),
+            fixes: Vec::new(),
+            fixed: FxHashSet::default(),
         };
         intravisit::walk_body(&mut contains_finder, body);
         self.record = contains_finder.record;
+        self.fixes = contains_finder.fixes;
     }
 
     fn report(&self, graph: &Graph) {
@@ -81,6 +188,10 @@ impl OptCheck for StringLowercaseCheck {
     fn cnt(&self) -> usize {
         self.record.len()
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        self.fixes.clone()
+    }
 }
 
 fn report_string_ascii_bug(graph: &Graph, contains_span: Span) {
@@ -95,8 +206,9 @@ fn report_string_ascii_bug(graph: &Graph, contains_span: Span) {
                 .span(relative_pos_range(graph.span, contains_span))
                 .label("Checked here."),
         );
+    record_finding(TITLE, contains_span);
     let message = Level::Warning
-        .title("Unnecessary encoding checkings detected.")
+        .title(TITLE)
         .snippet(snippet)
         .footer(Level::Help.title("Use to_ascii_lowercase istead."));
     let renderer = Renderer::styled();
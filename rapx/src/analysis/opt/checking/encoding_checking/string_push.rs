@@ -11,7 +11,10 @@ use crate::{
         core::dataflow::{graph::*, *},
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use annotate_snippets::{Level, Renderer, Snippet};
 
@@ -122,6 +125,9 @@ fn report_string_push_bug(graph: &Graph, spans: &Vec<Span>) {
                 .label("Checked here."),
         )
     }
+    if let Some(span) = spans.first() {
+        record_finding("Unnecessary encoding checkings detected", *span);
+    }
     let message = Level::Warning
         .title("Unnecessary encoding checkings detected")
         .snippet(snippet)
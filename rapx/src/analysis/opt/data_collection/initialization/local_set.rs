@@ -8,7 +8,10 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use rustc_hir::def_id::DefId;
 use rustc_middle::{mir::Local, ty::TyCtxt};
@@ -94,6 +97,7 @@ fn report_local_set(graph: &Graph, span: Span) {
                 .span(relative_pos_range(graph.span, span))
                 .label("Initialization happens here"),
         );
+    record_finding("Unnecessary data collection initialization detected", span);
     let message = Level::Warning
         .title("Unnecessary data collection initialization detected")
         .snippet(snippet)
@@ -1,13 +1,17 @@
 pub mod flatten_collect;
+pub mod loop_invariant_alloc;
+pub mod needless_collect;
 pub mod unreserved_hash;
 pub mod unreserved_vec;
 
 use flatten_collect::FlattenCollectCheck;
+use loop_invariant_alloc::LoopInvariantAllocCheck;
+use needless_collect::NeedlessCollectCheck;
 use unreserved_hash::UnreservedHashCheck;
 use unreserved_vec::UnreservedVecCheck;
 
 use crate::analysis::core::dataflow::graph::Graph;
-use crate::analysis::opt::OptCheck;
+use crate::analysis::opt::{fix::Fix, OptCheck};
 
 use rustc_middle::ty::TyCtxt;
 
@@ -15,6 +19,8 @@ pub struct ReservationCheck {
     unreserved_hash: UnreservedHashCheck,
     unreserved_vec: UnreservedVecCheck,
     flatten_collect: FlattenCollectCheck,
+    loop_invariant_alloc: LoopInvariantAllocCheck,
+    needless_collect: NeedlessCollectCheck,
 }
 
 impl OptCheck for ReservationCheck {
@@ -23,6 +29,8 @@ impl OptCheck for ReservationCheck {
             unreserved_hash: UnreservedHashCheck::new(),
             unreserved_vec: UnreservedVecCheck::new(),
             flatten_collect: FlattenCollectCheck::new(),
+            loop_invariant_alloc: LoopInvariantAllocCheck::new(),
+            needless_collect: NeedlessCollectCheck::new(),
         }
     }
 
@@ -30,15 +38,30 @@ impl OptCheck for ReservationCheck {
         self.unreserved_hash.check(graph, tcx);
         self.unreserved_vec.check(graph, tcx);
         self.flatten_collect.check(graph, tcx);
+        self.loop_invariant_alloc.check(graph, tcx);
+        self.needless_collect.check(graph, tcx);
     }
 
     fn report(&self, graph: &Graph) {
         self.unreserved_hash.report(graph);
         self.unreserved_vec.report(graph);
         self.flatten_collect.report(graph);
+        self.loop_invariant_alloc.report(graph);
+        self.needless_collect.report(graph);
     }
 
     fn cnt(&self) -> usize {
-        self.unreserved_hash.cnt() + self.unreserved_vec.cnt() + self.flatten_collect.cnt()
+        self.unreserved_hash.cnt()
+            + self.unreserved_vec.cnt()
+            + self.flatten_collect.cnt()
+            + self.loop_invariant_alloc.cnt()
+            + self.needless_collect.cnt()
+    }
+
+    fn fixes(&self) -> Vec<Fix> {
+        let mut fixes = self.unreserved_hash.fixes();
+        fixes.extend(self.unreserved_vec.fixes());
+        fixes.extend(self.needless_collect.fixes());
+        fixes
     }
 }
@@ -8,7 +8,10 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use annotate_snippets::{Level, Renderer, Snippet};
 use rustc_span::Span;
@@ -105,6 +108,7 @@ fn report_flatten_collect(graph: &Graph, span: Span) {
                 .label("Flatten then collect."),
         );
 
+    record_finding("Data collection inefficiency detected", span);
     let message = Level::Error
         .title("Data collection inefficiency detected")
         .snippet(snippet)
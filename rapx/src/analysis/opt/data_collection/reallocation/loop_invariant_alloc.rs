@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+
+use crate::{
+    analysis::{
+        core::dataflow::{graph::*, *},
+        opt::{data_collection::reallocation::unreserved_vec::LoopFinder, OptCheck},
+        utils::def_path::{def_path_def_ids, DefPath},
+    },
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use once_cell::sync::OnceCell;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{def_id::DefId, LangItem};
+use rustc_middle::{
+    mir::{Body, BorrowKind, Local, RawPtrKind, Rvalue, StatementKind, TerminatorKind},
+    ty::{TyCtxt, TypingEnv},
+};
+use rustc_span::Span;
+
+static DEFPATHS: OnceCell<DefPaths> = OnceCell::new();
+
+struct DefPaths {
+    // the collections that can be reused with `clear()`.
+    collections: Vec<DefPath>,
+    // the functions computing an owned value, e.g., `format!` and `to_string()`.
+    values: Vec<DefPath>,
+    // the conversions that allocate if they return an owned value, e.g., `String::from`.
+    conversions: Vec<DefPath>,
+    // the functions returning the value passed to them, e.g., `unwrap()`.
+    passthroughs: Vec<DefPath>,
+    clone: DefPath,
+    // the constructors of crates that may not be used by the crate under analysis.
+    optional_values: Vec<DefId>,
+}
+
+impl DefPaths {
+    pub fn new(tcx: &TyCtxt<'_>) -> Self {
+        Self {
+            collections: vec![
+                DefPath::new("std::vec::Vec::new", tcx),
+                DefPath::new("std::vec::Vec::with_capacity", tcx),
+                DefPath::new("std::string::String::new", tcx),
+                DefPath::new("std::string::String::with_capacity", tcx),
+                DefPath::new("std::collections::HashMap::new", tcx),
+                DefPath::new("std::collections::HashMap::with_capacity", tcx),
+                DefPath::new("std::collections::HashSet::new", tcx),
+                DefPath::new("std::collections::HashSet::with_capacity", tcx),
+                DefPath::new("std::collections::BTreeMap::new", tcx),
+            ],
+            values: vec![
+                DefPath::new("std::fmt::format", tcx),
+                DefPath::new("std::string::ToString::to_string", tcx),
+                DefPath::new("std::borrow::ToOwned::to_owned", tcx),
+            ],
+            conversions: vec![
+                DefPath::new("std::convert::From::from", tcx),
+                DefPath::new("std::convert::Into::into", tcx),
+            ],
+            passthroughs: vec![
+                // `format!` passes its result to `must_use`.
+                DefPath::new("core::hint::must_use", tcx),
+                DefPath::new("std::result::Result::unwrap", tcx),
+                DefPath::new("std::result::Result::expect", tcx),
+                DefPath::new("std::option::Option::unwrap", tcx),
+                DefPath::new("std::option::Option::expect", tcx),
+            ],
+            clone: DefPath::new("std::clone::Clone::clone", tcx),
+            optional_values: def_path_def_ids(tcx, &["regex", "Regex", "new"]).collect(),
+        }
+    }
+
+    fn contains(def_paths: &[DefPath], def_id: DefId) -> bool {
+        def_paths
+            .iter()
+            .any(|def_path| def_path.last_def_id() == def_id)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Alloc {
+    Collection,
+    Value,
+}
+
+// The locals written in one loop of the function.
+#[derive(Default)]
+struct LoopFacts {
+    // assigned as a whole in the loop.
+    assigned: FxHashSet<Local>,
+    // assigned before the loop or as the arguments.
+    assigned_outside: FxHashSet<Local>,
+    // borrowed mutably, assigned partially, or returned by a call that may have side effects.
+    variant: FxHashSet<Local>,
+    dropped: FxHashSet<Local>,
+}
+
+impl LoopFacts {
+    fn new(body: &Body, loop_span: Span, is_pure: impl Fn(DefId) -> bool) -> Self {
+        let mut facts = LoopFacts::default();
+        facts
+            .assigned_outside
+            .extend(body.args_iter().chain([Local::from_usize(0)]));
+        for block in body.basic_blocks.iter() {
+            for statement in block.statements.iter() {
+                let StatementKind::Assign(assign) = &statement.kind else {
+                    continue;
+                };
+                if !loop_span.contains(statement.source_info.span.source_callsite()) {
+                    facts.assigned_outside.insert(assign.0.local);
+                    continue;
+                }
+                match assign.0.projection.is_empty() {
+                    true => facts.assigned.insert(assign.0.local),
+                    false => facts.variant.insert(assign.0.local),
+                };
+                match &assign.1 {
+                    Rvalue::Ref(_, BorrowKind::Mut { .. }, place)
+                    | Rvalue::RawPtr(RawPtrKind::Mut, place) => {
+                        facts.variant.insert(place.local);
+                    }
+                    _ => {}
+                }
+            }
+            let terminator = block.terminator();
+            let in_loop = loop_span.contains(terminator.source_info.span.source_callsite());
+            match &terminator.kind {
+                TerminatorKind::Call {
+                    func, destination, ..
+                } => {
+                    if !in_loop {
+                        facts.assigned_outside.insert(destination.local);
+                        continue;
+                    }
+                    let pure = func
+                        .const_fn_def()
+                        .is_some_and(|(def_id, _)| is_pure(def_id));
+                    if pure && destination.projection.is_empty() {
+                        facts.assigned.insert(destination.local);
+                    } else {
+                        facts.variant.insert(destination.local);
+                    }
+                }
+                TerminatorKind::Drop { place, .. } if in_loop && place.projection.is_empty() => {
+                    facts.dropped.insert(place.local);
+                }
+                _ => {}
+            }
+        }
+        facts
+    }
+}
+
+pub struct LoopInvariantAllocCheck {
+    record: Vec<(Span, Alloc)>,
+}
+
+// The function only computes its return value from the arguments.
+fn is_pure(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    let def_paths = DEFPATHS.get().unwrap();
+    if DefPaths::contains(&def_paths.collections, def_id)
+        || DefPaths::contains(&def_paths.values, def_id)
+        || DefPaths::contains(&def_paths.conversions, def_id)
+        || DefPaths::contains(&def_paths.passthroughs, def_id)
+        || def_paths.clone.last_def_id() == def_id
+        || def_paths.optional_values.contains(&def_id)
+    {
+        return true;
+    }
+    // the constructors of the arguments of `format!`.
+    const FORMAT_ITEMS: [LangItem; 5] = [
+        LangItem::FormatArgument,
+        LangItem::FormatArguments,
+        LangItem::FormatCount,
+        LangItem::FormatPlaceholder,
+        LangItem::FormatUnsafeArg,
+    ];
+    let Some(impl_id) = tcx.impl_of_assoc(def_id) else {
+        return false;
+    };
+    tcx.type_of(impl_id)
+        .instantiate_identity()
+        .ty_adt_def()
+        .is_some_and(|adt| {
+            FORMAT_ITEMS
+                .iter()
+                .any(|item| tcx.is_lang_item(adt.did(), *item))
+        })
+}
+
+fn alloc_kind<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    def_id: DefId,
+    dest: Local,
+) -> Option<Alloc> {
+    let def_paths = DEFPATHS.get().unwrap();
+    if DefPaths::contains(&def_paths.collections, def_id) {
+        return Some(Alloc::Collection);
+    }
+    if DefPaths::contains(&def_paths.values, def_id) || def_paths.optional_values.contains(&def_id)
+    {
+        return Some(Alloc::Value);
+    }
+    if DefPaths::contains(&def_paths.conversions, def_id) {
+        let ty = body.local_decls[dest].ty;
+        let typing_env = TypingEnv::post_analysis(tcx, body.source.def_id());
+        if ty.needs_drop(tcx, typing_env) {
+            return Some(Alloc::Value);
+        }
+    }
+    None
+}
+
+// The value does not depend on any local that changes in the loop.
+fn is_loop_invariant(graph: &Graph, facts: &LoopFacts, dest: Local) -> bool {
+    graph
+        .collect_ancestor_locals(dest, false)
+        .into_iter()
+        .filter(|local| !graph.is_marker(*local))
+        .all(|local| {
+            let loop_carried =
+                facts.assigned.contains(&local) && facts.assigned_outside.contains(&local);
+            !facts.variant.contains(&local) && !loop_carried
+        })
+}
+
+// The value, or the value passed through a call like `unwrap()`, is dropped in the same iteration.
+fn is_dropped_in_loop(graph: &Graph, facts: &LoopFacts, dest: Local) -> bool {
+    let def_paths = DEFPATHS.get().unwrap();
+    let mut values = graph.collect_equivalent_locals(dest, false);
+    values.insert(dest);
+    let mut worklist: Vec<Local> = values.iter().copied().collect();
+    while let Some(local) = worklist.pop() {
+        for edge_idx in graph.nodes[local].out_edges.iter() {
+            let edge = &graph.edges[*edge_idx];
+            let passed = graph.nodes[edge.dst].ops.iter().any(|op| {
+                matches!(op, NodeOp::Call(def_id) if DefPaths::contains(&def_paths.passthroughs, *def_id))
+            });
+            if matches!(edge.op, EdgeOp::Move) && passed && !values.contains(&edge.dst) {
+                for value in graph.collect_equivalent_locals(edge.dst, false) {
+                    if values.insert(value) {
+                        worklist.push(value);
+                    }
+                }
+            }
+        }
+    }
+    values.iter().any(|local| facts.dropped.contains(local))
+}
+
+impl OptCheck for LoopInvariantAllocCheck {
+    fn new() -> Self {
+        Self { record: Vec::new() }
+    }
+
+    fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
+        let _ = &DEFPATHS.get_or_init(|| DefPaths::new(tcx));
+        let tcx = *tcx;
+        let def_id = graph.def_id;
+        let loops: Vec<Span> = LoopFinder::new(tcx, def_id.as_local().unwrap())
+            .spans()
+            .map(|span| span.source_callsite())
+            .collect();
+        if loops.is_empty() {
+            return;
+        }
+        let body = tcx.optimized_mir(def_id);
+        let mut reported = HashSet::new();
+        let mut loop_facts: FxHashMap<Span, LoopFacts> = FxHashMap::default();
+        for block in body.basic_blocks.iter() {
+            let terminator = block.terminator();
+            let TerminatorKind::Call {
+                func, destination, ..
+            } = &terminator.kind
+            else {
+                continue;
+            };
+            let Some((callee, _)) = func.const_fn_def() else {
+                continue;
+            };
+            if !destination.projection.is_empty() {
+                continue;
+            }
+            let dest = destination.local;
+            let Some(alloc) = alloc_kind(tcx, body, callee, dest) else {
+                continue;
+            };
+            let span = terminator.source_info.span.source_callsite();
+            // the invariance is checked against the innermost loop.
+            let Some(loop_span) = loops
+                .iter()
+                .filter(|loop_span| loop_span.contains(span))
+                .min_by_key(|loop_span| loop_span.hi() - loop_span.lo())
+            else {
+                continue;
+            };
+            let facts = loop_facts
+                .entry(*loop_span)
+                .or_insert_with(|| LoopFacts::new(body, *loop_span, |def_id| is_pure(tcx, def_id)));
+            if is_loop_invariant(graph, facts, dest)
+                && is_dropped_in_loop(graph, facts, dest)
+                && reported.insert(span)
+            {
+                self.record.push((span, alloc));
+            }
+        }
+    }
+
+    fn report(&self, graph: &Graph) {
+        for (span, alloc) in self.record.iter() {
+            report_loop_invariant_alloc_bug(graph, *span, *alloc);
+        }
+    }
+
+    fn cnt(&self) -> usize {
+        self.record.len()
+    }
+}
+
+fn report_loop_invariant_alloc_bug(graph: &Graph, span: Span, alloc: Alloc) {
+    let code_source = span_to_source_code(graph.span);
+    let filename = span_to_filename(span);
+    let snippet: Snippet<'_> = Snippet::source(&code_source)
+        .line_start(span_to_line_number(graph.span))
+        .origin(&filename)
+        .fold(true)
+        .annotation(
+            Level::Error
+                .span(relative_pos_range(graph.span, span))
+                .label("Allocated again in every iteration."),
+        );
+    let help = match alloc {
+        Alloc::Collection => "Allocate it once before the loop and reuse it with `clear()`.",
+        Alloc::Value => "Hoist the expression out of the loop.",
+    };
+    record_finding("Loop-invariant allocation detected", span);
+    let message = Level::Warning
+        .title("Loop-invariant allocation detected")
+        .snippet(snippet)
+        .footer(Level::Help.title(help));
+    let renderer = Renderer::styled();
+    println!("{}", renderer.render(message));
+}
@@ -0,0 +1,368 @@
+use std::collections::HashSet;
+
+use once_cell::sync::OnceCell;
+
+use rustc_hir::{def_id::DefId, intravisit, Expr, ExprKind};
+use rustc_middle::{
+    mir::{Body, Local},
+    ty::{Ty, TyCtxt, TyKind, TypeckResults},
+};
+
+use crate::{
+    analysis::{
+        core::dataflow::{graph::Graph, *},
+        opt::{fix::Fix, OptCheck},
+        utils::def_path::DefPath,
+    },
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_span::Span;
+
+static DEFPATHS: OnceCell<DefPaths> = OnceCell::new();
+
+struct DefPaths {
+    collect: DefPath,
+    vec: DefPath,
+    hashset: DefPath,
+    btreeset: DefPath,
+    string: DefPath,
+    deref: DefPath,
+    into_iterator: DefPath,
+    into_iter: DefPath,
+    slice_iter: DefPath,
+    hashset_iter: DefPath,
+    str_chars: DefPath,
+    str_bytes: DefPath,
+    vec_len: DefPath,
+    string_push_str: DefPath,
+}
+
+impl DefPaths {
+    fn new(tcx: &TyCtxt<'_>) -> Self {
+        Self {
+            collect: DefPath::new("std::iter::Iterator::collect", tcx),
+            vec: DefPath::new("std::vec::Vec", tcx),
+            hashset: DefPath::new("std::collections::HashSet", tcx),
+            btreeset: DefPath::new("std::collections::BTreeSet", tcx),
+            string: DefPath::new("std::string::String", tcx),
+            deref: DefPath::new("std::ops::Deref::deref", tcx),
+            into_iterator: DefPath::new("std::iter::IntoIterator", tcx),
+            into_iter: DefPath::new("std::iter::IntoIterator::into_iter", tcx),
+            slice_iter: DefPath::new("slice::iter", tcx),
+            hashset_iter: DefPath::new("std::collections::HashSet::iter", tcx),
+            str_chars: DefPath::new("str::chars", tcx),
+            str_bytes: DefPath::new("str::bytes", tcx),
+            vec_len: DefPath::new("std::vec::Vec::len", tcx),
+            string_push_str: DefPath::new("std::string::String::push_str", tcx),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Collection {
+    Vec,
+    HashSet,
+    String,
+}
+
+fn collection_of(ty: Ty<'_>) -> Option<Collection> {
+    let def_paths = DEFPATHS.get().unwrap();
+    let TyKind::Adt(adt_def, _) = ty.kind() else {
+        return None;
+    };
+    let did = adt_def.did();
+    if did == def_paths.vec.last_def_id() {
+        Some(Collection::Vec)
+    } else if did == def_paths.hashset.last_def_id() {
+        Some(Collection::HashSet)
+    } else if did == def_paths.string.last_def_id() {
+        Some(Collection::String)
+    } else {
+        None
+    }
+}
+
+fn is_collect_node(node: &GraphNode) -> bool {
+    let def_paths = &DEFPATHS.get().unwrap();
+    for op in node.ops.iter() {
+        if let NodeOp::Call(def_id) = op {
+            if *def_id == def_paths.collect.last_def_id() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+fn call_of(node: &GraphNode) -> Option<DefId> {
+    node.ops.iter().find_map(|op| match op {
+        NodeOp::Call(def_id) => Some(*def_id),
+        _ => None,
+    })
+}
+
+// A use of the collection: the call, the callee, and the position of the collection in its
+// arguments.
+struct Use {
+    node: Local,
+    callee: DefId,
+    arg: usize,
+}
+
+// Follow the collection through moves, borrowings and `deref()` to the calls using it. It fails if
+// the collection is used other than being passed to a call, e.g., its field is accessed.
+fn collect_uses(graph: &Graph, collection: Local) -> Option<Vec<Use>> {
+    let deref = DEFPATHS.get().unwrap().deref.last_def_id();
+    let mut values = graph.collect_equivalent_locals(collection, false);
+    values.insert(collection);
+    let mut worklist: Vec<Local> = values.iter().copied().collect();
+    let mut uses = Vec::new();
+    let mut seen = HashSet::new();
+    while let Some(value) = worklist.pop() {
+        for edge_idx in graph.nodes[value].out_edges.iter() {
+            let edge = &graph.edges[*edge_idx];
+            if values.contains(&edge.dst) || !seen.insert(*edge_idx) {
+                continue;
+            }
+            let dst_node = &graph.nodes[edge.dst];
+            match call_of(dst_node) {
+                Some(callee) if callee == deref => {
+                    for value in graph.collect_equivalent_locals(edge.dst, false) {
+                        if values.insert(value) {
+                            worklist.push(value);
+                        }
+                    }
+                }
+                Some(callee) => {
+                    let arg = dst_node
+                        .in_edges
+                        .iter()
+                        .filter(|idx| graph.edges[**idx].seq == edge.seq)
+                        .position(|idx| idx == edge_idx)?;
+                    uses.push(Use {
+                        node: edge.dst,
+                        callee,
+                        arg,
+                    });
+                }
+                None => return None,
+            }
+        }
+    }
+    Some(uses)
+}
+
+// The parameter of the callee at `arg` is a generic type bounded by `IntoIterator`.
+fn accepts_into_iterator(tcx: TyCtxt<'_>, callee: DefId, arg: usize) -> bool {
+    let into_iterator = DEFPATHS.get().unwrap().into_iterator.last_def_id();
+    let fn_sig = tcx.fn_sig(callee).instantiate_identity().skip_binder();
+    let Some(param) = fn_sig.inputs().get(arg) else {
+        return false;
+    };
+    if !matches!(param.kind(), TyKind::Param(_)) {
+        return false;
+    }
+    tcx.predicates_of(callee)
+        .instantiate_identity(tcx)
+        .predicates
+        .iter()
+        .filter_map(|clause| clause.as_trait_clause())
+        .any(|trait_clause| {
+            let trait_ref = trait_clause.skip_binder().trait_ref;
+            trait_ref.def_id == into_iterator && trait_ref.self_ty() == *param
+        })
+}
+
+// The receiver of the call is a set, which removes the duplicates as the collected set does.
+fn is_set_receiver(graph: &Graph, body: &Body<'_>, node: Local) -> bool {
+    let def_paths = DEFPATHS.get().unwrap();
+    let Some(edge_idx) = graph.nodes[node].in_edges.first() else {
+        return false;
+    };
+    let receiver = graph.edges[*edge_idx].src;
+    if graph.is_marker(receiver) {
+        return false;
+    }
+    let ty = body.local_decls[receiver].ty.peel_refs();
+    matches!(ty.kind(), TyKind::Adt(adt_def, _)
+        if adt_def.did() == def_paths.hashset.last_def_id()
+            || adt_def.did() == def_paths.btreeset.last_def_id())
+}
+
+// The suggestion if the collection is only used by the call.
+fn suggestion(
+    tcx: TyCtxt<'_>,
+    graph: &Graph,
+    body: &Body<'_>,
+    collection: Collection,
+    used: &Use,
+) -> Option<&'static str> {
+    let def_paths = DEFPATHS.get().unwrap();
+    let is = |def_path: &DefPath| used.callee == def_path.last_def_id();
+    // collecting into a set removes the duplicates, so the set is only removable if the
+    // elements are put into another set.
+    match collection {
+        Collection::Vec | Collection::String
+            if is(&def_paths.into_iter)
+                || is(&def_paths.slice_iter)
+                || is(&def_paths.str_chars)
+                || is(&def_paths.str_bytes) =>
+        {
+            Some("Iterate the iterator directly instead of collecting it.")
+        }
+        Collection::Vec if is(&def_paths.vec_len) => Some(COUNT_HELP),
+        Collection::String if is(&def_paths.string_push_str) && used.arg == 1 => {
+            Some("Extend the string with the iterator, e.g., `s.extend(iter)`.")
+        }
+        Collection::HashSet if is(&def_paths.hashset_iter) || is(&def_paths.into_iter) => None,
+        _ if accepts_into_iterator(tcx, used.callee, used.arg)
+            && (collection != Collection::HashSet || is_set_receiver(graph, body, used.node)) =>
+        {
+            Some("Pass the iterator directly instead of collecting it.")
+        }
+        _ => None,
+    }
+}
+
+const COUNT_HELP: &str = "Use `count()` instead of collecting the iterator.";
+
+// The `collect()` calls whose vectors are only measured by `len()` in the same expression, whose
+// collecting and measuring are replaced with `count()`.
+struct CountFinder<'tcx> {
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    // the spans of the collecting calls to fix.
+    collect_spans: Vec<Span>,
+    fixes: Vec<Fix>,
+}
+
+impl<'tcx> intravisit::Visitor<'tcx> for CountFinder<'tcx> {
+    fn visit_expr(&mut self, ex: &'tcx Expr<'tcx>) {
+        let def_paths = DEFPATHS.get().unwrap();
+        if let ExprKind::MethodCall(_, receiver, [], _) = ex.kind {
+            if let ExprKind::MethodCall(segment, ..) = receiver.kind {
+                let is = |ex: &Expr<'_>, def_path: &DefPath| {
+                    self.typeck_results.type_dependent_def_id(ex.hir_id)
+                        == Some(def_path.last_def_id())
+                };
+                let fixed = is(ex, &def_paths.vec_len)
+                    && is(receiver, &def_paths.collect)
+                    && !ex.span.from_expansion()
+                    && !segment.ident.span.from_expansion()
+                    && self
+                        .collect_spans
+                        .iter()
+                        .any(|span| span.contains(segment.ident.span));
+                if fixed {
+                    self.fixes.push(Fix::new(
+                        "Needless intermediate collection detected",
+                        COUNT_HELP,
+                        segment.ident.span.with_hi(ex.span.hi()),
+                        "count()".to_string(),
+                    ));
+                }
+            }
+        }
+        intravisit::walk_expr(self, ex);
+    }
+}
+
+pub struct NeedlessCollectCheck {
+    record: Vec<(Span, Span, &'static str)>,
+    fixes: Vec<Fix>,
+}
+
+impl OptCheck for NeedlessCollectCheck {
+    fn new() -> Self {
+        Self {
+            record: Vec::new(),
+            fixes: Vec::new(),
+        }
+    }
+
+    fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
+        let _ = &DEFPATHS.get_or_init(|| DefPaths::new(tcx));
+        let body = tcx.optimized_mir(graph.def_id);
+        for (node_idx, node) in graph.nodes.iter_enumerated() {
+            if graph.is_marker(node_idx) || !is_collect_node(node) {
+                continue;
+            }
+            let Some(collection) = collection_of(body.local_decls[node_idx].ty) else {
+                continue;
+            };
+            // the collection is removable if it is used only once.
+            let Some(uses) = collect_uses(graph, node_idx) else {
+                continue;
+            };
+            let [used] = uses.as_slice() else {
+                continue;
+            };
+            if let Some(help) = suggestion(*tcx, graph, body, collection, used) {
+                self.record
+                    .push((node.span, graph.nodes[used.node].span, help));
+            }
+        }
+
+        let collect_spans: Vec<Span> = self
+            .record
+            .iter()
+            .filter(|(.., help)| *help == COUNT_HELP)
+            .map(|(collect_span, ..)| *collect_span)
+            .collect();
+        if collect_spans.is_empty() {
+            return;
+        }
+        let def_id = graph.def_id.as_local().unwrap();
+        let mut count_finder = CountFinder {
+            typeck_results: tcx.typeck(def_id),
+            collect_spans,
+            fixes: Vec::new(),
+        };
+        intravisit::walk_body(&mut count_finder, tcx.hir_body_owned_by(def_id));
+        self.fixes = count_finder.fixes;
+    }
+
+    fn report(&self, graph: &Graph) {
+        for (collect_span, use_span, help) in self.record.iter() {
+            report_needless_collect(graph, *collect_span, *use_span, help);
+        }
+    }
+
+    fn cnt(&self) -> usize {
+        self.record.len()
+    }
+
+    fn fixes(&self) -> Vec<Fix> {
+        self.fixes.clone()
+    }
+}
+
+fn report_needless_collect(graph: &Graph, collect_span: Span, use_span: Span, help: &str) {
+    let code_source = span_to_source_code(graph.span);
+    let filename = span_to_filename(collect_span);
+    let snippet: Snippet<'_> = Snippet::source(&code_source)
+        .line_start(span_to_line_number(graph.span))
+        .origin(&filename)
+        .fold(true)
+        .annotation(
+            Level::Error
+                .span(relative_pos_range(graph.span, collect_span))
+                .label("Collected here."),
+        )
+        .annotation(
+            Level::Info
+                .span(relative_pos_range(graph.span, use_span))
+                .label("Only used here."),
+        );
+
+    record_finding("Needless intermediate collection detected", collect_span);
+    let message = Level::Warning
+        .title("Needless intermediate collection detected")
+        .snippet(snippet)
+        .footer(Level::Help.title(help));
+    let renderer = Renderer::styled();
+    println!("{}", renderer.render(message));
+}
@@ -3,10 +3,13 @@ use std::collections::HashSet;
 use crate::{
     analysis::{
         core::dataflow::{graph::*, *},
-        opt::OptCheck,
+        opt::{fix::Fix, OptCheck},
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use once_cell::sync::OnceCell;
 use rustc_middle::{mir::Local, ty::TyCtxt};
@@ -36,8 +39,11 @@ impl DefPaths {
     }
 }
 
+use super::unreserved_vec::{reservation_fix, ForLoopFinder};
+
 pub struct UnreservedHashCheck {
     record: Vec<(Span, Span)>,
+    fixes: Vec<Fix>,
 }
 
 fn is_hash_new_node(node: &GraphNode) -> bool {
@@ -86,7 +92,10 @@ fn find_downside_hash_insert_node(graph: &Graph, node_idx: Local) -> Option<Loca
 
 impl OptCheck for UnreservedHashCheck {
     fn new() -> Self {
-        Self { record: Vec::new() }
+        Self {
+            record: Vec::new(),
+            fixes: Vec::new(),
+        }
     }
 
     fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
@@ -99,6 +108,34 @@ impl OptCheck for UnreservedHashCheck {
                 }
             }
         }
+        if self.record.is_empty() {
+            return;
+        }
+
+        // the insertions in the `for` loops whose iteration counts are known are fixed as the
+        // pushes of the unreserved vectors.
+        let def_paths = DEFPATHS.get().unwrap();
+        let inserts = [
+            def_paths.hashmap_insert.last_def_id(),
+            def_paths.hashset_insert.last_def_id(),
+        ];
+        let constructors = [
+            def_paths.hashmap_new.last_def_id(),
+            def_paths.hashset_new.last_def_id(),
+        ];
+        let def_id = graph.def_id.as_local().unwrap();
+        let typeck_results = tcx.typeck(def_id);
+        let for_loop_finder = ForLoopFinder::new(*tcx, def_id, &inserts);
+        for (_, insert_span) in self.record.iter() {
+            let fix = for_loop_finder
+                .record
+                .iter()
+                .find(|for_loop| insert_span.contains(for_loop.push_span))
+                .and_then(|for_loop| {
+                    reservation_fix(*tcx, typeck_results, &constructors, for_loop)
+                });
+            self.fixes.extend(fix);
+        }
     }
 
     fn report(&self, graph: &Graph) {
@@ -110,6 +147,10 @@ impl OptCheck for UnreservedHashCheck {
     fn cnt(&self) -> usize {
         self.record.len()
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        self.fixes.clone()
+    }
 }
 
 fn report_unreserved_hash_bug(graph: &Graph, hash_span: Span, insert_span: Span) {
@@ -129,6 +170,7 @@ fn report_unreserved_hash_bug(graph: &Graph, hash_span: Span, insert_span: Span)
                 .span(relative_pos_range(graph.span, insert_span))
                 .label("Insertion happens here."),
         );
+    record_finding("Improper data collection detected", hash_span);
     let message = Level::Warning
         .title("Improper data collection detected")
         .snippet(snippet)
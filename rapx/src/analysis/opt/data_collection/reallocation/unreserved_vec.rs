@@ -6,25 +6,40 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use once_cell::sync::OnceCell;
-use rustc_middle::{mir::Local, ty::TyCtxt};
+use rustc_middle::{
+    mir::Local,
+    ty::{Ty, TyCtxt, TyKind},
+};
 
 use annotate_snippets::{Level, Renderer, Snippet};
 use rustc_span::Span;
 
 static DEFPATHS: OnceCell<DefPaths> = OnceCell::new();
 
-use super::super::super::LEVEL;
-use rustc_hir::{intravisit, Expr, ExprKind};
+use super::super::super::{fix::Fix, LEVEL};
+use rustc_ast::LitKind;
+use rustc_hir::{
+    def::Res,
+    def_id::{DefId, LocalDefId},
+    intravisit, Block, Expr, ExprKind, HirId, LangItem, MatchSource, Node, QPath,
+};
 use rustc_middle::ty::TypeckResults;
+use rustc_span::source_map::get_source_map;
+
+const TITLE: &str = "Improper data collection detected";
 
 struct DefPaths {
     vec_new: DefPath,
     vec_push: DefPath,
     vec_with_capacity: DefPath,
     vec_reserve: DefPath,
+    vec: DefPath,
 }
 
 impl DefPaths {
@@ -34,30 +49,59 @@ impl DefPaths {
             vec_push: DefPath::new("std::vec::Vec::push", tcx),
             vec_with_capacity: DefPath::new("std::vec::Vec::with_capacity", tcx),
             vec_reserve: DefPath::new("std::vec::Vec::reserve", tcx),
+            vec: DefPath::new("std::vec::Vec", tcx),
         }
     }
 }
 
+/// The loops of a function body with their blocks, shared by the checks looking for calls in a
+/// loop.
 pub struct LoopFinder<'tcx> {
-    pub typeck_results: &'tcx TypeckResults<'tcx>,
-    pub record: Vec<(Span, Vec<Span>)>,
+    pub record: Vec<(Span, &'tcx Block<'tcx>)>,
+}
+
+impl<'tcx> LoopFinder<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: LocalDefId) -> Self {
+        let mut loop_finder = LoopFinder { record: Vec::new() };
+        intravisit::walk_body(&mut loop_finder, tcx.hir_body_owned_by(def_id));
+        loop_finder
+    }
+
+    /// The spans of the loops, outer loops first.
+    pub fn spans(&self) -> impl Iterator<Item = Span> + '_ {
+        self.record.iter().map(|(span, _)| *span)
+    }
 }
 
-pub struct PushFinder<'tcx> {
+/// The calls of the methods growing a collection, e.g., `Vec::push`, with their receivers.
+pub struct PushFinder<'tcx, 'a> {
     typeck_results: &'tcx TypeckResults<'tcx>,
+    methods: &'a [DefId],
     record: Vec<Span>,
+    receivers: Vec<&'tcx Expr<'tcx>>,
 }
 
-impl<'tcx> intravisit::Visitor<'tcx> for PushFinder<'tcx> {
+impl<'tcx, 'a> PushFinder<'tcx, 'a> {
+    fn new(typeck_results: &'tcx TypeckResults<'tcx>, methods: &'a [DefId]) -> Self {
+        Self {
+            typeck_results,
+            methods,
+            record: Vec::new(),
+            receivers: Vec::new(),
+        }
+    }
+}
+
+impl<'tcx, 'a> intravisit::Visitor<'tcx> for PushFinder<'tcx, 'a> {
     fn visit_expr(&mut self, ex: &'tcx Expr<'tcx>) {
-        if let ExprKind::MethodCall(.., span) = ex.kind {
+        if let ExprKind::MethodCall(_, receiver, _, span) = ex.kind {
             let def_id = self
                 .typeck_results
                 .type_dependent_def_id(ex.hir_id)
                 .unwrap();
-            let target_def_id = (&DEFPATHS.get().unwrap()).vec_push.last_def_id();
-            if def_id == target_def_id {
+            if self.methods.contains(&def_id) {
                 self.record.push(span);
+                self.receivers.push(receiver);
             }
         }
         intravisit::walk_expr(self, ex);
@@ -67,25 +111,204 @@ impl<'tcx> intravisit::Visitor<'tcx> for PushFinder<'tcx> {
 impl<'tcx> intravisit::Visitor<'tcx> for LoopFinder<'tcx> {
     fn visit_expr(&mut self, ex: &'tcx Expr<'tcx>) {
         if let ExprKind::Loop(block, ..) = ex.kind {
-            let mut push_finder = PushFinder {
-                typeck_results: self.typeck_results,
-                record: Vec::new(),
-            };
-            intravisit::walk_block(&mut push_finder, block);
-            // if !push_finder.record.is_empty() {
-            //     self.record.push((ex.span, push_finder.record));
-            // }
-            if push_finder.record.len() == 1 {
-                // we only use simple cases
-                self.record.push((ex.span, push_finder.record));
+            self.record.push((ex.span, block));
+        }
+        intravisit::walk_expr(self, ex);
+    }
+}
+
+// A `for` loop pushing once into a collection, whose iteration count is known before the loop.
+pub(super) struct ForLoop<'tcx> {
+    span: Span,
+    pub(super) push_span: Span,
+    receiver: &'tcx Expr<'tcx>,
+    count: String,
+    // the locals used to compute the iteration count.
+    count_locals: Vec<HirId>,
+}
+
+pub(super) struct ForLoopFinder<'tcx, 'a> {
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    methods: &'a [DefId],
+    pub(super) record: Vec<ForLoop<'tcx>>,
+}
+
+fn local_of(ex: &Expr<'_>) -> Option<HirId> {
+    match ex.kind {
+        ExprKind::Path(QPath::Resolved(None, path)) => match path.res {
+            Res::Local(hir_id) => Some(hir_id),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn source_of(ex: &Expr<'_>) -> Option<String> {
+    if ex.span.from_expansion() {
+        return None;
+    }
+    get_source_map()?.span_to_snippet(ex.span).ok()
+}
+
+fn has_len(ty: Ty<'_>) -> bool {
+    let vec = DEFPATHS.get().unwrap().vec.last_def_id();
+    match ty.peel_refs().kind() {
+        TyKind::Array(..) | TyKind::Slice(..) => true,
+        TyKind::Adt(adt_def, _) => adt_def.did() == vec,
+        _ => false,
+    }
+}
+
+impl<'tcx, 'a> ForLoopFinder<'tcx, 'a> {
+    /// The `for` loops of the body calling one of the methods once.
+    pub(super) fn new(tcx: TyCtxt<'tcx>, def_id: LocalDefId, methods: &'a [DefId]) -> Self {
+        let _ = &DEFPATHS.get_or_init(|| DefPaths::new(&tcx));
+        let mut for_loop_finder = ForLoopFinder {
+            typeck_results: tcx.typeck(def_id),
+            methods,
+            record: Vec::new(),
+        };
+        intravisit::walk_body(&mut for_loop_finder, tcx.hir_body_owned_by(def_id));
+        for_loop_finder
+    }
+
+    // The iteration count of the loop over `head`, which only reads locals. It supports ranges of
+    // `usize`, and vectors, arrays and slices iterated directly or with `iter()`.
+    fn iteration_count(&self, head: &'tcx Expr<'tcx>) -> Option<(String, Vec<HirId>)> {
+        match head.kind {
+            ExprKind::Struct(QPath::LangItem(LangItem::Range, _), [start, end], _) => {
+                if !self.typeck_results.expr_ty(end.expr).is_usize() {
+                    return None;
+                }
+                let bound = |ex: &Expr<'_>| match ex.kind {
+                    ExprKind::Lit(_) => Some((source_of(ex)?, None)),
+                    _ => Some((source_of(ex)?, Some(local_of(ex)?))),
+                };
+                let (end_source, end_local) = bound(end.expr)?;
+                let (start_source, start_local) = bound(start.expr)?;
+                let count_locals = end_local.into_iter().chain(start_local).collect();
+                let from_zero = matches!(start.expr.kind,
+                    ExprKind::Lit(lit) if matches!(lit.node, LitKind::Int(n, _) if n.get() == 0));
+                let count = match from_zero {
+                    true => end_source,
+                    false => format!("usize::saturating_sub({}, {})", end_source, start_source),
+                };
+                Some((count, count_locals))
+            }
+            ExprKind::MethodCall(segment, receiver, [], _)
+                if matches!(segment.ident.as_str(), "iter" | "iter_mut") =>
+            {
+                self.len_of(receiver)
+            }
+            ExprKind::AddrOf(_, _, inner) => self.len_of(inner),
+            _ => self.len_of(head),
+        }
+    }
+
+    fn len_of(&self, ex: &'tcx Expr<'tcx>) -> Option<(String, Vec<HirId>)> {
+        let local = local_of(ex)?;
+        if !has_len(self.typeck_results.expr_ty(ex)) {
+            return None;
+        }
+        Some((format!("{}.len()", source_of(ex)?), vec![local]))
+    }
+}
+
+impl<'tcx, 'a> intravisit::Visitor<'tcx> for ForLoopFinder<'tcx, 'a> {
+    fn visit_expr(&mut self, ex: &'tcx Expr<'tcx>) {
+        if let ExprKind::Match(scrutinee, [arm], MatchSource::ForLoopDesugar) = ex.kind {
+            if let (ExprKind::Call(_, [head]), ExprKind::Loop(block, ..)) =
+                (scrutinee.kind, arm.body.kind)
+            {
+                let mut push_finder = PushFinder::new(self.typeck_results, self.methods);
+                intravisit::walk_block(&mut push_finder, block);
+                if let ([push_span], [receiver], Some((count, count_locals))) = (
+                    push_finder.record.as_slice(),
+                    push_finder.receivers.as_slice(),
+                    self.iteration_count(head),
+                ) {
+                    self.record.push(ForLoop {
+                        span: ex.span.source_callsite(),
+                        push_span: *push_span,
+                        receiver,
+                        count,
+                        count_locals,
+                    });
+                }
             }
         }
         intravisit::walk_expr(self, ex);
     }
 }
 
+/// The collection is created by one of the `new` constructors, e.g., `Vec::new()`, in the `let`
+/// statement, which is replaced with `with_capacity(n)` if the iteration count can be computed
+/// there; otherwise `reserve(n)` is called before the loop.
+pub(super) fn reservation_fix(
+    tcx: TyCtxt<'_>,
+    typeck_results: &TypeckResults<'_>,
+    constructors: &[DefId],
+    for_loop: &ForLoop,
+) -> Option<Fix> {
+    let collection = local_of(for_loop.receiver)?;
+    if tcx.hir_span(collection).lo() >= for_loop.span.lo() {
+        return None;
+    }
+    if let Node::LetStmt(let_stmt) = tcx.parent_hir_node(collection) {
+        if let Some(Expr {
+            kind: ExprKind::Call(func, []),
+            span: init_span,
+            ..
+        }) = let_stmt.init
+        {
+            if let ExprKind::Path(qpath @ QPath::TypeRelative(_, segment)) = func.kind {
+                let is_new = matches!(typeck_results.qpath_res(&qpath, func.hir_id),
+                    Res::Def(_, def_id) if constructors.contains(&def_id));
+                let count_in_scope = for_loop
+                    .count_locals
+                    .iter()
+                    .all(|local| tcx.hir_span(*local).lo() < let_stmt.span.lo());
+                if is_new && count_in_scope && !init_span.from_expansion() {
+                    return Some(Fix::new(
+                        TITLE,
+                        "Allocate enough space when creating the collection.",
+                        segment.ident.span.with_hi(init_span.hi()),
+                        format!("with_capacity({})", for_loop.count),
+                    ));
+                }
+            }
+        }
+    }
+    // the loop begins its line, so the call is inserted as a statement before it.
+    let source_map = get_source_map()?;
+    let prev_source = source_map.span_to_prev_source(for_loop.span).ok()?;
+    let indent = prev_source.rsplit('\n').next()?;
+    if !indent.chars().all(char::is_whitespace) || for_loop.span.from_expansion() {
+        return None;
+    }
+    // rustc records where it removes a `\r` from the line endings, besides the BOM at 0.
+    let source_file = source_map.lookup_source_file(for_loop.span.lo());
+    let line_ending = match source_file.normalized_pos.iter().any(|pos| pos.pos.0 > 0) {
+        true => "\r\n",
+        false => "\n",
+    };
+    Some(Fix::insert_before(
+        TITLE,
+        "Reserve enough space before the loop.",
+        for_loop.span,
+        format!(
+            "{}.reserve({});{}{}",
+            source_of(for_loop.receiver)?,
+            for_loop.count,
+            line_ending,
+            indent
+        ),
+    ))
+}
+
 pub struct UnreservedVecCheck {
     record: Vec<Span>,
+    fixes: Vec<Fix>,
 }
 
 fn is_vec_new_node(node: &GraphNode) -> bool {
@@ -143,7 +366,10 @@ fn find_upside_reservation(graph: &Graph, node_idx: Local) -> Option<Local> {
 
 impl OptCheck for UnreservedVecCheck {
     fn new() -> Self {
-        Self { record: Vec::new() }
+        Self {
+            record: Vec::new(),
+            fixes: Vec::new(),
+        }
     }
 
     fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
@@ -162,19 +388,32 @@ impl OptCheck for UnreservedVecCheck {
             }
         }
 
-        let def_id = graph.def_id;
-        let body = tcx.hir_body_owned_by(def_id.as_local().unwrap());
-        let typeck_results = tcx.typeck(def_id.as_local().unwrap());
-        let mut loop_finder = LoopFinder {
-            typeck_results,
-            record: Vec::new(),
-        };
-        intravisit::walk_body(&mut loop_finder, body);
-        for (_, push_record) in loop_finder.record {
-            for push_span in push_record {
+        let def_paths = DEFPATHS.get().unwrap();
+        let pushes = [def_paths.vec_push.last_def_id()];
+        let constructors = [def_paths.vec_new.last_def_id()];
+        let def_id = graph.def_id.as_local().unwrap();
+        let typeck_results = tcx.typeck(def_id);
+        let loop_finder = LoopFinder::new(*tcx, def_id);
+        let for_loop_finder = ForLoopFinder::new(*tcx, def_id, &pushes);
+        for (_, block) in loop_finder.record {
+            let mut push_finder = PushFinder::new(typeck_results, &pushes);
+            intravisit::walk_block(&mut push_finder, block);
+            // we only use simple cases
+            if push_finder.record.len() != 1 {
+                continue;
+            }
+            for push_span in push_finder.record {
                 if let Some((node_idx, _)) = graph.query_node_by_span(push_span, false) {
                     if let None = find_upside_reservation(graph, node_idx) {
                         self.record.push(push_span);
+                        let fix = for_loop_finder
+                            .record
+                            .iter()
+                            .find(|for_loop| for_loop.push_span == push_span)
+                            .and_then(|for_loop| {
+                                reservation_fix(*tcx, typeck_results, &constructors, for_loop)
+                            });
+                        self.fixes.extend(fix);
                     }
                 }
             }
@@ -190,6 +429,10 @@ impl OptCheck for UnreservedVecCheck {
     fn cnt(&self) -> usize {
         self.record.len()
     }
+
+    fn fixes(&self) -> Vec<Fix> {
+        self.fixes.clone()
+    }
 }
 
 fn report_unreserved_vec_bug(graph: &Graph, span: Span) {
@@ -204,8 +447,9 @@ fn report_unreserved_vec_bug(graph: &Graph, span: Span) {
                 .span(relative_pos_range(graph.span, span))
                 .label("Space unreserved."),
         );
+    record_finding(TITLE, span);
     let message = Level::Warning
-        .title("Improper data collection detected")
+        .title(TITLE)
         .snippet(snippet)
         .footer(Level::Help.title("Reserve enough space."));
     let renderer = Renderer::styled();
@@ -1,7 +1,9 @@
+pub mod map_double_lookup;
 pub mod participant;
 pub mod slice_contains;
 pub mod vec_remove;
 
+use map_double_lookup::MapDoubleLookupCheck;
 use participant::ParticipantCheck;
 use slice_contains::SliceContainsCheck;
 use vec_remove::VecRemoveCheck;
@@ -14,6 +16,7 @@ use rustc_middle::ty::TyCtxt;
 use super::super::LEVEL;
 
 pub struct SuboptimalCheck {
+    map_double_lookup: MapDoubleLookupCheck,
     participant: ParticipantCheck,
     slice_contains: SliceContainsCheck,
     vec_remove: VecRemoveCheck,
@@ -22,6 +25,7 @@ pub struct SuboptimalCheck {
 impl OptCheck for SuboptimalCheck {
     fn new() -> Self {
         Self {
+            map_double_lookup: MapDoubleLookupCheck::new(),
             participant: ParticipantCheck::new(),
             slice_contains: SliceContainsCheck::new(),
             vec_remove: VecRemoveCheck::new(),
@@ -30,6 +34,7 @@ impl OptCheck for SuboptimalCheck {
 
     fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
         self.vec_remove.check(graph, tcx);
+        self.map_double_lookup.check(graph, tcx);
         let level = LEVEL.lock().unwrap();
         if *level == 2 {
             self.participant.check(graph, tcx);
@@ -41,9 +46,13 @@ impl OptCheck for SuboptimalCheck {
         self.participant.report(graph);
         self.slice_contains.report(graph);
         self.vec_remove.report(graph);
+        self.map_double_lookup.report(graph);
     }
 
     fn cnt(&self) -> usize {
-        self.participant.cnt() + self.slice_contains.cnt() + self.vec_remove.cnt()
+        self.participant.cnt()
+            + self.slice_contains.cnt()
+            + self.vec_remove.cnt()
+            + self.map_double_lookup.cnt()
     }
 }
@@ -0,0 +1,280 @@
+use annotate_snippets::{Level, Renderer, Snippet};
+
+use once_cell::sync::OnceCell;
+
+use crate::{
+    analysis::{
+        core::dataflow::{graph::*, *},
+        opt::OptCheck,
+        utils::def_path::DefPath,
+    },
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
+};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::{
+    mir::{BasicBlock, Body, Local, Operand, Rvalue, StatementKind, TerminatorKind},
+    ty::TyCtxt,
+};
+use rustc_span::Span;
+
+struct DefPaths {
+    hashmap_contains_key: DefPath,
+    hashmap_get: DefPath,
+    hashmap_get_mut: DefPath,
+    hashmap_remove: DefPath,
+    hashmap_insert: DefPath,
+    btreemap_contains_key: DefPath,
+    btreemap_get: DefPath,
+    btreemap_get_mut: DefPath,
+    btreemap_remove: DefPath,
+    btreemap_insert: DefPath,
+    hashset_contains: DefPath,
+    hashset_insert: DefPath,
+    clone: DefPath,
+}
+
+static DEFPATHS: OnceCell<DefPaths> = OnceCell::new();
+
+impl DefPaths {
+    fn new(tcx: &TyCtxt<'_>) -> Self {
+        Self {
+            hashmap_contains_key: DefPath::new("std::collections::HashMap::contains_key", tcx),
+            hashmap_get: DefPath::new("std::collections::HashMap::get", tcx),
+            hashmap_get_mut: DefPath::new("std::collections::HashMap::get_mut", tcx),
+            hashmap_remove: DefPath::new("std::collections::HashMap::remove", tcx),
+            hashmap_insert: DefPath::new("std::collections::HashMap::insert", tcx),
+            btreemap_contains_key: DefPath::new("std::collections::BTreeMap::contains_key", tcx),
+            btreemap_get: DefPath::new("std::collections::BTreeMap::get", tcx),
+            btreemap_get_mut: DefPath::new("std::collections::BTreeMap::get_mut", tcx),
+            btreemap_remove: DefPath::new("std::collections::BTreeMap::remove", tcx),
+            btreemap_insert: DefPath::new("std::collections::BTreeMap::insert", tcx),
+            hashset_contains: DefPath::new("std::collections::HashSet::contains", tcx),
+            hashset_insert: DefPath::new("std::collections::HashSet::insert", tcx),
+            clone: DefPath::new("std::clone::Clone::clone", tcx),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Lookup {
+    ContainsKey,
+    Get,
+    Remove,
+    Insert,
+    SetContains,
+}
+
+fn lookup_of(node: &GraphNode) -> Option<Lookup> {
+    let def_paths = DEFPATHS.get().unwrap();
+    for op in node.ops.iter() {
+        if let NodeOp::Call(def_id) = op {
+            let is = |def_path: &DefPath| *def_id == def_path.last_def_id();
+            if is(&def_paths.hashmap_contains_key) || is(&def_paths.btreemap_contains_key) {
+                return Some(Lookup::ContainsKey);
+            }
+            if is(&def_paths.hashmap_get)
+                || is(&def_paths.hashmap_get_mut)
+                || is(&def_paths.btreemap_get)
+                || is(&def_paths.btreemap_get_mut)
+            {
+                return Some(Lookup::Get);
+            }
+            if is(&def_paths.hashmap_remove) || is(&def_paths.btreemap_remove) {
+                return Some(Lookup::Remove);
+            }
+            if is(&def_paths.hashmap_insert)
+                || is(&def_paths.btreemap_insert)
+                || is(&def_paths.hashset_insert)
+            {
+                return Some(Lookup::Insert);
+            }
+            if is(&def_paths.hashset_contains) {
+                return Some(Lookup::SetContains);
+            }
+        }
+    }
+    None
+}
+
+// the suggestion for a lookup followed by another one of the same key.
+fn suggestion(first: Lookup, second: Lookup) -> Option<&'static str> {
+    match (first, second) {
+        (Lookup::ContainsKey, Lookup::Insert) => {
+            Some("Use the entry API, e.g., `map.entry(k).or_insert(v)`.")
+        }
+        (Lookup::ContainsKey, Lookup::Get) => {
+            Some("Match on the result of `get` instead of checking `contains_key` first.")
+        }
+        (Lookup::Get, Lookup::Insert) => {
+            Some("Use the entry API, e.g., `map.entry(k).or_insert_with(f)`.")
+        }
+        (Lookup::Remove, Lookup::Insert) => {
+            Some("Use the old value returned by `insert` instead of removing it first.")
+        }
+        (Lookup::SetContains, Lookup::Insert) => {
+            Some("Use the bool returned by `insert`, which tells whether the value is new.")
+        }
+        _ => None,
+    }
+}
+
+// a cloned key is the same value as the key it is cloned from.
+fn key_of(graph: &Graph, node: &GraphNode) -> Local {
+    let key = graph.edges[node.in_edges[1]].src; // the first param is self, so we use 1
+    let clone = DEFPATHS.get().unwrap().clone.last_def_id();
+    let key_node = &graph.nodes[key];
+    let is_clone = key_node
+        .ops
+        .iter()
+        .any(|op| matches!(op, NodeOp::Call(def_id) if *def_id == clone));
+    if is_clone && !key_node.in_edges.is_empty() {
+        return graph.edges[key_node.in_edges[0]].src;
+    }
+    key
+}
+
+fn is_same_value(graph: &Graph, a: Local, b: Local) -> bool {
+    a == b || graph.collect_equivalent_locals(a, false).contains(&b)
+}
+
+fn is_reachable(body: &Body, from: BasicBlock, to: BasicBlock) -> bool {
+    let mut seen = FxHashSet::default();
+    let mut worklist = vec![from];
+    while let Some(bb) = worklist.pop() {
+        if bb == to {
+            return true;
+        }
+        if seen.insert(bb) {
+            worklist.extend(body.basic_blocks[bb].terminator().successors());
+        }
+    }
+    false
+}
+
+// the blocks the result of `get` flows into if it is `None`.
+fn none_targets(graph: &Graph, body: &Body, result: Local) -> Vec<BasicBlock> {
+    let results = graph.collect_equivalent_locals(result, false);
+    let mut targets = Vec::new();
+    for block in body.basic_blocks.iter() {
+        let TerminatorKind::SwitchInt {
+            discr: Operand::Move(discr) | Operand::Copy(discr),
+            targets: switch_targets,
+        } = &block.terminator().kind
+        else {
+            continue;
+        };
+        let switch_on_result = block.statements.iter().any(|statement| {
+            matches!(
+                &statement.kind,
+                StatementKind::Assign(assign)
+                    if assign.0.local == discr.local
+                        && matches!(assign.1, Rvalue::Discriminant(place)
+                            if place.local == result || results.contains(&place.local))
+            )
+        });
+        if switch_on_result {
+            // `None` is the variant 0 of `Option`.
+            targets.push(switch_targets.target_for_value(0));
+        }
+    }
+    targets
+}
+
+pub struct MapDoubleLookupCheck {
+    record: Vec<(Span, Span, &'static str)>,
+}
+
+impl OptCheck for MapDoubleLookupCheck {
+    fn new() -> Self {
+        Self { record: vec![] }
+    }
+
+    fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
+        let _ = &DEFPATHS.get_or_init(|| DefPaths::new(tcx));
+        let body = tcx.optimized_mir(graph.def_id);
+        // the block of each lookup and the block it returns to.
+        let mut lookups: FxHashMap<Local, (Lookup, BasicBlock, BasicBlock)> = FxHashMap::default();
+        for (bb, block) in body.basic_blocks.iter_enumerated() {
+            if let TerminatorKind::Call {
+                destination,
+                target: Some(target),
+                ..
+            } = &block.terminator().kind
+            {
+                if let Some(lookup) = lookup_of(&graph.nodes[destination.local]) {
+                    lookups.insert(destination.local, (lookup, bb, *target));
+                }
+            }
+        }
+        let mut lookups: Vec<_> = lookups.into_iter().collect();
+        lookups.sort_by_key(|(_, (_, bb, _))| *bb);
+
+        for (first_idx, (first, _, target)) in lookups.iter() {
+            let first_node = &graph.nodes[*first_idx];
+            let map = graph.edges[first_node.in_edges[0]].src;
+            let key = key_of(graph, first_node);
+            let from = match first {
+                Lookup::Get => none_targets(graph, body, *first_idx),
+                _ => vec![*target],
+            };
+            for (second_idx, (second, second_bb, _)) in lookups.iter() {
+                let Some(suggestion) = suggestion(*first, *second) else {
+                    continue;
+                };
+                let second_node = &graph.nodes[*second_idx];
+                if from.iter().any(|bb| is_reachable(body, *bb, *second_bb))
+                    && is_same_value(graph, map, graph.edges[second_node.in_edges[0]].src)
+                    && is_same_value(graph, key, key_of(graph, second_node))
+                {
+                    self.record
+                        .push((first_node.span, second_node.span, suggestion));
+                    break;
+                }
+            }
+        }
+    }
+
+    fn report(&self, graph: &Graph) {
+        for (first_span, second_span, suggestion) in self.record.iter() {
+            report_map_double_lookup_bug(graph, *first_span, *second_span, suggestion);
+        }
+    }
+
+    fn cnt(&self) -> usize {
+        self.record.len()
+    }
+}
+
+fn report_map_double_lookup_bug(
+    graph: &Graph,
+    first_span: Span,
+    second_span: Span,
+    suggestion: &str,
+) {
+    let code_source = span_to_source_code(graph.span);
+    let filename = span_to_filename(graph.span);
+    let snippet = Snippet::source(&code_source)
+        .line_start(span_to_line_number(graph.span))
+        .origin(&filename)
+        .fold(true)
+        .annotation(
+            Level::Error
+                .span(relative_pos_range(graph.span, first_span))
+                .label("The key is looked up here."),
+        )
+        .annotation(
+            Level::Error
+                .span(relative_pos_range(graph.span, second_span))
+                .label("The same key is looked up again here."),
+        );
+    record_finding("Redundant map lookup detected", first_span);
+    let message = Level::Warning
+        .title("Redundant map lookup detected")
+        .snippet(snippet)
+        .footer(Level::Help.title(suggestion));
+    let renderer = Renderer::styled();
+    println!("{}", renderer.render(message));
+}
@@ -8,7 +8,10 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use rustc_hir::def_id::DefId;
 use rustc_middle::ty::TyCtxt;
@@ -91,6 +94,7 @@ fn report_participant(graph: &Graph, span: Span) {
                 .span(relative_pos_range(graph.span, span))
                 .label("Data collection created here"),
         );
+    record_finding("Suboptimal data collection detected", span);
     let message = Level::Warning
         .title("Suboptimal data collection detected")
         .snippet(snippet)
@@ -4,7 +4,10 @@ use once_cell::sync::OnceCell;
 
 use crate::{
     analysis::{core::dataflow::graph::*, opt::OptCheck, utils::def_path::DefPath},
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use rustc_hir::{intravisit, Expr, ExprKind};
 use rustc_middle::ty::{TyCtxt, TypeckResults};
@@ -90,6 +93,7 @@ fn report_slice_contains_bug(graph: &Graph, contains_span: Span) {
                 .span(relative_pos_range(graph.span, contains_span))
                 .label("Slice contains happens here."),
         );
+    record_finding("Improper data collection detected", contains_span);
     let message = Level::Warning
         .title("Improper data collection detected")
         .snippet(snippet)
@@ -8,7 +8,10 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use rustc_middle::ty::TyCtxt;
 use rustc_span::Span;
@@ -99,6 +102,7 @@ fn report_vec_remove_bug(graph: &Graph, span: Span) {
                 .span(relative_pos_range(graph.span, span))
                 .label("Vec increasement / decreasement happens here."),
         );
+    record_finding("Improper data collection detected", span);
     let message = Level::Warning
         .title("Improper data collection detected")
         .snippet(snippet)
@@ -0,0 +1,226 @@
+//! Machine-applicable fixes of the optimization checks.
+//!
+//! A check may attach to a report the edit that applies its suggestion, i.e., the span to replace
+//! and the replacement text; an insertion replaces an empty span. The edits are written as rustc
+//! JSON diagnostics, which `rustfix` and `cargo fix` understand, to the file named by
+//! `RAP_OPT_SUGGESTIONS`, and `-fix` applies the machine-applicable ones to the source files of
+//! the crate under analysis. An edit that may change the behavior, e.g., lowercasing only the ASCII
+//! characters of a string used elsewhere, is emitted as `MaybeIncorrect` and not applied.
+//!
+//! The byte offsets of the edits are those of the files on disk, before rustc removes their BOM
+//! and turns their CRLF line endings into LF.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use rustc_errors::Applicability;
+use rustc_hir::def_id::LOCAL_CRATE;
+use rustc_span::source_map::get_source_map;
+use rustc_span::{FileName, Pos, SourceFile, Span};
+use serde_json::{json, Value};
+
+use crate::utils::log::span_to_filename;
+use crate::{rap_debug, rap_info, rap_warn};
+
+// The byte range in a file to replace and the replacement.
+type Edit<'a> = (usize, usize, &'a str);
+
+#[derive(Debug, Clone)]
+pub struct Fix {
+    /// The title of the report, e.g., "Improper data collection detected".
+    pub title: &'static str,
+    /// What the edit does, e.g., "Reserve enough space before the loop.".
+    pub help: &'static str,
+    pub span: Span,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+impl Fix {
+    pub fn new(title: &'static str, help: &'static str, span: Span, replacement: String) -> Self {
+        Self {
+            title,
+            help,
+            span,
+            replacement,
+            applicability: Applicability::MachineApplicable,
+        }
+    }
+
+    /// Mark the fix as one that may change the behavior; it is not applied by `-fix`.
+    pub fn maybe_incorrect(mut self) -> Self {
+        self.applicability = Applicability::MaybeIncorrect;
+        self
+    }
+
+    /// Insert the text before the span.
+    pub fn insert_before(
+        title: &'static str,
+        help: &'static str,
+        span: Span,
+        text: String,
+    ) -> Self {
+        Self::new(title, help, span.shrink_to_lo(), text)
+    }
+
+    fn json_span(&self, suggestion: bool) -> Value {
+        let source_map = get_source_map().unwrap();
+        let lo = source_map.lookup_char_pos(self.span.lo());
+        let hi = source_map.lookup_char_pos(self.span.hi());
+        let byte_start = lo
+            .file
+            .original_relative_byte_pos(self.span.lo())
+            .to_usize();
+        let byte_end = lo
+            .file
+            .original_relative_byte_pos(self.span.hi())
+            .to_usize();
+        let text: Vec<Value> = (lo.line..=hi.line)
+            .map(|line| {
+                let source = lo.file.get_line(line - 1).unwrap_or_default();
+                let highlight_start = if line == lo.line {
+                    lo.col_display + 1
+                } else {
+                    1
+                };
+                let highlight_end = if line == hi.line {
+                    hi.col_display + 1
+                } else {
+                    source.chars().count() + 1
+                };
+                json!({
+                    "text": source,
+                    "highlight_start": highlight_start,
+                    "highlight_end": highlight_end,
+                })
+            })
+            .collect();
+        json!({
+            "file_name": span_to_filename(self.span),
+            "byte_start": byte_start,
+            "byte_end": byte_end,
+            "line_start": lo.line,
+            "line_end": hi.line,
+            "column_start": lo.col_display + 1,
+            "column_end": hi.col_display + 1,
+            "is_primary": true,
+            "text": text,
+            "label": Value::Null,
+            "suggested_replacement": if suggestion { json!(self.replacement) } else { Value::Null },
+            "suggestion_applicability": if suggestion { json!(format!("{:?}", self.applicability)) } else { Value::Null },
+            "expansion": Value::Null,
+        })
+    }
+
+    /// The fix as a rustc JSON diagnostic, with the edit in its help.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "$message_type": "diagnostic",
+            "message": self.title,
+            "code": Value::Null,
+            "level": "warning",
+            "spans": [self.json_span(false)],
+            "children": [{
+                "message": self.help,
+                "code": Value::Null,
+                "level": "help",
+                "spans": [self.json_span(true)],
+                "children": [],
+                "rendered": Value::Null,
+            }],
+            "rendered": Value::Null,
+        })
+    }
+}
+
+/// Append the fixes to the file named by `RAP_OPT_SUGGESTIONS`, one JSON diagnostic per line.
+pub fn emit_suggestions(fixes: &[Fix]) {
+    let Some(path) = std::env::var_os("RAP_OPT_SUGGESTIONS") else {
+        return;
+    };
+    let file = OpenOptions::new().create(true).append(true).open(&path);
+    let mut file = match file {
+        Ok(file) => file,
+        Err(err) => {
+            rap_warn!("Cannot open {}: {}", PathBuf::from(path).display(), err);
+            return;
+        }
+    };
+    for fix in fixes {
+        if let Err(err) = writeln!(file, "{}", fix.to_json()) {
+            rap_warn!("Cannot write the suggestions: {}", err);
+            return;
+        }
+    }
+}
+
+/// Apply the machine-applicable fixes to the source files of the crate under analysis. An edit
+/// overlapping a previous one in the same file is skipped, so is a file changed since it was
+/// compiled. The fixes that may be incorrect are counted but left to the user.
+pub fn apply_fixes(fixes: &[Fix]) {
+    let source_map = get_source_map().unwrap();
+    let mut edits: BTreeMap<PathBuf, (Arc<SourceFile>, Vec<Edit>)> = BTreeMap::new();
+    let mut unapplied = 0;
+    for fix in fixes {
+        if fix.applicability != Applicability::MachineApplicable {
+            unapplied += 1;
+            continue;
+        }
+        if fix.span.from_expansion() {
+            continue;
+        }
+        let pos = source_map.lookup_byte_offset(fix.span.lo());
+        if pos.sf.cnum != LOCAL_CRATE {
+            continue;
+        }
+        let FileName::Real(name) = &pos.sf.name else {
+            continue;
+        };
+        let Some(path) = name.local_path() else {
+            continue;
+        };
+        let start = pos.sf.original_relative_byte_pos(fix.span.lo()).to_usize();
+        let end = pos.sf.original_relative_byte_pos(fix.span.hi()).to_usize();
+        edits
+            .entry(path.to_path_buf())
+            .or_insert_with(|| (pos.sf.clone(), Vec::new()))
+            .1
+            .push((start, end, &fix.replacement));
+    }
+
+    for (path, (source_file, mut file_edits)) in edits {
+        let Ok(mut source) = fs::read_to_string(&path) else {
+            rap_warn!("Cannot read {}", path.display());
+            continue;
+        };
+        if !source_file.src_hash.matches(&source) {
+            rap_debug!("{} is changed since it was compiled", path.display());
+            continue;
+        }
+        file_edits.sort();
+        file_edits.dedup();
+        let mut applied: Vec<Edit> = Vec::new();
+        for edit in file_edits {
+            if applied.last().is_some_and(|last| edit.0 < last.1) {
+                continue;
+            }
+            applied.push(edit);
+        }
+        for (start, end, replacement) in applied.iter().rev() {
+            source.replace_range(*start..*end, replacement);
+        }
+        match fs::write(&path, source) {
+            Ok(()) => rap_info!("Applied {} fixes to {}", applied.len(), path.display()),
+            Err(err) => rap_warn!("Cannot write {}: {}", path.display(), err),
+        }
+    }
+    if unapplied > 0 {
+        rap_info!(
+            "Skipped {} fixes that may change the behavior; see the suggestions of the reports",
+            unapplied
+        );
+    }
+}
@@ -8,6 +8,7 @@ use rustc_span::Span;
 use crate::analysis::core::dataflow::graph::Graph;
 use crate::analysis::opt::OptCheck;
 use crate::analysis::utils::def_path::DefPath;
+use crate::utils::findings::record_finding;
 use crate::utils::log::{
     relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code,
 };
@@ -112,6 +113,9 @@ fn report_next_iterator_bug(next_record: &Vec<Span>, chain_record: &Vec<Span>, g
     for chain_span in chain_record {
         snippet = snippet.annotation(Level::Error.span(relative_pos_range(graph_span, *chain_span)))
     }
+    if let Some(span) = next_record.first() {
+        record_finding("Inefficient iterators detected", *span);
+    }
     let message = Level::Warning
         .title("Inefficient iterators detected")
         .snippet(snippet)
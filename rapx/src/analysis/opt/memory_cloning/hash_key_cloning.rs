@@ -4,7 +4,10 @@ use crate::{
         opt::OptCheck,
         utils::def_path::DefPath,
     },
-    utils::log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
 };
 use annotate_snippets::{Level, Renderer, Snippet};
 use once_cell::sync::OnceCell;
@@ -142,6 +145,7 @@ fn report_hash_key_cloning(graph: &Graph, clone_span: Span, insert_span: Span) {
                 .span(relative_pos_range(graph.span, insert_span))
                 .label("Used here."),
         );
+    record_finding("Unnecessary memory cloning detected", clone_span);
     let message = Level::Warning
         .title("Unnecessary memory cloning detected")
         .snippet(snippet)
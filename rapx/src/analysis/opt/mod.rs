@@ -1,12 +1,14 @@
 pub mod checking;
 pub mod data_collection;
+pub mod fix;
 pub mod iterator;
 pub mod memory_cloning;
+pub mod rules;
 
 use rustc_middle::ty::TyCtxt;
 
 use crate::rap_warn;
-use crate::utils::log::span_to_source_code;
+use crate::utils::{boundary::guarded, log::span_to_source_code};
 
 use super::core::dataflow::{default::DataFlowAnalyzer, graph::Graph};
 use checking::bounds_checking::BoundsCheck;
@@ -14,10 +16,13 @@ use checking::encoding_checking::EncodingCheck;
 use data_collection::initialization::InitializationCheck;
 use data_collection::reallocation::ReservationCheck;
 use data_collection::suboptimal::SuboptimalCheck;
+use fix::Fix;
 use memory_cloning::used_as_immutable::UsedAsImmutableCheck;
+use rules::{load_rules, RuleCheck};
 
 use lazy_static::lazy_static;
 use rustc_span::symbol::Symbol;
+use std::collections::BTreeMap;
 use std::sync::Mutex;
 
 lazy_static! {
@@ -25,9 +30,20 @@ lazy_static! {
     pub static ref LEVEL: Mutex<usize> = Mutex::new(0);
 }
 
+const CATEGORIES: [&str; 6] = [
+    "Bounds Checking",
+    "Encoding Checking",
+    "Suboptimal",
+    "Initialization",
+    "Reallocation",
+    "Cloning",
+];
+
 pub struct Opt<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub level: usize,
+    /// Apply the fixes of the checks to the source files.
+    pub fix: bool,
 }
 
 pub trait OptCheck {
@@ -35,11 +51,19 @@ pub trait OptCheck {
     fn check(&mut self, graph: &Graph, tcx: &TyCtxt);
     fn report(&self, graph: &Graph);
     fn cnt(&self) -> usize;
+    /// The edits applying the suggestions of the reports, if they are machine-applicable.
+    fn fixes(&self) -> Vec<Fix> {
+        Vec::new()
+    }
 }
 
 impl<'tcx> Opt<'tcx> {
-    pub fn new(tcx: TyCtxt<'tcx>, level: usize) -> Self {
-        Self { tcx, level }
+    pub fn new(tcx: TyCtxt<'tcx>, level: usize, fix: bool) -> Self {
+        Self { tcx, level, fix }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "Opt Check"
     }
 
     fn has_crate(&self, name: &str) -> bool {
@@ -66,49 +90,74 @@ impl<'tcx> Opt<'tcx> {
         }
 
         let mut statistics = vec![0 as usize; 6];
+        // the categories of the user rules that are not built-in.
+        let mut custom_statistics: BTreeMap<String, usize> = BTreeMap::new();
+        let mut fixes: Vec<Fix> = Vec::new();
+        let rules = load_rules(&self.tcx);
+
+        let no_std = *NO_STD.lock().unwrap();
+        for (def_id, graph) in dataflow.graphs.iter() {
+            guarded(self.name(), *def_id, || {
+                let mut bounds_check = BoundsCheck::new();
+                bounds_check.check(graph, &self.tcx);
+                statistics[0] += bounds_check.cnt();
 
-        dataflow.graphs.iter().for_each(|(_, graph)| {
-            let mut bounds_check = BoundsCheck::new();
-            bounds_check.check(graph, &self.tcx);
-            statistics[0] += bounds_check.cnt();
-
-            if self.level > 0 {
-                bounds_check.report(graph);
-            }
-
-            let no_std = NO_STD.lock().unwrap();
-            if !*no_std {
-                let mut encoding_check = EncodingCheck::new();
-                encoding_check.check(graph, &self.tcx);
-                statistics[1] += encoding_check.cnt();
-
-                let mut suboptimal_check = SuboptimalCheck::new();
-                suboptimal_check.check(graph, &self.tcx);
-                statistics[2] += suboptimal_check.cnt();
-
-                let mut initialization_check = InitializationCheck::new();
-                initialization_check.check(graph, &self.tcx);
-                statistics[3] += initialization_check.cnt();
-
-                let mut reservation_check = ReservationCheck::new();
-                reservation_check.check(graph, &self.tcx);
-                statistics[4] += reservation_check.cnt();
+                if self.level > 0 {
+                    bounds_check.report(graph);
+                }
 
-                let mut used_as_immutable_check = UsedAsImmutableCheck::new();
-                used_as_immutable_check.check(graph, &self.tcx);
-                statistics[5] += used_as_immutable_check.cnt();
+                let mut rule_check = RuleCheck::with_rules(&rules);
+                rule_check.check(graph, &self.tcx);
+                for (category, cnt) in rule_check.categories() {
+                    match CATEGORIES
+                        .iter()
+                        .position(|name| name.eq_ignore_ascii_case(category))
+                    {
+                        Some(idx) => statistics[idx] += cnt,
+                        None => *custom_statistics.entry(category.to_string()).or_default() += cnt,
+                    }
+                }
 
                 if self.level > 0 {
-                    encoding_check.report(graph);
-                    suboptimal_check.report(graph);
-                    initialization_check.report(graph);
-                    reservation_check.report(graph);
-                    used_as_immutable_check.report(graph);
+                    rule_check.report(graph);
                 }
-            }
-        });
 
-        let bug_cnt: usize = statistics.iter().sum();
+                if !no_std {
+                    let mut encoding_check = EncodingCheck::new();
+                    encoding_check.check(graph, &self.tcx);
+                    statistics[1] += encoding_check.cnt();
+
+                    let mut suboptimal_check = SuboptimalCheck::new();
+                    suboptimal_check.check(graph, &self.tcx);
+                    statistics[2] += suboptimal_check.cnt();
+
+                    let mut initialization_check = InitializationCheck::new();
+                    initialization_check.check(graph, &self.tcx);
+                    statistics[3] += initialization_check.cnt();
+
+                    let mut reservation_check = ReservationCheck::new();
+                    reservation_check.check(graph, &self.tcx);
+                    statistics[4] += reservation_check.cnt();
+
+                    let mut used_as_immutable_check = UsedAsImmutableCheck::new();
+                    used_as_immutable_check.check(graph, &self.tcx);
+                    statistics[5] += used_as_immutable_check.cnt();
+
+                    if self.level > 0 {
+                        encoding_check.report(graph);
+                        suboptimal_check.report(graph);
+                        initialization_check.report(graph);
+                        reservation_check.report(graph);
+                        used_as_immutable_check.report(graph);
+                    }
+                    fixes.extend(encoding_check.fixes());
+                    fixes.extend(reservation_check.fixes());
+                }
+            });
+        }
+
+        let bug_cnt: usize =
+            statistics.iter().sum::<usize>() + custom_statistics.values().sum::<usize>();
         let func_cnt: usize = dataflow.graphs.iter().count();
         let line_cnt: usize = dataflow
             .graphs
@@ -121,12 +170,16 @@ impl<'tcx> Opt<'tcx> {
                 "RAPx detects {} code inefficiencies from {} functions ({} lines)",
                 bug_cnt, func_cnt, line_cnt,
             );
-            println!("  Bounds Checking: {}", statistics[0]);
-            println!("  Encoding Checking: {}", statistics[1]);
-            println!("  Suboptimal: {}", statistics[2]);
-            println!("  Initialization: {}", statistics[3]);
-            println!("  Reallocation: {}", statistics[4]);
-            println!("  Cloning: {}", statistics[5]);
+            for (name, cnt) in CATEGORIES.iter().zip(statistics.iter()) {
+                println!("  {}: {}", name, cnt);
+            }
+            for (name, cnt) in custom_statistics.iter() {
+                println!("  {}: {}", name, cnt);
+            }
+        }
+        fix::emit_suggestions(&fixes);
+        if self.fix {
+            fix::apply_fixes(&fixes);
         }
     }
 }
@@ -0,0 +1,424 @@
+//! Declarative rules of code inefficiencies.
+//!
+//! Besides the built-in checks, -opt loads the rules in the TOML files listed in the
+//! `RAP_OPT_RULES` environment variable. A rule matches a sequence of calls over the dataflow graph
+//! of a function: the first call is applied to a value `x`, and each following call is applied to
+//! a node equivalent to `x` after the previous one, optionally inside a loop. The rule does not
+//! match if `x` is also passed to one of the calls in `without` between the first and the last
+//! call. For example, the rule below reports pushing to a vector in a loop without reserving its
+//! space first:
+//!
+//! ```toml
+//! [[rule]]
+//! name = "unreserved_push"
+//! message = "Improper data collection detected"
+//! label = "Space unreserved."
+//! help = "Reserve enough space."
+//! category = "Reallocation"
+//! level = "warning"
+//! calls = [
+//!     { path = "std::vec::Vec::new", node = "return" },
+//!     { path = "std::vec::Vec::push", in_loop = true },
+//! ]
+//! without = [{ path = "std::vec::Vec::reserve" }]
+//! ```
+//!
+//! `node` selects the node of a call that stands for `x`: `return` for the return value, or `argN`
+//! for the N-th argument; it is `arg0`, i.e., the receiver of a method, by default. `level` is one
+//! of `error`, `warning`, `info`, `note` and `help`. The matches are counted in the statistics
+//! under `category`, which may be one of the built-in categories. A rule whose calls cannot be
+//! resolved in the crate under analysis, e.g., because the crate does not use the library, is
+//! ignored.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    analysis::{
+        core::dataflow::{graph::*, *},
+        opt::{data_collection::reallocation::unreserved_vec::LoopFinder, OptCheck},
+        utils::def_path::def_path_def_ids,
+    },
+    rap_debug, rap_warn,
+    utils::{
+        findings::record_finding,
+        log::{relative_pos_range, span_to_filename, span_to_line_number, span_to_source_code},
+    },
+};
+use annotate_snippets::{Level, Renderer, Snippet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{mir::Local, ty::TyCtxt};
+use rustc_span::Span;
+
+/// The node of a call that a rule refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallNode {
+    Return,
+    Arg(usize),
+}
+
+impl CallNode {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "return" => Some(CallNode::Return),
+            s => s
+                .strip_prefix("arg")
+                .and_then(|n| n.parse().ok())
+                .map(CallNode::Arg),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CallPattern {
+    pub path: String,
+    pub node: CallNode,
+    pub in_loop: bool,
+    def_ids: Vec<DefId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub message: String,
+    pub label: String,
+    pub help: Option<String>,
+    pub category: String,
+    pub level: Level,
+    pub calls: Vec<CallPattern>,
+    pub without: Vec<CallPattern>,
+}
+
+fn parse_level(s: &str) -> Option<Level> {
+    match s.to_lowercase().as_str() {
+        "error" => Some(Level::Error),
+        "warning" => Some(Level::Warning),
+        "info" => Some(Level::Info),
+        "note" => Some(Level::Note),
+        "help" => Some(Level::Help),
+        _ => None,
+    }
+}
+
+fn parse_calls(value: Option<&toml::Value>, key: &str, name: &str) -> Option<Vec<CallPattern>> {
+    let Some(value) = value else {
+        return Some(Vec::new());
+    };
+    let Some(calls) = value.as_array() else {
+        rap_warn!("The {} of rule {} should be an array.", key, name);
+        return None;
+    };
+    let mut patterns = Vec::new();
+    for call in calls {
+        let Some(path) = call.get("path").and_then(|path| path.as_str()) else {
+            rap_warn!("A call in the {} of rule {} has no path.", key, name);
+            return None;
+        };
+        let node = match call
+            .get("node")
+            .map(|node| node.as_str().and_then(CallNode::parse))
+        {
+            None => CallNode::Arg(0),
+            Some(Some(node)) => node,
+            Some(None) => {
+                rap_warn!("Unknown node of call {} in rule {}.", path, name);
+                return None;
+            }
+        };
+        let in_loop = call
+            .get("in_loop")
+            .and_then(|in_loop| in_loop.as_bool())
+            .unwrap_or(false);
+        patterns.push(CallPattern {
+            path: path.to_string(),
+            node,
+            in_loop,
+            def_ids: Vec::new(),
+        });
+    }
+    Some(patterns)
+}
+
+impl Rule {
+    fn parse(rule: &toml::Value, source: &str) -> Option<Self> {
+        let get_str = |key: &str| rule.get(key).and_then(|value| value.as_str());
+        let Some(name) = get_str("name") else {
+            rap_warn!("A rule in {} has no name.", source);
+            return None;
+        };
+        let Some(message) = get_str("message") else {
+            rap_warn!("Rule {} in {} has no message.", name, source);
+            return None;
+        };
+        let level = match get_str("level") {
+            None => Level::Warning,
+            Some(level) => match parse_level(level) {
+                Some(level) => level,
+                None => {
+                    rap_warn!("Unknown level {} of rule {} in {}.", level, name, source);
+                    return None;
+                }
+            },
+        };
+        let calls = parse_calls(rule.get("calls"), "calls", name)?;
+        if calls.is_empty() {
+            rap_warn!("Rule {} in {} has no calls.", name, source);
+            return None;
+        }
+        let without = parse_calls(rule.get("without"), "without", name)?;
+        Some(Rule {
+            name: name.to_string(),
+            message: message.to_string(),
+            label: get_str("label").unwrap_or(message).to_string(),
+            help: get_str("help").map(|help| help.to_string()),
+            category: get_str("category").unwrap_or("Custom").to_string(),
+            level,
+            calls,
+            without,
+        })
+    }
+
+    /// Resolve the paths of the calls; it fails if a call in `calls` is not found.
+    fn resolve(&mut self, tcx: &TyCtxt<'_>) -> bool {
+        for call in self.calls.iter_mut().chain(self.without.iter_mut()) {
+            let path: Vec<&str> = call.path.split("::").collect();
+            call.def_ids = def_path_def_ids(tcx, &path).collect();
+        }
+        match self.calls.iter().find(|call| call.def_ids.is_empty()) {
+            Some(call) => {
+                rap_debug!("Rule {} is ignored: {} is not found.", self.name, call.path);
+                false
+            }
+            None => true,
+        }
+    }
+}
+
+fn read_rules(path: &Path, rules: &mut Vec<Rule>) {
+    if path.is_dir() {
+        let entries = match std::fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                rap_warn!("Failed to read rule directory {}: {}", path.display(), e);
+                return;
+            }
+        };
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        files.sort();
+        for file in files {
+            read_rules(&file, rules);
+        }
+        return;
+    }
+    let source = path.display().to_string();
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) => {
+            rap_warn!("Failed to read rule file {}: {}", source, e);
+            return;
+        }
+    };
+    let table: toml::Table = match content.parse() {
+        Ok(table) => table,
+        Err(e) => {
+            rap_warn!("Failed to parse rule file {}: {}", source, e);
+            return;
+        }
+    };
+    let Some(entries) = table.get("rule").and_then(|rule| rule.as_array()) else {
+        rap_warn!(
+            "Rule file {} should contain an array of [[rule]] tables.",
+            source
+        );
+        return;
+    };
+    rules.extend(entries.iter().filter_map(|rule| Rule::parse(rule, &source)));
+}
+
+/// Load the rules in the files or directories listed in `RAP_OPT_RULES`. The calls are resolved
+/// in `tcx`, so the rules are loaded again for every crate.
+pub fn load_rules(tcx: &TyCtxt<'_>) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    if let Some(paths) = std::env::var_os("RAP_OPT_RULES") {
+        for path in std::env::split_paths(&paths) {
+            read_rules(&path, &mut rules);
+        }
+    }
+    rules.retain_mut(|rule| rule.resolve(tcx));
+    rules
+}
+
+/// The nodes standing for `x` of the calls matching `call` in the graph.
+fn matched_calls(graph: &Graph, call: &CallPattern) -> Vec<(Local, Span)> {
+    let mut matched = Vec::new();
+    for (node_idx, node) in graph.nodes.iter_enumerated() {
+        for (seq, op) in node.ops.iter().enumerate() {
+            let NodeOp::Call(def_id) = op else {
+                continue;
+            };
+            if !call.def_ids.contains(def_id) {
+                continue;
+            }
+            let target = match call.node {
+                CallNode::Return => Some(node_idx),
+                CallNode::Arg(n) => node
+                    .in_edges
+                    .iter()
+                    .map(|edge_idx| &graph.edges[*edge_idx])
+                    .filter(|edge| edge.seq == seq)
+                    .nth(n)
+                    .map(|edge| edge.src),
+            };
+            if let Some(target) = target {
+                matched.push((target, node.span));
+            }
+        }
+    }
+    matched
+}
+
+pub struct RuleCheck<'a> {
+    rules: &'a [Rule],
+    // the index of the rule and the span of the last call matched.
+    record: Vec<(usize, Span)>,
+}
+
+impl<'a> RuleCheck<'a> {
+    pub fn with_rules(rules: &'a [Rule]) -> Self {
+        Self {
+            rules,
+            record: Vec::new(),
+        }
+    }
+
+    fn match_rule(&mut self, graph: &Graph, rule_idx: usize, rule: &Rule, loops: &[Span]) {
+        let in_loop = |span: Span| loops.iter().any(|loop_span| loop_span.contains(span));
+        let candidates: Vec<Vec<(Local, Span)>> = rule
+            .calls
+            .iter()
+            .map(|call| {
+                matched_calls(graph, call)
+                    .into_iter()
+                    .filter(|(_, span)| !call.in_loop || in_loop(*span))
+                    .collect()
+            })
+            .collect();
+        let without: Vec<(Local, Span)> = rule
+            .without
+            .iter()
+            .flat_map(|call| matched_calls(graph, call))
+            .collect();
+        for (x, span) in candidates[0].iter() {
+            let mut equivalent = graph.collect_equivalent_locals(*x, false);
+            equivalent.insert(*x);
+            // whether one of the calls in `without` is applied to `x` between the first call and
+            // the call at `last`.
+            let excluded = |last: Span| {
+                without.iter().any(|(node, without_span)| {
+                    equivalent.contains(node)
+                        && without_span.lo() > span.lo()
+                        && without_span.lo() < last.lo()
+                })
+            };
+            // every call is matched with the earliest one after the previous call, and the last
+            // call is reported wherever it is matched.
+            let mut prev = *span;
+            let mut matched = true;
+            for calls in candidates[1..candidates.len() - 1].iter() {
+                match calls
+                    .iter()
+                    .filter(|(node, span)| equivalent.contains(node) && span.lo() > prev.lo())
+                    .min_by_key(|(_, span)| span.lo())
+                {
+                    Some((_, span)) => prev = *span,
+                    None => {
+                        matched = false;
+                        break;
+                    }
+                }
+            }
+            if !matched {
+                continue;
+            }
+            let last: Vec<Span> = match candidates.len() {
+                1 => vec![*span],
+                _ => candidates[candidates.len() - 1]
+                    .iter()
+                    .filter(|(node, span)| equivalent.contains(node) && span.lo() > prev.lo())
+                    .map(|(_, span)| *span)
+                    .collect(),
+            };
+            let last: Vec<Span> = last.into_iter().filter(|span| !excluded(*span)).collect();
+            for span in last {
+                if !self.record.contains(&(rule_idx, span)) {
+                    self.record.push((rule_idx, span));
+                }
+            }
+        }
+    }
+
+    /// The number of matches of each category.
+    pub fn categories(&self) -> BTreeMap<&'a str, usize> {
+        let mut categories = BTreeMap::new();
+        for (rule_idx, _) in self.record.iter() {
+            *categories
+                .entry(self.rules[*rule_idx].category.as_str())
+                .or_default() += 1;
+        }
+        categories
+    }
+}
+
+impl OptCheck for RuleCheck<'_> {
+    fn new() -> Self {
+        Self::with_rules(&[])
+    }
+
+    fn check(&mut self, graph: &Graph, tcx: &TyCtxt) {
+        let rules = self.rules;
+        if rules.is_empty() {
+            return;
+        }
+        let Some(local_def_id) = graph.def_id.as_local() else {
+            return;
+        };
+        let loops: Vec<Span> = LoopFinder::new(*tcx, local_def_id).spans().collect();
+        for (rule_idx, rule) in rules.iter().enumerate() {
+            self.match_rule(graph, rule_idx, rule, &loops);
+        }
+    }
+
+    fn report(&self, graph: &Graph) {
+        for (rule_idx, span) in self.record.iter() {
+            report_rule_match(graph, &self.rules[*rule_idx], *span);
+        }
+    }
+
+    fn cnt(&self) -> usize {
+        self.record.len()
+    }
+}
+
+fn report_rule_match(graph: &Graph, rule: &Rule, span: Span) {
+    let code_source = span_to_source_code(graph.span);
+    let filename = span_to_filename(span);
+    let snippet: Snippet<'_> = Snippet::source(&code_source)
+        .line_start(span_to_line_number(graph.span))
+        .origin(&filename)
+        .fold(true)
+        .annotation(
+            Level::Error
+                .span(relative_pos_range(graph.span, span))
+                .label(&rule.label),
+        );
+    record_finding(rule.message.clone(), span);
+    let mut message = rule.level.title(&rule.message).snippet(snippet);
+    if let Some(help) = &rule.help {
+        message = message.footer(Level::Help.title(help));
+    }
+    let renderer = Renderer::styled();
+    println!("{}", renderer.render(message));
+}
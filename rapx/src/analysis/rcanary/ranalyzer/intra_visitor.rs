@@ -22,6 +22,7 @@ use crate::{
     analysis::core::ownedheap_analysis::{default::*, *},
     rap_debug, rap_error, rap_trace, rap_warn,
     utils::{
+        findings::record_finding,
         log::{
             are_spans_in_same_file, relative_pos_range, span_to_filename, span_to_line_number,
             span_to_source_code,
@@ -2483,6 +2484,7 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
                 .fold(false);
 
             for source in self.taint_source.iter() {
+                record_finding("Memory Leak detected.", source.source_info.span);
                 if are_spans_in_same_file(self.body.span, source.source_info.span) {
                     snippet = snippet.annotation(
                         Level::Warning
@@ -2833,15 +2835,11 @@ impl<'tcx, 'ctx, 'a> IntraFlowAnalysis<'tcx, 'ctx, 'a> {
                         break;
                     }
                 }
-                ProjectionElem::ConstantIndex { .. }
-                | ProjectionElem::Subslice { .. }
-                | ProjectionElem::Index(..)
-                | ProjectionElem::OpaqueCast(..)
-                | ProjectionElem::Subtype(..) => {
+                // e.g., ConstantIndex, Subslice, Index, OpaqueCast and Subtype.
+                _ => {
                     prj.unsupport = true;
                     break;
                 }
-                _ => todo!(),
             }
         }
         prj
@@ -67,7 +67,7 @@ impl<'tcx> NodeOrder<'tcx> {
                         None => (),
                     }
                 }
-                TerminatorKind::TailCall { .. } => todo!(),
+                TerminatorKind::TailCall { .. } => (),
             }
             // Update the lev for generating topo order.
             for index in result.iter() {
@@ -1,6 +1,9 @@
 use super::graph::*;
 use crate::{
-    analysis::core::alias_analysis::default::{types::*, MopAAFact, MopAAResultMap},
+    analysis::core::alias_analysis::{
+        default::{types::*, MopAAFact, MopAAResultMap},
+        DEREF_FIELD,
+    },
     rap_error,
 };
 use rustc_middle::{
@@ -147,13 +150,20 @@ impl<'tcx> SafeDropGraph<'tcx> {
     pub fn projection(&mut self, tcx: TyCtxt<'tcx>, is_right: bool, place: Place<'tcx>) -> usize {
         let mut local = place.local.as_usize();
         let mut proj_id = local;
-        for proj in place.projection {
+        let mut variant = None;
+        for (base, proj) in place.iter_projections() {
             let new_id = self.values.len();
             match proj {
                 ProjectionElem::Deref => {
                     //proj_id = self.values[proj_id].alias[0];
-                    proj_id = self.alias_set[proj_id];
+                    // A multi-level pointer leads to its pointee; a single-level pointer is
+                    // aliased with the object it points to.
+                    proj_id = match self.values[proj_id].fields.get(&DEREF_FIELD) {
+                        Some(&pointee) => pointee,
+                        None => self.alias_set[proj_id],
+                    };
                 }
+                ProjectionElem::Downcast(_, idx) => variant = Some(idx),
                 /*
                  * Objective: 2 = 1.0; 0 = 2.0; => 0 = 1.0.0
                  */
@@ -162,7 +172,13 @@ impl<'tcx> SafeDropGraph<'tcx> {
                         proj_id = self.alias_set[proj_id];
                         local = self.values[proj_id].local;
                     }
-                    let field_idx = field.as_usize();
+                    let field_idx = match variant.take() {
+                        Some(variant) => {
+                            let body = tcx.optimized_mir(self.def_id);
+                            variant_field(tcx, base.ty(body, tcx).ty, variant, field)
+                        }
+                        None => field.as_usize(),
+                    };
                     if !self.values[proj_id].fields.contains_key(&field_idx) {
                         let ty_env = TypingEnv::post_analysis(tcx, self.def_id);
                         let need_drop = ty.needs_drop(tcx, ty_env);
@@ -178,6 +194,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                         self.values.push(node);
                     }
                     proj_id = *self.values[proj_id].fields.get(&field_idx).unwrap();
+                    self.add_pointees(proj_id, ty);
                 }
                 _ => {}
             }
@@ -277,7 +294,14 @@ impl<'tcx> SafeDropGraph<'tcx> {
             }
             rv = *self.values[rv].fields.get(&index).unwrap();
         }
-        self.merge_alias(lv, rv, 0);
+        // A callee that does not see the pointer levels of its arguments, e.g., a generic one,
+        // relates a multi-level pointer to its pointee.
+        match self.values[lv].fields.get(&DEREF_FIELD) {
+            Some(&pointee) if !self.values[rv].fields.contains_key(&DEREF_FIELD) => {
+                self.merge_alias(pointee, rv, 0)
+            }
+            _ => self.merge_alias(lv, rv, 0),
+        }
     }
 
     #[inline(always)]
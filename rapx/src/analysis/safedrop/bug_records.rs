@@ -1,7 +1,9 @@
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_span::Span;
 
+use super::panic_safety::PanicSafetyBug;
 use crate::rap_warn;
+use crate::utils::findings::record_finding;
 use crate::utils::log::are_spans_in_same_file;
 use rustc_span::symbol::Symbol;
 
@@ -15,20 +17,23 @@ use crate::utils::log::{
 
 pub struct BugRecords {
     pub df_bugs: FxHashMap<usize, Span>,
-    pub df_bugs_unwind: FxHashMap<usize, Span>,
     pub uaf_bugs: FxHashSet<Span>,
     pub dp_bugs: FxHashSet<Span>,
     pub dp_bugs_unwind: FxHashSet<Span>,
+    // the suspension points where cancelling the future leaves a dangling pointer.
+    pub dp_bugs_cancel: FxHashSet<Span>,
+    pub panic_safety_bugs: Vec<PanicSafetyBug>,
 }
 
 impl BugRecords {
     pub fn new() -> BugRecords {
         BugRecords {
             df_bugs: FxHashMap::default(),
-            df_bugs_unwind: FxHashMap::default(),
             uaf_bugs: FxHashSet::default(),
             dp_bugs: FxHashSet::default(),
             dp_bugs_unwind: FxHashSet::default(),
+            dp_bugs_cancel: FxHashSet::default(),
+            panic_safety_bugs: Vec::new(),
         }
     }
 
@@ -37,6 +42,8 @@ impl BugRecords {
             && self.uaf_bugs.is_empty()
             && self.dp_bugs.is_empty()
             && self.dp_bugs_unwind.is_empty()
+            && self.dp_bugs_cancel.is_empty()
+            && self.panic_safety_bugs.is_empty()
     }
 
     pub fn df_bugs_output(&self, fn_name: Symbol, span: Span) {
@@ -49,6 +56,7 @@ impl BugRecords {
                 .origin(&filename)
                 .fold(false);
             for i in self.df_bugs.iter() {
+                record_finding("Double free detected.", *i.1);
                 //todo: remove this condition
                 if are_spans_in_same_file(span, *i.1) {
                     snippet = snippet.annotation(
@@ -76,6 +84,7 @@ impl BugRecords {
                 .origin(&filename)
                 .fold(true);
             for i in self.uaf_bugs.iter() {
+                record_finding("Use after free detected.", *i);
                 //todo: remove this condition
                 if are_spans_in_same_file(span, *i) {
                     snippet = snippet.annotation(
@@ -94,51 +103,104 @@ impl BugRecords {
     }
 
     pub fn dp_bug_output(&self, fn_name: Symbol, span: Span) {
-        let code_source = span_to_source_code(span);
-        let filename = span_to_filename(span);
         if !self.dp_bugs.is_empty() {
             rap_warn!("Dangling pointer detected in function {:?}", fn_name);
-            let mut snippet = Snippet::source(&code_source)
-                .line_start(span_to_line_number(span))
-                .origin(&filename)
-                .fold(false);
-            for i in self.dp_bugs.iter() {
-                //todo: remove this condition
-                if are_spans_in_same_file(span, *i) {
-                    snippet = snippet.annotation(
-                        Level::Warning
-                            .span(relative_pos_range(span, *i))
-                            .label("Dangling pointer detected."),
-                    );
-                }
-            }
-            let message = Level::Warning
-                .title("Dangling pointer detected.")
-                .snippet(snippet);
-            let renderer = Renderer::styled();
-            println!("{}", renderer.render(message));
+            Self::dp_spans_output(
+                span,
+                &self.dp_bugs,
+                "Dangling pointer detected.",
+                "Dangling pointer detected.",
+            );
         }
         if !self.dp_bugs_unwind.is_empty() {
             rap_warn!(
                 "Dangling pointer detected in function {:?} during unwinding.",
                 fn_name
             );
+            Self::dp_spans_output(
+                span,
+                &self.dp_bugs_unwind,
+                "Dangling pointer detected during unwinding.",
+                "Dangling pointer detected during unwinding.",
+            );
+        }
+        if !self.dp_bugs_cancel.is_empty() {
+            rap_warn!(
+                "Dangling pointer detected in function {:?} if the future is cancelled.",
+                fn_name
+            );
+            Self::dp_spans_output(
+                span,
+                &self.dp_bugs_cancel,
+                "Dangling pointer detected if the future is cancelled.",
+                "Dangling pointer detected if the future is dropped here.",
+            );
+        }
+    }
+
+    fn dp_spans_output(span: Span, bugs: &FxHashSet<Span>, title: &str, label: &str) {
+        let code_source = span_to_source_code(span);
+        let filename = span_to_filename(span);
+        let mut snippet = Snippet::source(&code_source)
+            .line_start(span_to_line_number(span))
+            .origin(&filename)
+            .fold(false);
+        for i in bugs.iter() {
+            record_finding(title, *i);
+            //todo: remove this condition
+            if are_spans_in_same_file(span, *i) {
+                snippet = snippet.annotation(
+                    Level::Warning
+                        .span(relative_pos_range(span, *i))
+                        .label(label),
+                );
+            }
+        }
+        let message = Level::Warning.title(title).snippet(snippet);
+        let renderer = Renderer::styled();
+        println!("{}", renderer.render(message));
+    }
+
+    pub fn panic_safety_output(&self, fn_name: Symbol, span: Span) {
+        if self.panic_safety_bugs.is_empty() {
+            return;
+        }
+        rap_warn!("Panic safety bug detected in function {:?}", fn_name);
+        let code_source = span_to_source_code(span);
+        let filename = span_to_filename(span);
+        for bug in self.panic_safety_bugs.iter() {
+            record_finding(
+                "Broken invariant is observable if the call panics.",
+                bug.broken,
+            );
+            //todo: remove this condition
+            if !are_spans_in_same_file(span, bug.broken) {
+                continue;
+            }
+            let broken = format!("Invariant broken: {}.", bug.kind);
             let mut snippet = Snippet::source(&code_source)
                 .line_start(span_to_line_number(span))
                 .origin(&filename)
-                .fold(false);
-            for i in self.dp_bugs_unwind.iter() {
-                //todo: remove this condition
-                if are_spans_in_same_file(span, *i) {
-                    snippet = snippet.annotation(
-                        Level::Warning
-                            .span(relative_pos_range(span, *i))
-                            .label("Dangling pointer detected during unwinding."),
-                    );
-                }
+                .fold(true)
+                .annotation(
+                    Level::Warning
+                        .span(relative_pos_range(span, bug.broken))
+                        .label(&broken),
+                )
+                .annotation(
+                    Level::Warning
+                        .span(relative_pos_range(span, bug.panicking))
+                        .label("Calling code that may panic before the invariant is restored."),
+                );
+            if let Some(restored) = bug.restored {
+                snippet = snippet.annotation(
+                    Level::Info
+                        .span(relative_pos_range(span, restored))
+                        .label("Invariant restored."),
+                );
             }
             let message = Level::Warning
-                .title("Dangling pointer detected during unwinding.")
+                .title("Broken invariant is observable if the call panics.")
                 .snippet(snippet);
             let renderer = Renderer::styled();
             println!("{}", renderer.render(message));
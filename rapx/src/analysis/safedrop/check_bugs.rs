@@ -1,13 +1,29 @@
 use super::graph::*;
-use crate::utils::source::*;
+use crate::{
+    analysis::core::alias_analysis::{default::types::pointee_ty, DEREF_FIELD},
+    utils::source::*,
+};
+use rustc_abi::FieldIdx;
 use rustc_data_structures::fx::FxHashSet;
-use rustc_middle::mir::SourceInfo;
+use rustc_hir::def::DefKind;
+use rustc_middle::{
+    mir::{BasicBlock, SourceInfo},
+    ty::{self, TypingEnv},
+};
 use rustc_span::symbol::Symbol;
 use rustc_span::Span;
 
 impl<'tcx> SafeDropGraph<'tcx> {
     pub fn report_bugs(&self) {
-        let filename = get_filename(self.tcx, self.def_id);
+        // a coroutine, e.g., the body of an async fn or async closure, is reported with the
+        // function defining it.
+        let def_id = match self.tcx.is_coroutine(self.def_id)
+            || self.tcx.def_kind(self.def_id) == DefKind::SyntheticCoroutineBody
+        {
+            true => self.tcx.typeck_root_def_id(self.def_id),
+            false => self.def_id,
+        };
+        let filename = get_filename(self.tcx, def_id);
         match filename {
             Some(filename) => {
                 if filename.contains(".cargo") {
@@ -19,13 +35,14 @@ impl<'tcx> SafeDropGraph<'tcx> {
         if self.bug_records.is_bug_free() {
             return;
         }
-        let fn_name = match get_name(self.tcx, self.def_id) {
+        let fn_name = match get_name(self.tcx, def_id) {
             Some(name) => name,
             None => Symbol::intern("no symbol available"),
         };
         self.bug_records.df_bugs_output(fn_name, self.span);
         self.bug_records.uaf_bugs_output(fn_name, self.span);
         self.bug_records.dp_bug_output(fn_name, self.span);
+        self.bug_records.panic_safety_output(fn_name, self.span);
     }
 
     pub fn uaf_check(&mut self, aliaset_idx: usize, span: Span, local: usize, is_func_call: bool) {
@@ -34,18 +51,22 @@ impl<'tcx> SafeDropGraph<'tcx> {
             && (!self.values[aliaset_idx].is_ptr()
                 || self.values[aliaset_idx].local != local
                 || is_func_call)
-            && self.exist_dead(aliaset_idx, &mut record, false)
+            && self.exist_dead(aliaset_idx, &mut record, false, is_func_call)
             && !self.bug_records.uaf_bugs.contains(&span)
         {
             self.bug_records.uaf_bugs.insert(span.clone());
         }
     }
 
+    /// Check whether the node, its aliases or its fields are dead. The pointee of a multi-level
+    /// pointer is only checked if `deref` is set, and the pointees behind it are not, because
+    /// moving or copying a pointer does not use the object it points to.
     pub fn exist_dead(
         &mut self,
         node: usize,
         record: &mut FxHashSet<usize>,
         dangling: bool,
+        deref: bool,
     ) -> bool {
         if node >= self.values.len() {
             return false;
@@ -68,13 +89,17 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 if i != node && !self.union_is_same(i, node) {
                     continue;
                 }
-                if record.contains(&i) == false && self.exist_dead(i, record, dangling) {
+                if !record.contains(&i) && self.exist_dead(i, record, dangling, deref) {
                     return true;
                 }
             }
         }
         for i in self.values[node].fields.clone().into_iter() {
-            if record.contains(&i.1) == false && self.exist_dead(i.1, record, dangling) {
+            if i.0 == DEREF_FIELD && !deref {
+                continue;
+            }
+            let deref = deref && i.0 != DEREF_FIELD;
+            if !record.contains(&i.1) && self.exist_dead(i.1, record, dangling, deref) {
                 return true;
             }
         }
@@ -83,7 +108,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
 
     pub fn is_dangling(&mut self, local: usize) -> bool {
         let mut record = FxHashSet::default();
-        return self.exist_dead(local, &mut record, local != 0);
+        self.exist_dead(local, &mut record, local != 0, true)
     }
 
     pub fn df_check(&mut self, drop: usize, span: Span) -> bool {
@@ -119,6 +144,68 @@ impl<'tcx> SafeDropGraph<'tcx> {
         }
     }
 
+    /// Check the coroutine suspended at the block `bb_index` against being dropped there, i.e., the
+    /// future being cancelled. Its drop glue drops the locals held across the suspension point, so
+    /// the objects outside the future must not point to them afterwards.
+    pub fn cancel_check(&mut self, bb_index: usize) {
+        let Some(&(state, variant)) = self.suspensions.get(&bb_index) else {
+            return;
+        };
+        let tcx = self.tcx;
+        let body = tcx.optimized_mir(self.def_id);
+        let ty::Coroutine(def_id, args) = *state.ty(body, tcx).ty.kind() else {
+            return;
+        };
+        let Ok(layout) = tcx.coroutine_layout(def_id, args) else {
+            return;
+        };
+        let backup_values = self.values.clone();
+        let backup_alias_set = self.alias_set.clone();
+        let backup_dead_record = self.dead_record.clone();
+
+        let ty_env = TypingEnv::post_analysis(tcx, self.def_id);
+        let info = body.basic_blocks[BasicBlock::from_usize(bb_index)]
+            .terminator()
+            .source_info;
+        let birth = self.scc_indices[bb_index];
+        // the pointers held by the future, i.e., its upvars and the locals held across the point.
+        let mut holders = Vec::new();
+        for (field, ty) in args.as_coroutine().upvar_tys().iter().enumerate() {
+            if pointee_ty(tcx, ty).is_some() {
+                let place = tcx.mk_place_field(state, FieldIdx::from_usize(field), ty);
+                holders.push(self.projection(tcx, false, place));
+            }
+        }
+        let downcast = tcx.mk_place_downcast_unnamed(state, variant);
+        for (field, saved) in layout.variant_fields[variant].iter_enumerated() {
+            let ty = layout.field_tys[*saved].ty;
+            let node = self.projection(tcx, false, tcx.mk_place_field(downcast, field, ty));
+            if ty.needs_drop(tcx, ty_env) {
+                self.dead_node(node, birth, &info, false);
+            } else if pointee_ty(tcx, ty).is_some() {
+                holders.push(node);
+            }
+        }
+        // A pointer aliased with a dropped local points into the future itself; the other
+        // pointers lead out of the future and must not reach a dropped local.
+        for holder in holders {
+            let into_future = (0..self.values.len())
+                .any(|i| self.union_is_same(i, holder) && !self.values[i].is_alive());
+            if into_future {
+                continue;
+            }
+            if self.exist_dead(holder, &mut FxHashSet::default(), false, true) {
+                self.bug_records
+                    .dp_bugs_cancel
+                    .insert(layout.variant_source_info[variant].span);
+            }
+        }
+
+        self.values = backup_values;
+        self.alias_set = backup_alias_set;
+        self.dead_record = backup_dead_record;
+    }
+
     pub fn dead_node(&mut self, drop: usize, birth: usize, info: &SourceInfo, alias: bool) {
         //Rc drop
         if self.values[drop].is_corner_case() {
@@ -146,6 +233,11 @@ impl<'tcx> SafeDropGraph<'tcx> {
                     continue;
                 }
                 self.dead_node(i, birth, info, true);
+                // The object behind a multi-level pointer is accessed through the copies of the
+                // pointee, whose fields are dropped together with the pointee.
+                if !alias && self.values[drop].field_id == DEREF_FIELD {
+                    self.dead_fields(i, birth, info, &mut FxHashSet::default());
+                }
             }
         }
         //drop the fields of the root node.
@@ -164,13 +256,18 @@ impl<'tcx> SafeDropGraph<'tcx> {
         }
     }
 
-    pub fn get_field_seq(&self, value: &ValueNode) -> Vec<usize> {
-        let mut field_id_seq = vec![];
-        let mut node_ref = value;
-        while node_ref.field_id != usize::MAX {
-            field_id_seq.push(node_ref.field_id);
-            node_ref = &self.values[value.father];
-        }
-        return field_id_seq;
+    fn dead_fields(
+        &mut self,
+        node: usize,
+        birth: usize,
+        info: &SourceInfo,
+        record: &mut FxHashSet<usize>,
+    ) {
+        for field in self.values[node].fields.clone().into_values() {
+            if record.insert(field) {
+                self.dead_node(field, birth, info, true);
+                self.dead_fields(field, birth, info, record);
+            }
+        }
     }
 }
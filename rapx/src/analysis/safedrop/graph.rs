@@ -1,14 +1,18 @@
 use super::bug_records::*;
 use crate::{
-    analysis::{core::alias_analysis::default::types::*, core::ownedheap_analysis::OHAResultMap},
+    analysis::{
+        core::alias_analysis::default::types::*,
+        core::range_analysis::feasibility::{prune_infeasible_paths, PathChecker},
+    },
     def_id::*,
 };
+use rustc_abi::VariantIdx;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::mir::{
     BasicBlock, Body, Const, Operand, Place, Rvalue, StatementKind, Terminator, TerminatorKind,
     UnwindAction,
 };
-use rustc_middle::ty::{self, TyCtxt, TypingEnv};
+use rustc_middle::ty::{self, CoroutineArgsExt, Ty, TyCtxt, TypingEnv};
 use rustc_span::{def_id::DefId, Span};
 use std::{cell::RefCell, cmp::min, vec::Vec};
 
@@ -146,6 +150,10 @@ impl ValueNode {
     }
 }
 
+/// Whether a value dropped at a place of the type, known to be of the variant, owns heap. A type
+/// missing from the map is assumed to own heap.
+pub type DropHeaps<'tcx> = FxHashMap<(Ty<'tcx>, Option<VariantIdx>), bool>;
+
 pub struct SafeDropGraph<'tcx> {
     pub def_id: DefId,
     pub tcx: TyCtxt<'tcx>,
@@ -168,7 +176,7 @@ pub struct SafeDropGraph<'tcx> {
     pub alias_set: Vec<usize>,
     pub dead_record: Vec<bool>,
     // analysis of heap item
-    pub adt_owner: OHAResultMap,
+    pub drop_heaps: DropHeaps<'tcx>,
     pub child_scc: FxHashMap<
         usize,
         (
@@ -179,6 +187,11 @@ pub struct SafeDropGraph<'tcx> {
     >,
     pub disc_map: FxHashMap<usize, usize>,
     pub terms: Vec<TerminatorKind<'tcx>>,
+    // the blocks on the path being visited, used to skip the branches proved infeasible.
+    pub path: Vec<usize>,
+    pub path_checker: Option<PathChecker<'tcx>>,
+    // the suspension points of a coroutine: block -> (coroutine state, variant saved to resume from).
+    pub suspensions: FxHashMap<usize, (Place<'tcx>, VariantIdx)>,
 }
 
 impl<'tcx> SafeDropGraph<'tcx> {
@@ -186,7 +199,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         body: &Body<'tcx>,
         tcx: TyCtxt<'tcx>,
         def_id: DefId,
-        adt_owner: OHAResultMap,
+        drop_heaps: DropHeaps<'tcx>,
     ) -> SafeDropGraph<'tcx> {
         // handle variables
         let locals = &body.local_decls;
@@ -215,6 +228,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         let mut scc_indices = Vec::<usize>::new();
         let mut disc_map = FxHashMap::default();
         let mut terms = Vec::new();
+        let mut suspensions = FxHashMap::default();
 
         // handle each basicblock
         for i in 0..basicblocks.len() {
@@ -225,6 +239,20 @@ impl<'tcx> SafeDropGraph<'tcx> {
 
             // handle general statements
             for stmt in &basicblocks[iter].statements {
+                // After the state transform, a coroutine suspends by saving the variant to resume
+                // from and returning.
+                if let StatementKind::SetDiscriminant {
+                    ref place,
+                    variant_index,
+                } = stmt.kind
+                {
+                    if matches!(place.ty(locals, tcx).ty.kind(), ty::Coroutine(..))
+                        && variant_index.as_usize() >= ty::CoroutineArgs::RESERVED_VARIANTS
+                        && matches!(terminator.kind, TerminatorKind::Return)
+                    {
+                        suspensions.insert(i, (**place, variant_index));
+                    }
+                }
                 /* Assign is a tuple defined as Assign(Box<(Place<'tcx>, Rvalue<'tcx>)>) */
                 let span = stmt.source_info.span;
                 if let StatementKind::Assign(ref assign) = stmt.kind {
@@ -273,6 +301,19 @@ impl<'tcx> SafeDropGraph<'tcx> {
                             }
                         }
                         Rvalue::Ref(_, _, rv) | Rvalue::RawPtr(_, rv) => {
+                            let rv_local = rv.local.as_usize();
+                            if values[lv_local].may_drop && values[rv_local].may_drop {
+                                // A multi-level pointer keeps the object it points to as its
+                                // pointee, i.e., `*lv = rv`.
+                                let lv = match multi_level_pointee(tcx, lv.ty(locals, tcx).ty) {
+                                    Some(_) => tcx.mk_place_deref(lv),
+                                    None => lv,
+                                };
+                                let assign = Assignment::new(lv, rv, AssignType::Copy, span);
+                                cur_bb.assignments.push(assign);
+                            }
+                        }
+                        Rvalue::CopyForDeref(rv) => {
                             let rv_local = rv.local.as_usize();
                             if values[lv_local].may_drop && values[rv_local].may_drop {
                                 let assign = Assignment::new(lv, rv, AssignType::Copy, span);
@@ -282,8 +323,9 @@ impl<'tcx> SafeDropGraph<'tcx> {
                         Rvalue::ShallowInitBox(x, _) => {
                             /*
                              * Original ShllowInitBox is a two-level pointer: lvl0 -> lvl1 -> lvl2
-                             * Since our alias analysis does not consider multi-level pointer,
-                             * We simplify it as: lvl0
+                             * The derefs of a box are lowered to derefs of its NonNull field, so
+                             * lvl1 is modelled as field 0 of lvl0, and lvl2 as the pointee of that
+                             * NonNull if lvl2 is a pointer itself.
                              */
                             #[allow(clippy::map_entry)]
                             if !values[lv_local].fields.contains_key(&0) {
@@ -414,7 +456,8 @@ impl<'tcx> SafeDropGraph<'tcx> {
                     }
                     cur_bb.calls.push(terminator.clone());
                 }
-                TerminatorKind::TailCall { .. } => todo!(),
+                // a tail call leaves the function like a return.
+                TerminatorKind::TailCall { .. } => {}
                 TerminatorKind::Assert {
                     cond: _,
                     expected: _,
@@ -450,9 +493,8 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 } => {
                     cur_bb.add_next(real_target.as_usize());
                 }
-                TerminatorKind::CoroutineDrop {} => {
-                    // todo
-                }
+                // the drop glue of a suspended coroutine is checked at the suspension points.
+                TerminatorKind::CoroutineDrop {} => {}
                 TerminatorKind::InlineAsm {
                     template: _,
                     operands: _,
@@ -473,7 +515,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
             blocks.push(cur_bb);
         }
 
-        SafeDropGraph {
+        let mut graph = SafeDropGraph {
             def_id,
             tcx,
             span: body.span,
@@ -487,13 +529,53 @@ impl<'tcx> SafeDropGraph<'tcx> {
             visit_times: 0,
             alias_set: alias,
             dead_record: dead,
-            adt_owner,
+            drop_heaps,
             child_scc: FxHashMap::default(),
             disc_map,
             terms,
+            path: Vec::new(),
+            path_checker: prune_infeasible_paths().then(|| PathChecker::new(tcx, def_id)),
+            suspensions,
+        };
+        for (local, local_decl) in locals.iter_enumerated() {
+            graph.add_pointees(local.as_usize(), local_decl.ty);
+        }
+        graph
+    }
+
+    /// Create the pointee nodes of a multi-level pointer as the children of the pointer node; see
+    /// `pointee_chain`.
+    pub fn add_pointees(&mut self, node: usize, ty: Ty<'tcx>) {
+        let mut node = node;
+        for (field_id, ty) in pointee_chain(self.tcx, ty) {
+            node = self.add_child(node, field_id, ty);
         }
     }
 
+    fn add_child(&mut self, node: usize, field_id: usize, ty: Ty<'tcx>) -> usize {
+        if let Some(&child) = self.values[node].fields.get(&field_id) {
+            return child;
+        }
+        let ty_env = TypingEnv::post_analysis(self.tcx, self.def_id);
+        let need_drop = ty.needs_drop(self.tcx, ty_env);
+        let may_drop = !is_not_drop(self.tcx, ty);
+        let mut child = ValueNode::new(
+            self.values.len(),
+            self.values[node].local,
+            need_drop,
+            need_drop || may_drop,
+        );
+        child.kind = kind(ty);
+        child.birth = self.values[node].birth;
+        child.field_id = field_id;
+        let index = child.index;
+        self.values[node].fields.insert(field_id, index);
+        self.alias_set.push(index);
+        self.dead_record.push(false);
+        self.values.push(child);
+        index
+    }
+
     pub fn tarjan(
         &mut self,
         index: usize,
@@ -3,17 +3,22 @@ pub mod bug_records;
 pub mod check_bugs;
 pub mod corner_handle;
 pub mod graph;
+pub mod panic_safety;
 #[allow(clippy::module_inception)]
 pub mod safedrop;
 
 use rustc_hir::def_id::DefId;
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::{
+    mir::{Body, TerminatorKind},
+    ty::{TyCtxt, TyKind},
+};
 
 use crate::analysis::core::{
-    alias_analysis::default::{AliasAnalyzer, MopAAResultMap},
-    ownedheap_analysis::{default::OwnedHeapAnalyzer, OHAResultMap, OwnedHeapAnalysis},
+    alias_analysis::{default::MopAAResultMap, AliasBackend},
+    ownedheap_analysis::{default::OwnedHeapAnalyzer, OwnedHeap},
 };
-use graph::SafeDropGraph;
+use crate::utils::boundary::guarded;
+use graph::{DropHeaps, SafeDropGraph};
 use safedrop::*;
 
 use super::Analysis;
@@ -26,32 +31,56 @@ impl<'tcx> SafeDrop<'tcx> {
     pub fn new(tcx: TyCtxt<'tcx>) -> Self {
         Self { tcx }
     }
+
+    pub fn name(&self) -> &'static str {
+        "SafeDrop"
+    }
+
     pub fn start(&self) {
-        let mut mop = AliasAnalyzer::new(self.tcx);
-        mop.run();
-        let fn_map = mop.get_all_fn_alias_raw();
+        let fn_map = AliasBackend::from_env().run(self.tcx);
 
         let mut heap = OwnedHeapAnalyzer::new(self.tcx);
         heap.run();
-        let adt_owner = heap.get_all_items();
 
         let mir_keys = self.tcx.mir_keys(());
         for local_def_id in mir_keys {
-            query_safedrop(
-                self.tcx,
-                &fn_map,
-                local_def_id.to_def_id(),
-                adt_owner.clone(),
-            );
+            let def_id = local_def_id.to_def_id();
+            guarded(self.name(), def_id, || {
+                query_safedrop(self.tcx, &fn_map, def_id, &mut heap)
+            });
+        }
+    }
+}
+
+/// Whether the ADTs dropped in the body own heap, evaluated once per type and variant through the
+/// instances cached by the heap analysis.
+fn drop_heaps<'tcx>(body: &Body<'tcx>, heap: &mut OwnedHeapAnalyzer<'tcx>) -> DropHeaps<'tcx> {
+    let tcx = heap.tcx();
+    let mut drop_heaps = DropHeaps::default();
+    for data in body.basic_blocks.iter() {
+        let TerminatorKind::Drop { place, .. } = data.terminator().kind else {
+            continue;
+        };
+        let place_ty = place.ty(&body.local_decls, tcx);
+        if !matches!(place_ty.ty.kind(), TyKind::Adt(..)) {
+            continue;
         }
+        drop_heaps
+            .entry((place_ty.ty, place_ty.variant_index))
+            .or_insert_with(|| {
+                heap.ty_heap_of_variant(place_ty.ty, place_ty.variant_index)
+                    .heap
+                    != OwnedHeap::False
+            });
     }
+    drop_heaps
 }
 
-pub fn query_safedrop(
-    tcx: TyCtxt,
+pub fn query_safedrop<'tcx>(
+    tcx: TyCtxt<'tcx>,
     fn_map: &MopAAResultMap,
     def_id: DefId,
-    adt_owner: OHAResultMap,
+    heap: &mut OwnedHeapAnalyzer<'tcx>,
 ) {
     /* filter const mir */
     if let Some(_other) = tcx.hir_body_const_context(def_id.expect_local()) {
@@ -59,7 +88,9 @@ pub fn query_safedrop(
     }
     if tcx.is_mir_available(def_id) {
         let body = tcx.optimized_mir(def_id);
-        let mut safedrop_graph = SafeDropGraph::new(body, tcx, def_id, adt_owner);
+        let drop_heaps = drop_heaps(body, heap);
+        let mut safedrop_graph = SafeDropGraph::new(body, tcx, def_id, drop_heaps);
+        safedrop_graph.panic_safety_check(body);
         safedrop_graph.solve_scc();
         safedrop_graph.check(0, tcx, fn_map);
         if safedrop_graph.visit_times <= VISIT_LIMIT {
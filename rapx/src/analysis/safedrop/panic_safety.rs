@@ -0,0 +1,418 @@
+use super::graph::*;
+use crate::analysis::{
+    core::dataflow::{default::DataFlowAnalyzer, graph::Graph, EdgeOp, NodeOp},
+    utils::{
+        def_path::{is_adt_method, is_ptr_method},
+        fn_info::operand_local,
+    },
+};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{BasicBlock, Body, Local, Operand, Terminator, TerminatorKind, UnwindAction},
+    ty::{self, GenericArgsRef, TypeVisitableExt},
+};
+use rustc_span::{sym, Span};
+use std::{collections::VecDeque, fmt};
+
+/// The invariant that unsafe code temporarily breaks before calling code it does not control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantKind {
+    /// `Vec::set_len` covers elements that are not initialized yet.
+    SetLen,
+    /// `ptr::read` duplicates the ownership of a value.
+    PtrRead,
+    /// A value is treated as initialized while it is not, e.g., `mem::uninitialized`.
+    Uninit,
+}
+
+impl fmt::Display for InvariantKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantKind::SetLen => write!(f, "length covers uninitialized elements"),
+            InvariantKind::PtrRead => write!(f, "ownership duplicated by `ptr::read`"),
+            InvariantKind::Uninit => write!(f, "uninitialized value treated as initialized"),
+        }
+    }
+}
+
+/// A "broken invariant -> panicking call -> restoration" triple.
+/// If the panicking call unwinds, the broken value is observed by drop code.
+#[derive(Debug, Clone)]
+pub struct PanicSafetyBug {
+    pub kind: InvariantKind,
+    pub broken: Span,
+    pub panicking: Span,
+    pub restored: Option<Span>,
+}
+
+struct InvariantBreak {
+    kind: InvariantKind,
+    block: BasicBlock,
+    span: Span,
+    dest: Local,
+    // `set_len(0)` makes the vector leak instead of exposing its elements, so it does not
+    // break the invariant but guards later `set_len` calls on the same vector.
+    shrinks: bool,
+    // locals owning the broken value; the value is observed during unwinding if they are
+    // reachable from the caller or dropped on the unwind path.
+    owners: FxHashSet<Local>,
+    // locals through which the invariant can be restored, e.g., the duplicate of `ptr::read`.
+    related: FxHashSet<Local>,
+}
+
+impl<'tcx> SafeDropGraph<'tcx> {
+    // Detect panic-safety bugs: unsafe code breaks an invariant and then calls generic or
+    // user-provided code that may panic before the invariant is restored.
+    pub fn panic_safety_check(&mut self, body: &Body<'tcx>) {
+        let candidates: Vec<(BasicBlock, InvariantKind)> = body
+            .basic_blocks
+            .iter_enumerated()
+            .filter(|(_, data)| !data.is_cleanup)
+            .filter_map(|(bb, data)| {
+                let (def_id, _) = callee(data.terminator())?;
+                self.invariant_break_kind(def_id).map(|kind| (bb, kind))
+            })
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+
+        // The dataflow graph is only built for functions that may break an invariant.
+        let mut dataflow = DataFlowAnalyzer::new(self.tcx, false);
+        dataflow.build_graph(self.def_id);
+        let graph = &dataflow.graphs[&self.def_id];
+
+        let breaks: Vec<InvariantBreak> = candidates
+            .into_iter()
+            .filter_map(|(bb, kind)| self.invariant_break(body, graph, bb, kind))
+            .collect();
+
+        for brk in breaks.iter() {
+            if brk.shrinks || self.restores_set_len(body, brk, &breaks) {
+                continue;
+            }
+            let Some(call_bb) = self.find_panicking_call(body, graph, brk) else {
+                continue;
+            };
+            let restored = normal_reachable(body, call_bb, |bb| {
+                self.is_restoration(body, graph, body[bb].terminator(), brk)
+            })
+            .map(|bb| body[bb].terminator().source_info.span);
+            let panicking = body[call_bb].terminator().source_info.span;
+            self.bug_records.panic_safety_bugs.push(PanicSafetyBug {
+                kind: brk.kind,
+                broken: brk.span,
+                panicking,
+                restored,
+            });
+        }
+    }
+
+    fn invariant_break_kind(&self, def_id: DefId) -> Option<InvariantKind> {
+        match self.tcx.get_diagnostic_name(def_id) {
+            Some(sym::ptr_read | sym::ptr_read_unaligned) => return Some(InvariantKind::PtrRead),
+            Some(sym::mem_uninitialized | sym::assume_init) => return Some(InvariantKind::Uninit),
+            _ => {}
+        }
+        if is_adt_method(self.tcx, def_id, self.vec_did(), &["set_len"]) {
+            Some(InvariantKind::SetLen)
+        } else if is_ptr_method(self.tcx, def_id, &["read", "read_unaligned"]) {
+            Some(InvariantKind::PtrRead)
+        } else {
+            None
+        }
+    }
+
+    fn vec_did(&self) -> Option<DefId> {
+        self.tcx.get_diagnostic_item(sym::Vec)
+    }
+
+    fn invariant_break(
+        &self,
+        body: &Body<'tcx>,
+        graph: &Graph,
+        bb: BasicBlock,
+        kind: InvariantKind,
+    ) -> Option<InvariantBreak> {
+        let terminator = body[bb].terminator();
+        let TerminatorKind::Call {
+            args, destination, ..
+        } = &terminator.kind
+        else {
+            return None;
+        };
+        let dest = destination.local;
+        let mut shrinks = false;
+        let (owners, related) = match kind {
+            InvariantKind::SetLen => {
+                if let Some(Operand::Constant(len)) = args.get(1).map(|arg| &arg.node) {
+                    shrinks = len
+                        .const_
+                        .try_to_scalar_int()
+                        .is_some_and(|len| len.is_null());
+                }
+                let owners = self.owner_locals(body, graph, operand_local(&args[0].node)?);
+                (owners.clone(), owners)
+            }
+            InvariantKind::PtrRead => {
+                let owners = self.owner_locals(body, graph, operand_local(&args[0].node)?);
+                let mut related = owners.clone();
+                related.insert(dest);
+                (owners, related)
+            }
+            InvariantKind::Uninit => {
+                // `assume_init` only breaks the invariant when it is applied to a fresh
+                // `MaybeUninit::uninit()` that has never been written through.
+                if let Some(arg) = args.first() {
+                    if !self.is_fresh_uninit(graph, operand_local(&arg.node)?) {
+                        return None;
+                    }
+                }
+                let mut owners: FxHashSet<Local> = graph
+                    .collect_descending_locals(dest, true)
+                    .into_iter()
+                    .filter(|local| !graph.is_marker(*local))
+                    .collect();
+                owners.insert(dest);
+                (owners.clone(), owners)
+            }
+        };
+        Some(InvariantBreak {
+            kind,
+            block: bb,
+            span: terminator.source_info.span,
+            dest,
+            shrinks,
+            owners,
+            related,
+        })
+    }
+
+    // The non-trivial locals that a value derives from, e.g., the vector behind `&mut (*v)`.
+    fn owner_locals(&self, body: &Body<'tcx>, graph: &Graph, local: Local) -> FxHashSet<Local> {
+        graph
+            .collect_ancestor_locals(local, true)
+            .into_iter()
+            .filter(|local| !graph.is_marker(*local))
+            .filter(|local| !body.local_decls[*local].ty.is_primitive())
+            .collect()
+    }
+
+    fn is_fresh_uninit(&self, graph: &Graph, local: Local) -> bool {
+        let equivalent = graph.collect_equivalent_locals(local, false);
+        let from_uninit = equivalent.iter().any(|local| {
+            graph.nodes[*local].ops.iter().any(|op| match op {
+                NodeOp::Call(def_id) => self
+                    .tcx
+                    .is_diagnostic_item(sym::maybe_uninit_uninit, *def_id),
+                _ => false,
+            })
+        });
+        let written = equivalent.iter().any(|local| {
+            graph.nodes[*local]
+                .out_edges
+                .iter()
+                .any(|edge| matches!(graph.edges[*edge].op, EdgeOp::Mut))
+        });
+        from_uninit && !written
+    }
+
+    // A `set_len` reached from another `set_len` on the same vector restores the length
+    // rather than breaking it, e.g., `v.set_len(0); ...; v.set_len(len)`.
+    fn restores_set_len(
+        &self,
+        body: &Body<'tcx>,
+        brk: &InvariantBreak,
+        breaks: &[InvariantBreak],
+    ) -> bool {
+        brk.kind == InvariantKind::SetLen
+            && breaks.iter().any(|other| {
+                other.kind == InvariantKind::SetLen
+                    && other.block != brk.block
+                    && !other.owners.is_disjoint(&brk.owners)
+                    && normal_reachable(body, other.block, |bb| bb == brk.block).is_some()
+            })
+    }
+
+    fn is_restoration(
+        &self,
+        body: &Body<'tcx>,
+        graph: &Graph,
+        terminator: &Terminator<'tcx>,
+        brk: &InvariantBreak,
+    ) -> bool {
+        let Some((def_id, _)) = callee(terminator) else {
+            return false;
+        };
+        let restores = match self.tcx.get_diagnostic_name(def_id) {
+            Some(sym::ptr_write | sym::ptr_write_unaligned | sym::mem_forget) => true,
+            _ => {
+                let write = ["write", "write_unaligned"];
+                (brk.kind == InvariantKind::SetLen
+                    && is_adt_method(self.tcx, def_id, self.vec_did(), &["set_len"]))
+                    || is_ptr_method(self.tcx, def_id, &write)
+                    || is_adt_method(
+                        self.tcx,
+                        def_id,
+                        self.tcx.lang_items().maybe_uninit(),
+                        &write,
+                    )
+            }
+        };
+        let TerminatorKind::Call { args, .. } = &terminator.kind else {
+            return false;
+        };
+        restores
+            && args
+                .first()
+                .and_then(|arg| operand_local(&arg.node))
+                .is_some_and(|local| {
+                    local == brk.dest
+                        || !self
+                            .owner_locals(body, graph, local)
+                            .is_disjoint(&brk.related)
+                })
+    }
+
+    // Search along the non-unwinding edges for generic or user-provided code that may panic
+    // while the invariant is broken and the broken value is observable during unwinding.
+    fn find_panicking_call(
+        &self,
+        body: &Body<'tcx>,
+        graph: &Graph,
+        brk: &InvariantBreak,
+    ) -> Option<BasicBlock> {
+        let mut visited = FxHashSet::default();
+        let mut worklist: VecDeque<BasicBlock> = normal_successors(body, brk.block).into();
+        while let Some(bb) = worklist.pop_front() {
+            if !visited.insert(bb) {
+                continue;
+            }
+            let terminator = body[bb].terminator();
+            if self.is_restoration(body, graph, terminator, brk) {
+                continue;
+            }
+            if let Some(unwind) = self.calls_user_code(body, terminator) {
+                if self.observed_on_unwind(unwind, &brk.owners, body.arg_count) {
+                    return Some(bb);
+                }
+            }
+            worklist.extend(normal_successors(body, bb));
+        }
+        None
+    }
+
+    // If the terminator may run generic or user-provided code that unwinds, return its unwind
+    // action. Such code includes trait methods on type parameters or trait objects, e.g.,
+    // `T::clone` or a closure called via `FnMut::call_mut`, calls through function pointers,
+    // and drops of generic values.
+    fn calls_user_code(
+        &self,
+        body: &Body<'tcx>,
+        terminator: &Terminator<'tcx>,
+    ) -> Option<UnwindAction> {
+        match &terminator.kind {
+            TerminatorKind::Call { func, unwind, .. } if may_unwind(unwind) => match func {
+                Operand::Constant(_) => {
+                    let (def_id, args) = callee(terminator)?;
+                    self.tcx.trait_of_assoc(def_id)?;
+                    let self_ty = args.types().next()?;
+                    (self_ty.has_param() || matches!(self_ty.kind(), ty::Dynamic(..)))
+                        .then_some(*unwind)
+                }
+                Operand::Copy(_) | Operand::Move(_) => Some(*unwind),
+            },
+            TerminatorKind::Drop { place, unwind, .. } if may_unwind(unwind) => {
+                let ty = place.ty(&body.local_decls, self.tcx).ty;
+                ty.has_param().then_some(*unwind)
+            }
+            _ => None,
+        }
+    }
+
+    // The broken value is observed during unwinding if it is reachable from the caller or
+    // dropped by the cleanup blocks of the unwind path.
+    fn observed_on_unwind(
+        &self,
+        unwind: UnwindAction,
+        owners: &FxHashSet<Local>,
+        arg_count: usize,
+    ) -> bool {
+        if owners
+            .iter()
+            .any(|local| (1..=arg_count).contains(&local.as_usize()))
+        {
+            return true;
+        }
+        let UnwindAction::Cleanup(cleanup) = unwind else {
+            return false;
+        };
+        let mut visited = FxHashSet::default();
+        let mut worklist = vec![cleanup.as_usize()];
+        while let Some(idx) = worklist.pop() {
+            if !visited.insert(idx) || !self.blocks[idx].is_cleanup {
+                continue;
+            }
+            let block = &self.blocks[idx];
+            for drop in block.drops.iter() {
+                let dropped = match &drop.kind {
+                    TerminatorKind::Drop { place, .. } => Some(place.local),
+                    TerminatorKind::Call { args, .. } => {
+                        args.first().and_then(|arg| operand_local(&arg.node))
+                    }
+                    _ => None,
+                };
+                if dropped.is_some_and(|local| owners.contains(&local)) {
+                    return true;
+                }
+            }
+            worklist.extend(block.next.iter().copied());
+        }
+        false
+    }
+}
+
+fn callee<'tcx>(terminator: &Terminator<'tcx>) -> Option<(DefId, GenericArgsRef<'tcx>)> {
+    if let TerminatorKind::Call {
+        func: Operand::Constant(func),
+        ..
+    } = &terminator.kind
+    {
+        if let ty::FnDef(def_id, args) = func.ty().kind() {
+            return Some((*def_id, args));
+        }
+    }
+    None
+}
+
+fn may_unwind(unwind: &UnwindAction) -> bool {
+    matches!(unwind, UnwindAction::Cleanup(_) | UnwindAction::Continue)
+}
+
+fn normal_successors(body: &Body<'_>, bb: BasicBlock) -> Vec<BasicBlock> {
+    body[bb]
+        .terminator()
+        .successors()
+        .filter(|succ| !body[*succ].is_cleanup)
+        .collect()
+}
+
+fn normal_reachable(
+    body: &Body<'_>,
+    from: BasicBlock,
+    mut found: impl FnMut(BasicBlock) -> bool,
+) -> Option<BasicBlock> {
+    let mut visited = FxHashSet::default();
+    let mut worklist: VecDeque<BasicBlock> = normal_successors(body, from).into();
+    while let Some(bb) = worklist.pop_front() {
+        if !visited.insert(bb) {
+            continue;
+        }
+        if found(bb) {
+            return Some(bb);
+        }
+        worklist.extend(normal_successors(body, bb));
+    }
+    None
+}
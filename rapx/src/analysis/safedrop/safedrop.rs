@@ -1,4 +1,9 @@
-use crate::analysis::{core::alias_analysis::default::MopAAResultMap, safedrop::SafeDropGraph};
+use crate::analysis::{
+    core::{
+        alias_analysis::default::MopAAResultMap, range_analysis::feasibility::is_infeasible_branch,
+    },
+    safedrop::SafeDropGraph,
+};
 use crate::rap_error;
 use rustc_data_structures::fx::FxHashSet;
 use rustc_middle::{
@@ -60,25 +65,21 @@ impl<'tcx> SafeDropGraph<'tcx> {
     pub fn drop_heap_item_check(&self, place: &Place<'tcx>, tcx: TyCtxt<'tcx>) -> bool {
         let place_ty = place.ty(&tcx.optimized_mir(self.def_id).local_decls, tcx);
         match place_ty.ty.kind() {
-            TyKind::Adt(adtdef, ..) => match self.adt_owner.get(&adtdef.did()) {
-                None => true,
-                Some(owenr_unit) => {
-                    let idx = match place_ty.variant_index {
-                        Some(vdx) => vdx.index(),
-                        None => 0,
-                    };
-                    if owenr_unit[idx].0.is_onheap() || owenr_unit[idx].1.contains(&true) {
-                        true
-                    } else {
-                        false
-                    }
-                }
-            },
+            // the generic arguments known in the function decide whether the ADT owns heap.
+            TyKind::Adt(..) => self
+                .drop_heaps
+                .get(&(place_ty.ty, place_ty.variant_index))
+                .copied()
+                .unwrap_or(true),
             _ => true,
         }
     }
 
     pub fn split_check(&mut self, bb_index: usize, tcx: TyCtxt<'tcx>, fn_map: &MopAAResultMap) {
+        if is_infeasible_branch(self.path_checker.as_ref(), &mut self.path, bb_index) {
+            return;
+        }
+        let path_len = self.path.len();
         /* duplicate the status before visiting a path; */
         let backup_values = self.values.clone(); // duplicate the status when visiting different paths;
         let backup_constant = self.constant.clone();
@@ -86,6 +87,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         let backup_dead = self.dead_record.clone();
         self.check(bb_index, tcx, fn_map);
         /* restore after visit */
+        self.path.truncate(path_len);
         self.values = backup_values;
         self.constant = backup_constant;
         self.alias_set = backup_alias_set;
@@ -100,6 +102,10 @@ impl<'tcx> SafeDropGraph<'tcx> {
         tcx: TyCtxt<'tcx>,
         fn_map: &MopAAResultMap,
     ) {
+        if is_infeasible_branch(self.path_checker.as_ref(), &mut self.path, bb_index) {
+            return;
+        }
+        let path_len = self.path.len();
         /* duplicate the status before visiting a path; */
         let backup_values = self.values.clone(); // duplicate the status when visiting different paths;
         let backup_constant = self.constant.clone();
@@ -109,6 +115,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         self.constant.insert(path_discr_id, path_discr_val);
         self.check(bb_index, tcx, fn_map);
         /* restore after visit */
+        self.path.truncate(path_len);
         self.values = backup_values;
         self.constant = backup_constant;
         self.alias_set = backup_alias_set;
@@ -121,6 +128,8 @@ impl<'tcx> SafeDropGraph<'tcx> {
         if self.visit_times > VISIT_LIMIT {
             return;
         }
+        self.path.push(bb_index);
+        let path_len = self.path.len();
         let cur_block = self.blocks[self.scc_indices[bb_index]].clone();
         self.alias_bb(self.scc_indices[bb_index], tcx);
         self.alias_bbcall(self.scc_indices[bb_index], tcx, fn_map);
@@ -132,6 +141,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 self.child_scc.get(&init_index).unwrap().clone();
 
             for enum_index in cur_targets.all_targets() {
+                self.path.truncate(path_len);
                 let backup_values = self.values.clone();
                 let backup_constant = self.constant.clone();
 
@@ -241,6 +251,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                         // check the bugs.
                         if Self::should_check(self.def_id) {
                             self.dp_check(&cur_block);
+                            self.cancel_check(bb_index);
                         }
                         return;
                     }
@@ -250,6 +261,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                          * We cannot use [0] for FxHashSet.
                          */
                         for next in block_node.next {
+                            self.path.truncate(path_len);
                             self.check(next, tcx, fn_map);
                         }
                     }
@@ -289,6 +301,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
         let backup_constant = self.constant.clone();
         let backup_alias_set = self.alias_set.clone();
         for scc_each in order {
+            self.path.truncate(path_len);
             self.alias_set = backup_alias_set.clone();
             self.values = backup_values.clone();
             self.constant = backup_constant.clone();
@@ -306,6 +319,7 @@ impl<'tcx> SafeDropGraph<'tcx> {
                 0 => {
                     if Self::should_check(self.def_id) {
                         self.dp_check(&cur_block);
+                        self.cancel_check(bb_index);
                     }
                     return;
                 }
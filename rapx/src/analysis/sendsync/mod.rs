@@ -0,0 +1,489 @@
+pub mod usage;
+
+use rustc_hir::{def::DefKind, def_id::DefId, Safety};
+use rustc_infer::infer::TyCtxtInferExt;
+use rustc_middle::{
+    mir::{Operand, TerminatorKind},
+    ty::{self, GenericArgs, ImplPolarity, ParamEnv, Ty, TyCtxt, TyKind, TypingMode},
+};
+use rustc_span::sym;
+use rustc_trait_selection::infer::InferCtxtExt;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use crate::{
+    analysis::senryx::generic_check::GenericChecker,
+    utils::{
+        boundary::guarded,
+        log::{report_fn_bugs, BugReport},
+    },
+};
+use usage::{ParamUsage, ParamUsageCollector};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ThreadSafeTrait {
+    Send,
+    Sync,
+}
+
+impl Display for ThreadSafeTrait {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThreadSafeTrait::Send => write!(f, "Send"),
+            ThreadSafeTrait::Sync => write!(f, "Sync"),
+        }
+    }
+}
+
+/// The reason why an `unsafe impl Send/Sync` is considered unsound.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SendSyncIssue {
+    /// A type parameter lacks a bound required by how it is held or exposed.
+    MissingBound {
+        param: String,
+        bound: ThreadSafeTrait,
+        cause: String,
+    },
+    /// A field has a concrete type that can never be safely sent or shared, e.g., `Rc<_>`.
+    NonThreadSafeField { field: String, ty: String },
+    /// A raw pointer stored in the type may point to thread-local data.
+    ThreadLocalPtr { method: String },
+}
+
+impl Display for SendSyncIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SendSyncIssue::MissingBound {
+                param,
+                bound,
+                cause,
+            } => write!(f, "Missing bound `{}: {}`: {}.", param, bound, cause),
+            SendSyncIssue::NonThreadSafeField { field, ty } => {
+                write!(f, "Field `{}` has a non-thread-safe type `{}`.", field, ty)
+            }
+            SendSyncIssue::ThreadLocalPtr { method } => write!(
+                f,
+                "Raw pointer may refer to thread-local data obtained in `{}`.",
+                method
+            ),
+        }
+    }
+}
+
+pub struct SendSyncBug {
+    pub impl_id: DefId,
+    pub trait_kind: ThreadSafeTrait,
+    pub self_ty: String,
+    pub issues: Vec<SendSyncIssue>,
+}
+
+/// This detector finds `unsafe impl Send/Sync` on generic types whose bounds are missing or too
+/// weak for how the type parameters are held by the fields or exposed by the methods.
+pub struct SendSyncCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub bugs: Vec<SendSyncBug>,
+}
+
+impl<'tcx> SendSyncCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            bugs: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "SendSync Check"
+    }
+
+    pub fn start(&mut self) {
+        let tcx = self.tcx;
+        let send_did = tcx.get_diagnostic_item(sym::Send);
+        let sync_did = tcx.get_diagnostic_item(sym::Sync);
+        let (Some(send_did), Some(sync_did)) = (send_did, sync_did) else {
+            return;
+        };
+
+        // Collect the local impls of each ADT, which provide the APIs of the type.
+        let mut adt_impls: HashMap<DefId, Vec<DefId>> = HashMap::new();
+        let mut unsafe_impls = Vec::new();
+        for local_def_id in tcx.hir_crate_items(()).definitions() {
+            let impl_id = local_def_id.to_def_id();
+            if !matches!(tcx.def_kind(impl_id), DefKind::Impl { .. }) {
+                continue;
+            }
+            let self_ty = tcx.type_of(impl_id).instantiate_identity();
+            let TyKind::Adt(adt_def, _) = self_ty.kind() else {
+                continue;
+            };
+            match tcx.impl_trait_header(impl_id) {
+                Some(header) => {
+                    let trait_did = header.trait_ref.skip_binder().def_id;
+                    if trait_did == send_did || trait_did == sync_did {
+                        if header.safety == Safety::Unsafe
+                            && header.polarity == ImplPolarity::Positive
+                        {
+                            let trait_kind = if trait_did == send_did {
+                                ThreadSafeTrait::Send
+                            } else {
+                                ThreadSafeTrait::Sync
+                            };
+                            unsafe_impls.push((impl_id, adt_def.did(), trait_kind));
+                        }
+                        continue;
+                    }
+                    adt_impls.entry(adt_def.did()).or_default().push(impl_id);
+                }
+                None => adt_impls.entry(adt_def.did()).or_default().push(impl_id),
+            }
+        }
+
+        for (impl_id, adt_did, trait_kind) in unsafe_impls {
+            let impls = adt_impls.get(&adt_did).cloned().unwrap_or_default();
+            let Some(issues) = guarded(self.name(), impl_id, || {
+                self.check_impl(impl_id, adt_did, trait_kind, &impls)
+            }) else {
+                continue;
+            };
+            if !issues.is_empty() {
+                self.bugs.push(SendSyncBug {
+                    impl_id,
+                    trait_kind,
+                    self_ty: format!("{}", tcx.type_of(impl_id).instantiate_identity()),
+                    issues,
+                });
+            }
+        }
+        self.report_bugs();
+    }
+
+    fn check_impl(
+        &self,
+        impl_id: DefId,
+        adt_did: DefId,
+        trait_kind: ThreadSafeTrait,
+        impls: &[DefId],
+    ) -> Vec<SendSyncIssue> {
+        let tcx = self.tcx;
+        let mut issues = Vec::new();
+
+        // requirement: adt param index -> (required bound -> cause)
+        let mut requirements: HashMap<u32, Vec<(ThreadSafeTrait, String)>> = HashMap::new();
+        let adt_def = tcx.adt_def(adt_did);
+        let identity_args = GenericArgs::identity_for_item(tcx, adt_did);
+        for field in adt_def.all_fields() {
+            let field_ty = field.ty(tcx, identity_args);
+            if let Some(ty_name) = self.non_thread_safe_ty(field_ty, trait_kind) {
+                issues.push(SendSyncIssue::NonThreadSafeField {
+                    field: field.name.to_string(),
+                    ty: ty_name,
+                });
+            }
+            for (index, usages) in ParamUsageCollector::collect(tcx, field_ty) {
+                for usage in usages {
+                    let cause = format!("field `{}` holds it as `{}`", field.name, field_ty);
+                    match trait_kind {
+                        ThreadSafeTrait::Send => {
+                            let (send, sync) = usage.send_requirement();
+                            if send {
+                                requirements
+                                    .entry(index)
+                                    .or_default()
+                                    .push((ThreadSafeTrait::Send, cause.clone()));
+                            }
+                            if sync {
+                                requirements
+                                    .entry(index)
+                                    .or_default()
+                                    .push((ThreadSafeTrait::Sync, cause));
+                            }
+                        }
+                        // Sharing `&Foo<T>` shares `&&T` or `&Arc<T>` in the fields.
+                        ThreadSafeTrait::Sync => match usage {
+                            ParamUsage::SharedRef => requirements
+                                .entry(index)
+                                .or_default()
+                                .push((ThreadSafeTrait::Sync, cause)),
+                            ParamUsage::Shared => {
+                                let entry = requirements.entry(index).or_default();
+                                entry.push((ThreadSafeTrait::Send, cause.clone()));
+                                entry.push((ThreadSafeTrait::Sync, cause));
+                            }
+                            _ => {}
+                        },
+                    }
+                }
+            }
+        }
+
+        if trait_kind == ThreadSafeTrait::Sync {
+            for (index, bound, cause) in self.api_requirements(adt_did, impls) {
+                requirements.entry(index).or_default().push((bound, cause));
+            }
+        }
+
+        if trait_kind == ThreadSafeTrait::Send && self.has_raw_ptr_field(adt_did) {
+            for method in self.thread_local_accessors(impls) {
+                issues.push(SendSyncIssue::ThreadLocalPtr { method });
+            }
+        }
+
+        // Check the requirements against the bounds of the `unsafe impl`.
+        let param_env = tcx.param_env(impl_id);
+        let generic_checker = GenericChecker::new(tcx, param_env);
+        let impl_self_ty = tcx.type_of(impl_id).instantiate_identity();
+        let TyKind::Adt(_, impl_args) = impl_self_ty.kind() else {
+            return issues;
+        };
+        let mut indices: Vec<_> = requirements.keys().copied().collect();
+        indices.sort();
+        for index in indices {
+            let Some(arg_ty) = impl_args.get(index as usize).and_then(|arg| arg.as_type()) else {
+                continue;
+            };
+            let mut checked = HashSet::new();
+            for (bound, cause) in &requirements[&index] {
+                if !checked.insert(*bound) {
+                    continue;
+                }
+                let bound_did = match bound {
+                    ThreadSafeTrait::Send => tcx.get_diagnostic_item(sym::Send),
+                    ThreadSafeTrait::Sync => tcx.get_diagnostic_item(sym::Sync),
+                };
+                let Some(bound_did) = bound_did else {
+                    continue;
+                };
+                let satisfied = match arg_ty.kind() {
+                    TyKind::Param(param_ty) => generic_checker
+                        .has_trait_bnd(param_ty.name.as_str(), &tcx.def_path_str(bound_did)),
+                    _ => self.implements_trait(arg_ty, bound_did, param_env),
+                };
+                if !satisfied {
+                    issues.push(SendSyncIssue::MissingBound {
+                        param: format!("{}", arg_ty),
+                        bound: *bound,
+                        cause: cause.clone(),
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    // For a `Sync` type, the methods taking `&self` can be called from multiple threads
+    // concurrently. Returning `&T` requires `T: Sync`, while moving `T` in or out requires
+    // `T: Send`.
+    fn api_requirements(
+        &self,
+        adt_did: DefId,
+        impls: &[DefId],
+    ) -> Vec<(u32, ThreadSafeTrait, String)> {
+        let tcx = self.tcx;
+        let mut res = Vec::new();
+        for impl_id in impls {
+            // Map the generic parameters of the impl to those of the ADT.
+            let impl_self_ty = tcx.type_of(*impl_id).instantiate_identity();
+            let TyKind::Adt(_, impl_args) = impl_self_ty.kind() else {
+                continue;
+            };
+            let mut param_map: HashMap<u32, u32> = HashMap::new();
+            for (index, arg) in impl_args.iter().enumerate() {
+                if let Some(arg_ty) = arg.as_type() {
+                    if let TyKind::Param(param_ty) = arg_ty.kind() {
+                        param_map.insert(param_ty.index, index as u32);
+                    }
+                }
+            }
+
+            for item in tcx.associated_items(*impl_id).in_definition_order() {
+                if !item.is_method() {
+                    continue;
+                }
+                let fn_sig = tcx.fn_sig(item.def_id).instantiate_identity().skip_binder();
+                let Some(self_arg) = fn_sig.inputs().first() else {
+                    continue;
+                };
+                if !self.is_shared_self(*self_arg, adt_did) {
+                    continue;
+                }
+                let method = tcx.def_path_str(item.def_id);
+                let output = ParamUsageCollector::collect(tcx, fn_sig.output());
+                for (index, usages) in output {
+                    let Some(adt_index) = param_map.get(&index) else {
+                        continue;
+                    };
+                    for usage in usages {
+                        let (bound, cause) = match usage {
+                            ParamUsage::SharedRef => (
+                                ThreadSafeTrait::Sync,
+                                format!("`{}` returns a shared reference from `&self`", method),
+                            ),
+                            _ => (
+                                ThreadSafeTrait::Send,
+                                format!("`{}` moves the value out from `&self`", method),
+                            ),
+                        };
+                        res.push((*adt_index, bound, cause));
+                    }
+                }
+                for input in fn_sig.inputs().iter().skip(1) {
+                    for (index, usages) in ParamUsageCollector::collect(tcx, *input) {
+                        let Some(adt_index) = param_map.get(&index) else {
+                            continue;
+                        };
+                        if usages.iter().any(|usage| *usage != ParamUsage::SharedRef) {
+                            res.push((
+                                *adt_index,
+                                ThreadSafeTrait::Send,
+                                format!("`{}` moves the value in through `&self`", method),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    fn is_shared_self(&self, ty: Ty<'tcx>, adt_did: DefId) -> bool {
+        if let TyKind::Ref(_, inner, mutbl) = ty.kind() {
+            if let TyKind::Adt(adt_def, _) = inner.kind() {
+                return !mutbl.is_mut() && adt_def.did() == adt_did;
+            }
+        }
+        false
+    }
+
+    // Returns the type name if the type contains a component that is never Send (or Sync), no
+    // matter how its type parameters are instantiated.
+    fn non_thread_safe_ty(&self, ty: Ty<'tcx>, trait_kind: ThreadSafeTrait) -> Option<String> {
+        let tcx = self.tcx;
+        let non_send = [
+            sym::Rc,
+            sym::RcWeak,
+            sym::MutexGuard,
+            sym::RwLockReadGuard,
+            sym::RwLockWriteGuard,
+        ];
+        let non_sync = [sym::Rc, sym::RcWeak, sym::Cell, sym::RefCell];
+        let names: &[_] = match trait_kind {
+            ThreadSafeTrait::Send => &non_send,
+            ThreadSafeTrait::Sync => &non_sync,
+        };
+        held_tys(ty).into_iter().find_map(|held| match held.kind() {
+            TyKind::Adt(adt_def, _)
+                if names
+                    .iter()
+                    .any(|name| tcx.is_diagnostic_item(*name, adt_def.did())) =>
+            {
+                Some(format!("{}", held))
+            }
+            _ => None,
+        })
+    }
+
+    fn has_raw_ptr_field(&self, adt_did: DefId) -> bool {
+        let tcx = self.tcx;
+        let identity_args = GenericArgs::identity_for_item(tcx, adt_did);
+        tcx.adt_def(adt_did).all_fields().any(|field| {
+            held_tys(field.ty(tcx, identity_args))
+                .iter()
+                .any(|ty| ty.is_raw_ptr())
+        })
+    }
+
+    // Returns the methods of the type that access thread-local storage via `LocalKey`.
+    fn thread_local_accessors(&self, impls: &[DefId]) -> Vec<String> {
+        let tcx = self.tcx;
+        let Some(local_key) = tcx.get_diagnostic_item(sym::LocalKey) else {
+            return Vec::new();
+        };
+        let mut res = Vec::new();
+        for impl_id in impls {
+            for item in tcx.associated_items(*impl_id).in_definition_order() {
+                if !item.is_fn() || !tcx.is_mir_available(item.def_id) {
+                    continue;
+                }
+                let body = tcx.optimized_mir(item.def_id);
+                let calls_local_key = body.basic_blocks.iter().any(|bb| {
+                    if let TerminatorKind::Call {
+                        func: Operand::Constant(c),
+                        ..
+                    } = &bb.terminator().kind
+                    {
+                        if let ty::FnDef(callee, _) = c.ty().kind() {
+                            return tcx
+                                .impl_of_assoc(*callee)
+                                .and_then(|impl_did| {
+                                    tcx.type_of(impl_did).skip_binder().ty_adt_def()
+                                })
+                                .is_some_and(|adt_def| adt_def.did() == local_key);
+                        }
+                    }
+                    false
+                });
+                if calls_local_key {
+                    res.push(tcx.def_path_str(item.def_id));
+                }
+            }
+        }
+        res
+    }
+
+    fn implements_trait(&self, ty: Ty<'tcx>, trait_did: DefId, param_env: ParamEnv<'tcx>) -> bool {
+        let infcx = self.tcx.infer_ctxt().build(TypingMode::non_body_analysis());
+        infcx
+            .type_implements_trait(trait_did, [ty], param_env)
+            .must_apply_modulo_regions()
+    }
+
+    pub fn report_bugs(&self) {
+        for bug in &self.bugs {
+            let span = self.tcx.def_span(bug.impl_id);
+            let reports: Vec<BugReport> = bug
+                .issues
+                .iter()
+                .map(|issue| BugReport {
+                    title: format!("Unsound `unsafe impl {}`.", bug.trait_kind),
+                    span,
+                    label: issue.to_string(),
+                    notes: Vec::new(),
+                    help: match issue {
+                        SendSyncIssue::MissingBound { param, bound, .. } => {
+                            Some(format!("Add the bound `{}: {}` to the impl.", param, bound))
+                        }
+                        _ => None,
+                    },
+                })
+                .collect();
+            report_fn_bugs(
+                span,
+                &format!(
+                    "Unsound {} implementation detected for type {}.",
+                    bug.trait_kind, bug.self_ty
+                ),
+                &reports,
+            );
+        }
+    }
+}
+
+// The types a value of the type holds, i.e., without the types only named by a `PhantomData` or in
+// the signature of a function pointer or item.
+fn held_tys(ty: Ty<'_>) -> Vec<Ty<'_>> {
+    let mut walker = ty.walk();
+    let mut res = Vec::new();
+    while let Some(arg) = walker.next() {
+        let Some(ty) = arg.as_type() else {
+            continue;
+        };
+        match ty.kind() {
+            TyKind::FnPtr(..) | TyKind::FnDef(..) => walker.skip_current_subtree(),
+            TyKind::Adt(adt_def, _) if adt_def.is_phantom_data() => walker.skip_current_subtree(),
+            _ => res.push(ty),
+        }
+    }
+    res
+}
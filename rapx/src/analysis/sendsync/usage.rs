@@ -0,0 +1,117 @@
+use rustc_middle::ty::{Ty, TyCtxt, TyKind, TypeSuperVisitable, TypeVisitable, TypeVisitor};
+use rustc_span::sym;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::ControlFlow,
+};
+
+/// How a type parameter is held inside a type.
+/// The variants are ordered by the strength of the thread-safety requirement they imply, so that
+/// a nested usage takes the stronger one, e.g., `&Arc<T>` is treated as `Shared`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ParamUsage {
+    /// The parameter is held by value, e.g., `T`, `Box<T>`, `*mut T` or `PhantomData<T>`.
+    Owned,
+    /// The parameter is held behind a mutable reference, e.g., `&mut T`.
+    MutRef,
+    /// The parameter is held behind a shared reference, e.g., `&T`.
+    SharedRef,
+    /// The parameter is shared among multiple owners, e.g., `Arc<T>` or `Rc<T>`.
+    Shared,
+}
+
+impl ParamUsage {
+    /// Bounds required on `T` when a value holding `T` in this way is sent to another thread.
+    pub fn send_requirement(&self) -> (bool, bool) {
+        // (requires T: Send, requires T: Sync)
+        match self {
+            ParamUsage::Owned | ParamUsage::MutRef => (true, false),
+            ParamUsage::SharedRef => (false, true),
+            ParamUsage::Shared => (true, true),
+        }
+    }
+}
+
+// This visitor collects the usage of each type parameter that appears in a type.
+// The result is indexed by `ParamTy::index`.
+//
+// Given a struct Example<A, B, T, S>:
+//
+// struct Example<'a, A, B, T, S> {
+//     a: Box<A>,
+//     b: &'a B,
+//     c: Arc<T>,
+//     d: PhantomData<fn() -> S>,
+// }
+//
+// the result for <A, B, T, S> is <{Owned}, {SharedRef}, {Shared}, {}>.
+pub struct ParamUsageCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    record: HashMap<u32, HashSet<ParamUsage>>,
+    context: ParamUsage,
+}
+
+impl<'tcx> ParamUsageCollector<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            record: HashMap::new(),
+            context: ParamUsage::Owned,
+        }
+    }
+
+    pub fn collect(tcx: TyCtxt<'tcx>, ty: Ty<'tcx>) -> HashMap<u32, HashSet<ParamUsage>> {
+        let mut collector = Self::new(tcx);
+        let _ = ty.visit_with(&mut collector);
+        collector.record
+    }
+
+    fn visit_in_context(&mut self, ty: Ty<'tcx>, usage: ParamUsage) -> ControlFlow<()> {
+        let prev = self.context;
+        self.context = prev.max(usage);
+        let res = ty.super_visit_with(self);
+        self.context = prev;
+        res
+    }
+
+    fn is_shared_owner(&self, ty: Ty<'tcx>) -> bool {
+        if let TyKind::Adt(adt_def, _) = ty.kind() {
+            let did = adt_def.did();
+            return [sym::Arc, sym::ArcWeak, sym::Rc, sym::RcWeak]
+                .iter()
+                .any(|name| self.tcx.is_diagnostic_item(*name, did));
+        }
+        false
+    }
+}
+
+impl<'tcx> TypeVisitor<TyCtxt<'tcx>> for ParamUsageCollector<'tcx> {
+    type Result = ControlFlow<()>;
+
+    fn visit_ty(&mut self, ty: Ty<'tcx>) -> Self::Result {
+        match ty.kind() {
+            TyKind::Param(param_ty) => {
+                self.record
+                    .entry(param_ty.index)
+                    .or_default()
+                    .insert(self.context);
+                ControlFlow::Continue(())
+            }
+            TyKind::Ref(_, _, mutbl) => {
+                let usage = if mutbl.is_mut() {
+                    ParamUsage::MutRef
+                } else {
+                    ParamUsage::SharedRef
+                };
+                self.visit_in_context(ty, usage)
+            }
+            TyKind::Adt(..) if self.is_shared_owner(ty) => {
+                self.visit_in_context(ty, ParamUsage::Shared)
+            }
+            // Function pointers and function items do not hold any value of their parameters,
+            // e.g., `PhantomData<fn() -> T>` is always `Send` and `Sync`.
+            TyKind::FnPtr(..) | TyKind::FnDef(..) => ControlFlow::Continue(()),
+            _ => ty.super_visit_with(self),
+        }
+    }
+}
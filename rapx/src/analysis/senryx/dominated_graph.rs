@@ -1,5 +1,6 @@
 use crate::{
     analysis::{
+        core::alias_analysis::DEREF_FIELD,
         senryx::contracts::{
             contract,
             property::{CisRangeItem, ContractualInvariantState, PropertyContract},
@@ -80,7 +81,9 @@ impl<'tcx> InterResultNode<'tcx> {
     }
 
     pub fn construct_from_var_node(chain: DominatedGraph<'tcx>, var_id: usize) -> Self {
-        let var_node = chain.get_var_node(var_id).unwrap();
+        let Some(var_node) = chain.get_var_node(var_id) else {
+            return Self::new_default(None);
+        };
         let point_node = if var_node.points_to.is_none() {
             None
         } else {
@@ -234,9 +237,13 @@ impl<'tcx> DominatedGraph<'tcx> {
     }
 
     pub fn init_self_with_inter(&mut self, inter_result: InterResultNode<'tcx>) {
-        let self_node = self.get_var_node(1).unwrap().clone();
-        if self_node.ty.unwrap().is_ref() {
-            let obj_node = self.get_var_node(self.get_point_to_id(1)).unwrap();
+        let Some(self_node) = self.get_var_node(1).cloned() else {
+            return;
+        };
+        if self_node.ty.is_some_and(|ty| ty.is_ref()) {
+            let Some(obj_node) = self.get_var_node(self.get_point_to_id(1)) else {
+                return;
+            };
             self.dfs_insert_inter_results(inter_result, obj_node.id);
         } else {
             self.dfs_insert_inter_results(inter_result, self_node.id);
@@ -245,7 +252,9 @@ impl<'tcx> DominatedGraph<'tcx> {
 
     pub fn dfs_insert_inter_results(&mut self, inter_result: InterResultNode<'tcx>, local: usize) {
         let new_id = self.generate_node_id();
-        let node = self.get_var_node_mut(local).unwrap();
+        let Some(node) = self.get_var_node_mut(local) else {
+            return;
+        };
         // node.ty = inter_result.ty;
         node.ots = inter_result.states;
         node.const_value = inter_result.const_value;
@@ -300,16 +309,20 @@ impl<'tcx> DominatedGraph<'tcx> {
     }
 
     fn insert_cis_for_arg(&mut self, local: usize, contract: PropertyContract<'tcx>) {
-        let node = self.get_var_node_mut(local).unwrap();
-        node.cis.add_contract(contract);
+        if let Some(node) = self.get_var_node_mut(local) {
+            node.cis.add_contract(contract);
+        }
     }
 
     /// When generate obj node, this function will add InBound Sp automatically.
     pub fn generate_ptr_with_obj_node(&mut self, local_ty: Ty<'tcx>, idx: usize) -> usize {
         let new_id = self.generate_node_id();
+        let Some(node) = self.get_var_node_mut(idx) else {
+            return idx;
+        };
         if is_ptr(local_ty) {
             // modify ptr node pointed
-            self.get_var_node_mut(idx).unwrap().points_to = Some(new_id);
+            node.points_to = Some(new_id);
             // insert pointed object node
             self.insert_node(
                 new_id,
@@ -321,7 +334,7 @@ impl<'tcx> DominatedGraph<'tcx> {
             self.add_bound_for_obj(new_id, local_ty);
         } else if is_ref(local_ty) {
             // modify ptr node pointed
-            self.get_var_node_mut(idx).unwrap().points_to = Some(new_id);
+            node.points_to = Some(new_id);
             // insert ref object node
             self.insert_node(
                 new_id,
@@ -336,7 +349,9 @@ impl<'tcx> DominatedGraph<'tcx> {
     }
 
     fn add_bound_for_obj(&mut self, new_id: usize, local_ty: Ty<'tcx>) {
-        let new_node = self.get_var_node_mut(new_id).unwrap();
+        let Some(new_node) = self.get_var_node_mut(new_id) else {
+            return;
+        };
         let new_node_ty = get_pointee(local_ty);
         let contract = if is_slice(new_node_ty).is_some() {
             let inner_ty = is_slice(new_node_ty).unwrap();
@@ -349,10 +364,10 @@ impl<'tcx> DominatedGraph<'tcx> {
 
     // if current node is ptr or ref, then return the new node pointed by it.
     pub fn check_ptr(&mut self, arg: usize) -> usize {
-        if self.get_var_node_mut(arg).unwrap().ty.is_none() {
+        let Some(node_ty) = self.get_var_node(arg).and_then(|node| node.ty) else {
             display_hashmap(&self.variables, 1);
+            return arg;
         };
-        let node_ty = self.get_var_node_mut(arg).unwrap().ty.unwrap();
         if is_ptr(node_ty) || is_ref(node_ty) {
             return self.generate_ptr_with_obj_node(node_ty, arg);
         }
@@ -363,15 +378,15 @@ impl<'tcx> DominatedGraph<'tcx> {
         let body = self.tcx.optimized_mir(self.def_id);
         let locals = body.local_decls.clone();
         if arg < locals.len() {
-            return Some(locals[Local::from(arg)].ty);
+            Some(locals[Local::from(arg)].ty)
         } else {
             // If the arg is a field of some place, we search the whole map for it.
-            return self.get_var_node(arg).unwrap().ty;
+            self.get_var_node(arg)?.ty
         }
     }
 
     pub fn get_obj_ty_through_chain(&self, arg: usize) -> Option<Ty<'tcx>> {
-        let var = self.get_var_node(arg).unwrap();
+        let var = self.get_var_node(arg)?;
         // If the var is ptr or ref, then find its pointed obj.
         if let Some(pointed_idx) = var.points_to {
             // let pointed_var = self.get_var_node(pointed_idx).unwrap();
@@ -385,11 +400,9 @@ impl<'tcx> DominatedGraph<'tcx> {
     pub fn get_point_to_id(&self, arg: usize) -> usize {
         // display_hashmap(&self.variables,1);
         // println!("{:?}",self.def_id);
-        let var = self.get_var_node(arg).unwrap();
-        if let Some(pointed_idx) = var.points_to {
-            pointed_idx
-        } else {
-            arg
+        match self.get_var_node(arg).and_then(|var| var.points_to) {
+            Some(pointed_idx) => pointed_idx,
+            None => arg,
         }
     }
 
@@ -415,8 +428,10 @@ impl<'tcx> DominatedGraph<'tcx> {
         field_idx: usize,
         ty: Option<Ty<'tcx>>,
     ) -> usize {
-        let node = self.get_var_node(local).unwrap();
-        if let Some(alias_local) = node.field.get(&field_idx) {
+        let field = self
+            .get_var_node(local)
+            .and_then(|node| node.field.get(&field_idx));
+        if let Some(alias_local) = field {
             *alias_local
         } else {
             self.insert_field_node(local, field_idx, ty)
@@ -433,18 +448,29 @@ impl<'tcx> DominatedGraph<'tcx> {
         let new_id = self.generate_node_id();
         self.variables
             .insert(new_id, VariableNode::new_default(new_id, ty));
-        let mut_node = self.get_var_node_mut(local).unwrap();
-        mut_node.field.insert(field_idx, new_id);
+        if let Some(mut_node) = self.get_var_node_mut(local) {
+            mut_node.field.insert(field_idx, new_id);
+        }
         return new_id;
     }
 
     pub fn find_var_id_with_fields_seq(&mut self, local: usize, fields: Vec<usize>) -> usize {
         let mut cur = local;
         for field in fields.clone() {
-            let mut cur_node = self.get_var_node(cur).unwrap();
-            if let TyKind::Ref(_, ty, _) = cur_node.ty.unwrap().kind() {
+            // The pointee of a multi-level pointer is the node it points to.
+            if field == DEREF_FIELD {
+                cur = self.get_point_to_id(cur);
+                continue;
+            }
+            let Some(mut cur_node) = self.get_var_node(cur) else {
+                return cur;
+            };
+            if let Some(TyKind::Ref(..)) = cur_node.ty.map(|ty| ty.kind()) {
                 let point_to = self.get_point_to_id(cur);
-                cur_node = self.get_var_node(point_to).unwrap();
+                let Some(point_to_node) = self.get_var_node(point_to) else {
+                    return cur;
+                };
+                cur_node = point_to_node;
             }
             // If there exist a field node, then get it as cur node
             if cur_node.field.get(&field).is_some() {
@@ -452,8 +478,8 @@ impl<'tcx> DominatedGraph<'tcx> {
                 continue;
             }
             // Otherwise, insert a new field node.
-            match cur_node.ty.unwrap().kind() {
-                TyKind::Adt(adt_def, substs) => {
+            match cur_node.ty.map(|ty| ty.kind()) {
+                Some(TyKind::Adt(adt_def, substs)) => {
                     if adt_def.is_struct() {
                         for (idx, field_def) in adt_def.all_fields().enumerate() {
                             if idx == field {
@@ -468,7 +494,7 @@ impl<'tcx> DominatedGraph<'tcx> {
                 }
                 // TODO: maybe unsafe here for setting ty as None!
                 _ => {
-                    rap_warn!("ty {:?}, field: {:?}", cur_node.ty.unwrap(), field);
+                    rap_warn!("ty {:?}, field: {:?}", cur_node.ty, field);
                     rap_warn!("set field type as None! --- src: Dominated Graph / find_var_id_with_fields_seq");
                     cur = self.get_field_node_id(cur, field, None);
                 }
@@ -479,20 +505,26 @@ impl<'tcx> DominatedGraph<'tcx> {
 
     pub fn point(&mut self, lv: usize, rv: usize) {
         // rap_warn!("{lv} = & or * {rv}");
-        let rv_node = self.get_var_node_mut(rv).unwrap();
-        rv_node.pointed_by.insert(lv);
-        let lv_node = self.get_var_node_mut(lv).unwrap();
+        if self.get_var_node(rv).is_none() {
+            return;
+        }
+        let Some(lv_node) = self.get_var_node_mut(lv) else {
+            return;
+        };
         let ori_to = lv_node.points_to.clone();
         lv_node.points_to = Some(rv);
+        if let Some(rv_node) = self.get_var_node_mut(rv) {
+            rv_node.pointed_by.insert(lv);
+        }
         // Delete lv from the origin pointed node's pointed_by.
-        if let Some(to) = ori_to {
-            let ori_to_node = self.get_var_node_mut(to).unwrap();
+        if let Some(ori_to_node) = ori_to.and_then(|to| self.get_var_node_mut(to)) {
             ori_to_node.pointed_by.remove(&lv);
         }
     }
 
     pub fn get_var_nod_id(&self, local_id: usize) -> usize {
-        self.get_var_node(local_id).unwrap().id
+        self.get_var_node(local_id)
+            .map_or(local_id, |var_node| var_node.id)
     }
 
     pub fn get_map_idx_node(&self, local_id: usize) -> &VariableNode<'tcx> {
@@ -526,18 +558,24 @@ impl<'tcx> DominatedGraph<'tcx> {
     // In this case, lv will be the same with rv.
     // And the nodes pointing to lv originally will re-point to rv.
     pub fn merge(&mut self, lv: usize, rv: usize) {
-        let lv_node = self.get_var_node_mut(lv).unwrap().clone();
-        if lv_node.alias_set.contains(&rv) {
+        let Some(lv_node) = self.get_var_node(lv).cloned() else {
+            return;
+        };
+        if lv_node.alias_set.contains(&rv) || self.get_var_node(rv).is_none() {
             return;
         }
         for lv_pointed_by in lv_node.pointed_by.clone() {
             self.point(lv_pointed_by, rv);
         }
-        let lv_node = self.get_var_node_mut(lv).unwrap();
+        let Some(lv_node) = self.get_var_node_mut(lv) else {
+            return;
+        };
         lv_node.alias_set.remove(&lv);
         let lv_ty = lv_node.ty;
         let lv_states = lv_node.ots.clone();
-        let rv_node = self.get_var_node_mut(rv).unwrap();
+        let Some(rv_node) = self.get_var_node_mut(rv) else {
+            return;
+        };
         rv_node.alias_set.insert(lv);
         // rv_node.states.merge_states(&lv_states);
         if rv_node.ty.is_none() {
@@ -547,9 +585,12 @@ impl<'tcx> DominatedGraph<'tcx> {
 
     // Called when (lv = copy rv);
     pub fn copy_node(&mut self, lv: usize, rv: usize) {
-        let rv_node = self.get_var_node_mut(rv).unwrap().clone();
-        let lv_node = self.get_var_node_mut(lv).unwrap();
-        let lv_ty = lv_node.ty.unwrap();
+        let Some(rv_node) = self.get_var_node(rv).cloned() else {
+            return;
+        };
+        let Some(lv_node) = self.get_var_node_mut(lv) else {
+            return;
+        };
         lv_node.ots = rv_node.ots;
         lv_node.cis = rv_node.cis;
         lv_node.is_dropped = rv_node.is_dropped;
@@ -564,10 +605,12 @@ impl<'tcx> DominatedGraph<'tcx> {
     }
 
     fn break_node_connection(&mut self, lv: usize, rv: usize) {
-        let rv_node = self.get_var_node_mut(rv).unwrap();
-        rv_node.pointed_by.remove(&lv);
-        let lv_node = self.get_var_node_mut(lv).unwrap();
-        lv_node.points_to = None;
+        if let Some(rv_node) = self.get_var_node_mut(rv) {
+            rv_node.pointed_by.remove(&lv);
+        }
+        if let Some(lv_node) = self.get_var_node_mut(lv) {
+            lv_node.points_to = None;
+        }
     }
 
     fn insert_node(
@@ -585,13 +628,15 @@ impl<'tcx> DominatedGraph<'tcx> {
     }
 
     fn delete_node(&mut self, idx: usize) {
-        let node = self.get_var_node(idx).unwrap().clone();
-        for pre_idx in &node.pointed_by.clone() {
-            let pre_node = self.get_var_node_mut(*pre_idx).unwrap();
-            pre_node.points_to = None;
+        let Some(node) = self.get_var_node(idx).cloned() else {
+            return;
+        };
+        for pre_idx in &node.pointed_by {
+            if let Some(pre_node) = self.get_var_node_mut(*pre_idx) {
+                pre_node.points_to = None;
+            }
         }
-        if let Some(to) = &node.points_to.clone() {
-            let next_node = self.get_var_node_mut(*to).unwrap();
+        if let Some(next_node) = node.points_to.and_then(|to| self.get_var_node_mut(to)) {
             next_node.pointed_by.remove(&idx);
         }
         self.variables.remove(&idx);
@@ -609,20 +654,23 @@ impl<'tcx> DominatedGraph<'tcx> {
     }
 
     pub fn update_value(&mut self, arg: usize, value: usize) {
-        let node = self.get_var_node_mut(arg).unwrap();
-        node.const_value = value;
-        node.ots.init = true;
+        if let Some(node) = self.get_var_node_mut(arg) {
+            node.const_value = value;
+            node.ots.init = true;
+        }
     }
 
     pub fn insert_patial_op(&mut self, p1: usize, p2: usize, op: &BinOp) {
-        let p1_node = self.get_var_node_mut(p1).unwrap();
-        p1_node
-            .cis
-            .add_contract(PropertyContract::new_patial_order(p2, *op));
-        let p2_node = self.get_var_node_mut(p2).unwrap();
-        p2_node
-            .cis
-            .add_contract(PropertyContract::new_patial_order(p1, reverse_op(*op)));
+        if let Some(p1_node) = self.get_var_node_mut(p1) {
+            p1_node
+                .cis
+                .add_contract(PropertyContract::new_patial_order(p2, *op));
+        }
+        if let Some(p2_node) = self.get_var_node_mut(p2) {
+            p2_node
+                .cis
+                .add_contract(PropertyContract::new_patial_order(p1, reverse_op(*op)));
+        }
     }
 
     pub fn print_graph(&self) {
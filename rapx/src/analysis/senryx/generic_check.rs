@@ -8,6 +8,7 @@ use rustc_middle::ty::{FloatTy, IntTy, ParamEnv, Ty, TyCtxt, TyKind, UintTy};
 pub struct GenericChecker<'tcx> {
     // tcx: TyCtxt<'tcx>,
     trait_map: HashMap<String, HashSet<Ty<'tcx>>>,
+    trait_bnd_map: HashMap<String, HashSet<String>>,
 }
 
 impl<'tcx> GenericChecker<'tcx> {
@@ -104,6 +105,7 @@ impl<'tcx> GenericChecker<'tcx> {
 
         GenericChecker {
             trait_map: satisfied_ty_map_for_generic,
+            trait_bnd_map: trait_bnd_map_for_generic,
         }
     }
 
@@ -111,6 +113,13 @@ impl<'tcx> GenericChecker<'tcx> {
         self.trait_map.clone()
     }
 
+    // check if the generic parameter (e.g., "T") is bounded by the trait (e.g., "std::marker::Send")
+    pub fn has_trait_bnd(&self, generic_name: &str, trait_name: &str) -> bool {
+        self.trait_bnd_map
+            .get(generic_name)
+            .is_some_and(|bnd_set| bnd_set.contains(trait_name))
+    }
+
     fn get_satisfied_ty_for_pod(tcx: TyCtxt<'tcx>) -> HashSet<Ty<'tcx>> {
         let mut satisfied_ty_set_for_pod: HashSet<Ty<'tcx>> = HashSet::new();
         // f64, u64, i8, i32, u8, i16, u16, u32, usize, i128, isize, i64, u128, f32
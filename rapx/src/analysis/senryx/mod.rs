@@ -22,15 +22,15 @@ use visitor::{BodyVisitor, CheckResult};
 
 use crate::{
     analysis::{
-        core::alias_analysis::{default::AliasAnalyzer, AAResult, AliasAnalysis},
+        core::alias_analysis::{AAResult, AAResultMap, AliasBackend},
         unsafety_isolation::{
             hir_visitor::{ContainsUnsafe, RelatedFnCollector},
             UnsafetyIsolationCheck,
         },
         utils::fn_info::*,
-        Analysis,
     },
     rap_info, rap_warn,
+    utils::{boundary::guarded, findings::record_finding},
 };
 
 macro_rules! cond_print {
@@ -58,11 +58,17 @@ impl<'tcx> SenryxCheck<'tcx> {
         }
     }
 
+    pub fn name(&self) -> &'static str {
+        "Senryx"
+    }
+
     pub fn start(&mut self, check_level: CheckLevel, is_verify: bool) {
         let tcx = self.tcx;
-        let mut analyzer = AliasAnalyzer::new(self.tcx);
-        analyzer.run();
-        let fn_map = &analyzer.get_all_fn_alias();
+        let fn_map: &AAResultMap = &AliasBackend::from_env()
+            .run(self.tcx)
+            .into_iter()
+            .map(|(def_id, result)| (def_id, result.into()))
+            .collect();
         let related_items = RelatedFnCollector::collect(tcx);
         for vec in related_items.clone().values() {
             for (body_id, _span) in vec {
@@ -76,13 +82,13 @@ impl<'tcx> SenryxCheck<'tcx> {
                     && is_verify
                     && !get_all_std_unsafe_callees(self.tcx, def_id).is_empty()
                 {
-                    self.check_soundness(def_id, fn_map);
+                    guarded(self.name(), def_id, || self.check_soundness(def_id, fn_map));
                 }
                 if function_unsafe
                     && !is_verify
                     && !get_all_std_unsafe_callees(self.tcx, def_id).is_empty()
                 {
-                    self.annotate_safety(def_id);
+                    guarded(self.name(), def_id, || self.annotate_safety(def_id));
                     // let mutable_methods = get_all_mutable_methods(self.tcx, def_id);
                     // println!("mutable_methods: {:?}", mutable_methods);
                 }
@@ -225,6 +231,15 @@ impl<'tcx> SenryxCheck<'tcx> {
             get_cleaned_def_path_name(tcx, def_id)
         );
         for check_result in &check_results {
+            if !check_result.failed_contracts.is_empty() {
+                record_finding(
+                    format!(
+                        "Safety properties of unsafe api {} may be violated.",
+                        check_result.func_name
+                    ),
+                    check_result.func_span,
+                );
+            }
             cond_print!(
                 !check_result.failed_contracts.is_empty(),
                 "  Use unsafe api {:?}.",
@@ -2,10 +2,13 @@ use crate::{
     analysis::{
         core::{
             alias_analysis::AAResult,
-            ownedheap_analysis::OHAResultMap,
-            range_analysis::{default::RangeAnalyzer, RangeAnalysis},
+            range_analysis::{
+                default::RangeAnalyzer,
+                feasibility::{prune_infeasible_paths, Feasibility},
+                RangeAnalysis,
+            },
         },
-        safedrop::graph::SafeDropGraph,
+        safedrop::graph::{DropHeaps, SafeDropGraph},
         senryx::contracts::property::{CisRangeItem, PropertyContract},
         utils::{
             fn_info::{
@@ -125,7 +128,7 @@ impl<'tcx> BodyVisitor<'tcx> {
         Self {
             tcx,
             def_id,
-            safedrop_graph: SafeDropGraph::new(body, tcx, def_id, OHAResultMap::default()),
+            safedrop_graph: SafeDropGraph::new(body, tcx, def_id, DropHeaps::default()),
             abstract_states: HashMap::new(),
             unsafe_callee_report: HashMap::new(),
             local_ty: HashMap::new(),
@@ -397,8 +400,7 @@ impl<'tcx> BodyVisitor<'tcx> {
                                 idx,
                                 Some(Ty::new_uint(self.tcx, rustc_middle::ty::UintTy::Usize)),
                             );
-                        } else {
-                            let node = self.chains.get_var_node_mut(lpjc_local).unwrap();
+                        } else if let Some(node) = self.chains.get_var_node_mut(lpjc_local) {
                             node.field.insert(idx, val);
                         }
                     }
@@ -506,7 +508,9 @@ impl<'tcx> BodyVisitor<'tcx> {
         let d_local = self.handle_proj(false, dst_place.clone());
         let ptr_local = get_arg_place(&args[0].node).1;
         let mem_local = self.chains.get_point_to_id(ptr_local);
-        let mem_var = self.chains.get_var_node_mut(mem_local).unwrap();
+        let Some(mem_var) = self.chains.get_var_node_mut(mem_local) else {
+            return;
+        };
         for cis in &mut mem_var.cis.contracts {
             if let PropertyContract::InBound(cis_ty, len) = cis {
                 *len = CisRangeItem::new_var(d_local);
@@ -565,10 +569,13 @@ impl<'tcx> BodyVisitor<'tcx> {
                 let snd_to = self.chains.get_point_to_id(snd_var);
                 let is_fst_point = fst_to != fst_var;
                 let is_snd_point = snd_to != snd_var;
-                let fst_node = self.chains.get_var_node(fst_var).unwrap();
-                let snd_node = self.chains.get_var_node(snd_var).unwrap();
-                let is_fst_ptr = is_ptr(fst_node.ty.unwrap()) || is_ref(fst_node.ty.unwrap());
-                let is_snd_ptr = is_ptr(snd_node.ty.unwrap()) || is_ref(snd_node.ty.unwrap());
+                let fst_ty = self.chains.get_var_node(fst_var).and_then(|node| node.ty);
+                let snd_ty = self.chains.get_var_node(snd_var).and_then(|node| node.ty);
+                let (Some(fst_ty), Some(snd_ty)) = (fst_ty, snd_ty) else {
+                    continue;
+                };
+                let is_fst_ptr = is_ptr(fst_ty) || is_ref(fst_ty);
+                let is_snd_ptr = is_ptr(snd_ty) || is_ref(snd_ty);
                 rap_debug!(
                     "{:?}: {fst_var},{fst_to},{is_fst_ptr} -- {snd_var},{snd_to},{is_snd_ptr}",
                     def_id
@@ -663,6 +670,12 @@ impl<'tcx> BodyVisitor<'tcx> {
             } else {
                 path_constraints_option.unwrap()
             };
+        if prune_infeasible_paths() {
+            if let Some(feasibility) = range_analyzer.get_fn_path_feasibility(self.def_id) {
+                path_constraints
+                    .retain(|path, _| feasibility.get(path) != Some(&Feasibility::Infeasible));
+            }
+        }
         self.safedrop_graph.solve_scc();
         // If it's the first level analysis, then filter the paths not containing unsafe
         if self.visit_time == 0 {
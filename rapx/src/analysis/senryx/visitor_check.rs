@@ -425,7 +425,10 @@ impl<'tcx> BodyVisitor<'tcx> {
         // rap_warn!("Checking Align {arg}!");
         // display_hashmap(&self.chains.variables, 1);
         // 1. Check the var's cis.
-        let var = self.chains.get_var_node(arg).unwrap();
+        // A node missing from the graph cannot be proved aligned.
+        let Some(var) = self.chains.get_var_node(arg) else {
+            return false;
+        };
         let required_ty = self.visit_ty_and_get_layout(contract_required_ty);
         for cis in &var.cis.contracts {
             if let PropertyContract::Align(cis_ty) = cis {
@@ -434,22 +437,26 @@ impl<'tcx> BodyVisitor<'tcx> {
             }
         }
         // 2. If the var does not have cis, then check its type and the value type
-        let mem = self.chains.get_obj_ty_through_chain(arg);
-        let mem_ty = self.visit_ty_and_get_layout(mem.unwrap());
-        let cur_ty = self.visit_ty_and_get_layout(var.ty.unwrap());
+        let (Some(mem), Some(var_ty)) = (self.chains.get_obj_ty_through_chain(arg), var.ty) else {
+            return false;
+        };
+        let mem_ty = self.visit_ty_and_get_layout(mem);
+        let cur_ty = self.visit_ty_and_get_layout(var_ty);
         let point_to_id = self.chains.get_point_to_id(arg);
-        let var_ty = self.chains.get_var_node(point_to_id);
+        let Some(obj) = self.chains.get_var_node(point_to_id) else {
+            return false;
+        };
         // display_hashmap(&self.chains.variables, 1);
         // rap_warn!("{:?}, {:?}, {:?}, {:?}", arg, cur_ty, point_to_id, mem_ty);
-        return AlignState::Cast(mem_ty, cur_ty).check() && var_ty.unwrap().ots.align;
+        return AlignState::Cast(mem_ty, cur_ty).check() && obj.ots.align;
     }
 
     pub fn check_non_zst(&self, arg: usize) -> bool {
-        let obj_ty = self.chains.get_obj_ty_through_chain(arg);
-        if obj_ty.is_none() {
+        let Some(obj_ty) = self.chains.get_obj_ty_through_chain(arg) else {
             self.show_error_info(arg);
-        }
-        let ori_ty = self.visit_ty_and_get_layout(obj_ty.unwrap());
+            return false;
+        };
+        let ori_ty = self.visit_ty_and_get_layout(obj_ty);
         match ori_ty {
             PlaceTy::Ty(_align, size) => size == 0,
             PlaceTy::GenericTy(_, _, tys) => {
@@ -469,10 +476,13 @@ impl<'tcx> BodyVisitor<'tcx> {
 
     // checking the value ptr points to is valid for its type
     pub fn check_typed(&self, arg: usize) -> bool {
-        let obj_ty = self.chains.get_obj_ty_through_chain(arg).unwrap();
-        let var = self.chains.get_var_node(arg);
+        let Some(obj_ty) = self.chains.get_obj_ty_through_chain(arg) else {
+            return false;
+        };
         // display_hashmap(&self.chains.variables, 1);
-        let var_ty = var.unwrap().ty.unwrap();
+        let Some(var_ty) = self.chains.get_var_node(arg).and_then(|var| var.ty) else {
+            return false;
+        };
         if obj_ty != var_ty && is_strict_ty_convert(self.tcx, obj_ty, var_ty) {
             return false;
         }
@@ -481,27 +491,29 @@ impl<'tcx> BodyVisitor<'tcx> {
 
     pub fn check_non_null(&self, arg: usize) -> bool {
         let point_to_id = self.chains.get_point_to_id(arg);
-        let var_ty = self.chains.get_var_node(point_to_id);
-        if var_ty.is_none() {
+        let Some(var) = self.chains.get_var_node(point_to_id) else {
             self.show_error_info(arg);
-        }
-        var_ty.unwrap().ots.nonnull
+            return false;
+        };
+        var.ots.nonnull
     }
 
     // check each field's init state in the tree.
     // check arg itself when it doesn't have fields.
     pub fn check_init(&self, arg: usize) -> bool {
         let point_to_id = self.chains.get_point_to_id(arg);
-        let var = self.chains.get_var_node(point_to_id);
+        let Some(var) = self.chains.get_var_node(point_to_id) else {
+            return false;
+        };
         // display_hashmap(&self.chains.variables, 1);
-        if var.unwrap().field.is_empty() {
+        if var.field.is_empty() {
             let mut init_flag = true;
-            for field in &var.unwrap().field {
+            for field in &var.field {
                 init_flag &= self.check_init(*field.1);
             }
             init_flag
         } else {
-            var.unwrap().ots.init
+            var.ots.init
         }
     }
 
@@ -516,7 +528,9 @@ impl<'tcx> BodyVisitor<'tcx> {
     pub fn check_inbound(&self, arg: usize, length_arg: usize, contract_ty: Ty<'tcx>) -> bool {
         // 1. Check the var's cis.
         let mem_arg = self.chains.get_point_to_id(arg);
-        let mem_var = self.chains.get_var_node(mem_arg).unwrap();
+        let Some(mem_var) = self.chains.get_var_node(mem_arg) else {
+            return false;
+        };
         for cis in &mem_var.cis.contracts {
             if let PropertyContract::InBound(cis_ty, len) = cis {
                 // display_hashmap(&self.chains.variables, 1);
@@ -571,13 +585,15 @@ impl<'tcx> BodyVisitor<'tcx> {
 
     /// compare two args, return true if left <= right
     fn compare_patial_order_of_two_args(&self, left: usize, right: usize) -> bool {
-        // Find the same value node set
+        // Find the same value node set, also through the helpers returning their argument
         let mut dataflow_analyzer = DataFlowAnalyzer::new(self.tcx, false);
-        dataflow_analyzer.build_graph(self.def_id);
+        dataflow_analyzer.build_graph_with_callees(self.def_id);
         let left_local = rustc_middle::mir::Local::from(left);
         let right_local = rustc_middle::mir::Local::from(right);
-        let left_local_set = dataflow_analyzer.collect_equivalent_locals(self.def_id, left_local);
-        let right_local_set = dataflow_analyzer.collect_equivalent_locals(self.def_id, right_local);
+        let left_local_set =
+            dataflow_analyzer.collect_interproc_equivalent_locals(self.def_id, left_local);
+        let right_local_set =
+            dataflow_analyzer.collect_interproc_equivalent_locals(self.def_id, right_local);
         // If left == right
         if right_local_set.contains(&rustc_middle::mir::Local::from(left)) {
             return true;
@@ -0,0 +1,617 @@
+pub mod spec;
+
+use std::collections::{hash_map::Entry, HashMap, HashSet, VecDeque};
+
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        BasicBlock, BinOp, Body, Local, Operand, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::TyCtxt,
+};
+use rustc_span::Span;
+
+use crate::{
+    analysis::{
+        core::dataflow::{
+            default::DataFlowAnalyzer, graph::Graph, Arg2RetMap, DataFlowAnalysis, EdgeOp, NodeOp,
+        },
+        utils::def_path::plain_path,
+    },
+    rap_debug,
+    utils::{
+        boundary::guarded,
+        log::{report_fn_bugs, BugReport},
+    },
+};
+use spec::TaintSpec;
+
+/// A point of a flow and what happens to the value there.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub span: Span,
+    pub label: String,
+}
+
+/// The flow of an untrusted value from a source to a sink in one function.
+#[derive(Debug, Clone)]
+pub struct TaintFlow {
+    /// The path of the source, e.g., `std::env::var`.
+    pub source: String,
+    /// The path of the sink, e.g., `std::ptr::const_ptr::add`.
+    pub sink: String,
+    /// The source, the calls passing the value, and the sink, in order.
+    pub steps: Vec<Step>,
+}
+
+// The summaries of the local functions.
+#[derive(Default)]
+struct Summaries {
+    arg2ret: Arg2RetMap,
+    // the functions returning an untrusted value of their own, and the path of its source.
+    returns: HashMap<DefId, String>,
+    // the parameters of a function that reach a sink in it or its callees, and the path of the sink.
+    params: HashMap<DefId, HashMap<usize, String>>,
+}
+
+struct Seed {
+    local: Local,
+    source: String,
+    step: Step,
+}
+
+#[derive(Default)]
+struct Propagation {
+    flows: Vec<TaintFlow>,
+    // the source of an untrusted value returned by the function.
+    returned: Option<String>,
+}
+
+/// Track untrusted values, e.g., those read from the network or the environment, to the unsafe
+/// operations they may break, e.g., pointer arithmetic, unchecked indexing and allocation sizes.
+///
+/// The values are propagated over the dataflow graphs of the functions, from the sources to the
+/// sinks declared in the [`TaintSpec`]. A call to a sanitizer stops the propagation, and a sink is
+/// not reached by a value bounded from above, e.g., by `n < len`, on a branch dominating it. The calls to the local functions are summarized:
+/// an argument flows to the return value only if the callee returns it according to
+/// `get_fn_arg2ret`, a callee may return an untrusted value of its own, and an argument may reach a
+/// sink in the callee. The calls to other functions pass their arguments to the return value.
+pub struct TaintCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub spec: TaintSpec,
+    pub flows: Vec<(DefId, Vec<TaintFlow>)>,
+}
+
+impl<'tcx> TaintCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            spec: TaintSpec::load(),
+            flows: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "Taint Check"
+    }
+
+    pub fn start(&mut self) {
+        let mut dataflow_analyzer = DataFlowAnalyzer::new(self.tcx, false);
+        dataflow_analyzer.build_graphs();
+        let mut summaries = Summaries {
+            arg2ret: dataflow_analyzer.get_all_arg2ret(),
+            ..Default::default()
+        };
+        let graphs = &dataflow_analyzer.graphs;
+
+        // the summaries only grow, so they reach a fixpoint.
+        loop {
+            let mut changed = false;
+            for (def_id, graph) in graphs.iter() {
+                let Some((returned, params)) = guarded(self.name(), *def_id, || {
+                    let fn_taint = FnTaint::new(self.tcx, &self.spec, &summaries, graph);
+                    let returned = match summaries.returns.contains_key(def_id) {
+                        true => None,
+                        false => fn_taint.propagate(fn_taint.source_seeds()).returned,
+                    };
+                    let known = summaries.params.get(def_id);
+                    let params: Vec<(usize, String)> = (0..graph.argc)
+                        .filter(|param| !known.is_some_and(|known| known.contains_key(param)))
+                        .filter_map(|param| {
+                            let seed = fn_taint.param_seed(param, String::new(), String::new());
+                            let flow = fn_taint.propagate(vec![seed]).flows.into_iter().next()?;
+                            Some((param, flow.sink))
+                        })
+                        .collect();
+                    (returned, params)
+                }) else {
+                    continue;
+                };
+                if let Some(source) = returned {
+                    summaries.returns.insert(*def_id, source);
+                    changed = true;
+                }
+                if !params.is_empty() {
+                    summaries.params.entry(*def_id).or_default().extend(params);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        rap_debug!("Taint summaries of returns: {:?}", summaries.returns);
+        rap_debug!("Taint summaries of parameters: {:?}", summaries.params);
+
+        for (def_id, graph) in graphs.iter() {
+            let Some(flows) = guarded(self.name(), *def_id, || {
+                let fn_taint = FnTaint::new(self.tcx, &self.spec, &summaries, graph);
+                let mut seeds = fn_taint.source_seeds();
+                let paths = TaintSpec::paths_of(self.tcx, *def_id);
+                for source in self.spec.param_sources(&paths) {
+                    let param = source.param.unwrap();
+                    if param < graph.argc {
+                        let label = format!("Parameter {} is untrusted.", param);
+                        seeds.push(fn_taint.param_seed(param, source.path.clone(), label));
+                    }
+                }
+                fn_taint.propagate(seeds).flows
+            }) else {
+                continue;
+            };
+            if !flows.is_empty() {
+                self.flows.push((*def_id, flows));
+            }
+        }
+        self.flows
+            .sort_by_key(|(def_id, _)| self.tcx.def_span(*def_id));
+        self.report_flows();
+    }
+
+    fn report_flows(&self) {
+        for (def_id, flows) in self.flows.iter() {
+            let reports: Vec<BugReport> = flows
+                .iter()
+                .filter_map(|flow| {
+                    let (sink, path) = flow.steps.split_last()?;
+                    Some(BugReport {
+                        title: format!(
+                            "Untrusted value from `{}` reaches `{}`",
+                            flow.source, flow.sink
+                        ),
+                        span: sink.span,
+                        label: sink.label.clone(),
+                        notes: path
+                            .iter()
+                            .map(|step| (step.span, step.label.clone()))
+                            .collect(),
+                        help: Some(
+                            "Check the value against a bound, or clamp it with `min`, first."
+                                .to_string(),
+                        ),
+                    })
+                })
+                .collect();
+            report_fn_bugs(
+                self.tcx.optimized_mir(*def_id).span,
+                &format!(
+                    "Untrusted value reaches unsafe code in function {:?}",
+                    self.tcx.def_path_str(*def_id)
+                ),
+                &reports,
+            );
+        }
+    }
+}
+
+// The taint propagation in one function.
+struct FnTaint<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    spec: &'a TaintSpec,
+    summaries: &'a Summaries,
+    graph: &'a Graph,
+    body: &'tcx Body<'tcx>,
+    // the locals bounded from above by a branch, e.g., by a bounds check, and the blocks the
+    // branch bounds them in.
+    bounds: HashMap<Local, Vec<BasicBlock>>,
+    // the blocks of the calls returning to the locals.
+    call_blocks: HashMap<Local, BasicBlock>,
+}
+
+impl<'a, 'tcx> FnTaint<'a, 'tcx> {
+    fn new(
+        tcx: TyCtxt<'tcx>,
+        spec: &'a TaintSpec,
+        summaries: &'a Summaries,
+        graph: &'a Graph,
+    ) -> Self {
+        let body = tcx.optimized_mir(graph.def_id);
+        let call_blocks = body
+            .basic_blocks
+            .iter_enumerated()
+            .filter_map(|(bb, block)| match &block.terminator().kind {
+                TerminatorKind::Call { destination, .. } => Some((destination.local, bb)),
+                _ => None,
+            })
+            .collect();
+        Self {
+            tcx,
+            spec,
+            summaries,
+            graph,
+            body,
+            bounds: upper_bounds(body),
+            call_blocks,
+        }
+    }
+
+    // The value reaching the call returning to `dst` is bounded from above on a branch dominating
+    // the call, either itself or one of the values it is computed from.
+    fn is_bounded(
+        &self,
+        parents: &HashMap<Local, Result<(Local, usize), usize>>,
+        local: Local,
+        dst: Local,
+    ) -> bool {
+        let Some(call_block) = self.call_blocks.get(&dst) else {
+            return false;
+        };
+        let dominators = self.body.basic_blocks.dominators();
+        let mut local = local;
+        loop {
+            if self.bounds.get(&local).is_some_and(|blocks| {
+                blocks
+                    .iter()
+                    .any(|bb| dominators.dominates(*bb, *call_block))
+            }) {
+                return true;
+            }
+            match parents[&local] {
+                Ok((parent, _)) => local = parent,
+                Err(_) => return false,
+            }
+        }
+    }
+
+    // The return values of the calls to the sources and to the local functions returning an
+    // untrusted value, and the pointees of the arguments the sources write to.
+    fn source_seeds(&self) -> Vec<Seed> {
+        let mut seeds = self.arg_source_seeds();
+        for (local, node) in self.graph.nodes.iter_enumerated() {
+            for op in node.ops.iter() {
+                let NodeOp::Call(callee) = op else {
+                    continue;
+                };
+                let paths = TaintSpec::paths_of(self.tcx, *callee);
+                let (source, label) = if let Some(source) = self.spec.return_source(&paths) {
+                    let label = format!("Untrusted value from `{}`.", source.path);
+                    (source.path.clone(), label)
+                } else if let Some(source) = self.summaries.returns.get(callee) {
+                    let label = format!(
+                        "`{}` returns an untrusted value from `{}`.",
+                        self.tcx.def_path_str(*callee),
+                        source
+                    );
+                    (source.clone(), label)
+                } else {
+                    continue;
+                };
+                seeds.push(Seed {
+                    local,
+                    source,
+                    step: Step {
+                        span: node.span.source_callsite(),
+                        label,
+                    },
+                });
+            }
+        }
+        seeds
+    }
+
+    fn arg_source_seeds(&self) -> Vec<Seed> {
+        let mut seeds = Vec::new();
+        for block in self.body.basic_blocks.iter() {
+            let terminator = block.terminator();
+            let TerminatorKind::Call { func, args, .. } = &terminator.kind else {
+                continue;
+            };
+            let Some((callee, _)) = func.const_fn_def() else {
+                continue;
+            };
+            let paths = TaintSpec::paths_of(self.tcx, callee);
+            for source in self.spec.arg_sources(&paths) {
+                let arg = source.arg.unwrap();
+                let Some(local) = args
+                    .get(arg)
+                    .and_then(|arg| arg.node.place())
+                    .and_then(|place| pointee_of(self.body, place.local))
+                else {
+                    continue;
+                };
+                seeds.push(Seed {
+                    local,
+                    source: source.path.clone(),
+                    step: Step {
+                        span: terminator.source_info.span.source_callsite(),
+                        label: format!(
+                            "Untrusted value written by `{}` to argument {}.",
+                            source.path, arg
+                        ),
+                    },
+                });
+            }
+        }
+        seeds
+    }
+
+    fn param_seed(&self, param: usize, source: String, label: String) -> Seed {
+        let local = Local::from_usize(param + 1);
+        Seed {
+            local,
+            source,
+            step: Step {
+                span: self.body.local_decls[local].source_info.span,
+                label,
+            },
+        }
+    }
+
+    // The argument position of an edge into a call.
+    fn arg_of(&self, edge_idx: usize) -> Option<usize> {
+        let edge = &self.graph.edges[edge_idx];
+        self.graph.nodes[edge.dst]
+            .in_edges
+            .iter()
+            .filter(|idx| self.graph.edges[**idx].seq == edge.seq)
+            .position(|idx| *idx == edge_idx)
+    }
+
+    fn propagate(&self, seeds: Vec<Seed>) -> Propagation {
+        let mut propagation = Propagation::default();
+        // the local and the op of the step reaching a local, or the seed starting from it.
+        let mut parents: HashMap<Local, Result<(Local, usize), usize>> = HashMap::new();
+        let mut worklist = VecDeque::new();
+        for (idx, seed) in seeds.iter().enumerate() {
+            if parents.insert(seed.local, Err(idx)).is_none() {
+                worklist.push_back(seed.local);
+            }
+        }
+        let mut reached_sinks = HashSet::new();
+        while let Some(local) = worklist.pop_front() {
+            if local == Local::from_usize(0) && propagation.returned.is_none() {
+                let seed = &seeds[self.seed_of(&parents, local)];
+                propagation.returned = Some(seed.source.clone());
+            }
+            for edge_idx in self.graph.nodes[local].out_edges.iter() {
+                let edge = &self.graph.edges[*edge_idx];
+                let dst = &self.graph.nodes[edge.dst];
+                // the index of a place does not make the element untrusted.
+                if matches!(edge.op, EdgeOp::Nop) && self.graph.is_marker(edge.dst) {
+                    continue;
+                }
+                if let Some(NodeOp::Call(callee)) = dst.ops.get(edge.seq) {
+                    let Some(arg) = self.arg_of(*edge_idx) else {
+                        continue;
+                    };
+                    let paths = TaintSpec::paths_of(self.tcx, *callee);
+                    let sink = match self.spec.sink(&paths, arg) {
+                        Some(sink) => Some((
+                            sink.path.clone(),
+                            format!("Used as argument {} of `{}`.", arg, sink.path),
+                        )),
+                        None => self
+                            .summaries
+                            .params
+                            .get(callee)
+                            .and_then(|params| params.get(&arg))
+                            .map(|sink| {
+                                let label = format!(
+                                    "Passed to `{}`, where it reaches `{}`.",
+                                    self.tcx.def_path_str(*callee),
+                                    sink
+                                );
+                                (sink.clone(), label)
+                            }),
+                    };
+                    if sink.is_some() && self.is_bounded(&parents, local, edge.dst) {
+                        continue;
+                    }
+                    // a flow is reported at the first sink it reaches.
+                    if let Some((sink, label)) = sink {
+                        if reached_sinks.insert((edge.dst, edge.seq)) {
+                            let seed = &seeds[self.seed_of(&parents, local)];
+                            let mut steps = self.steps_to(&parents, seed, local);
+                            steps.push(Step {
+                                span: dst.span.source_callsite(),
+                                label,
+                            });
+                            propagation.flows.push(TaintFlow {
+                                source: seed.source.clone(),
+                                sink,
+                                steps,
+                            });
+                        }
+                        continue;
+                    }
+                    // a source writing to the argument, e.g., `read`, does not return its pointee.
+                    if self.spec.is_sanitizer(&paths)
+                        || self
+                            .spec
+                            .arg_sources(&paths)
+                            .any(|source| source.arg == Some(arg))
+                    {
+                        continue;
+                    }
+                    if let Some(arg2ret) = self.summaries.arg2ret.get(callee) {
+                        if !arg2ret
+                            .get(Local::from_usize(arg + 1))
+                            .is_some_and(|dep| *dep)
+                        {
+                            continue;
+                        }
+                    }
+                }
+                if let Entry::Vacant(entry) = parents.entry(edge.dst) {
+                    entry.insert(Ok((local, edge.seq)));
+                    worklist.push_back(edge.dst);
+                }
+            }
+        }
+        propagation
+    }
+
+    fn seed_of(
+        &self,
+        parents: &HashMap<Local, Result<(Local, usize), usize>>,
+        local: Local,
+    ) -> usize {
+        let mut local = local;
+        loop {
+            match parents[&local] {
+                Ok((parent, _)) => local = parent,
+                Err(seed) => return seed,
+            }
+        }
+    }
+
+    // The source and the calls passing the value to the local; a call overlapping the previous
+    // step, e.g., `unwrap()` chained to the source, is omitted.
+    fn steps_to(
+        &self,
+        parents: &HashMap<Local, Result<(Local, usize), usize>>,
+        seed: &Seed,
+        local: Local,
+    ) -> Vec<Step> {
+        let mut calls = Vec::new();
+        let mut local = local;
+        while let Ok((parent, seq)) = parents[&local] {
+            if let Some(NodeOp::Call(callee)) = self.graph.nodes[local].ops.get(seq) {
+                calls.push((self.graph.nodes[local].span.source_callsite(), *callee));
+            }
+            local = parent;
+        }
+        let mut steps = vec![seed.step.clone()];
+        for (span, callee) in calls.into_iter().rev() {
+            if steps.last().is_some_and(|step| step.span.overlaps(span)) {
+                continue;
+            }
+            steps.push(Step {
+                span,
+                label: format!("Passed through `{}`.", plain_path(self.tcx, callee)),
+            });
+        }
+        steps
+    }
+}
+
+// The local a reference points to, following the references, reborrows and casts defining it,
+// e.g., `buf` for `&mut buf[..]` passed to `read`.
+fn pointee_of(body: &Body<'_>, local: Local) -> Option<Local> {
+    let mut definitions = HashMap::new();
+    for block in body.basic_blocks.iter() {
+        for statement in block.statements.iter() {
+            if let StatementKind::Assign(assign) = &statement.kind {
+                if assign.0.projection.is_empty() {
+                    definitions.insert(assign.0.local, &assign.1);
+                }
+            }
+        }
+    }
+    let mut local = local;
+    for _ in 0..body.local_decls.len() {
+        match definitions.get(&local)? {
+            Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => {
+                if place.projection.first() != Some(&ProjectionElem::Deref) {
+                    return Some(place.local);
+                }
+                local = place.local;
+            }
+            Rvalue::Use(operand) | Rvalue::Cast(_, operand, _) => local = operand.place()?.local,
+            _ => return None,
+        }
+    }
+    None
+}
+
+// The locals bounded from above by the conditions of branches, e.g., `n` by `n < len` on the
+// branch taken if it holds and `len` on the other one, with the blocks the branches enter, and
+// the locals they are copied from. A block entered from elsewhere too is not bounded.
+fn upper_bounds(body: &Body<'_>) -> HashMap<Local, Vec<BasicBlock>> {
+    let predecessors = body.basic_blocks.predecessors();
+    // the blocks entered if a condition holds, and if it does not.
+    let mut branches: HashMap<Local, (Vec<BasicBlock>, Vec<BasicBlock>)> = HashMap::new();
+    for block in body.basic_blocks.iter() {
+        let TerminatorKind::SwitchInt { discr, targets } = &block.terminator().kind else {
+            continue;
+        };
+        let Some(place) = discr.place().filter(|place| place.projection.is_empty()) else {
+            continue;
+        };
+        let [(0, false_block)] = targets.iter().collect::<Vec<_>>()[..] else {
+            continue;
+        };
+        let branch = branches.entry(place.local).or_default();
+        for (target, blocks) in [
+            (targets.otherwise(), &mut branch.0),
+            (false_block, &mut branch.1),
+        ] {
+            if predecessors[target].len() == 1 {
+                blocks.push(target);
+            }
+        }
+    }
+    let mut bounds: HashMap<Local, Vec<BasicBlock>> = HashMap::new();
+    let mut copies = HashMap::new();
+    for block in body.basic_blocks.iter() {
+        for statement in block.statements.iter() {
+            let StatementKind::Assign(assign) = &statement.kind else {
+                continue;
+            };
+            match &assign.1 {
+                Rvalue::BinaryOp(
+                    op @ (BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge),
+                    operands,
+                ) => {
+                    let Some((holds, fails)) = branches.get(&assign.0.local) else {
+                        continue;
+                    };
+                    // the smaller operand if the condition holds, and the other one otherwise.
+                    let (smaller, greater) = match op {
+                        BinOp::Lt | BinOp::Le => (&operands.0, &operands.1),
+                        _ => (&operands.1, &operands.0),
+                    };
+                    for (operand, blocks) in [(smaller, holds), (greater, fails)] {
+                        if let Some(place) =
+                            operand.place().filter(|place| place.projection.is_empty())
+                        {
+                            bounds.entry(place.local).or_default().extend(blocks);
+                        }
+                    }
+                }
+                Rvalue::Use(Operand::Copy(place) | Operand::Move(place))
+                    if place.projection.is_empty() && assign.0.projection.is_empty() =>
+                {
+                    copies.insert(assign.0.local, place.local);
+                }
+                _ => {}
+            }
+        }
+    }
+    let mut worklist: Vec<Local> = bounds.keys().copied().collect();
+    while let Some(local) = worklist.pop() {
+        if let Some(copied) = copies.get(&local) {
+            let blocks = bounds[&local].clone();
+            let copied_bounds = bounds.entry(*copied).or_default();
+            let len = copied_bounds.len();
+            copied_bounds.extend(blocks);
+            copied_bounds.sort();
+            copied_bounds.dedup();
+            if copied_bounds.len() > len {
+                worklist.push(*copied);
+            }
+        }
+    }
+    bounds
+}
@@ -0,0 +1,184 @@
+use crate::rap_warn;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_middle::ty::TyCtxt;
+
+use crate::analysis::utils::def_path::plain_path;
+
+/// A function whose return value is untrusted, or whose parameter is if `param` is given, or
+/// which writes an untrusted value to the pointee of its argument `arg`, e.g., a reader filling a
+/// buffer.
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub path: String,
+    pub param: Option<usize>,
+    pub arg: Option<usize>,
+}
+
+/// A function that must not be passed an untrusted value as its argument `arg`.
+#[derive(Debug, Clone)]
+pub struct Sink {
+    pub path: String,
+    pub arg: usize,
+}
+
+/// The sources, sinks and sanitizers of the taint analysis.
+///
+/// The built-in specification covers reads from the environment and readers as the sources,
+/// pointer arithmetic, unchecked indexing, raw slices and allocation sizes as the sinks, and
+/// `min` and `clamp` as the sanitizers. It can be extended by a TOML file given in the
+/// `RAP_TAINT_CONFIG` environment variable, e.g.,
+///
+/// ```toml
+/// [[source]]
+/// path = "server::read_header"
+///
+/// [[source]]
+/// path = "server::handle_request"
+/// param = 1
+///
+/// [[source]]
+/// path = "server::Connection::recv_into"
+/// arg = 1
+///
+/// [[sink]]
+/// path = "server::raw::copy_into"
+/// arg = 2
+///
+/// [[sanitizer]]
+/// path = "server::validate_len"
+/// ```
+///
+/// A source without `param` or `arg` taints the return value of the function, one with `param`
+/// taints the parameter at the index, and one with `arg` taints what the argument at the index
+/// points to after the call. `arg` is also the index of the argument of a sink, the receiver of a
+/// method being 0. A sanitizer returns a trusted value from untrusted arguments. The paths are written
+/// without generic arguments and the local crate is named by its crate name; `core::` and
+/// `alloc::` are the same as `std::`.
+#[derive(Debug, Clone, Default)]
+pub struct TaintSpec {
+    pub sources: Vec<Source>,
+    pub sinks: Vec<Sink>,
+    pub sanitizers: Vec<String>,
+}
+
+impl TaintSpec {
+    pub fn load() -> Self {
+        let mut spec = TaintSpec::default();
+        spec.extend(include_str!("data/taint_spec.toml"), "built-in");
+        if let Ok(path) = std::env::var("RAP_TAINT_CONFIG") {
+            match std::fs::read_to_string(&path) {
+                Ok(content) => spec.extend(&content, &path),
+                Err(e) => rap_warn!("Failed to read taint config {}: {}", path, e),
+            }
+        }
+        spec
+    }
+
+    fn extend(&mut self, content: &str, source: &str) {
+        let table: toml::Table = match content.parse() {
+            Ok(table) => table,
+            Err(e) => {
+                rap_warn!("Failed to parse taint config {}: {}", source, e);
+                return;
+            }
+        };
+        for (key, value) in table.iter() {
+            let Some(entries) = value.as_array() else {
+                rap_warn!(
+                    "Taint config {} should contain arrays of [[{}]] tables.",
+                    source,
+                    key
+                );
+                continue;
+            };
+            for entry in entries {
+                let path = entry.get("path").and_then(|path| path.as_str());
+                let index = |name: &str| {
+                    entry
+                        .get(name)
+                        .map(|value| value.as_integer().and_then(|i| usize::try_from(i).ok()))
+                };
+                match (key.as_str(), path) {
+                    ("source", Some(path)) => match (index("param"), index("arg")) {
+                        (Some(None), _) | (_, Some(None)) | (Some(_), Some(_)) => rap_warn!(
+                            "Invalid param or arg of source {} in taint config {}.",
+                            path,
+                            source
+                        ),
+                        (param, arg) => self.sources.push(Source {
+                            path: normalize(path),
+                            param: param.flatten(),
+                            arg: arg.flatten(),
+                        }),
+                    },
+                    ("sink", Some(path)) => match index("arg") {
+                        Some(Some(arg)) => self.sinks.push(Sink {
+                            path: normalize(path),
+                            arg,
+                        }),
+                        _ => rap_warn!(
+                            "Missing or invalid arg of sink {} in taint config {}.",
+                            path,
+                            source
+                        ),
+                    },
+                    ("sanitizer", Some(path)) => self.sanitizers.push(normalize(path)),
+                    ("source" | "sink" | "sanitizer", None) => {
+                        rap_warn!("Missing path of a {} in taint config {}.", key, source)
+                    }
+                    _ => rap_warn!("Unknown table [[{}]] in taint config {}.", key, source),
+                }
+            }
+        }
+    }
+
+    /// The paths a function is matched by, with and without the crate name for a local one.
+    pub fn paths_of(tcx: TyCtxt<'_>, def_id: DefId) -> Vec<String> {
+        let path = normalize(&plain_path(tcx, def_id));
+        if def_id.is_local() {
+            let crate_path = format!("{}::{}", tcx.crate_name(LOCAL_CRATE), path);
+            vec![path, crate_path]
+        } else {
+            vec![path]
+        }
+    }
+
+    pub fn return_source(&self, paths: &[String]) -> Option<&Source> {
+        self.sources.iter().find(|source| {
+            source.param.is_none() && source.arg.is_none() && paths.contains(&source.path)
+        })
+    }
+
+    pub fn param_sources<'a>(&'a self, paths: &'a [String]) -> impl Iterator<Item = &'a Source> {
+        self.sources
+            .iter()
+            .filter(|source| source.param.is_some() && paths.contains(&source.path))
+    }
+
+    pub fn arg_sources<'a>(&'a self, paths: &'a [String]) -> impl Iterator<Item = &'a Source> {
+        self.sources
+            .iter()
+            .filter(|source| source.arg.is_some() && paths.contains(&source.path))
+    }
+
+    pub fn sink(&self, paths: &[String], arg: usize) -> Option<&Sink> {
+        self.sinks
+            .iter()
+            .find(|sink| sink.arg == arg && paths.contains(&sink.path))
+    }
+
+    pub fn is_sanitizer(&self, paths: &[String]) -> bool {
+        self.sanitizers
+            .iter()
+            .any(|sanitizer| paths.contains(sanitizer))
+    }
+}
+
+fn normalize(path: &str) -> String {
+    for krate in ["core::", "alloc::"] {
+        if let Some(rest) = path.strip_prefix(krate) {
+            return format!("std::{}", rest);
+        }
+    }
+    path.to_string()
+}
@@ -11,9 +11,14 @@ use crate::{
         Analysis,
     },
     rap_info,
+    utils::source::get_fn_name,
 };
 
-use rustc_middle::ty::TyCtxt;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{Local, Location, Place},
+    ty::TyCtxt,
+};
 
 pub struct Test<'tcx> {
     pub tcx: TyCtxt<'tcx>,
@@ -29,11 +34,24 @@ impl<'tcx> Test<'tcx> {
         alias_analysis.run();
         let result = alias_analysis.get_local_fn_alias();
         rap_info!("{}", AAResultMapWrapper(result));
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if alias_analysis.alias_states.contains_key(&def_id) {
+                self.print_ret_alias_sets(&alias_analysis, def_id);
+            }
+        }
 
         let mut dataflow_analysis = DataFlowAnalyzer::new(self.tcx, false);
         dataflow_analysis.run();
         let dataflow = dataflow_analysis.get_all_arg2ret();
         rap_info!("{}", Arg2RetMapWrapper(dataflow));
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if dataflow_analysis.graphs.contains_key(&def_id) {
+                self.print_interproc_arg2ret(&dataflow_analysis, def_id);
+                self.print_interproc_equivalents(&dataflow_analysis, def_id);
+            }
+        }
 
         let mut heap_analysis = OwnedHeapAnalyzer::new(self.tcx);
         heap_analysis.run();
@@ -45,4 +63,81 @@ impl<'tcx> Test<'tcx> {
         let path_constraint = range_analysis.get_all_path_constraints();
         rap_info!("{}", PathConstraintMapWrapper(path_constraint));
     }
+
+    /// Print the arguments that may alias the return value after each basic block.
+    fn print_ret_alias_sets(&self, alias_analysis: &AliasAnalyzer<'tcx>, def_id: DefId) {
+        let body = self.tcx.optimized_mir(def_id);
+        let fn_name = get_fn_name(self.tcx, def_id);
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            let location = Location {
+                block,
+                statement_index: data.statements.len(),
+            };
+            let mut args = alias_analysis
+                .alias_set(def_id, location, Place::return_place())
+                .into_iter()
+                .filter(|place| {
+                    place.fields.is_empty() && (1..=body.arg_count).contains(&place.local)
+                })
+                .map(|place| place.local)
+                .collect::<Vec<_>>();
+            if args.is_empty() {
+                continue;
+            }
+            args.sort();
+            let args = args
+                .iter()
+                .map(|arg| format!("_{}", arg))
+                .collect::<Vec<_>>();
+            rap_info!(
+                "Alias set of _0 in {:?} at {:?}: {}",
+                fn_name,
+                block,
+                args.join(", ")
+            );
+        }
+    }
+
+    /// Print the arguments that flow to the return value through the callees.
+    fn print_interproc_arg2ret(&self, dataflow_analysis: &DataFlowAnalyzer<'tcx>, def_id: DefId) {
+        let args = dataflow_analysis
+            .get_fn_interproc_arg2ret(def_id)
+            .iter_enumerated()
+            .filter(|(local, depends)| local.as_usize() > 0 && **depends)
+            .map(|(local, _)| format!("{:?}", local))
+            .collect::<Vec<_>>();
+        if !args.is_empty() {
+            rap_info!(
+                "Inter-procedural dataflow to _0 in {:?}: {}",
+                get_fn_name(self.tcx, def_id),
+                args.join(", ")
+            );
+        }
+    }
+
+    /// Print the arguments equal to the return value through the callees.
+    fn print_interproc_equivalents(
+        &self,
+        dataflow_analysis: &DataFlowAnalyzer<'tcx>,
+        def_id: DefId,
+    ) {
+        let argc = dataflow_analysis.graphs[&def_id].argc;
+        let mut args = dataflow_analysis
+            .collect_interproc_equivalent_locals(def_id, Local::from_usize(0))
+            .into_iter()
+            .filter(|local| (1..=argc).contains(&local.as_usize()))
+            .collect::<Vec<_>>();
+        args.sort();
+        if !args.is_empty() {
+            let args = args
+                .iter()
+                .map(|arg| format!("{:?}", arg))
+                .collect::<Vec<_>>();
+            rap_info!(
+                "Inter-procedural equivalents of _0 in {:?}: {}",
+                get_fn_name(self.tcx, def_id),
+                args.join(", ")
+            );
+        }
+    }
 }
@@ -0,0 +1,490 @@
+use rustc_abi::BackendRepr;
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext, Visitor},
+        Body, CastKind, Local, Location, Operand, Place, PlaceElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{self, layout::TyAndLayout, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::{sym, Span};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::analysis::utils::def_path::{is_def_path, is_ptr_method};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransmuteBugKind {
+    /// More bytes are read from the source than it holds, e.g., by `transmute_copy`.
+    SizeMismatch { src_size: u64, dst_size: u64 },
+    /// The target type requires a stricter alignment than the memory it is derived from.
+    Misaligned { src_align: u64, dst_align: u64 },
+    /// A constant that breaks the validity invariant of the target type.
+    InvalidValue(u128),
+    /// Some values of the source type break the validity invariant of the target type.
+    MayBeInvalid,
+}
+
+#[derive(Debug, Clone)]
+pub struct TransmuteBug {
+    pub kind: TransmuteBugKind,
+    pub src_ty: String,
+    pub dst_ty: String,
+    pub span: Span,
+    // the span of the cast that reinterpreted the pointer, if the bug is found at a later use.
+    pub cast: Option<Span>,
+}
+
+impl fmt::Display for TransmuteBug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            TransmuteBugKind::SizeMismatch { src_size, dst_size } => write!(
+                f,
+                "Reading {} bytes of `{}` from {} bytes of `{}`.",
+                dst_size, self.dst_ty, src_size, self.src_ty
+            ),
+            TransmuteBugKind::Misaligned {
+                src_align,
+                dst_align,
+            } => write!(
+                f,
+                "`{}` requires an alignment of {}, but the memory holds `{}` aligned to {}.",
+                self.dst_ty, dst_align, self.src_ty, src_align
+            ),
+            TransmuteBugKind::InvalidValue(value) => {
+                write!(f, "`{}` is not a valid value of `{}`.", value, self.dst_ty)
+            }
+            TransmuteBugKind::MayBeInvalid => write!(
+                f,
+                "Values of `{}` may be invalid for `{}`.",
+                self.src_ty, self.dst_ty
+            ),
+        }
+    }
+}
+
+// How a reinterpreted pointer is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Read,
+    Write,
+    Borrow,
+}
+
+/// Check the transmutes and pointer casts of a function body.
+///
+/// Raw pointers are tracked from the reference or `as_ptr` they are derived from, so that the
+/// type of the memory they point to is known when they are cast to another pointee type and
+/// dereferenced.
+pub struct FnChecker<'a, 'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub body: &'a Body<'tcx>,
+    pub typing_env: TypingEnv<'tcx>,
+    // the type of the memory a pointer local points to.
+    origins: HashMap<Local, Ty<'tcx>>,
+    // the cast that changed the pointee type of a local.
+    casts: HashMap<Local, Span>,
+    reported: HashSet<Span>,
+    pub bugs: Vec<TransmuteBug>,
+}
+
+impl<'a, 'tcx> FnChecker<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId) -> Self {
+        Self {
+            tcx,
+            body: tcx.optimized_mir(def_id),
+            typing_env: TypingEnv::post_analysis(tcx, def_id),
+            origins: HashMap::new(),
+            casts: HashMap::new(),
+            reported: HashSet::new(),
+            bugs: Vec::new(),
+        }
+    }
+
+    pub fn check(&mut self) {
+        let body = self.body;
+        for arg in body.args_iter() {
+            if let ty::Ref(_, pointee, _) = body.local_decls[arg].ty.kind() {
+                self.origins.insert(arg, *pointee);
+            }
+        }
+        for bb in body.basic_blocks.reverse_postorder() {
+            let data = &body.basic_blocks[*bb];
+            if data.is_cleanup {
+                continue;
+            }
+            for stmt in data.statements.iter() {
+                if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind {
+                    if let Some(local) = place.as_local() {
+                        self.track_assign(local, rvalue, stmt.source_info.span);
+                    }
+                }
+            }
+            let terminator = data.terminator();
+            if let TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } = &terminator.kind
+            {
+                let span = terminator.source_info.span;
+                if let Some(local) = destination.as_local() {
+                    self.track_call(func, args.iter().map(|arg| &arg.node), local, span);
+                }
+                self.check_call(func, args.iter().map(|arg| &arg.node), span);
+            }
+        }
+        self.visit_body(body);
+    }
+
+    // Record the pointee of the memory the assigned pointer refers to, and check transmutes.
+    fn track_assign(&mut self, local: Local, rvalue: &Rvalue<'tcx>, span: Span) {
+        match rvalue {
+            Rvalue::Ref(_, _, place) | Rvalue::RawPtr(_, place) => {
+                let origin = match place.as_ref().projection {
+                    [PlaceElem::Deref] => self.origins.get(&place.local).copied(),
+                    _ => None,
+                };
+                let origin = origin.unwrap_or_else(|| place.ty(self.body, self.tcx).ty);
+                self.origins.insert(local, origin);
+                if let Some(cast) = self.casts.get(&place.local).copied() {
+                    self.casts.insert(local, cast);
+                }
+            }
+            Rvalue::Use(Operand::Copy(src) | Operand::Move(src)) => {
+                if let Some(src) = src.as_local() {
+                    self.propagate(src, local, None);
+                }
+            }
+            Rvalue::Cast(CastKind::PtrToPtr, operand, _) => {
+                if let Some(src) = operand.place().and_then(|place| place.as_local()) {
+                    self.propagate(src, local, Some(span));
+                }
+            }
+            Rvalue::Cast(CastKind::Transmute, operand, dst_ty) => {
+                self.check_transmute(local, operand, *dst_ty, span);
+            }
+            _ => {}
+        }
+    }
+
+    fn track_call<'b>(
+        &mut self,
+        func: &Operand<'tcx>,
+        mut args: impl Iterator<Item = &'b Operand<'tcx>>,
+        local: Local,
+        span: Span,
+    ) where
+        'tcx: 'b,
+    {
+        let Some((callee, _)) = func.const_fn_def() else {
+            return;
+        };
+        let name = self.tcx.item_name(callee);
+        let dst_ty = self.body.local_decls[local].ty;
+        match name.as_str() {
+            "as_ptr" | "as_mut_ptr" => {
+                if let ty::RawPtr(pointee, _) = dst_ty.kind() {
+                    self.origins.insert(local, *pointee);
+                }
+            }
+            "cast" | "cast_mut" | "cast_const" | "add" | "sub" | "offset" | "wrapping_add"
+            | "wrapping_sub" | "wrapping_offset"
+                if dst_ty.is_raw_ptr() =>
+            {
+                if let Some(src) = args
+                    .next()
+                    .and_then(|arg| arg.place())
+                    .and_then(|place| place.as_local())
+                {
+                    self.propagate(src, local, Some(span));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn propagate(&mut self, src: Local, dst: Local, cast: Option<Span>) {
+        let Some(origin) = self.origins.get(&src).copied() else {
+            return;
+        };
+        self.origins.insert(dst, origin);
+        if let Some(span) = self.casts.get(&src).copied() {
+            self.casts.insert(dst, span);
+        } else if let Some(span) = cast {
+            if self.pointee(dst).is_some_and(|pointee| pointee != origin) {
+                self.casts.insert(dst, span);
+            }
+        }
+    }
+
+    fn check_transmute(
+        &mut self,
+        local: Local,
+        operand: &Operand<'tcx>,
+        dst_ty: Ty<'tcx>,
+        span: Span,
+    ) {
+        let src_ty = operand.ty(self.body, self.tcx);
+        let src_local = operand.place().and_then(|place| place.as_local());
+        if is_pointer(src_ty) && is_pointer(dst_ty) {
+            if let Some(src) = src_local {
+                self.propagate(src, local, Some(span));
+            }
+            // A reference must be aligned and point to a valid value as soon as it is created.
+            if dst_ty.is_ref() {
+                let origin = src_local
+                    .and_then(|src| self.origins.get(&src).copied())
+                    .or_else(|| src_ty.builtin_deref(true));
+                if let (Some(origin), Some(pointee)) = (origin, dst_ty.builtin_deref(true)) {
+                    self.check_pointee(origin, pointee, Access::Read, span, None);
+                }
+            }
+            return;
+        }
+        let constant = match operand {
+            Operand::Constant(c) => c
+                .const_
+                .try_to_scalar_int()
+                .map(|val| val.to_uint(val.size())),
+            _ => None,
+        };
+        match constant {
+            Some(value) => {
+                if let Some(layout) = self.layout(dst_ty) {
+                    let valid = match layout.backend_repr {
+                        BackendRepr::Scalar(scalar) => {
+                            scalar.valid_range(&self.tcx).contains(value)
+                                && !layout.is_uninhabited()
+                        }
+                        _ => true,
+                    };
+                    if !valid {
+                        self.push_bug(
+                            TransmuteBugKind::InvalidValue(value),
+                            src_ty,
+                            dst_ty,
+                            span,
+                            None,
+                        );
+                    }
+                }
+            }
+            None => {
+                if self.may_be_invalid(src_ty, dst_ty) {
+                    self.push_bug(TransmuteBugKind::MayBeInvalid, src_ty, dst_ty, span, None);
+                }
+            }
+        }
+    }
+
+    fn check_call<'b>(
+        &mut self,
+        func: &Operand<'tcx>,
+        mut args: impl Iterator<Item = &'b Operand<'tcx>>,
+        span: Span,
+    ) where
+        'tcx: 'b,
+    {
+        let Some((callee, generics)) = func.const_fn_def() else {
+            return;
+        };
+        let tcx = self.tcx;
+        if is_def_path(tcx, callee, "core::mem::transmute_copy") {
+            let (src_ty, dst_ty) = (generics.type_at(0), generics.type_at(1));
+            self.check_transmute_copy(src_ty, dst_ty, span);
+            return;
+        }
+        let access = match tcx.get_diagnostic_name(callee) {
+            Some(
+                sym::slice_from_raw_parts
+                | sym::slice_from_raw_parts_mut
+                | sym::ptr_read
+                | sym::ptr_read_volatile,
+            ) => Some(Access::Read),
+            Some(sym::ptr_write | sym::ptr_write_volatile | sym::ptr_write_bytes) => {
+                Some(Access::Write)
+            }
+            _ if is_ptr_method(tcx, callee, &["read", "read_volatile"]) => Some(Access::Read),
+            _ if is_ptr_method(tcx, callee, &["write", "write_volatile", "write_bytes"]) => {
+                Some(Access::Write)
+            }
+            _ => None,
+        };
+        if let Some(access) = access {
+            if let Some(local) = args
+                .next()
+                .and_then(|arg| arg.place())
+                .and_then(|place| place.as_local())
+            {
+                self.check_use(local, access, span);
+            }
+        }
+    }
+
+    fn check_transmute_copy(&mut self, src_ty: Ty<'tcx>, dst_ty: Ty<'tcx>, span: Span) {
+        let (Some(src), Some(dst)) = (self.layout(src_ty), self.layout(dst_ty)) else {
+            return;
+        };
+        let (src_size, dst_size) = (src.size.bytes(), dst.size.bytes());
+        if dst_size > src_size {
+            self.push_bug(
+                TransmuteBugKind::SizeMismatch { src_size, dst_size },
+                src_ty,
+                dst_ty,
+                span,
+                None,
+            );
+        } else if self.may_be_invalid(src_ty, dst_ty) {
+            self.push_bug(TransmuteBugKind::MayBeInvalid, src_ty, dst_ty, span, None);
+        }
+    }
+
+    // Check an access through a pointer whose pointee type differs from the memory it points to.
+    fn check_use(&mut self, local: Local, access: Access, span: Span) {
+        let Some(cast) = self.casts.get(&local).copied() else {
+            return;
+        };
+        let (Some(origin), Some(pointee)) =
+            (self.origins.get(&local).copied(), self.pointee(local))
+        else {
+            return;
+        };
+        self.check_pointee(origin, pointee, access, span, Some(cast));
+    }
+
+    fn check_pointee(
+        &mut self,
+        origin: Ty<'tcx>,
+        pointee: Ty<'tcx>,
+        access: Access,
+        span: Span,
+        cast: Option<Span>,
+    ) {
+        if origin == pointee {
+            return;
+        }
+        let (Some(src), Some(dst)) = (self.layout(elem_ty(origin)), self.layout(elem_ty(pointee)))
+        else {
+            return;
+        };
+        let (src_align, dst_align) = (src.align.abi.bytes(), dst.align.abi.bytes());
+        if dst_align > src_align {
+            self.push_bug(
+                TransmuteBugKind::Misaligned {
+                    src_align,
+                    dst_align,
+                },
+                origin,
+                pointee,
+                span,
+                cast,
+            );
+        } else if access == Access::Read && self.may_be_invalid(elem_ty(origin), elem_ty(pointee)) {
+            self.push_bug(TransmuteBugKind::MayBeInvalid, origin, pointee, span, cast);
+        }
+    }
+
+    // Whether some bit patterns of `src` break the validity invariant of `dst`. The check compares
+    // the largest niche of both types, which covers `bool`, `char`, enums, references and
+    // `NonZero*`.
+    fn may_be_invalid(&self, src_ty: Ty<'tcx>, dst_ty: Ty<'tcx>) -> bool {
+        if src_ty == dst_ty {
+            return false;
+        }
+        let (Some(src), Some(dst)) = (self.layout(src_ty), self.layout(dst_ty)) else {
+            return false;
+        };
+        if dst.is_uninhabited() {
+            return true;
+        }
+        let Some(dst_niche) = dst.largest_niche else {
+            return false;
+        };
+        let size = dst_niche.value.size(&self.tcx);
+        let covered = src.largest_niche.is_some_and(|src_niche| {
+            src_niche.offset == dst_niche.offset
+                && src_niche.value.size(&self.tcx) == size
+                && dst_niche
+                    .valid_range
+                    .contains_range(src_niche.valid_range, size)
+        });
+        !covered
+    }
+
+    fn push_bug(
+        &mut self,
+        kind: TransmuteBugKind,
+        src_ty: Ty<'tcx>,
+        dst_ty: Ty<'tcx>,
+        span: Span,
+        cast: Option<Span>,
+    ) {
+        // Report each reinterpretation once, at its first problematic use.
+        if !self.reported.insert(cast.unwrap_or(span)) {
+            return;
+        }
+        self.bugs.push(TransmuteBug {
+            kind,
+            src_ty: src_ty.to_string(),
+            dst_ty: dst_ty.to_string(),
+            span,
+            cast,
+        });
+    }
+
+    fn pointee(&self, local: Local) -> Option<Ty<'tcx>> {
+        self.body.local_decls[local].ty.builtin_deref(true)
+    }
+
+    fn layout(&self, ty: Ty<'tcx>) -> Option<TyAndLayout<'tcx>> {
+        self.tcx.layout_of(self.typing_env.as_query_input(ty)).ok()
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for FnChecker<'a, 'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, context: PlaceContext, location: Location) {
+        if place.projection.first() != Some(&PlaceElem::Deref) {
+            return;
+        }
+        let access = match context {
+            PlaceContext::NonMutatingUse(
+                NonMutatingUseContext::Copy
+                | NonMutatingUseContext::Move
+                | NonMutatingUseContext::Inspect,
+            ) => Access::Read,
+            PlaceContext::NonMutatingUse(NonMutatingUseContext::SharedBorrow)
+            | PlaceContext::MutatingUse(MutatingUseContext::Borrow) => Access::Borrow,
+            PlaceContext::MutatingUse(
+                MutatingUseContext::Store
+                | MutatingUseContext::Call
+                | MutatingUseContext::SetDiscriminant,
+            ) => Access::Write,
+            _ => return,
+        };
+        // Only a read of the whole pointee is checked for validity.
+        let access = if access == Access::Read && place.projection.len() > 1 {
+            Access::Borrow
+        } else {
+            access
+        };
+        let span = self.body.source_info(location).span;
+        self.check_use(place.local, access, span);
+    }
+}
+
+fn is_pointer(ty: Ty<'_>) -> bool {
+    ty.is_ref() || ty.is_raw_ptr()
+}
+
+// The element type of slices and arrays, whose alignment is that of the whole.
+fn elem_ty(ty: Ty<'_>) -> Ty<'_> {
+    match ty.kind() {
+        ty::Slice(elem) | ty::Array(elem, _) => elem_ty(*elem),
+        _ => ty,
+    }
+}
@@ -0,0 +1,98 @@
+pub mod body;
+
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::ty::TyCtxt;
+
+use crate::{
+    rap_debug,
+    utils::{
+        boundary::guarded,
+        log::{report_fn_bugs, BugReport},
+    },
+};
+use body::{FnChecker, TransmuteBug};
+
+/// Detect transmutes and pointer casts that break the size, alignment or validity requirements
+/// of the target type.
+///
+/// The checker covers `mem::transmute`, `mem::transmute_copy`, raw pointer casts (`as` and
+/// `ptr::cast`) followed by a dereference, and `slice::from_raw_parts` on reinterpreted pointers.
+/// Validity is checked against the niche of the target type, which covers `bool`, `char`, enums,
+/// references and `NonZero*`.
+pub struct TransmuteCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub bugs: Vec<(DefId, Vec<TransmuteBug>)>,
+}
+
+impl<'tcx> TransmuteCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            bugs: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "Transmute Check"
+    }
+
+    pub fn start(&mut self) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if !self.is_target(def_id) {
+                continue;
+            }
+            rap_debug!("Transmute check for {:?}", def_id);
+            let Some(bugs) = guarded(self.name(), def_id, || {
+                let mut checker = FnChecker::new(self.tcx, def_id);
+                checker.check();
+                checker.bugs
+            }) else {
+                continue;
+            };
+            if !bugs.is_empty() {
+                self.bugs.push((def_id, bugs));
+            }
+        }
+        self.report_bugs();
+    }
+
+    fn is_target(&self, def_id: DefId) -> bool {
+        matches!(
+            self.tcx.def_kind(def_id),
+            DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+        ) && self
+            .tcx
+            .hir_body_const_context(def_id.expect_local())
+            .is_none()
+            && self.tcx.is_mir_available(def_id)
+    }
+
+    fn report_bugs(&self) {
+        for (def_id, bugs) in self.bugs.iter() {
+            let reports: Vec<BugReport> = bugs
+                .iter()
+                .map(|bug| BugReport {
+                    title: bug.to_string(),
+                    span: bug.span,
+                    label: "Reinterpreted value used here.".to_string(),
+                    notes: bug
+                        .cast
+                        .filter(|cast| *cast != bug.span)
+                        .map(|cast| (cast, "Pointer reinterpreted here.".to_string()))
+                        .into_iter()
+                        .collect(),
+                    help: None,
+                })
+                .collect();
+            report_fn_bugs(
+                self.tcx.optimized_mir(*def_id).span,
+                &format!(
+                    "Invalid transmute or pointer cast detected in function {:?}",
+                    self.tcx.def_path_str(*def_id)
+                ),
+                &reports,
+            );
+        }
+    }
+}
@@ -0,0 +1,940 @@
+use super::state::*;
+use crate::analysis::utils::{
+    def_path::{inherent_self_ty, is_adt_method, is_def_path},
+    fn_info::operand_local,
+};
+use rustc_hir::def_id::DefId;
+use rustc_middle::{
+    mir::{
+        BasicBlock, Body, Local, Operand, Place, PlaceElem, Rvalue, StatementKind, TerminatorKind,
+    },
+    ty::{self, Ty, TyCtxt, TypingEnv},
+};
+use rustc_span::{source_map::Spanned, sym, Span};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// The kind of allocation whose initialization is tracked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjKind {
+    /// The buffer of a vector created by `Vec::with_capacity`.
+    Vec,
+    /// A value created by `MaybeUninit::uninit`.
+    MaybeUninit,
+    /// A value created by `mem::uninitialized`.
+    Uninitialized,
+    /// Memory returned by `alloc::alloc` or `alloc::alloc_zeroed`.
+    Alloc,
+    /// The pointee of a raw pointer or `&mut MaybeUninit<T>` parameter. Its reads and writes
+    /// form the summary of the function.
+    Param(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct ObjInfo<'tcx> {
+    pub kind: ObjKind,
+    // the element type for vectors; the value type otherwise. `None` if it is unknown.
+    pub ty: Option<Ty<'tcx>>,
+    pub span: Span,
+    pub initial: ObjState,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UninitBugKind {
+    Read,
+    Reference,
+    AssumeInit,
+    Exposure(Span),
+    RawSlice,
+    CalleeRead(String),
+}
+
+impl fmt::Display for UninitBugKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UninitBugKind::Read => write!(f, "Reading possibly uninitialized memory."),
+            UninitBugKind::Reference => {
+                write!(f, "Creating a reference to possibly uninitialized memory.")
+            }
+            UninitBugKind::AssumeInit => {
+                write!(
+                    f,
+                    "Calling `assume_init` on a possibly uninitialized value."
+                )
+            }
+            UninitBugKind::Exposure(_) => {
+                write!(f, "Using a vector whose elements may be uninitialized.")
+            }
+            UninitBugKind::RawSlice => {
+                write!(f, "Creating a slice over possibly uninitialized memory.")
+            }
+            UninitBugKind::CalleeRead(callee) => write!(
+                f,
+                "Passing possibly uninitialized memory to `{}` which reads it.",
+                callee
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UninitBug {
+    pub kind: UninitBugKind,
+    pub span: Span,
+    // where the uninitialized memory comes from.
+    pub origin: Span,
+}
+
+/// The effect of a function on the pointees of its raw pointer and `&mut MaybeUninit<T>`
+/// parameters, indexed by the local of the parameter.
+#[derive(Debug, Clone, Default)]
+pub struct FnSummary {
+    // parameters whose pointee is read before the function initializes it.
+    pub reads: HashSet<usize>,
+    // the initialization state of each pointee when the function returns.
+    pub writes: HashMap<usize, ObjState>,
+}
+
+pub struct FnAnalysis<'a, 'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub def_id: DefId,
+    pub body: &'tcx Body<'tcx>,
+    pub typing_env: TypingEnv<'tcx>,
+    pub objs: Vec<ObjInfo<'tcx>>,
+    // allocation call sites.
+    pub sites: HashMap<BasicBlock, usize>,
+    // locals holding the result of `Layout::new::<T>()`.
+    pub layouts: HashMap<Local, Ty<'tcx>>,
+    // locals assigned from a single integer constant.
+    pub consts: HashMap<Local, u64>,
+    pub summaries: &'a HashMap<DefId, FnSummary>,
+    pub summary: FnSummary,
+    pub bugs: Vec<UninitBug>,
+    report: bool,
+}
+
+impl<'a, 'tcx> FnAnalysis<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>, def_id: DefId, summaries: &'a HashMap<DefId, FnSummary>) -> Self {
+        let body = tcx.optimized_mir(def_id);
+        let mut analysis = Self {
+            tcx,
+            def_id,
+            body,
+            typing_env: TypingEnv::post_analysis(tcx, def_id),
+            objs: Vec::new(),
+            sites: HashMap::new(),
+            layouts: HashMap::new(),
+            consts: HashMap::new(),
+            summaries,
+            summary: FnSummary::default(),
+            bugs: Vec::new(),
+            report: false,
+        };
+        analysis.collect_consts();
+        analysis.collect_sites();
+        analysis
+    }
+
+    /// Compute the fixed point of the initialization states and then report the accesses to
+    /// possibly uninitialized memory.
+    pub fn run(&mut self) {
+        let body = self.body;
+        let mut entry: HashMap<BasicBlock, State> = HashMap::new();
+        entry.insert(BasicBlock::from_usize(0), self.initial_state());
+        let mut worklist = vec![BasicBlock::from_usize(0)];
+        let mut visits: HashMap<BasicBlock, usize> = HashMap::new();
+        while let Some(bb) = worklist.pop() {
+            let count = visits.entry(bb).or_default();
+            *count += 1;
+            if *count > VISIT_LIMIT {
+                continue;
+            }
+            let mut state = entry[&bb].clone();
+            self.transfer_block(bb, &mut state);
+            for succ in body[bb].terminator().successors() {
+                if body[succ].is_cleanup {
+                    continue;
+                }
+                let joined = match entry.get(&succ) {
+                    Some(old) => old.join(&state),
+                    None => state.clone(),
+                };
+                if entry.get(&succ) != Some(&joined) {
+                    entry.insert(succ, joined);
+                    worklist.push(succ);
+                }
+            }
+        }
+
+        self.report = true;
+        for (bb, data) in body.basic_blocks.iter_enumerated() {
+            if data.is_cleanup {
+                continue;
+            }
+            if let Some(state) = entry.get(&bb) {
+                let mut state = state.clone();
+                self.transfer_block(bb, &mut state);
+            }
+        }
+    }
+
+    fn initial_state(&mut self) -> State {
+        let mut state = State {
+            objs: self.objs.iter().map(|obj| obj.initial.clone()).collect(),
+            targets: HashMap::new(),
+        };
+        for (idx, obj) in self.objs.iter().enumerate() {
+            if let ObjKind::Param(arg) = obj.kind {
+                let local = Local::from_usize(arg);
+                let via = if self.body.local_decls[local].ty.is_raw_ptr() {
+                    Via::Ptr
+                } else {
+                    Via::Ref
+                };
+                state.targets.insert(local, Target::new(idx, via));
+            }
+        }
+        state
+    }
+
+    fn collect_consts(&mut self) {
+        let mut assigned: HashMap<Local, usize> = HashMap::new();
+        for data in self.body.basic_blocks.iter() {
+            for stmt in data.statements.iter() {
+                if let StatementKind::Assign(assign) = &stmt.kind {
+                    if !assign.0.projection.is_empty() {
+                        continue;
+                    }
+                    *assigned.entry(assign.0.local).or_default() += 1;
+                    if let Rvalue::Use(Operand::Constant(c)) = &assign.1 {
+                        if let Some(val) = c.const_.try_to_scalar_int() {
+                            self.consts
+                                .insert(assign.0.local, val.to_uint(val.size()) as u64);
+                        }
+                    }
+                }
+            }
+        }
+        self.consts.retain(|local, _| assigned[local] == 1);
+    }
+
+    fn collect_sites(&mut self) {
+        let body = self.body;
+        for (arg, local) in body.args_iter().enumerate() {
+            let ty = body.local_decls[local].ty;
+            let pointee = match ty.kind() {
+                ty::RawPtr(pointee, _) => Some(*pointee),
+                ty::Ref(_, inner, mutbl) if mutbl.is_mut() => self.maybe_uninit_inner(*inner),
+                _ => None,
+            };
+            if let Some(pointee) = pointee {
+                self.objs.push(ObjInfo {
+                    kind: ObjKind::Param(arg + 1),
+                    ty: Some(pointee),
+                    span: body.span,
+                    initial: ObjState::default(),
+                });
+            }
+        }
+        let calls: Vec<_> = body
+            .basic_blocks
+            .iter_enumerated()
+            .filter(|(_, data)| !data.is_cleanup)
+            .filter_map(|(bb, data)| {
+                let terminator = data.terminator();
+                let TerminatorKind::Call {
+                    func,
+                    args,
+                    destination,
+                    ..
+                } = &terminator.kind
+                else {
+                    return None;
+                };
+                let (def_id, gargs) = func.const_fn_def()?;
+                Some((bb, terminator, def_id, gargs, args, destination))
+            })
+            .collect();
+        // The layouts are collected first: the block order does not follow the control flow,
+        // so `Layout::new` may come after the allocation using it.
+        for &(_, _, def_id, gargs, _, destination) in calls.iter() {
+            let layout_did = self.tcx.lang_items().alloc_layout();
+            if is_adt_method(self.tcx, def_id, layout_did, &["new"]) {
+                if let Some(ty) = gargs.types().next() {
+                    self.layouts.insert(destination.local, ty);
+                }
+            }
+        }
+        for &(bb, terminator, def_id, gargs, args, _) in calls.iter() {
+            let tcx = self.tcx;
+            let first_ty = gargs.types().next();
+            let (kind, ty, initial) = match tcx.get_diagnostic_name(def_id) {
+                Some(sym::vec_with_capacity) => (ObjKind::Vec, first_ty, ObjState::default()),
+                Some(sym::maybe_uninit_uninit) => {
+                    (ObjKind::MaybeUninit, first_ty, ObjState::default())
+                }
+                Some(sym::mem_uninitialized) => {
+                    (ObjKind::Uninitialized, first_ty, ObjState::default())
+                }
+                _ if is_def_path(tcx, def_id, "alloc::alloc::alloc") => {
+                    (ObjKind::Alloc, self.layout_arg(args), ObjState::default())
+                }
+                _ if is_def_path(tcx, def_id, "alloc::alloc::alloc_zeroed") => {
+                    (ObjKind::Alloc, self.layout_arg(args), ObjState::new_init())
+                }
+                _ => continue,
+            };
+            self.sites.insert(bb, self.objs.len());
+            self.objs.push(ObjInfo {
+                kind,
+                ty,
+                span: terminator.source_info.span,
+                initial,
+            });
+        }
+    }
+
+    fn transfer_block(&mut self, bb: BasicBlock, state: &mut State) {
+        let body = self.body;
+        for stmt in body[bb].statements.iter() {
+            if let StatementKind::Assign(assign) = &stmt.kind {
+                self.transfer_assign(&assign.0, &assign.1, stmt.source_info.span, state);
+            }
+        }
+        let terminator = body[bb].terminator();
+        let span = terminator.source_info.span;
+        match &terminator.kind {
+            TerminatorKind::Call {
+                func,
+                args,
+                destination,
+                ..
+            } => {
+                let args: Vec<&Operand<'tcx>> = args.iter().map(|arg| &arg.node).collect();
+                state.targets.remove(&destination.local);
+                self.transfer_call(bb, func, &args, destination.local, span, state);
+            }
+            TerminatorKind::Drop { place, .. } if place.projection.is_empty() => {
+                self.transfer_drop(place.local, span, state);
+            }
+            TerminatorKind::Return => {
+                for (idx, obj) in self.objs.iter().enumerate() {
+                    if let ObjKind::Param(arg) = obj.kind {
+                        let joined = match self.summary.writes.get(&arg) {
+                            Some(old) => old.join(&state.objs[idx]),
+                            None => state.objs[idx].clone(),
+                        };
+                        self.summary.writes.insert(arg, joined);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn transfer_assign(
+        &mut self,
+        lhs: &Place<'tcx>,
+        rvalue: &Rvalue<'tcx>,
+        span: Span,
+        state: &mut State,
+    ) {
+        let mut new_target = None;
+        match rvalue {
+            Rvalue::Use(operand) | Rvalue::Cast(_, operand, _) => {
+                self.use_operand(operand, span, state);
+                new_target = operand_local(operand)
+                    .filter(|_| operand.place().is_some_and(|p| p.projection.is_empty()))
+                    .and_then(|local| state.targets.get(&local).copied());
+            }
+            Rvalue::CopyForDeref(place) => {
+                self.use_operand(&Operand::Copy(*place), span, state);
+            }
+            Rvalue::Ref(_, _, place) => {
+                new_target = self.borrow(place, true, span, state);
+            }
+            Rvalue::RawPtr(_, place) => {
+                new_target = self.borrow(place, false, span, state);
+            }
+            Rvalue::Repeat(operand, _) | Rvalue::UnaryOp(_, operand) => {
+                self.use_operand(operand, span, state);
+            }
+            Rvalue::BinaryOp(_, operands) => {
+                self.use_operand(&operands.0, span, state);
+                self.use_operand(&operands.1, span, state);
+            }
+            Rvalue::Aggregate(_, operands) => {
+                for operand in operands.iter() {
+                    self.use_operand(operand, span, state);
+                }
+            }
+            _ => {}
+        }
+        self.write_place(lhs, state);
+        if lhs.projection.is_empty() {
+            match new_target {
+                Some(target) => {
+                    state.targets.insert(lhs.local, target);
+                }
+                None => {
+                    state.targets.remove(&lhs.local);
+                }
+            }
+        }
+    }
+
+    fn use_operand(&mut self, operand: &Operand<'tcx>, span: Span, state: &mut State) {
+        let Some(place) = operand.place() else {
+            return;
+        };
+        let Some(target) = state.targets.get(&place.local).copied() else {
+            return;
+        };
+        let kind = self.objs[target.obj].kind;
+        match (target.via, kind) {
+            (Via::Value, ObjKind::Vec) if place.projection.is_empty() => {
+                self.check_exposure(target.obj, span, state);
+            }
+            (Via::Value, ObjKind::Uninitialized) => {
+                if let Some((offset, ty)) = self.place_range(&place, 0, target.offset) {
+                    self.check_access(target.obj, offset, ty, UninitBugKind::Read, span, state);
+                }
+            }
+            (Via::Ptr, _) if place.projection.first() == Some(&PlaceElem::Deref) => {
+                if let Some((offset, ty)) = self.place_range(&place, 1, target.offset) {
+                    self.check_access(target.obj, offset, ty, UninitBugKind::Read, span, state);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Handle `&place` (checked) and `&raw place` (unchecked); return the target of the result.
+    fn borrow(
+        &mut self,
+        place: &Place<'tcx>,
+        checked: bool,
+        span: Span,
+        state: &mut State,
+    ) -> Option<Target> {
+        let target = *state.targets.get(&place.local)?;
+        let kind = self.objs[target.obj].kind;
+        let (offset, ty) = match (target.via, kind) {
+            (Via::Value, ObjKind::Uninitialized) => self.place_range(place, 0, target.offset)?,
+            (Via::Value, _) if place.projection.is_empty() => {
+                return Some(Target::new(target.obj, Via::Ref));
+            }
+            (Via::Ref, _)
+                if place.projection.len() == 1 && place.projection[0] == PlaceElem::Deref =>
+            {
+                return Some(target);
+            }
+            (Via::Ptr, _) if place.projection.first() == Some(&PlaceElem::Deref) => {
+                self.place_range(place, 1, target.offset)?
+            }
+            _ => return None,
+        };
+        if checked {
+            self.check_access(
+                target.obj,
+                offset,
+                ty,
+                UninitBugKind::Reference,
+                span,
+                state,
+            );
+        }
+        Some(Target {
+            obj: target.obj,
+            via: Via::Ptr,
+            offset,
+        })
+    }
+
+    fn write_place(&mut self, place: &Place<'tcx>, state: &mut State) {
+        let Some(target) = state.targets.get(&place.local).copied() else {
+            return;
+        };
+        let kind = self.objs[target.obj].kind;
+        let skip = match (target.via, kind) {
+            (Via::Value, ObjKind::Uninitialized) if !place.projection.is_empty() => 0,
+            (Via::Ptr, _) if place.projection.first() == Some(&PlaceElem::Deref) => 1,
+            _ => return,
+        };
+        if let Some((offset, ty)) = self.place_range(place, skip, target.offset) {
+            self.init_range(target.obj, offset, ty, state);
+        }
+    }
+
+    fn transfer_drop(&mut self, local: Local, span: Span, state: &mut State) {
+        let Some(target) = state.targets.get(&local).copied() else {
+            return;
+        };
+        let Some(ty) = self.objs[target.obj].ty else {
+            return;
+        };
+        if target.via != Via::Value || !ty.needs_drop(self.tcx, self.typing_env) {
+            return;
+        }
+        match self.objs[target.obj].kind {
+            ObjKind::Vec => self.check_exposure(target.obj, span, state),
+            ObjKind::Uninitialized => {
+                self.check_access(target.obj, Some(0), ty, UninitBugKind::Read, span, state)
+            }
+            _ => {}
+        }
+    }
+
+    fn transfer_call(
+        &mut self,
+        bb: BasicBlock,
+        func: &Operand<'tcx>,
+        args: &[&Operand<'tcx>],
+        dest: Local,
+        span: Span,
+        state: &mut State,
+    ) {
+        if let Some(obj) = self.sites.get(&bb).copied() {
+            state.objs[obj] = self.objs[obj].initial.clone();
+            let via = if self.objs[obj].kind == ObjKind::Alloc {
+                Via::Ptr
+            } else {
+                Via::Value
+            };
+            state.targets.insert(dest, Target::new(obj, via));
+            return;
+        }
+        let Some((def_id, gargs)) = func.const_fn_def() else {
+            self.unknown_call(args, span, state);
+            return;
+        };
+        let tcx = self.tcx;
+        let name = tcx.item_name(def_id).to_string();
+        let diagnostic_name = tcx.get_diagnostic_name(def_id);
+        let arg_target = |idx: usize| -> Option<Target> {
+            args.get(idx)
+                .and_then(|arg| operand_local(arg))
+                .and_then(|local| state.targets.get(&local).copied())
+        };
+        let first_ty = gargs.types().next();
+        let self_ty = inherent_self_ty(tcx, def_id);
+        let self_adt = self_ty
+            .and_then(|ty| ty.ty_adt_def())
+            .map(|adt_def| adt_def.did());
+        let is_vec = self_adt.is_some() && self_adt == tcx.get_diagnostic_item(sym::Vec);
+        let is_maybe_uninit = self_adt.is_some() && self_adt == tcx.lang_items().maybe_uninit();
+        // the methods of raw pointers, the intrinsics and the functions of `core::ptr`.
+        let is_ptr_fn = self_ty.is_some_and(|ty| ty.is_raw_ptr())
+            || tcx.intrinsic(def_id).is_some()
+            || is_def_path(tcx, tcx.parent(def_id), "core::ptr");
+        match name.as_str() {
+            "as_mut_ptr" | "as_ptr" if is_vec || is_maybe_uninit => {
+                if let Some(target) = arg_target(0) {
+                    state
+                        .targets
+                        .insert(dest, Target::new(target.obj, Via::Ptr));
+                }
+            }
+            "len" | "capacity" | "reserve" | "reserve_exact" | "spare_capacity_mut" if is_vec => {}
+            "set_len" if is_vec => {
+                let (Some(target), Some(elem)) = (arg_target(0), first_ty) else {
+                    return;
+                };
+                let required = self
+                    .const_operand(args.get(1))
+                    .zip(self.size_of(elem))
+                    .and_then(|(len, size)| len.checked_mul(size))
+                    .map(|bytes| vec![(0, bytes)]);
+                let obj = &mut state.objs[target.obj];
+                if !obj.covers(required.as_deref()) {
+                    obj.exposed = Some(span);
+                }
+            }
+            "assume_init" | "assume_init_ref" | "assume_init_mut" | "assume_init_read"
+            | "assume_init_drop"
+                if is_maybe_uninit =>
+            {
+                if let (Some(target), Some(ty)) = (arg_target(0), first_ty) {
+                    let kind = UninitBugKind::AssumeInit;
+                    self.check_access(target.obj, Some(0), ty, kind, span, state);
+                }
+            }
+            "write" if is_maybe_uninit => {
+                if let Some(target) = arg_target(0) {
+                    state.objs[target.obj].full = true;
+                    state
+                        .targets
+                        .insert(dest, Target::new(target.obj, Via::Ptr));
+                }
+            }
+            "read" | "read_unaligned" | "read_volatile" | "drop_in_place" if is_ptr_fn => {
+                if let (Some(target), Some(ty)) = (arg_target(0), first_ty) {
+                    if target.via == Via::Ptr {
+                        let kind = UninitBugKind::Read;
+                        self.check_access(target.obj, target.offset, ty, kind, span, state);
+                    }
+                }
+            }
+            "write" | "write_unaligned" | "write_volatile" if is_ptr_fn => {
+                if let (Some(target), Some(ty)) = (arg_target(0), first_ty) {
+                    self.init_range(target.obj, target.offset, ty, state);
+                }
+            }
+            "write_bytes"
+            | "copy"
+            | "copy_nonoverlapping"
+            | "copy_from"
+            | "copy_from_nonoverlapping"
+            | "copy_to"
+            | "copy_to_nonoverlapping"
+                if is_ptr_fn =>
+            {
+                // `copy(src, dst, n)` and `src.copy_to(dst, n)` write to the second argument.
+                let dst = match name.as_str() {
+                    "copy" | "copy_nonoverlapping" | "copy_to" | "copy_to_nonoverlapping" => 1,
+                    _ => 0,
+                };
+                if let (Some(target), Some(ty)) = (arg_target(dst), first_ty) {
+                    let count = self.const_operand(args.get(2));
+                    self.init_elems(target, ty, count, state);
+                }
+            }
+            "add" | "offset" | "wrapping_add" | "wrapping_offset" | "byte_add" | "cast"
+            | "cast_mut" | "cast_const"
+                if is_ptr_fn =>
+            {
+                let Some(target) = arg_target(0) else {
+                    return;
+                };
+                let step = match name.as_str() {
+                    "cast" | "cast_mut" | "cast_const" => Some(0),
+                    "byte_add" => self.const_operand(args.get(1)),
+                    _ => self
+                        .const_operand(args.get(1))
+                        .zip(first_ty.and_then(|ty| self.size_of(ty)))
+                        .and_then(|(count, size)| count.checked_mul(size)),
+                };
+                let offset = target
+                    .offset
+                    .zip(step)
+                    .and_then(|(offset, step)| offset.checked_add(step));
+                state.targets.insert(dest, Target { offset, ..target });
+            }
+            _ if matches!(
+                diagnostic_name,
+                Some(sym::slice_from_raw_parts | sym::slice_from_raw_parts_mut)
+            ) =>
+            {
+                if let (Some(target), Some(ty)) = (arg_target(0), first_ty) {
+                    let required = target
+                        .offset
+                        .zip(self.const_operand(args.get(1)))
+                        .zip(self.size_of(ty))
+                        .and_then(|((offset, len), size)| {
+                            let end = len.checked_mul(size)?.checked_add(offset)?;
+                            Some(vec![(offset, end)])
+                        });
+                    if !state.objs[target.obj].covers(required.as_deref()) {
+                        self.push_bug(target.obj, UninitBugKind::RawSlice, span);
+                    }
+                }
+            }
+            _ if diagnostic_name == Some(sym::mem_forget) => {}
+            _ => match self.summaries.get(&def_id) {
+                Some(summary) => self.apply_summary(&name, summary, args, span, state),
+                None => self.unknown_call(args, span, state),
+            },
+        }
+    }
+
+    fn apply_summary(
+        &mut self,
+        callee: &str,
+        summary: &FnSummary,
+        args: &[&Operand<'tcx>],
+        span: Span,
+        state: &mut State,
+    ) {
+        for (idx, arg) in args.iter().enumerate() {
+            let Some(target) = operand_local(arg).and_then(|l| state.targets.get(&l).copied())
+            else {
+                continue;
+            };
+            if target.via == Via::Value {
+                continue;
+            }
+            let Some(ty) = self.pointee_of(arg.ty(&self.body.local_decls, self.tcx)) else {
+                continue;
+            };
+            if summary.reads.contains(&(idx + 1)) {
+                let kind = UninitBugKind::CalleeRead(callee.to_string());
+                self.check_access(target.obj, target.offset, ty, kind, span, state);
+            }
+            let Some(written) = summary.writes.get(&(idx + 1)) else {
+                continue;
+            };
+            if written.unknown {
+                state.objs[target.obj].unknown = true;
+            } else if written.full {
+                self.init_range(target.obj, target.offset, ty, state);
+            } else if let Some(offset) = target.offset {
+                for &(lo, hi) in written.shift(offset).ranges() {
+                    state.objs[target.obj].ranges.insert(lo, hi);
+                }
+                self.update_full(target.obj, state);
+            } else if !written.ranges.ranges().is_empty() {
+                state.objs[target.obj].unknown = true;
+            }
+        }
+    }
+
+    // An unknown callee may initialize any memory passed to it, but moving an uninitialized
+    // value or passing an exposed vector to it is an access.
+    fn unknown_call(&mut self, args: &[&Operand<'tcx>], span: Span, state: &mut State) {
+        for arg in args.iter() {
+            let Some(target) = operand_local(arg).and_then(|l| state.targets.get(&l).copied())
+            else {
+                continue;
+            };
+            match (target.via, self.objs[target.obj].kind) {
+                (Via::Value, ObjKind::Uninitialized) => self.use_operand(arg, span, state),
+                (Via::Value | Via::Ref, ObjKind::Vec) => {
+                    self.check_exposure(target.obj, span, state);
+                    state.objs[target.obj].unknown = true;
+                }
+                _ => state.objs[target.obj].unknown = true,
+            }
+        }
+    }
+
+    fn check_exposure(&mut self, obj: usize, span: Span, state: &mut State) {
+        if let Some(set_len) = state.objs[obj].exposed {
+            if !state.objs[obj].unknown {
+                self.push_bug(obj, UninitBugKind::Exposure(set_len), span);
+            }
+        }
+    }
+
+    fn check_access(
+        &mut self,
+        obj: usize,
+        offset: Option<u64>,
+        ty: Ty<'tcx>,
+        kind: UninitBugKind,
+        span: Span,
+        state: &mut State,
+    ) {
+        let required = offset.and_then(|offset| self.leaf_ranges(ty, offset));
+        if state.objs[obj].covers(required.as_deref()) {
+            return;
+        }
+        if required.is_none() && offset.is_some() && !state.objs[obj].ranges.ranges().is_empty() {
+            // The layout is unknown, e.g., for generic types; only report when nothing has
+            // been initialized.
+            return;
+        }
+        if let ObjKind::Param(arg) = self.objs[obj].kind {
+            self.summary.reads.insert(arg);
+        } else {
+            self.push_bug(obj, kind, span);
+        }
+    }
+
+    fn init_range(&mut self, obj: usize, offset: Option<u64>, ty: Ty<'tcx>, state: &mut State) {
+        match (offset, self.size_of(ty)) {
+            (Some(offset), Some(size)) if offset.checked_add(size).is_some() => {
+                state.objs[obj].ranges.insert(offset, offset + size);
+                self.update_full(obj, state);
+            }
+            (Some(0), None) if self.objs[obj].ty == Some(ty) => state.objs[obj].full = true,
+            _ => state.objs[obj].unknown = true,
+        }
+    }
+
+    fn init_elems(&mut self, target: Target, ty: Ty<'tcx>, count: Option<u64>, state: &mut State) {
+        // An overflowing length is as unknown as a non-constant one.
+        let end = target
+            .offset
+            .zip(count)
+            .zip(self.size_of(ty))
+            .and_then(|((offset, count), size)| count.checked_mul(size)?.checked_add(offset));
+        match (target.offset, end) {
+            (Some(offset), Some(end)) => {
+                state.objs[target.obj].ranges.insert(offset, end);
+                self.update_full(target.obj, state);
+            }
+            _ => state.objs[target.obj].unknown = true,
+        }
+    }
+
+    fn update_full(&self, obj: usize, state: &mut State) {
+        if self.objs[obj].kind == ObjKind::Vec {
+            return;
+        }
+        let required = self.objs[obj].ty.and_then(|ty| self.leaf_ranges(ty, 0));
+        if let Some(required) = required {
+            if required
+                .iter()
+                .all(|&(lo, hi)| state.objs[obj].ranges.contains(lo, hi))
+            {
+                state.objs[obj].full = true;
+            }
+        }
+    }
+
+    fn push_bug(&mut self, obj: usize, kind: UninitBugKind, span: Span) {
+        if !self.report || matches!(self.objs[obj].kind, ObjKind::Param(_)) {
+            return;
+        }
+        // Only the first access to each allocation is reported.
+        let origin = self.objs[obj].span;
+        if self.bugs.iter().any(|bug| bug.origin == origin) {
+            return;
+        }
+        self.bugs.push(UninitBug { kind, span, origin });
+    }
+
+    // The byte offset and type of `place`, skipping the first `skip` projections, e.g., the
+    // deref of a raw pointer. The offset is `None` if it is not a constant.
+    fn place_range(
+        &self,
+        place: &Place<'tcx>,
+        skip: usize,
+        base: Option<u64>,
+    ) -> Option<(Option<u64>, Ty<'tcx>)> {
+        let mut ty = match skip {
+            0 => self.body.local_decls[place.local].ty,
+            _ => self.pointee_of(self.body.local_decls[place.local].ty)?,
+        };
+        let mut offset = base;
+        for elem in place.projection.iter().skip(skip) {
+            match elem {
+                PlaceElem::Field(idx, field_ty) => {
+                    offset = offset
+                        .zip(self.field_offset(ty, idx.as_usize()))
+                        .map(|(offset, field)| offset + field);
+                    ty = field_ty;
+                }
+                // A nested deref accesses another allocation.
+                PlaceElem::Deref => return None,
+                _ => {
+                    offset = None;
+                    ty = elem_ty(ty, elem)?;
+                }
+            }
+        }
+        Some((offset, ty))
+    }
+
+    fn size_of(&self, ty: Ty<'tcx>) -> Option<u64> {
+        self.tcx
+            .layout_of(self.typing_env.as_query_input(ty))
+            .ok()
+            .map(|layout| layout.size.bytes())
+    }
+
+    fn field_offset(&self, ty: Ty<'tcx>, idx: usize) -> Option<u64> {
+        match ty.kind() {
+            ty::Adt(adt_def, _) if adt_def.is_struct() => {}
+            ty::Tuple(_) => {}
+            _ => return None,
+        }
+        let layout = self
+            .tcx
+            .layout_of(self.typing_env.as_query_input(ty))
+            .ok()?;
+        Some(layout.fields.offset(idx).bytes())
+    }
+
+    // The byte ranges of `ty` that hold data, i.e., excluding the padding between fields.
+    fn leaf_ranges(&self, ty: Ty<'tcx>, base: u64) -> Option<Vec<(u64, u64)>> {
+        let layout = self
+            .tcx
+            .layout_of(self.typing_env.as_query_input(ty))
+            .ok()?;
+        let fields: Vec<Ty<'tcx>> = match ty.kind() {
+            ty::Adt(adt_def, args) if adt_def.is_struct() => adt_def
+                .non_enum_variant()
+                .fields
+                .iter()
+                .map(|field| field.ty(self.tcx, args))
+                .collect(),
+            ty::Tuple(tys) => tys.iter().collect(),
+            _ => {
+                let size = layout.size.bytes();
+                return Some(if size == 0 {
+                    vec![]
+                } else {
+                    vec![(base, base + size)]
+                });
+            }
+        };
+        let mut ranges = Vec::new();
+        for (idx, field_ty) in fields.into_iter().enumerate() {
+            let offset = layout.fields.offset(idx).bytes();
+            ranges.extend(self.leaf_ranges(field_ty, base + offset)?);
+        }
+        Some(ranges)
+    }
+
+    fn pointee_of(&self, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+        match ty.kind() {
+            ty::RawPtr(pointee, _) => Some(*pointee),
+            ty::Ref(_, inner, _) => Some(self.maybe_uninit_inner(*inner).unwrap_or(*inner)),
+            _ => None,
+        }
+    }
+
+    fn maybe_uninit_inner(&self, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+        match ty.kind() {
+            ty::Adt(adt_def, args)
+                if self
+                    .tcx
+                    .def_path_str(adt_def.did())
+                    .ends_with("MaybeUninit") =>
+            {
+                args.types().next()
+            }
+            _ => None,
+        }
+    }
+
+    fn const_operand(&self, operand: Option<&&Operand<'tcx>>) -> Option<u64> {
+        match operand? {
+            Operand::Constant(c) => c
+                .const_
+                .try_to_scalar_int()
+                .map(|val| val.to_uint(val.size()) as u64),
+            Operand::Copy(place) | Operand::Move(place) if place.projection.is_empty() => {
+                self.consts.get(&place.local).copied()
+            }
+            _ => None,
+        }
+    }
+
+    // The type allocated with the layout passed as the first argument, e.g., `Layout::new::<T>()`.
+    fn layout_arg(&self, args: &[Spanned<Operand<'tcx>>]) -> Option<Ty<'tcx>> {
+        args.first()
+            .and_then(|arg| operand_local(&arg.node))
+            .and_then(|local| self.layouts.get(&local).copied())
+    }
+}
+
+const VISIT_LIMIT: usize = 100;
+
+fn elem_ty<'tcx>(ty: Ty<'tcx>, elem: PlaceElem<'tcx>) -> Option<Ty<'tcx>> {
+    match elem {
+        PlaceElem::Index(_) | PlaceElem::ConstantIndex { .. } => match ty.kind() {
+            ty::Array(elem_ty, _) | ty::Slice(elem_ty) => Some(*elem_ty),
+            _ => None,
+        },
+        PlaceElem::Subslice { .. } | PlaceElem::Downcast(..) => Some(ty),
+        PlaceElem::OpaqueCast(ty) | PlaceElem::Subtype(ty) | PlaceElem::UnwrapUnsafeBinder(ty) => {
+            Some(ty)
+        }
+        PlaceElem::Field(_, ty) => Some(ty),
+        PlaceElem::Deref => ty.builtin_deref(true),
+    }
+}
@@ -0,0 +1,134 @@
+pub mod body;
+pub mod state;
+
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_middle::{mir::TerminatorKind, ty::TyCtxt};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    rap_debug,
+    utils::{
+        boundary::guarded,
+        log::{report_fn_bugs, BugReport},
+    },
+};
+use body::{FnAnalysis, FnSummary, UninitBug, UninitBugKind};
+
+/// Detect reads of, or references to, memory that may be uninitialized.
+///
+/// The checker tracks which byte ranges of an allocation are initialized, starting from
+/// `Vec::with_capacity`, `MaybeUninit::uninit`, `mem::uninitialized` and `alloc::alloc`.
+/// Functions taking raw pointers or `&mut MaybeUninit<T>` are summarized by the ranges of the
+/// pointee they initialize and whether they read it, and the summaries are applied at call sites.
+///
+/// It does not build on the `init` flag of Senryx or on the opt `InitializationCheck`: Senryx
+/// keeps one flag per variable node while verifying the contracts of a single unsafe call, and
+/// the opt check matches initialization patterns on the dataflow graph without control flow.
+/// Neither tracks partially initialized bytes, nor joins the states of different paths.
+pub struct UninitCheck<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+    pub summaries: HashMap<DefId, FnSummary>,
+    pub bugs: Vec<(DefId, Vec<UninitBug>)>,
+    in_progress: HashSet<DefId>,
+}
+
+impl<'tcx> UninitCheck<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self {
+            tcx,
+            summaries: HashMap::new(),
+            bugs: Vec::new(),
+            in_progress: HashSet::new(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "Uninit Check"
+    }
+
+    pub fn start(&mut self) {
+        for local_def_id in self.tcx.mir_keys(()) {
+            let def_id = local_def_id.to_def_id();
+            if self.is_target(def_id) {
+                guarded(self.name(), def_id, || self.analyze(def_id));
+            }
+        }
+        self.report_bugs();
+    }
+
+    fn is_target(&self, def_id: DefId) -> bool {
+        def_id.is_local()
+            && matches!(
+                self.tcx.def_kind(def_id),
+                DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+            )
+            && self
+                .tcx
+                .hir_body_const_context(def_id.expect_local())
+                .is_none()
+            && self.tcx.is_mir_available(def_id)
+    }
+
+    // Analyze the callees first so that their summaries are available at the call sites.
+    // Recursive calls are treated as unknown calls.
+    fn analyze(&mut self, def_id: DefId) {
+        if self.summaries.contains_key(&def_id) || !self.in_progress.insert(def_id) {
+            return;
+        }
+        let body = self.tcx.optimized_mir(def_id);
+        for data in body.basic_blocks.iter() {
+            if let TerminatorKind::Call { func, .. } = &data.terminator().kind {
+                if let Some((callee, _)) = func.const_fn_def() {
+                    if self.is_target(callee) {
+                        self.analyze(callee);
+                    }
+                }
+            }
+        }
+        rap_debug!("Uninit analysis for {:?}", def_id);
+        let mut analysis = FnAnalysis::new(self.tcx, def_id, &self.summaries);
+        analysis.run();
+        let summary = analysis.summary;
+        let bugs = analysis.bugs;
+        if !bugs.is_empty() {
+            self.bugs.push((def_id, bugs));
+        }
+        self.summaries.insert(def_id, summary);
+        self.in_progress.remove(&def_id);
+    }
+
+    fn report_bugs(&self) {
+        for (def_id, bugs) in self.bugs.iter() {
+            let reports: Vec<BugReport> = bugs
+                .iter()
+                .map(|bug| {
+                    let mut notes = vec![(
+                        bug.origin,
+                        "Uninitialized memory comes from here.".to_string(),
+                    )];
+                    if let UninitBugKind::Exposure(set_len) = bug.kind {
+                        notes.push((
+                            set_len,
+                            "Uninitialized elements exposed by `set_len`.".to_string(),
+                        ));
+                    }
+                    BugReport {
+                        title: bug.kind.to_string(),
+                        span: bug.span,
+                        label: "Possibly uninitialized memory accessed here.".to_string(),
+                        notes,
+                        help: None,
+                    }
+                })
+                .collect();
+            report_fn_bugs(
+                self.tcx.optimized_mir(*def_id).span,
+                &format!(
+                    "Possibly uninitialized memory accessed in function {:?}",
+                    self.tcx.def_path_str(*def_id)
+                ),
+                &reports,
+            );
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use rustc_middle::mir::Local;
+use rustc_span::Span;
+use std::collections::HashMap;
+
+/// A set of disjoint, sorted byte ranges `[lo, hi)` of an allocation that are initialized.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InitRanges(Vec<(u64, u64)>);
+
+impl InitRanges {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        &self.0
+    }
+
+    pub fn insert(&mut self, lo: u64, hi: u64) {
+        if lo >= hi {
+            return;
+        }
+        let (mut lo, mut hi) = (lo, hi);
+        let mut merged = Vec::with_capacity(self.0.len() + 1);
+        for &(l, h) in self.0.iter() {
+            if h < lo || l > hi {
+                merged.push((l, h));
+            } else {
+                lo = lo.min(l);
+                hi = hi.max(h);
+            }
+        }
+        merged.push((lo, hi));
+        merged.sort_unstable();
+        self.0 = merged;
+    }
+
+    pub fn contains(&self, lo: u64, hi: u64) -> bool {
+        lo >= hi || self.0.iter().any(|&(l, h)| l <= lo && hi <= h)
+    }
+
+    pub fn intersect(&self, other: &InitRanges) -> InitRanges {
+        let mut result = Vec::new();
+        for &(l1, h1) in self.0.iter() {
+            for &(l2, h2) in other.0.iter() {
+                let (lo, hi) = (l1.max(l2), h1.min(h2));
+                if lo < hi {
+                    result.push((lo, hi));
+                }
+            }
+        }
+        result.sort_unstable();
+        InitRanges(result)
+    }
+}
+
+/// The initialization state of an allocation at a program point.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ObjState {
+    pub ranges: InitRanges,
+    // the whole value has been written, e.g., by `MaybeUninit::write`.
+    pub full: bool,
+    // the allocation has been written at an unknown offset or by an unknown function,
+    // so it is conservatively treated as initialized.
+    pub unknown: bool,
+    // the span of the `set_len` that exposed uninitialized elements of a vector.
+    pub exposed: Option<Span>,
+}
+
+impl ObjState {
+    pub fn new_init() -> Self {
+        Self {
+            full: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_init(&self) -> bool {
+        self.full || self.unknown
+    }
+
+    pub fn covers(&self, required: Option<&[(u64, u64)]>) -> bool {
+        if self.is_init() {
+            return true;
+        }
+        match required {
+            Some(required) => required
+                .iter()
+                .all(|&(lo, hi)| self.ranges.contains(lo, hi)),
+            None => false,
+        }
+    }
+
+    pub fn shift(&self, offset: u64) -> InitRanges {
+        let mut ranges = InitRanges::new();
+        for &(lo, hi) in self.ranges.ranges() {
+            ranges.insert(lo + offset, hi + offset);
+        }
+        ranges
+    }
+
+    // The state after two paths join: a range is initialized only if it is initialized on both.
+    // A path that wrote the allocation at an unknown place keeps the ranges of the other path
+    // known, so that they are not lost together with the unknown flag.
+    pub fn join(&self, other: &ObjState) -> ObjState {
+        let ranges = match (self.is_init(), other.is_init()) {
+            (true, false) => other.ranges.clone(),
+            (false, true) => self.ranges.clone(),
+            _ => self.ranges.intersect(&other.ranges),
+        };
+        ObjState {
+            ranges,
+            full: self.full && other.full,
+            unknown: self.unknown || other.unknown,
+            exposed: self.exposed.or(other.exposed),
+        }
+    }
+}
+
+/// How a local refers to a tracked allocation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Via {
+    /// The local holds the allocation by value, e.g., a `Vec`, a `MaybeUninit` or a value
+    /// returned by `mem::uninitialized`.
+    Value,
+    /// The local is a reference or raw pointer to the local that holds the allocation.
+    Ref,
+    /// The local is a raw pointer into the bytes of the allocation.
+    Ptr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    pub obj: usize,
+    pub via: Via,
+    // the byte offset into the allocation; `None` if it is not a constant.
+    pub offset: Option<u64>,
+}
+
+impl Target {
+    pub fn new(obj: usize, via: Via) -> Self {
+        Self {
+            obj,
+            via,
+            offset: Some(0),
+        }
+    }
+}
+
+/// The abstract state at a program point.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct State {
+    pub objs: Vec<ObjState>,
+    pub targets: HashMap<Local, Target>,
+}
+
+impl State {
+    pub fn join(&self, other: &State) -> State {
+        let objs = self
+            .objs
+            .iter()
+            .zip(other.objs.iter())
+            .map(|(a, b)| a.join(b))
+            .collect();
+        let mut targets = HashMap::new();
+        for (local, t1) in self.targets.iter() {
+            if let Some(t2) = other.targets.get(local) {
+                if t1.obj == t2.obj && t1.via == t2.via {
+                    let offset = if t1.offset == t2.offset {
+                        t1.offset
+                    } else {
+                        None
+                    };
+                    targets.insert(*local, Target { offset, ..*t1 });
+                }
+            }
+        }
+        State { objs, targets }
+    }
+}
@@ -4,8 +4,8 @@ use rustc_hir::HirId;
 use rustc_hir::PrimTy;
 use rustc_hir::{ImplItemId, ItemKind, Mutability, Node, OwnerId, TraitItemId};
 use rustc_middle::ty::fast_reject::SimplifiedType;
-use rustc_middle::ty::TyCtxt;
 use rustc_middle::ty::{FloatTy, IntTy, UintTy};
+use rustc_middle::ty::{Ty, TyCtxt};
 use rustc_span::symbol::{Ident, Symbol};
 
 pub fn path_str_def_id<'tcx>(tcx: TyCtxt<'tcx>, path_str: &str) -> DefId {
@@ -19,6 +19,53 @@ pub fn def_path_last_def_id<'tcx>(tcx: &TyCtxt<'tcx>, path: &[&str]) -> DefId {
         .expect(&format!("can not resolve {:?}", path))
 }
 
+/// The path of the function without generic arguments, e.g., `std::vec::Vec::push` for
+/// `std::vec::Vec::<T, A>::push`.
+pub fn plain_path(tcx: TyCtxt<'_>, def_id: DefId) -> String {
+    let path = tcx.def_path_str(def_id);
+    let mut plain = String::with_capacity(path.len());
+    let mut depth = 0;
+    for c in path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            _ if depth == 0 => plain.push(c),
+            _ => {}
+        }
+    }
+    plain.replace("::::", "::")
+}
+
+/// Whether the function is the item of the def path, e.g., `core::mem::transmute_copy`, matched by
+/// `DefId` rather than by the printed path, which a user item may also contain.
+pub fn is_def_path(tcx: TyCtxt<'_>, def_id: DefId, path: &str) -> bool {
+    let path: Vec<&str> = path.split("::").collect();
+    def_path_def_ids(&tcx, &path).any(|did| did == def_id)
+}
+
+/// The self type of the inherent impl the method belongs to, e.g., `Vec<T, A>` for `set_len`.
+pub fn inherent_self_ty<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> Option<Ty<'tcx>> {
+    let impl_did = tcx.impl_of_assoc(def_id)?;
+    if tcx.trait_id_of_impl(impl_did).is_some() {
+        return None;
+    }
+    Some(tcx.type_of(impl_did).instantiate_identity())
+}
+
+/// Whether the function is one of the named methods of the ADT, e.g., `Vec::set_len`.
+pub fn is_adt_method(tcx: TyCtxt<'_>, def_id: DefId, adt: Option<DefId>, names: &[&str]) -> bool {
+    names.contains(&tcx.item_name(def_id).as_str())
+        && inherent_self_ty(tcx, def_id)
+            .and_then(|ty| ty.ty_adt_def())
+            .is_some_and(|adt_def| Some(adt_def.did()) == adt)
+}
+
+/// Whether the function is one of the named methods of raw pointers, e.g., `<*const T>::read`.
+pub fn is_ptr_method(tcx: TyCtxt<'_>, def_id: DefId, names: &[&str]) -> bool {
+    names.contains(&tcx.item_name(def_id).as_str())
+        && inherent_self_ty(tcx, def_id).is_some_and(|ty| ty.is_raw_ptr())
+}
+
 pub struct DefPath {
     def_ids: Vec<DefId>,
 }
@@ -164,15 +211,17 @@ fn local_item_children_by_name(tcx: &TyCtxt<'_>, local_id: LocalDefId, name: Sym
     let hir_node = tcx.hir_node_by_def_id(local_id);
     let item_kind = match hir_node {
         Node::Crate(module) => {
-            root_mod = ItemKind::Mod(hir_node.ident().unwrap(), module);
+            // the crate root has no ident in HIR.
+            root_mod = ItemKind::Mod(Ident::with_dummy_span(tcx.crate_name(LOCAL_CRATE)), module);
             &root_mod
         }
         Node::Item(item) => &item.kind,
         _ => return Vec::new(),
     };
 
-    let res = |ident: Ident, owner_id: OwnerId| {
-        if ident.name == name {
+    // items like `use` and `impl` have no ident.
+    let res = |item_name: Option<Symbol>, owner_id: OwnerId| {
+        if item_name == Some(name) {
             let def_id = owner_id.to_def_id();
             Some(Res::Def(tcx.def_kind(def_id), def_id))
         } else {
@@ -184,19 +233,19 @@ fn local_item_children_by_name(tcx: &TyCtxt<'_>, local_id: LocalDefId, name: Sym
         ItemKind::Mod(_ident, module) => module
             .item_ids
             .iter()
-            .filter_map(|&item_id| res(tcx.hir_ident(item_id.hir_id()), item_id.owner_id))
+            .filter_map(|&item_id| res(tcx.hir_opt_name(item_id.hir_id()), item_id.owner_id))
             .collect(),
         ItemKind::Impl(r#impl) => r#impl
             .items
             .iter()
             .filter_map(|&ImplItemId { owner_id }| {
-                res(tcx.hir_ident(HirId::from(owner_id)), owner_id)
+                res(tcx.hir_opt_name(HirId::from(owner_id)), owner_id)
             })
             .collect(),
         ItemKind::Trait(.., trait_item_refs) => trait_item_refs
             .iter()
             .filter_map(|&TraitItemId { owner_id }| {
-                res(tcx.hir_ident(HirId::from(owner_id)), owner_id)
+                res(tcx.hir_opt_name(HirId::from(owner_id)), owner_id)
             })
             .collect(),
         _ => Vec::new(),
@@ -0,0 +1,132 @@
+//! The control-flow graph of a function drawn as SVG.
+//!
+//! The basic blocks are laid out in layers by their distance from the entry; an edge to a block in
+//! the same or an upper layer, e.g., the back edge of a loop, is drawn as a curve on the right.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+
+use rustc_middle::mir::{BasicBlock, Body};
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 24;
+const GAP_X: usize = 20;
+const GAP_Y: usize = 32;
+const MARGIN: usize = 12;
+
+/// The marker of the arrowheads, defined once in the page.
+pub const ARROW: &str = r##"<svg width="0" height="0" style="position:absolute"><defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z" fill="#555"/></marker></defs></svg>"##;
+
+/// Draw the CFG; a block links to its code, and the blocks in `marked` are highlighted.
+pub fn draw_cfg(body: &Body<'_>, prefix: &str, marked: &HashSet<BasicBlock>) -> String {
+    let n_blocks = body.basic_blocks.len();
+    let mut layers: Vec<Option<usize>> = vec![None; n_blocks];
+    let mut worklist = VecDeque::new();
+    if n_blocks > 0 {
+        layers[0] = Some(0);
+        worklist.push_back(BasicBlock::from_usize(0));
+    }
+    while let Some(block) = worklist.pop_front() {
+        let layer = layers[block.as_usize()].unwrap();
+        for succ in body.basic_blocks[block].terminator().successors() {
+            if layers[succ.as_usize()].is_none() {
+                layers[succ.as_usize()] = Some(layer + 1);
+                worklist.push_back(succ);
+            }
+        }
+    }
+    // the unreachable blocks are put below the others.
+    let unreachable = layers.iter().flatten().max().map_or(0, |max| max + 1);
+    let layers: Vec<usize> = layers
+        .into_iter()
+        .map(|layer| layer.unwrap_or(unreachable))
+        .collect();
+    let mut columns = vec![0; n_blocks];
+    let mut widths = vec![0; unreachable + 1];
+    for (block, layer) in layers.iter().enumerate() {
+        columns[block] = widths[*layer];
+        widths[*layer] += 1;
+    }
+    let position = |block: usize| {
+        (
+            MARGIN + columns[block] * (WIDTH + GAP_X),
+            MARGIN + layers[block] * (HEIGHT + GAP_Y),
+        )
+    };
+    let max_width = widths.iter().max().copied().unwrap_or(0);
+    let width = 2 * MARGIN + max_width * (WIDTH + GAP_X) + 40;
+    let height = 2 * MARGIN + (unreachable + 1) * (HEIGHT + GAP_Y);
+
+    let mut svg = String::new();
+    write!(
+        svg,
+        "<svg class=\"cfg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width, height, width, height
+    )
+    .unwrap();
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        let (x, y) = position(block.as_usize());
+        for succ in data.terminator().successors() {
+            let (sx, sy) = position(succ.as_usize());
+            let dashed = match body.basic_blocks[succ].is_cleanup {
+                true => " stroke-dasharray=\"4 3\"",
+                false => "",
+            };
+            let path = if layers[succ.as_usize()] > layers[block.as_usize()] {
+                format!(
+                    "M {} {} L {} {}",
+                    x + WIDTH / 2,
+                    y + HEIGHT,
+                    sx + WIDTH / 2,
+                    sy
+                )
+            } else {
+                let bend = x.max(sx) + WIDTH + GAP_X;
+                format!(
+                    "M {} {} C {} {} {} {} {} {}",
+                    x + WIDTH,
+                    y + HEIGHT / 2,
+                    bend,
+                    y + HEIGHT / 2,
+                    bend,
+                    sy + HEIGHT / 2,
+                    sx + WIDTH,
+                    sy + HEIGHT / 2
+                )
+            };
+            write!(
+                svg,
+                "<path d=\"{}\" fill=\"none\" stroke=\"#555\"{} marker-end=\"url(#arrow)\"/>",
+                path, dashed
+            )
+            .unwrap();
+        }
+    }
+    for (block, data) in body.basic_blocks.iter_enumerated() {
+        let (x, y) = position(block.as_usize());
+        let fill = if marked.contains(&block) {
+            "#fbb"
+        } else if data.is_cleanup {
+            "#eee"
+        } else {
+            "#def"
+        };
+        write!(
+            svg,
+            "<a href=\"#{}-bb{}\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{}\" stroke=\"#557\"/><text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-family=\"monospace\" font-size=\"12\">{:?}</text></a>",
+            prefix,
+            block.as_usize(),
+            x,
+            y,
+            WIDTH,
+            HEIGHT,
+            fill,
+            x + WIDTH / 2,
+            y + HEIGHT / 2 + 4,
+            block
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>");
+    svg
+}
@@ -0,0 +1,83 @@
+//! The HTML fragments of the explorer.
+
+use std::fmt::Write;
+
+pub const STYLE: &str = r#"
+body { margin: 0; font-family: sans-serif; font-size: 14px; color: #222; }
+nav { position: fixed; top: 0; bottom: 0; left: 0; width: 260px; overflow-y: auto;
+      background: #f4f4f4; border-right: 1px solid #ccc; padding: 8px; box-sizing: border-box; }
+nav a { display: block; padding: 2px 0; color: #225; text-decoration: none; word-break: break-all; }
+nav a.buggy { color: #b00; font-weight: bold; }
+main { margin-left: 270px; padding: 8px 16px; }
+section.fn { border-top: 2px solid #888; padding-top: 8px; margin-bottom: 32px; }
+h2 { font-size: 17px; margin: 4px 0; }
+h3 { font-size: 15px; margin: 12px 0 4px; }
+summary { cursor: pointer; font-weight: bold; margin: 8px 0 4px; }
+pre, code, table.mono { font-family: monospace; font-size: 13px; }
+pre { margin: 0; }
+.loc { color: #666; }
+table { border-collapse: collapse; }
+td, th { padding: 1px 8px; vertical-align: top; text-align: left; }
+tr:target, div:target, li:target { background: #fff3a0; }
+table.src td.no { color: #999; text-align: right; user-select: none; }
+table.src tr.buggy { background: #fde2e2; }
+table.src td.refs a, td.refs a { font-size: 11px; margin-right: 4px; }
+div.bb { border: 1px solid #ccc; margin: 4px 0; padding: 2px 6px; }
+div.bb.cleanup { border-style: dashed; color: #666; }
+div.bb .stmt.buggy { background: #fde2e2; }
+ul.findings li { color: #b00; }
+a { color: #225; }
+"#;
+
+/// Escape the text for HTML.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape the MIR text, and link the basic blocks, e.g., `bb3`, to their code and the locals,
+/// e.g., `_2`, to their dataflow nodes.
+pub fn link_mir(text: &str, prefix: &str, n_blocks: usize, n_locals: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut html = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let boundary = i == 0 || !(chars[i - 1].is_alphanumeric() || chars[i - 1] == '_');
+        let (name_len, kind) = if boundary && chars[i] == '_' {
+            (1, "n")
+        } else if boundary && chars[i..].starts_with(&['b', 'b']) {
+            (2, "bb")
+        } else {
+            (0, "")
+        };
+        let digits = chars[i + name_len..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        let end = i + name_len + digits;
+        let ends_word = end == chars.len() || !(chars[end].is_alphanumeric() || chars[end] == '_');
+        if name_len > 0 && digits > 0 && ends_word {
+            let word: String = chars[i..end].iter().collect();
+            let idx: usize = word[name_len..].parse().unwrap_or(usize::MAX);
+            let bound = if kind == "bb" { n_blocks } else { n_locals };
+            if idx < bound {
+                write!(html, "<a href=\"#{}-{}{}\">{}</a>", prefix, kind, idx, word).unwrap();
+                i = end;
+                continue;
+            }
+        }
+        html.push_str(&escape(&chars[i].to_string()));
+        i += 1;
+    }
+    html
+}
@@ -0,0 +1,567 @@
+//! A static HTML site to explore the MIR of the crate together with the analysis results.
+//!
+//! For each function, the page shows its source, its MIR, its control-flow graph, its dataflow
+//! graph with the calls stitched to other functions, the alias sets at the end of each basic block,
+//! the ranges of the places, and the findings of the detectors run together with `-explore`. They
+//! are cross-linked: a source line links to the MIR statements from it, a statement to its source
+//! line, a block or a local in the MIR to its code or dataflow node, and a call to its callee. The
+//! page of a crate is a single file without scripts or external resources, named after the crate
+//! and its type, e.g., `foo-rlib.html`; it is written to the directory given by `RAP_EXPLORE_DIR`,
+//! `rapx_explore` by default, with an index of the crates.
+
+pub mod cfg;
+pub mod html;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rustc_hir::{
+    def::DefKind,
+    def_id::{DefId, LOCAL_CRATE},
+};
+use rustc_middle::{
+    mir::{BasicBlock, Body, Location},
+    ty::TyCtxt,
+};
+use rustc_span::{source_map::get_source_map, Span};
+
+use crate::{
+    analysis::{
+        core::{
+            alias_analysis::{default::AliasAnalyzer, AliasAnalysis},
+            dataflow::{default::DataFlowAnalyzer, NodeOp},
+            range_analysis::{default::RangeAnalyzer, RangeAnalysis},
+        },
+        Analysis,
+    },
+    rap_info, rap_warn,
+    utils::{
+        boundary::guarded,
+        findings::{findings, Finding},
+        log::{are_spans_in_same_file, span_to_filename},
+    },
+};
+use cfg::{draw_cfg, ARROW};
+use html::{escape, link_mir, STYLE};
+
+pub struct Explore<'tcx> {
+    pub tcx: TyCtxt<'tcx>,
+}
+
+// The results shared by the pages of the functions.
+struct Results<'tcx> {
+    dataflow: DataFlowAnalyzer<'tcx>,
+    alias: AliasAnalyzer<'tcx>,
+    range: RangeAnalyzer<'tcx, i128>,
+    findings: Vec<Finding>,
+    // the anchors of the functions.
+    ids: HashMap<DefId, String>,
+}
+
+impl<'tcx> Explore<'tcx> {
+    pub fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Self { tcx }
+    }
+
+    pub fn name(&self) -> &'static str {
+        "Explore"
+    }
+
+    pub fn start(&self) {
+        let targets: Vec<DefId> = self
+            .tcx
+            .mir_keys(())
+            .iter()
+            .map(|local_def_id| local_def_id.to_def_id())
+            .filter(|def_id| self.is_target(*def_id))
+            .collect();
+        let mut dataflow = DataFlowAnalyzer::new(self.tcx, false);
+        dataflow.build_graphs();
+        let mut alias = AliasAnalyzer::new(self.tcx);
+        alias.run();
+        let mut range = RangeAnalyzer::<i128>::new(self.tcx, false);
+        range.run();
+        let results = Results {
+            dataflow,
+            alias,
+            range,
+            findings: findings(),
+            ids: targets
+                .iter()
+                .enumerate()
+                .map(|(n, def_id)| (*def_id, format!("f{}", n)))
+                .collect(),
+        };
+
+        let crate_name = self.tcx.crate_name(LOCAL_CRATE).to_string();
+        let mut nav = String::new();
+        let mut sections = String::new();
+        for def_id in targets.iter() {
+            let Some(section) =
+                guarded(self.name(), *def_id, || self.fn_section(&results, *def_id))
+            else {
+                continue;
+            };
+            let class = match self.fn_findings(&results, *def_id).is_empty() {
+                true => "",
+                false => " class=\"buggy\"",
+            };
+            write!(
+                nav,
+                "<a href=\"#{}\"{}>{}</a>",
+                results.ids[def_id],
+                class,
+                escape(&self.tcx.def_path_str(*def_id))
+            )
+            .unwrap();
+            sections.push_str(&section);
+        }
+        let page = format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{name} - RAPx explorer</title><style>{STYLE}</style></head>\n<body>{ARROW}<nav><a href=\"index.html\">All crates</a><h2>{name}</h2>{nav}</nav><main>{sections}</main></body></html>\n",
+            name = escape(&crate_name),
+        );
+
+        let dir = PathBuf::from(
+            std::env::var("RAP_EXPLORE_DIR").unwrap_or_else(|_| "rapx_explore".to_string()),
+        );
+        // The lib and bin targets of a package share the crate name.
+        let path = dir.join(format!("{}-{}.html", crate_name, self.tcx.crate_types()[0]));
+        let written = fs::create_dir_all(&dir)
+            .and_then(|_| fs::write(&path, page))
+            .and_then(|_| write_index(&dir));
+        match written {
+            Ok(()) => rap_info!("Explorer written to {}", path.display()),
+            Err(err) => rap_warn!("Cannot write the explorer to {}: {}", dir.display(), err),
+        }
+    }
+
+    fn is_target(&self, def_id: DefId) -> bool {
+        matches!(
+            self.tcx.def_kind(def_id),
+            DefKind::Fn | DefKind::AssocFn | DefKind::Closure
+        ) && self
+            .tcx
+            .hir_body_const_context(def_id.expect_local())
+            .is_none()
+            && self.tcx.is_mir_available(def_id)
+    }
+
+    fn fn_findings<'a>(&self, results: &'a Results<'tcx>, def_id: DefId) -> Vec<&'a Finding> {
+        let span = self.tcx.optimized_mir(def_id).span;
+        results
+            .findings
+            .iter()
+            .filter(|finding| {
+                are_spans_in_same_file(span, finding.span) && span.contains(finding.span)
+            })
+            .collect()
+    }
+
+    // The line of the span in the file of the function.
+    fn line_of(&self, body: &Body<'_>, span: Span) -> Option<usize> {
+        let span = span.source_callsite();
+        if span.is_dummy() || !are_spans_in_same_file(body.span, span) {
+            return None;
+        }
+        Some(get_source_map().unwrap().lookup_char_pos(span.lo()).line)
+    }
+
+    fn fn_section(&self, results: &Results<'tcx>, def_id: DefId) -> String {
+        let tcx = self.tcx;
+        let body = tcx.optimized_mir(def_id);
+        let id = &results.ids[&def_id];
+        let findings = self.fn_findings(results, def_id);
+        let source_map = get_source_map().unwrap();
+
+        // the statements from each source line, and the statements at fault.
+        let mut statements_of_line: HashMap<usize, Vec<(BasicBlock, usize)>> = HashMap::new();
+        let mut marked_statements = HashSet::new();
+        let mut marked_blocks = HashSet::new();
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            for idx in 0..=data.statements.len() {
+                let span = body
+                    .source_info(Location {
+                        block,
+                        statement_index: idx,
+                    })
+                    .span;
+                if let Some(line) = self.line_of(body, span) {
+                    statements_of_line
+                        .entry(line)
+                        .or_default()
+                        .push((block, idx));
+                }
+                let span = span.source_callsite();
+                if findings
+                    .iter()
+                    .any(|finding| finding.span.source_callsite().overlaps(span))
+                {
+                    marked_statements.insert((block, idx));
+                    marked_blocks.insert(block);
+                }
+            }
+        }
+        let marked_lines: HashSet<usize> = findings
+            .iter()
+            .filter_map(|finding| self.line_of(body, finding.span))
+            .collect();
+
+        let mut html = String::new();
+        write!(
+            html,
+            "<section class=\"fn\" id=\"{}\"><h2>{}</h2><div class=\"loc\">{}:{}</div>",
+            id,
+            escape(&tcx.def_path_str(def_id)),
+            escape(&span_to_filename(body.span)),
+            source_map.lookup_char_pos(body.span.lo()).line
+        )
+        .unwrap();
+
+        if !findings.is_empty() {
+            html.push_str("<h3>Findings</h3><ul class=\"findings\">");
+            for finding in findings.iter() {
+                html.push_str(&format!("<li>{}", escape(&finding.title)));
+                if let Some(line) = self.line_of(body, finding.span) {
+                    write!(html, " at <a href=\"#{}-L{}\">line {}</a>", id, line, line).unwrap();
+                }
+                html.push_str("</li>");
+            }
+            html.push_str("</ul>");
+        }
+
+        // the source lines.
+        let body_span = body.span.source_callsite();
+        if !body_span.from_expansion() {
+            let lo = source_map.lookup_char_pos(body_span.lo());
+            let hi = source_map.lookup_char_pos(body_span.hi());
+            html.push_str("<details open><summary>Source</summary><table class=\"src\">");
+            for line in lo.line..=hi.line {
+                let text = lo.file.get_line(line - 1).unwrap_or_default();
+                let class = match marked_lines.contains(&line) {
+                    true => " class=\"buggy\"",
+                    false => "",
+                };
+                let refs: String = statements_of_line
+                    .get(&line)
+                    .into_iter()
+                    .flatten()
+                    .map(|(block, idx)| {
+                        format!(
+                            "<a href=\"#{}-bb{}-{}\">{:?}[{}]</a>",
+                            id,
+                            block.as_usize(),
+                            idx,
+                            block,
+                            idx
+                        )
+                    })
+                    .collect();
+                write!(
+                    html,
+                    "<tr id=\"{}-L{}\"{}><td class=\"no\">{}</td><td><pre>{}</pre></td><td class=\"refs\">{}</td></tr>",
+                    id,
+                    line,
+                    class,
+                    line,
+                    escape(&text),
+                    refs
+                )
+                .unwrap();
+            }
+            html.push_str("</table></details>");
+        }
+
+        // the MIR.
+        let n_blocks = body.basic_blocks.len();
+        let n_locals = body.local_decls.len();
+        let link = |text: &str| link_mir(text, id, n_blocks, n_locals);
+        html.push_str("<details open><summary>MIR</summary><table class=\"mono\">");
+        let names: HashMap<usize, String> = body
+            .var_debug_info
+            .iter()
+            .filter_map(|info| match &info.value {
+                rustc_middle::mir::VarDebugInfoContents::Place(place)
+                    if place.projection.is_empty() =>
+                {
+                    Some((place.local.as_usize(), info.name.to_string()))
+                }
+                _ => None,
+            })
+            .collect();
+        for (local, decl) in body.local_decls.iter_enumerated() {
+            let name = names.get(&local.as_usize()).cloned().unwrap_or_default();
+            write!(
+                html,
+                "<tr><td><a href=\"#{}-n{}\">{:?}</a></td><td>{}</td><td>{}</td></tr>",
+                id,
+                local.as_usize(),
+                local,
+                escape(&decl.ty.to_string()),
+                escape(&name)
+            )
+            .unwrap();
+        }
+        html.push_str("</table>");
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            let class = match data.is_cleanup {
+                true => "bb cleanup",
+                false => "bb",
+            };
+            write!(
+                html,
+                "<div class=\"{}\" id=\"{}-bb{}\"><b>{:?}</b>{}",
+                class,
+                id,
+                block.as_usize(),
+                block,
+                if data.is_cleanup { " (cleanup)" } else { "" }
+            )
+            .unwrap();
+            let texts = data
+                .statements
+                .iter()
+                .map(|statement| format!("{:?}", statement))
+                .chain([format!("{:?}", data.terminator().kind)]);
+            for (idx, text) in texts.enumerate() {
+                let span = body
+                    .source_info(Location {
+                        block,
+                        statement_index: idx,
+                    })
+                    .span;
+                let class = match marked_statements.contains(&(block, idx)) {
+                    true => "stmt buggy",
+                    false => "stmt",
+                };
+                let line = match self.line_of(body, span) {
+                    Some(line) => {
+                        format!(" <a class=\"loc\" href=\"#{}-L{}\">L{}</a>", id, line, line)
+                    }
+                    None => String::new(),
+                };
+                write!(
+                    html,
+                    "<div class=\"{}\" id=\"{}-bb{}-{}\"><code>{}</code>{}</div>",
+                    class,
+                    id,
+                    block.as_usize(),
+                    idx,
+                    link(&text),
+                    line
+                )
+                .unwrap();
+            }
+            html.push_str("</div>");
+        }
+        html.push_str("</details>");
+
+        html.push_str("<details open><summary>Control-flow graph</summary>");
+        html.push_str(&draw_cfg(body, id, &marked_blocks));
+        html.push_str("</details>");
+
+        html.push_str(&self.dataflow_section(results, def_id, body));
+        html.push_str(&self.alias_section(results, def_id, body));
+
+        if let Some(ranges) = results.range.get_fn_range(def_id) {
+            let mut ranges: Vec<(String, String)> = ranges
+                .iter()
+                .map(|(place, range)| (format!("{:?}", place), range.to_string()))
+                .collect();
+            ranges.sort();
+            if !ranges.is_empty() {
+                html.push_str(
+                    "<details><summary>Ranges</summary><table class=\"mono\"><tr><th>place</th><th>interval</th></tr>",
+                );
+                for (place, range) in ranges {
+                    write!(
+                        html,
+                        "<tr><td>{}</td><td>{}</td></tr>",
+                        link(&place),
+                        escape(&range)
+                    )
+                    .unwrap();
+                }
+                html.push_str("</table></details>");
+            }
+        }
+        html.push_str("</section>");
+        html
+    }
+
+    fn dataflow_section(&self, results: &Results<'tcx>, def_id: DefId, body: &Body<'_>) -> String {
+        let tcx = self.tcx;
+        let id = &results.ids[&def_id];
+        let Some(graph) = results.dataflow.graphs.get(&def_id) else {
+            return String::new();
+        };
+        let fn_link = |def_id: DefId| match results.ids.get(&def_id) {
+            Some(id) => format!(
+                "<a href=\"#{}\">{}</a>",
+                id,
+                escape(&tcx.def_path_str(def_id))
+            ),
+            None => escape(&tcx.def_path_str(def_id)),
+        };
+        let node_name = |idx: usize| match idx < graph.n_locals {
+            true => format!("_{}", idx),
+            false => format!("m{}", idx),
+        };
+
+        let mut html = String::new();
+        html.push_str("<details><summary>Dataflow graph</summary>");
+        let calls: Vec<_> = results
+            .dataflow
+            .call_sites
+            .sites
+            .iter()
+            .filter(|site| site.caller == def_id)
+            .collect();
+        let callers: Vec<_> = results
+            .dataflow
+            .call_sites
+            .sites
+            .iter()
+            .filter(|site| site.callee == def_id)
+            .collect();
+        if !calls.is_empty() || !callers.is_empty() {
+            html.push_str("<ul>");
+            for site in calls {
+                write!(
+                    html,
+                    "<li><a href=\"#{}-n{}\">_{}</a> = call to {}</li>",
+                    id,
+                    site.dest.as_usize(),
+                    site.dest.as_usize(),
+                    fn_link(site.callee)
+                )
+                .unwrap();
+            }
+            for site in callers {
+                write!(html, "<li>called by {}</li>", fn_link(site.caller)).unwrap();
+            }
+            html.push_str("</ul>");
+        }
+        html.push_str(
+            "<table class=\"mono\"><tr><th>node</th><th>ops</th><th>line</th><th>flows to</th></tr>",
+        );
+        for (idx, node) in graph.nodes.iter_enumerated() {
+            let ops: Vec<String> = node
+                .ops
+                .iter()
+                .map(|op| match op {
+                    NodeOp::Call(callee) => format!("Call({})", fn_link(*callee)),
+                    _ => escape(&format!("{:?}", op)),
+                })
+                .collect();
+            let line = match self.line_of(body, node.span) {
+                Some(line) => format!("<a href=\"#{}-L{}\">L{}</a>", id, line, line),
+                None => String::new(),
+            };
+            let edges: Vec<String> = node
+                .out_edges
+                .iter()
+                .map(|edge_idx| {
+                    let edge = &graph.edges[*edge_idx];
+                    format!(
+                        "<a href=\"#{}-n{}\">{}</a> ({})",
+                        id,
+                        edge.dst.as_usize(),
+                        node_name(edge.dst.as_usize()),
+                        escape(&format!("{:?}", edge.op))
+                    )
+                })
+                .collect();
+            write!(
+                html,
+                "<tr id=\"{}-n{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                id,
+                idx.as_usize(),
+                node_name(idx.as_usize()),
+                ops.join(", "),
+                line,
+                edges.join(", ")
+            )
+            .unwrap();
+        }
+        html.push_str("</table></details>");
+        html
+    }
+
+    fn alias_section(&self, results: &Results<'tcx>, def_id: DefId, body: &Body<'_>) -> String {
+        let id = &results.ids[&def_id];
+        if !results.alias.alias_states.contains_key(&def_id) {
+            return String::new();
+        }
+        let mut rows = String::new();
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            let location = Location {
+                block,
+                statement_index: data.statements.len(),
+            };
+            let mut classes: Vec<String> = results
+                .alias
+                .get_alias_state(def_id, location)
+                .classes()
+                .iter()
+                .map(|class| {
+                    let places: Vec<String> = class.iter().map(|place| place.to_string()).collect();
+                    format!("{{{}}}", places.join(", "))
+                })
+                .collect();
+            if classes.is_empty() {
+                continue;
+            }
+            classes.sort();
+            write!(
+                rows,
+                "<tr><td><a href=\"#{}-bb{}\">{:?}</a></td><td>{}</td></tr>",
+                id,
+                block.as_usize(),
+                block,
+                link_mir(
+                    &classes.join(" "),
+                    id,
+                    body.basic_blocks.len(),
+                    body.local_decls.len()
+                )
+            )
+            .unwrap();
+        }
+        if rows.is_empty() {
+            return String::new();
+        }
+        format!(
+            "<details><summary>Alias sets at the end of the blocks</summary><table class=\"mono\">{}</table></details>",
+            rows
+        )
+    }
+}
+
+// List the pages of the crates in the directory.
+fn write_index(dir: &Path) -> std::io::Result<()> {
+    let mut pages: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".html") && name != "index.html")
+        .collect();
+    pages.sort();
+    let links: String = pages
+        .iter()
+        .map(|page| {
+            format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                escape(page),
+                escape(page.trim_end_matches(".html"))
+            )
+        })
+        .collect();
+    fs::write(
+        dir.join("index.html"),
+        format!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>RAPx explorer</title><style>{}</style></head>\n<body><main><h2>Crates</h2><ul>{}</ul></main></body></html>\n",
+            STYLE, links
+        ),
+    )
+}
@@ -1030,3 +1030,11 @@ pub fn reflect_generic<'tcx>(
 
 //     None
 // }
+
+/// The local of a copied or moved place, ignoring its projections.
+pub fn operand_local(operand: &Operand<'_>) -> Option<Local> {
+    match operand {
+        Operand::Copy(place) | Operand::Move(place) => Some(place.local),
+        Operand::Constant(_) => None,
+    }
+}
@@ -1,4 +1,5 @@
 pub mod def_path;
+pub mod explore;
 #[allow(unused)]
 pub mod fn_info;
 pub mod show_mir;
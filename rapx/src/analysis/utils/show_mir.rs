@@ -51,7 +51,7 @@ impl<'tcx> Display for TerminatorKind<'tcx> {
                 },
                 _ => (),
             },
-            TerminatorKind::TailCall { .. } => todo!(),
+            TerminatorKind::TailCall { .. } => s += "TailCall",
         };
         s
     }
@@ -5,20 +5,38 @@ Usage:
 RAPx Options:
 
 Application:
-    -F or -uaf      use-after-free/double free detection.
+    -F or -uaf      use-after-free/double free detection, including panic safety bugs and the
+                    dangling pointers left by cancelling a future at an await point.
     -M or -mleak    memory leakage detection.
     -O or -opt      automatically detect code optimization chances.
+    -fix            apply the machine-applicable fixes of -O to the source files, like
+                    `cargo clippy --fix`; it implies -O. The unreserved vectors and hash tables,
+                    the lowercase strings and the collections only measured by `len()` have
+                    fixes; lowercasing only ASCII characters is suggested but not applied.
     -I or -infer    (under development) infer the safety properties required by unsafe APIs.
     -V or -verify   (under development) verify if the safety requirements of unsafe API are satisfied.
+    -ffi            detect ownership bugs of heap objects passed across FFI boundaries.
+    -lock           detect lock guards held across blocking calls, allocating loops, unknown
+                    closures, awaits or other locks, and locks acquired in inconsistent orders.
+    -sendsync       detect unsound `unsafe impl Send/Sync` with missing bounds on generic types.
+    -taint          track untrusted values, e.g., read from the network or the environment, to
+                    pointer arithmetic, unchecked indexing and allocation sizes.
+    -transmute      detect transmutes and pointer casts that break size, alignment or validity rules.
+    -uninit         detect reads of, or references to, possibly uninitialized memory.
 
 Analysis:
     -alias          perform alias analysis (meet-over-paths by default)
+    -alias=steensgaard
+                    perform flow-insensitive unification-based alias analysis, which always
+                    terminates on large functions
     -adg            generate API dependency graphs
     -audit          (under development) generate unsafe code audit units
     -callgraph      generate callgraphs
     -dataflow       generate dataflow graphs
+    -explore        write an HTML page per crate to browse the MIR, CFG, dataflow graph, alias sets
+                    and ranges of each function, with the findings of the other options given
     -ownedheap      analyze if the type holds a piece of memory on heap
-    -pathcond       extract path constraints
+    -pathcond       extract path constraints and check whether each path is feasible
     -range          perform range analysis
     -scan           print basic information of the crate, e.g., the number of APIs.
 
@@ -55,6 +73,42 @@ Environment Variables (Values are case insensitive):
                       
                      NOTE: for shallow or deep, rapx will enter each member
                      folder to do the check.
+
+    RAP_FFI_CONFIG   path to a TOML file mapping foreign functions to their ownership
+                     semantics for -ffi: consumes, borrows, returns_owned, frees
+                     * functions not listed are assumed to borrow their arguments
+
+    RAP_OPT_RULES    paths to TOML files, or directories of them, of user rules for -O,
+                     separated like PATH
+                     * a rule matches calls on a value, e.g., a call on x after another in a
+                       loop without a third one, and reports its message under its category
+
+    RAP_OPT_SUGGESTIONS
+                     path to a file the fixes of -O are appended to, one rustc JSON diagnostic
+                     per line, which rustfix can apply
+                     * a fix replaces a span of the source, e.g., `Vec::new()` with
+                       `Vec::with_capacity(n)`
+
+    RAP_TAINT_CONFIG path to a TOML file of user sources, sinks and sanitizers for -taint
+                     * a source is a function returning, taking as the given parameter, or
+                       writing to the given argument, an untrusted value; a sink is a function
+                       and the argument it must not take
+                     * they extend the built-in ones, e.g., std::env::var and ptr::add
+
+    RAP_EXPLORE_DIR  directory of the pages of -explore, rapx_explore by default
+                     * one page per crate, <crate>.html, listed in index.html
+
+    ALIAS_BACKEND    alias analysis of -F and -V: mop, steensgaard
+                     * mop is the default value
+
+    ALIAS_DEPTH      field depth of -alias=steensgaard, 5 by default
+                     * fields and pointees deeper than the limit are merged into their parent
+
+    RANGE_CALL_DEPTH call-string depth of -range, 2 by default
+                     * callees deeper than the limit are represented by their summaries
+
+    PRUNE_PATHS      skip the paths proved infeasible by Z3: true, false
+                     * applies to -F, -V and -alias; false is the default value
 "#;
 
 pub const RAPX_VERSION: &str = r#"
@@ -35,11 +35,14 @@ fn main() {
             continue;
         }
         match arg.as_str() {
-            "-alias" | "-alias0" | "-alias1" | "-alias2" => compiler.enable_alias(arg),
+            "-alias" | "-alias0" | "-alias1" | "-alias2" | "-alias=mop" | "-alias=steensgaard" => {
+                compiler.enable_alias(arg)
+            }
             "-adg" => compiler.enable_api_dependency(), // api dependency graph
             "-callgraph" => compiler.enable_callgraph(),
             "-dataflow" => compiler.enable_dataflow(1),
             "-dataflow=debug" => compiler.enable_dataflow(2),
+            "-explore" => compiler.enable_explore(),
             "-ownedheap" => compiler.enable_ownedheap(),
             "-range" => compiler.enable_range_analysis(1),
             "-range=print_mir" => compiler.enable_range_analysis(2),
@@ -52,8 +55,15 @@ fn main() {
             "-O" | "-opt" => compiler.enable_opt(1),
             "-opt=all" => compiler.enable_opt(2),
             "-opt=report" => compiler.enable_opt(0),
+            "-fix" => compiler.enable_opt_fix(),
             "-scan" => compiler.enable_scan(),
+            "-ffi" => compiler.enable_ffi(),
+            "-lock" => compiler.enable_lock(),
+            "-sendsync" => compiler.enable_sendsync(),
             "-ssa" => compiler.enable_ssa_transform(),
+            "-taint" => compiler.enable_taint(),
+            "-transmute" => compiler.enable_transmute(),
+            "-uninit" => compiler.enable_uninit(),
             "-audit" => compiler.enable_unsafety_isolation(1),
             "-doc" => compiler.enable_unsafety_isolation(2),
             "-upg" => compiler.enable_unsafety_isolation(3),
@@ -6,7 +6,6 @@
 pub mod utils;
 pub mod analysis;
 pub mod def_id;
-pub mod preprocess;
 extern crate intervals;
 extern crate rustc_abi;
 extern crate rustc_ast;
@@ -29,35 +28,42 @@ extern crate rustc_traits;
 extern crate rustc_type_ir;
 extern crate thin_vec;
 use crate::analysis::scan::ScanAnalysis;
+use crate::analysis::utils::explore::Explore;
+use crate::utils::boundary::report_coverage;
 use analysis::{
     core::{
-        alias_analysis::{default::AliasAnalyzer, AAResultMapWrapper, AliasAnalysis},
+        alias_analysis::{AAResultMapWrapper, AliasBackend},
         api_dependency::ApiDependencyAnalyzer,
         callgraph::{default::CallGraphAnalyzer, CallGraphAnalysis, CallGraphDisplay},
         dataflow::{
             default::DataFlowAnalyzer, Arg2RetMapWrapper, DataFlowAnalysis, DataFlowGraphMapWrapper,
         },
-        ownedheap_analysis::{default::OwnedHeapAnalyzer, OHAResultMapWrapper, OwnedHeapAnalysis},
+        ownedheap_analysis::{
+            default::OwnedHeapAnalyzer, OHAResultMapWrapper, OwnedHeapAnalysis, TyHeapMapWrapper,
+        },
         range_analysis::{
-            default::RangeAnalyzer, PathConstraintMapWrapper, RAResultMapWrapper, RangeAnalysis,
+            default::RangeAnalyzer, FnSummaryMapWrapper, PathConstraintMapWrapper,
+            PathFeasibilityMapWrapper, RAResultMapWrapper, RangeAnalysis,
         },
         ssa_transform::SSATrans,
     },
+    ffi::FfiCheck,
+    lock::LockCheck,
     opt::Opt,
     rcanary::rCanary,
     safedrop::SafeDrop,
+    sendsync::SendSyncCheck,
     senryx::{CheckLevel, SenryxCheck},
+    taint::TaintCheck,
     test::Test,
+    transmute::TransmuteCheck,
+    uninit::UninitCheck,
     unsafety_isolation::{UigInstruction, UnsafetyIsolationCheck},
     utils::show_mir::ShowMir,
     Analysis,
 };
-use rustc_ast::ast;
 use rustc_driver::{Callbacks, Compilation};
-use rustc_interface::{
-    interface::{self, Compiler},
-    Config,
-};
+use rustc_interface::{interface::Compiler, Config};
 use rustc_middle::{ty::TyCtxt, util::Providers};
 use rustc_session::search_paths::PathKind;
 use std::path::PathBuf;
@@ -72,18 +78,27 @@ pub static RAP_DEFAULT_ARGS: &[&str] = &["-Zalways-encode-mir", "-Zmir-opt-level
 #[derive(Debug, Clone, Hash)]
 pub struct RapCallback {
     alias: bool,
+    alias_backend: AliasBackend,
     api_dependency: bool,
     callgraph: bool,
     dataflow: usize,
+    explore: bool,
+    ffi: bool,
+    lock: bool,
     ownedheap: bool,
     range: usize,
     ssa: bool,
     test: bool,
     infer: bool,
     opt: usize,
+    opt_fix: bool,
     rcanary: bool,
     safedrop: bool,
+    sendsync: bool,
     show_mir: bool,
+    taint: bool,
+    transmute: bool,
+    uninit: bool,
     unsafety_isolation: usize,
     verify: bool,
     verify_std: bool,
@@ -96,18 +111,27 @@ impl Default for RapCallback {
     fn default() -> Self {
         Self {
             alias: false,
+            alias_backend: AliasBackend::from_env(),
             api_dependency: false,
             callgraph: false,
             dataflow: 0,
+            explore: false,
+            ffi: false,
+            lock: false,
             ownedheap: false,
             range: 0,
             ssa: false,
             test: false,
             infer: false,
             opt: usize::MAX,
+            opt_fix: false,
             rcanary: false,
             safedrop: false,
+            sendsync: false,
             show_mir: false,
+            taint: false,
+            transmute: false,
+            uninit: false,
             unsafety_isolation: 0,
             verify: false,
             verify_std: false,
@@ -133,14 +157,6 @@ impl Callbacks for RapCallback {
         });
     }
 
-    fn after_crate_root_parsing(
-        &mut self,
-        _compiler: &interface::Compiler,
-        _krate: &mut ast::Crate,
-    ) -> Compilation {
-        preprocess::ssa_preprocess::create_ssa_struct(_krate);
-        Compilation::Continue
-    }
     fn after_analysis<'tcx>(&mut self, _compiler: &Compiler, tcx: TyCtxt<'tcx>) -> Compilation {
         rap_trace!("Execute after_analysis() of compiler callbacks");
 
@@ -180,9 +196,17 @@ impl RapCallback {
     /// strongly-connected component.
     /// -alias1: set field depth limit to 20 (this is default setting).
     /// -alias2: set field depth limit to 30.
+    /// -alias=steensgaard: use the flow-insensitive unification-based algorithm instead of MoP.
+    /// The detectors built on top of alias analysis select it with `ALIAS_BACKEND` instead.
     pub fn enable_alias(&mut self, arg: String) {
         self.alias = true;
         match arg.as_str() {
+            "-alias=mop" => {
+                self.alias_backend = AliasBackend::Mop;
+            }
+            "-alias=steensgaard" => {
+                self.alias_backend = AliasBackend::Steensgaard;
+            }
             "-alias" => {
                 env::set_var("ALIAS", "1");
             }
@@ -244,6 +268,26 @@ impl RapCallback {
         self.dataflow
     }
 
+    /// Enable the detection of ownership bugs across FFI boundaries.
+    pub fn enable_ffi(&mut self) {
+        self.ffi = true;
+    }
+
+    /// Test if the detection of ownership bugs across FFI boundaries is enabled.
+    pub fn is_ffi_enabled(&self) -> bool {
+        self.ffi
+    }
+
+    /// Enable the detection of lock guards held too long and inconsistent lock orders.
+    pub fn enable_lock(&mut self) {
+        self.lock = true;
+    }
+
+    /// Test if the detection of lock guards held too long is enabled.
+    pub fn is_lock_enabled(&self) -> bool {
+        self.lock
+    }
+
     /// Enable range analysis.
     pub fn enable_range_analysis(&mut self, x: usize) {
         self.range = x;
@@ -284,6 +328,19 @@ impl RapCallback {
         self.opt
     }
 
+    /// Enable applying the fixes of optimization analysis, which implies `-opt`.
+    pub fn enable_opt_fix(&mut self) {
+        if self.opt == usize::MAX {
+            self.opt = 1;
+        }
+        self.opt_fix = true;
+    }
+
+    /// Test if the fixes of optimization analysis are applied.
+    pub fn is_opt_fix_enabled(&self) -> bool {
+        self.opt_fix
+    }
+
     /// Enable rcanary for memory leakage detection.
     pub fn enable_rcanary(&mut self) {
         self.rcanary = true;
@@ -329,6 +386,16 @@ impl RapCallback {
         self.safedrop
     }
 
+    /// Enable the detection of unsound `unsafe impl Send/Sync` on generic types.
+    pub fn enable_sendsync(&mut self) {
+        self.sendsync = true;
+    }
+
+    /// Test if the detection of unsound `unsafe impl Send/Sync` is enabled.
+    pub fn is_sendsync_enabled(&self) -> bool {
+        self.sendsync
+    }
+
     /// Enable mir display.
     pub fn enable_show_mir(&mut self) {
         self.show_mir = true;
@@ -339,6 +406,36 @@ impl RapCallback {
         self.show_mir
     }
 
+    /// Enable the tracking of untrusted values to unsafe operations.
+    pub fn enable_taint(&mut self) {
+        self.taint = true;
+    }
+
+    /// Test if the tracking of untrusted values is enabled.
+    pub fn is_taint_enabled(&self) -> bool {
+        self.taint
+    }
+
+    /// Enable the detection of invalid transmutes and pointer casts.
+    pub fn enable_transmute(&mut self) {
+        self.transmute = true;
+    }
+
+    /// Test if the detection of invalid transmutes and pointer casts is enabled.
+    pub fn is_transmute_enabled(&self) -> bool {
+        self.transmute
+    }
+
+    /// Enable the detection of accesses to possibly uninitialized memory.
+    pub fn enable_uninit(&mut self) {
+        self.uninit = true;
+    }
+
+    /// Test if the detection of accesses to possibly uninitialized memory is enabled.
+    pub fn is_uninit_enabled(&self) -> bool {
+        self.uninit
+    }
+
     pub fn enable_unsafety_isolation(&mut self, x: usize) {
         self.unsafety_isolation = x;
     }
@@ -379,6 +476,16 @@ impl RapCallback {
         self.scan
     }
 
+    /// Enable the HTML explorer of the MIR and the analysis results.
+    pub fn enable_explore(&mut self) {
+        self.explore = true;
+    }
+
+    /// Test if the HTML explorer is enabled.
+    pub fn is_explore_enabled(&self) -> bool {
+        self.explore
+    }
+
     pub fn set_test_crate(&mut self, crate_name: impl ToString) {
         self.test_crate = Some(crate_name.to_string())
     }
@@ -386,10 +493,17 @@ impl RapCallback {
 
 /// Start the analysis with the features enabled.
 pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
+    if callback.is_explore_enabled() {
+        utils::findings::enable_findings();
+    }
     if callback.is_alias_enabled() {
-        let mut analyzer = AliasAnalyzer::new(tcx);
-        analyzer.run();
-        let alias = analyzer.get_local_fn_alias();
+        let alias = callback
+            .alias_backend
+            .run(tcx)
+            .into_iter()
+            .filter(|(def_id, _)| def_id.is_local())
+            .map(|(def_id, result)| (def_id, result.into()))
+            .collect();
         rap_info!("{}", AAResultMapWrapper(alias));
     }
 
@@ -440,6 +554,8 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
         analyzer.run();
         let result = analyzer.get_all_items();
         rap_info!("{}", OHAResultMapWrapper(result));
+        let instances = analyzer.local_instances();
+        rap_info!("{}", TyHeapMapWrapper(instances));
     }
 
     if callback.is_range_analysis_enabled() {
@@ -449,18 +565,24 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
                 analyzer.run();
                 let result = analyzer.get_all_fn_ranges();
                 rap_info!("{}", RAResultMapWrapper(result));
+                let summaries = analyzer.get_all_fn_summaries();
+                rap_info!("{}", FnSummaryMapWrapper(summaries));
             }
             2 => {
                 let mut analyzer = RangeAnalyzer::<i128>::new(tcx, true);
                 analyzer.run();
                 let result = analyzer.get_all_fn_ranges();
                 rap_info!("{}", RAResultMapWrapper(result));
+                let summaries = analyzer.get_all_fn_summaries();
+                rap_info!("{}", FnSummaryMapWrapper(summaries));
             }
             3 => {
                 let mut analyzer = RangeAnalyzer::<i128>::new(tcx, false);
                 analyzer.start_path_constraints_analysis();
                 let result = analyzer.get_all_path_constraints();
                 rap_info!("{}", PathConstraintMapWrapper(result));
+                let feasibility = analyzer.get_all_path_feasibility();
+                rap_info!("{}", PathFeasibilityMapWrapper(feasibility));
             }
             _ => {}
         }
@@ -472,9 +594,9 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     }
 
     match callback.is_opt_enabled() {
-        0 => Opt::new(tcx, 0).start(),
-        1 => Opt::new(tcx, 1).start(),
-        2 => Opt::new(tcx, 2).start(),
+        0 => Opt::new(tcx, 0, callback.is_opt_fix_enabled()).start(),
+        1 => Opt::new(tcx, 1, callback.is_opt_fix_enabled()).start(),
+        2 => Opt::new(tcx, 2, callback.is_opt_fix_enabled()).start(),
         _ => {}
     }
 
@@ -493,6 +615,30 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
         SafeDrop::new(tcx).start();
     }
 
+    if callback.is_lock_enabled() {
+        LockCheck::new(tcx).start();
+    }
+
+    if callback.is_ffi_enabled() {
+        FfiCheck::new(tcx).start();
+    }
+
+    if callback.is_sendsync_enabled() {
+        SendSyncCheck::new(tcx).start();
+    }
+
+    if callback.is_taint_enabled() {
+        TaintCheck::new(tcx).start();
+    }
+
+    if callback.is_transmute_enabled() {
+        TransmuteCheck::new(tcx).start();
+    }
+
+    if callback.is_uninit_enabled() {
+        UninitCheck::new(tcx).start();
+    }
+
     if callback.is_show_mir_enabled() {
         ShowMir::new(tcx).start();
     }
@@ -527,4 +673,11 @@ pub fn start_analyzer(tcx: TyCtxt, callback: &RapCallback) {
     if callback.is_scan_enabled() {
         ScanAnalysis::new(tcx).run();
     }
+
+    // after the detectors, whose findings are shown in the explorer.
+    if callback.is_explore_enabled() {
+        Explore::new(tcx).start();
+    }
+
+    report_coverage();
 }
@@ -0,0 +1,133 @@
+//! Failure boundaries of the per-function analyses.
+//!
+//! An analysis that hits a MIR construct it does not support should give up on that function only,
+//! instead of aborting the whole run. [`guarded`] runs the analysis of one function and catches any
+//! panic raised by it; the function is then reported as skipped together with the reason, and the
+//! analysis goes on with the next function. [`report_coverage`] prints how many functions every
+//! analysis covered at the end of the run.
+//!
+//! A panic raised while a rustc query is running poisons that query, so it is not caught: the run
+//! is aborted as without a boundary.
+
+use crate::{rap_info, rap_warn, utils::source::get_fn_name_byid};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::tls;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{hash_map::Entry, BTreeMap},
+    panic::{self, AssertUnwindSafe},
+    sync::{Mutex, Once},
+};
+
+#[derive(Default)]
+struct Coverage {
+    functions: FxHashSet<DefId>,
+    // the skipped functions, with their names and the reason of the first failure.
+    skipped: FxHashMap<DefId, (String, String)>,
+}
+
+static COVERAGE: Mutex<BTreeMap<&'static str, Coverage>> = Mutex::new(BTreeMap::new());
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    static IN_BOUNDARY: Cell<bool> = const { Cell::new(false) };
+    static IN_QUERY_FAILURE: Cell<bool> = const { Cell::new(false) };
+    static LAST_FAILURE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// The panic hook of rustc reports every panic as an internal compiler error; the panics caught by
+/// a boundary are recorded instead.
+fn install_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !IN_BOUNDARY.with(|flag| flag.get()) {
+                default_hook(info);
+                return;
+            }
+            if tls::with_context_opt(|icx| icx.is_some_and(|icx| icx.query.is_some())) {
+                IN_QUERY_FAILURE.with(|flag| flag.set(true));
+                default_hook(info);
+                return;
+            }
+            let payload = info.payload();
+            let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+                msg.to_string()
+            } else if let Some(msg) = payload.downcast_ref::<String>() {
+                msg.clone()
+            } else {
+                "unknown panic".to_string()
+            };
+            let reason = match info.location() {
+                Some(location) => format!("{} at {}", msg, location),
+                None => msg,
+            };
+            LAST_FAILURE.with(|failure| *failure.borrow_mut() = Some(reason));
+        }));
+    });
+}
+
+/// Run `f`, i.e., the analysis `analysis` of the function `def_id`, under a failure boundary.
+/// It returns `None` and records the function as skipped if `f` panics.
+pub fn guarded<R>(analysis: &'static str, def_id: DefId, f: impl FnOnce() -> R) -> Option<R> {
+    install_hook();
+    let fn_name = get_fn_name_byid(&def_id);
+    COVERAGE
+        .lock()
+        .unwrap()
+        .entry(analysis)
+        .or_default()
+        .functions
+        .insert(def_id);
+
+    let outer = IN_BOUNDARY.with(|flag| flag.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    IN_BOUNDARY.with(|flag| flag.set(outer));
+
+    match result {
+        Ok(result) => Some(result),
+        Err(payload) => {
+            if IN_QUERY_FAILURE.with(|flag| flag.get()) {
+                panic::resume_unwind(payload);
+            }
+            let reason = LAST_FAILURE
+                .with(|failure| failure.borrow_mut().take())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            let mut coverage = COVERAGE.lock().unwrap();
+            let skipped = &mut coverage.entry(analysis).or_default().skipped;
+            // iterative analyses may visit the function again; it is reported once.
+            if let Entry::Vacant(entry) = skipped.entry(def_id) {
+                rap_warn!("{}: {:?} skipped: {}", analysis, fn_name, reason);
+                entry.insert((fn_name, reason));
+            }
+            None
+        }
+    }
+}
+
+/// Print the number of functions analyzed and skipped by each analysis that ran under a boundary.
+pub fn report_coverage() {
+    let coverage = COVERAGE.lock().unwrap();
+    if coverage.is_empty() {
+        return;
+    }
+    let mut report = String::from("=== Analysis coverage ===\n");
+    for (analysis, coverage) in coverage.iter() {
+        let total = coverage.functions.len();
+        let skipped = coverage.skipped.len();
+        report.push_str(&format!(
+            "{}: {} of {} functions analyzed, {} skipped\n",
+            analysis,
+            total - skipped,
+            total,
+            skipped
+        ));
+        let mut skipped: Vec<&(String, String)> = coverage.skipped.values().collect();
+        skipped.sort();
+        for (fn_name, reason) in skipped {
+            report.push_str(&format!("  {:?} skipped: {}\n", fn_name, reason));
+        }
+    }
+    rap_info!("{}", report);
+}
@@ -0,0 +1,40 @@
+//! The findings of the detectors in the current run, collected for `-explore`.
+//!
+//! A detector records every bug it reports together with the span at fault; the explorer shows
+//! them in the functions whose bodies contain the spans.
+
+use rustc_span::Span;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// The title of the report, e.g., "Use after free detected.".
+    pub title: String,
+    pub span: Span,
+}
+
+static FINDINGS: Mutex<Vec<Finding>> = Mutex::new(Vec::new());
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Start recording the findings; nothing is recorded unless the explorer runs.
+pub fn enable_findings() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn record_finding(title: impl Into<String>, span: Span) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    FINDINGS.lock().unwrap().push(Finding {
+        title: title.into(),
+        span,
+    });
+}
+
+/// The findings recorded so far.
+pub fn findings() -> Vec<Finding> {
+    FINDINGS.lock().unwrap().clone()
+}
@@ -1,3 +1,5 @@
+use crate::utils::findings::record_finding;
+use annotate_snippets::{Level, Renderer, Snippet};
 use chrono::Local;
 use fern::colors::{Color, ColoredLevelConfig};
 use fern::{self, Dispatch};
@@ -150,3 +152,61 @@ pub fn are_spans_in_same_file(span1: Span, span2: Span) -> bool {
     let file2 = get_source_map().unwrap().lookup_source_file(span2.lo());
     file1.name == file2.name
 }
+
+/// A bug found in a function body, printed with a snippet of the body.
+pub struct BugReport {
+    pub title: String,
+    /// The span at fault and its label.
+    pub span: Span,
+    pub label: String,
+    /// Other spans explaining the bug, e.g., where the memory comes from, and their labels.
+    pub notes: Vec<(Span, String)>,
+    pub help: Option<String>,
+}
+
+/// Print the bugs found in the function whose body spans `body` after the warning naming the
+/// function. Functions of dependencies are skipped.
+pub fn report_fn_bugs(body: Span, warning: &str, bugs: &[BugReport]) {
+    if span_to_filename(body).contains(".cargo") {
+        return;
+    }
+    rap_warn!("{}", warning);
+    for bug in bugs.iter() {
+        print_bug_report(body, bug);
+    }
+}
+
+/// Record the bug and print it with a snippet of the function body. A span in another file,
+/// e.g., in a macro of another crate, cannot be shown in the snippet and is left out.
+pub fn print_bug_report(body: Span, bug: &BugReport) {
+    record_finding(bug.title.clone(), bug.span);
+    if !are_spans_in_same_file(body, bug.span) {
+        return;
+    }
+    let filename = span_to_filename(body);
+    let code_source = span_to_source_code(body);
+    let mut snippet = Snippet::source(&code_source)
+        .line_start(span_to_line_number(body))
+        .origin(&filename)
+        .fold(true)
+        .annotation(
+            Level::Warning
+                .span(relative_pos_range(body, bug.span))
+                .label(&bug.label),
+        );
+    for (span, label) in bug.notes.iter() {
+        if *span != body && are_spans_in_same_file(body, *span) && body.contains(*span) {
+            snippet = snippet.annotation(
+                Level::Info
+                    .span(relative_pos_range(body, *span))
+                    .label(label),
+            );
+        }
+    }
+    let mut message = Level::Warning.title(&bug.title).snippet(snippet);
+    if let Some(help) = &bug.help {
+        message = message.footer(Level::Help.title(help));
+    }
+    let renderer = Renderer::styled();
+    println!("{}", renderer.render(message));
+}
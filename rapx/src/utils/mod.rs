@@ -1,3 +1,5 @@
+pub mod boundary;
+pub mod findings;
 pub mod fs;
 #[macro_use]
 pub mod log;
@@ -0,0 +1,25 @@
+#![allow(dead_code)]
+#![allow(unused_variables)]
+#![allow(clippy::borrowed_box)]
+
+// The pointee of the argument, not the argument itself.
+pub fn load(pp: *mut *mut String) -> *mut String {
+    unsafe { *pp }
+}
+
+// The object behind two levels of references.
+pub fn deref_twice<'a>(pp: &'a &'a mut String) -> &'a String {
+    &**pp
+}
+
+// The inner box of a box.
+pub fn inner(b: &Box<Box<String>>) -> &Box<String> {
+    &**b
+}
+
+// The pointer itself.
+pub fn same<'a>(pp: &'a mut &'a mut String) -> &'a mut &'a mut String {
+    pp
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+// Each call rotates the arguments, so the summary of `rotate` gains one alias per round of the
+// fixed-point iteration: (0,1), then (0,2) and (0,3).
+fn rotate<'a>(
+    a: &'a mut Vec<i32>,
+    b: &'a mut Vec<i32>,
+    c: &'a mut Vec<i32>,
+    n: usize,
+) -> &'a mut Vec<i32> {
+    if n == 0 {
+        a
+    } else {
+        rotate_again(b, c, a, n - 1)
+    }
+}
+
+// Mutually recursive with `rotate`.
+fn rotate_again<'a>(
+    a: &'a mut Vec<i32>,
+    b: &'a mut Vec<i32>,
+    c: &'a mut Vec<i32>,
+    n: usize,
+) -> &'a mut Vec<i32> {
+    rotate(a, b, c, n)
+}
+
+fn main() {
+    let mut a = vec![1];
+    let mut b = vec![2];
+    let mut c = vec![3];
+    rotate(&mut a, &mut b, &mut c, 5).push(4);
+}
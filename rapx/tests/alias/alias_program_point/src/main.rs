@@ -0,0 +1,13 @@
+// The return value aliases one argument on each branch, and both after the branches join.
+fn choose<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>, first: bool) -> &'a Vec<i32> {
+    if first {
+        return a;
+    }
+    b
+}
+
+fn main() {
+    let a = vec![1];
+    let b = vec![2];
+    choose(&a, &b, true);
+}
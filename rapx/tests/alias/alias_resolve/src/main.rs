@@ -0,0 +1,64 @@
+trait Pick {
+    fn pick<'a>(&self, a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32>;
+}
+
+struct First;
+struct Second;
+
+impl Pick for First {
+    fn pick<'a>(&self, a: &'a Vec<i32>, _b: &'a Vec<i32>) -> &'a Vec<i32> {
+        a
+    }
+}
+
+impl Pick for Second {
+    fn pick<'a>(&self, _a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+        b
+    }
+}
+
+fn first<'a>(a: &'a Vec<i32>, _b: &'a Vec<i32>) -> &'a Vec<i32> {
+    a
+}
+
+fn apply<'a, P: Pick>(p: &P, a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    p.pick(a, b)
+}
+
+//Expected alias analysis result: (0,1)
+fn via_trait<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    First.pick(a, b)
+}
+
+//Expected alias analysis result: (0,2)
+fn via_trait_second<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    Second.pick(a, b)
+}
+
+//Expected alias analysis result: (0,2)
+fn via_generic<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    apply(&Second, a, b)
+}
+
+//Expected alias analysis result: (0,1), (1,2), (0,2)
+//The tuple of the untupled arguments aliases both of them.
+fn via_closure<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    let f = |x: &'a Vec<i32>, _y: &'a Vec<i32>| x;
+    f(a, b)
+}
+
+//Expected alias analysis result: (0,1)
+fn via_fn_ptr<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    let f: fn(&'a Vec<i32>, &'a Vec<i32>) -> &'a Vec<i32> = first;
+    f(a, b)
+}
+
+fn main() {
+    let a = vec![1];
+    let b = vec![2];
+    via_trait(&a, &b);
+    via_trait_second(&a, &b);
+    via_generic(&a, &b);
+    via_closure(&a, &b);
+    via_fn_ptr(&a, &b);
+}
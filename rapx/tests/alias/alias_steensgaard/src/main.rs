@@ -0,0 +1,54 @@
+#![allow(dead_code)]
+
+// A state machine with more paths than MoP visits. The argument returned at the end is only
+// assigned on the last transitions.
+fn machine<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>, input: &[u8]) -> &'a Vec<i32> {
+    let mut r = a;
+    let mut state = 0;
+    for c in input {
+        state = match (state, c) {
+            (0, b'a') => 1,
+            (1, b'b') => 2,
+            (2, b'c') => 3,
+            (3, b'd') => 4,
+            (4, b'e') => 5,
+            (5, b'f') => 6,
+            (6, b'g') => 7,
+            (7, b'h') => 8,
+            (8, b'i') => 9,
+            (9, b'j') => 10,
+            (10, b'k') => 11,
+            (11, b'l') => {
+                r = b;
+                12
+            }
+            _ => 0,
+        };
+    }
+    r
+}
+
+struct Point {
+    x: Vec<i32>,
+    y: Vec<i32>,
+}
+
+fn field(p: &Point) -> &Vec<i32> {
+    if p.x.is_empty() {
+        &p.y
+    } else {
+        &p.x
+    }
+}
+
+fn first<'a>(a: &'a Vec<i32>, _b: &'a Vec<i32>) -> &'a Vec<i32> {
+    a
+}
+
+// The callee is resolved through the function pointer.
+fn via_fn_ptr<'a>(a: &'a Vec<i32>, b: &'a Vec<i32>) -> &'a Vec<i32> {
+    let f: fn(&'a Vec<i32>, &'a Vec<i32>) -> &'a Vec<i32> = first;
+    f(a, b)
+}
+
+fn main() {}
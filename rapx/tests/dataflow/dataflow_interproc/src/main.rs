@@ -0,0 +1,82 @@
+struct Pair {
+    len: usize,
+    cap: usize,
+}
+
+fn constant(_n: usize) -> usize {
+    16
+}
+
+fn identity(n: usize) -> usize {
+    n
+}
+
+// The argument is returned by the callee.
+fn through_identity(n: usize) -> usize {
+    identity(n)
+}
+
+// The argument is dropped by the callee.
+fn through_constant(n: usize) -> usize {
+    constant(n)
+}
+
+fn get_len(pair: Pair) -> usize {
+    pair.len
+}
+
+// Only the field `len` is returned by the callee.
+fn len_of(len: usize, cap: usize) -> usize {
+    get_len(Pair { len, cap })
+}
+
+// The return value of the first call does not flow back to the second call.
+fn pick(a: usize, b: usize) -> usize {
+    let _unused = identity(b);
+    identity(a)
+}
+
+trait Size {
+    fn size(&self, n: usize) -> usize;
+}
+
+struct Fixed;
+
+impl Size for Fixed {
+    fn size(&self, _n: usize) -> usize {
+        8
+    }
+}
+
+struct Echo;
+
+impl Size for Echo {
+    fn size(&self, n: usize) -> usize {
+        n
+    }
+}
+
+// The calls to the trait method are resolved to the impls.
+fn echo_size(echo: &Echo, n: usize) -> usize {
+    echo.size(n)
+}
+
+fn fixed_size(fixed: &Fixed, n: usize) -> usize {
+    fixed.size(n)
+}
+
+// The arguments of a closure are passed in a tuple.
+fn apply(n: usize, m: usize) -> usize {
+    let second = |_x: usize, y: usize| y;
+    second(n, m)
+}
+
+fn main() {
+    println!("{}", through_identity(1));
+    println!("{}", through_constant(1));
+    println!("{}", len_of(1, 2));
+    println!("{}", pick(1, 2));
+    println!("{}", echo_size(&Echo, 1));
+    println!("{}", fixed_size(&Fixed, 1));
+    println!("{}", apply(1, 2));
+}
@@ -0,0 +1,7 @@
+pub fn squares(n: usize) -> Vec<usize> {
+    let mut v = Vec::new();
+    for i in 0..n {
+        v.push(i * i);
+    }
+    v
+}
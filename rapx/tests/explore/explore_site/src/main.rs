@@ -0,0 +1,24 @@
+struct Counter {
+    total: u32,
+}
+
+fn sum_below(limit: u32) -> u32 {
+    let mut counter = Counter { total: 0 };
+    for i in 0..limit {
+        counter.total += i;
+    }
+    counter.total
+}
+
+fn first_or_zero(values: &[u32]) -> u32 {
+    match values.first() {
+        Some(value) => *value,
+        None => 0,
+    }
+}
+
+fn main() {
+    let values = vec![sum_below(10), 3];
+    let double = |x: u32| x * 2;
+    println!("{}", double(first_or_zero(&values)));
+}
@@ -0,0 +1,141 @@
+#![feature(vec_into_raw_parts)]
+#![allow(dead_code)]
+
+use std::ffi::{c_char, c_void, CString};
+
+extern "C" {
+    fn free(ptr: *mut c_void);
+    fn malloc(size: usize) -> *mut c_void;
+    fn strdup(s: *const c_char) -> *mut c_char;
+    fn puts(s: *const c_char) -> i32;
+    fn process(data: *mut u32, len: usize);
+    fn register_callback_data(data: *mut c_void);
+}
+
+// The string is handed to C, which only borrows it, and is never reclaimed.
+fn leak_to_c() {
+    let s = CString::new("hello").unwrap();
+    let p = s.into_raw();
+    unsafe {
+        puts(p);
+    }
+}
+
+// The pointer is reclaimed by `Box::from_raw`, which does not match `CString::into_raw`.
+fn mismatched_reclaim() {
+    let s = CString::new("hello").unwrap();
+    let p = s.into_raw();
+    unsafe {
+        puts(p);
+        let _b = Box::from_raw(p);
+    }
+}
+
+fn double_reclaim() {
+    let b = Box::new(42u32);
+    let p = Box::into_raw(b);
+    unsafe {
+        process(p, 1);
+        drop(Box::from_raw(p));
+        drop(Box::from_raw(p));
+    }
+}
+
+// Memory allocated by the Rust allocator must not be freed by `free`.
+fn rust_freed_by_c() {
+    let b = Box::new(1u32);
+    let p = Box::into_raw(b);
+    unsafe {
+        process(p, 1);
+        free(p as *mut c_void);
+    }
+}
+
+// Memory returned by C must be freed by C.
+fn c_freed_by_rust() {
+    unsafe {
+        let p = strdup(c"hello".as_ptr());
+        drop(CString::from_raw(p));
+    }
+}
+
+fn vec_leak() {
+    let v = vec![1u32, 2, 3];
+    let (ptr, len, _cap) = v.into_raw_parts();
+    unsafe {
+        process(ptr, len);
+    }
+}
+
+// The data is consumed by C, so reclaiming it afterwards takes ownership twice.
+fn reclaim_after_consume() {
+    let b = Box::new(7u32);
+    let p = Box::into_raw(b) as *mut c_void;
+    unsafe {
+        register_callback_data(p);
+        drop(Box::from_raw(p as *mut u32));
+    }
+}
+
+// The following functions are sound.
+fn roundtrip_cstring() {
+    let s = CString::new("hello").unwrap();
+    let p = s.into_raw();
+    unsafe {
+        puts(p);
+        drop(CString::from_raw(p));
+    }
+}
+
+fn roundtrip_vec() {
+    let v = vec![1u32, 2, 3];
+    let (ptr, len, cap) = v.into_raw_parts();
+    unsafe {
+        process(ptr, len);
+        drop(Vec::from_raw_parts(ptr, len, cap));
+    }
+}
+
+fn consumed_by_c() {
+    let b = Box::new(7u32);
+    let p = Box::into_raw(b) as *mut c_void;
+    unsafe {
+        register_callback_data(p);
+    }
+}
+
+fn c_freed_by_c() {
+    unsafe {
+        let p = malloc(16);
+        free(p);
+    }
+}
+
+fn returned_to_caller() -> *mut u32 {
+    let b = Box::new(3u32);
+    let p = Box::into_raw(b);
+    unsafe {
+        process(p, 1);
+    }
+    p
+}
+
+// A user type whose `into_raw` shares its name with the std APIs but owns no allocation.
+struct BoxPool {
+    slot: u8,
+}
+
+impl BoxPool {
+    fn into_raw(mut self) -> *mut u8 {
+        &mut self.slot as *mut u8
+    }
+}
+
+fn own_into_raw() {
+    let p = BoxPool { slot: 1 }.into_raw();
+    unsafe {
+        puts(p as *const c_char);
+    }
+}
+
+fn main() {}
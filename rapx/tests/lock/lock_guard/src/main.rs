@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+use std::time::Duration;
+
+struct Store {
+    log: Mutex<Vec<String>>,
+    cache: RwLock<Vec<u32>>,
+    accounts: Mutex<u64>,
+    audit: Mutex<u64>,
+}
+
+impl Store {
+    // bug: the file is written with the log locked.
+    fn flush(&self, file: &mut File) {
+        let log = self.log.lock().unwrap();
+        for line in log.iter() {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+    }
+
+    // fine: the log is copied out of the lock before writing it.
+    fn flush_copy(&self, file: &mut File) {
+        let lines = self.log.lock().unwrap().clone();
+        for line in lines.iter() {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+    }
+
+    // fine: the guard is dropped before writing.
+    fn flush_len(&self, file: &mut File) {
+        let log = self.log.lock().unwrap();
+        let len = log.len();
+        drop(log);
+        file.write_all(&len.to_le_bytes()).unwrap();
+    }
+
+    // bug: the cache grows in a loop with the write lock held.
+    fn fill(&self, n: u32) {
+        let mut cache = self.cache.write().unwrap();
+        for i in 0..n {
+            cache.push(i);
+        }
+    }
+
+    // bug: the callback may do anything with the log locked.
+    fn for_each_line<F: Fn(&str)>(&self, f: F) {
+        let log = self.log.lock().unwrap();
+        for line in log.iter() {
+            f(line);
+        }
+    }
+
+    // `accounts` is locked before `audit`.
+    fn transfer(&self, amount: u64) {
+        let mut accounts = self.accounts.lock().unwrap();
+        let mut audit = self.audit.lock().unwrap();
+        *accounts += amount;
+        *audit += 1;
+    }
+
+    // `audit` is locked before `accounts`, which is locked by the callee.
+    fn record(&self) {
+        let mut audit = self.audit.lock().unwrap();
+        *audit += 1;
+        self.bump_accounts();
+    }
+
+    fn bump_accounts(&self) {
+        *self.accounts.lock().unwrap() += 1;
+    }
+
+    // fine: `wait` releases the guard moved into it.
+    fn wait_log(&self, ready: &Condvar) {
+        let mut log = self.log.lock().unwrap();
+        while log.is_empty() {
+            log = ready.wait(log).unwrap();
+        }
+    }
+
+    // fine: the log is written into memory.
+    fn buffer_log(&self) -> Vec<u8> {
+        let log = self.log.lock().unwrap();
+        let mut buffer = Vec::new();
+        for line in log.iter() {
+            buffer.write_all(line.as_bytes()).unwrap();
+        }
+        buffer
+    }
+
+    // fine: an I/O error is only constructed.
+    fn check_log(&self) -> std::io::Result<usize> {
+        let log = self.log.lock().unwrap();
+        if log.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::Other, "empty log"));
+        }
+        Ok(log.len())
+    }
+}
+
+// bug: the thread sleeps with the cell borrowed.
+fn slow_push(cell: &RefCell<Vec<u8>>) {
+    let mut v = cell.borrow_mut();
+    std::thread::sleep(Duration::from_millis(1));
+    v.push(1);
+}
+
+async fn tick() {}
+
+// bug: the guard is held while the future is suspended.
+async fn update(counter: Arc<Mutex<u32>>) {
+    let mut guard = counter.lock().unwrap();
+    tick().await;
+    *guard += 1;
+}
+
+fn main() {
+    let store = Store {
+        log: Mutex::new(vec!["a".to_string()]),
+        cache: RwLock::new(Vec::new()),
+        accounts: Mutex::new(0),
+        audit: Mutex::new(0),
+    };
+    let mut file = File::create("/dev/null").unwrap();
+    store.flush(&mut file);
+    store.flush_copy(&mut file);
+    store.flush_len(&mut file);
+    store.fill(3);
+    store.for_each_line(|line| println!("{}", line));
+    store.transfer(1);
+    store.record();
+    store.wait_log(&Condvar::new());
+    println!("{:?} {:?}", store.buffer_log(), store.check_log());
+    slow_push(&RefCell::new(Vec::new()));
+    let _ = update(Arc::new(Mutex::new(0)));
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+fn constant_name(n: usize) -> usize {
+    let mut total = 0;
+    for _ in 0..n {
+        let name = "header".to_string();
+        total += name.len();
+    }
+    total
+}
+
+fn invariant_format(prefix: &str, n: usize) -> usize {
+    let mut total = 0;
+    for _ in 0..n {
+        let key = format!("{}-key", prefix);
+        total += key.len();
+    }
+    total
+}
+
+fn fresh_buffer(lines: &[&str]) -> usize {
+    let mut total = 0;
+    for line in lines {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(line.as_bytes());
+        total += buf.len();
+    }
+    total
+}
+
+fn fresh_map(keys: &[u32]) -> usize {
+    let mut total = 0;
+    for key in keys {
+        let mut seen = HashMap::new();
+        seen.insert(*key, 1);
+        total += seen.len();
+    }
+    total
+}
+
+fn variant_format(n: usize) -> usize {
+    let mut total = 0;
+    for i in 0..n {
+        let key = format!("{}-key", i);
+        total += key.len();
+    }
+    total
+}
+
+fn collected(n: usize) -> Vec<String> {
+    let mut names = Vec::new();
+    for _ in 0..n {
+        names.push("header".to_string());
+    }
+    names
+}
+
+fn main() {
+    println!(
+        "{} {} {} {} {} {:?}",
+        constant_name(2),
+        invariant_format("a", 2),
+        fresh_buffer(&["a"]),
+        fresh_map(&[1]),
+        variant_format(2),
+        collected(2)
+    );
+}
@@ -0,0 +1,65 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+fn count_words(words: &[String]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in words {
+        if !counts.contains_key(word) {
+            counts.insert(word.clone(), 0);
+        }
+    }
+    counts
+}
+
+fn cached_square(cache: &mut BTreeMap<u64, u64>, n: u64) -> u64 {
+    match cache.get(&n) {
+        Some(v) => *v,
+        None => {
+            cache.insert(n, n * n);
+            n * n
+        }
+    }
+}
+
+fn replace_name(names: &mut HashMap<u32, String>, id: u32, name: String) {
+    names.remove(&id);
+    names.insert(id, name);
+}
+
+fn first_visit(seen: &mut HashSet<u32>, id: u32) -> bool {
+    if seen.contains(&id) {
+        return false;
+    }
+    seen.insert(id);
+    true
+}
+
+fn count_with_entry(words: &[String]) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for word in words {
+        *counts.entry(word.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn update_other_key(cache: &mut HashMap<u64, u64>, a: u64, b: u64) {
+    if cache.get(&a).is_some() {
+        cache.insert(b, a);
+    }
+}
+
+fn main() {
+    let words = vec!["a".to_string(), "b".to_string()];
+    let mut cache = BTreeMap::new();
+    let mut names = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut other = HashMap::new();
+    update_other_key(&mut other, 1, 2);
+    replace_name(&mut names, 1, "a".to_string());
+    println!(
+        "{:?} {:?} {} {}",
+        count_words(&words),
+        count_with_entry(&words),
+        cached_square(&mut cache, 3),
+        first_visit(&mut seen, 1)
+    );
+}
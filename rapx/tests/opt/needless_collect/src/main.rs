@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+fn count_even(v: &[i32]) -> usize {
+    v.iter().filter(|x| **x % 2 == 0).collect::<Vec<_>>().len()
+}
+
+fn sum_doubled(v: &[i32]) -> i32 {
+    let doubled: Vec<i32> = v.iter().map(|x| x * 2).collect();
+    let mut sum = 0;
+    for x in doubled.iter() {
+        sum += x;
+    }
+    sum
+}
+
+fn append_digits(s: &mut String, v: &[u32]) {
+    s.push_str(&v.iter().map(|x| x.to_string()).collect::<String>());
+}
+
+fn extend_positive(out: &mut Vec<i32>, v: &[i32]) {
+    out.extend(v.iter().filter(|x| **x > 0).copied().collect::<Vec<_>>());
+}
+
+fn distinct(v: &[i32]) -> usize {
+    v.iter().collect::<HashSet<_>>().len()
+}
+
+fn sorted(v: &[i32]) -> Vec<i32> {
+    let mut sorted: Vec<i32> = v.iter().copied().collect();
+    sorted.sort();
+    sorted
+}
+
+fn twice(v: &[i32]) -> i32 {
+    let kept: Vec<i32> = v.iter().copied().collect();
+    kept.iter().sum::<i32>() + kept.len() as i32
+}
+
+fn main() {
+    let v = vec![1, 2, 3];
+    let mut s = String::new();
+    let mut out = Vec::new();
+    append_digits(&mut s, &[1, 2]);
+    extend_positive(&mut out, &v);
+    println!(
+        "{} {} {} {} {:?} {}",
+        count_even(&v),
+        sum_doubled(&v),
+        distinct(&v),
+        s,
+        sorted(&v),
+        twice(&v)
+    );
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+fn squares(n: usize) -> Vec<usize> {
+    let mut v = Vec::new();
+    for i in 0..n {
+        v.push(i * i);
+    }
+    v
+}
+
+fn lengths(words: &[String], out: &mut Vec<usize>) {
+    for word in words.iter() {
+        out.push(word.len());
+    }
+}
+
+fn same_name(a: &str, b: &String) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+fn is_not_yes(a: &str) -> bool {
+    a.to_lowercase() != "yes"
+}
+
+fn normalize(a: &str) -> String {
+    a.to_lowercase()
+}
+
+fn by_length(words: &[String]) -> HashMap<usize, bool> {
+    let mut m = HashMap::new();
+    for word in words.iter() {
+        m.insert(word.len(), word.is_empty());
+    }
+    m
+}
+
+fn count_long(words: &[String]) -> usize {
+    words.iter().filter(|w| w.len() > 3).collect::<Vec<_>>().len()
+}
+
+fn main() {
+    let words = vec!["Foo".to_string(), "bar".to_string()];
+    let mut out = Vec::new();
+    lengths(&words, &mut out);
+    println!("{:?} {:?}", squares(4), out);
+    println!(
+        "{} {} {}",
+        same_name("foo", &words[0]),
+        is_not_yes("Yes"),
+        normalize("BAR")
+    );
+    println!("{:?} {}", by_length(&words), count_long(&words));
+}
@@ -0,0 +1,63 @@
+pub struct Pool {
+    conns: Vec<u32>,
+    warm: bool,
+}
+
+impl Pool {
+    pub fn new() -> Self {
+        Pool {
+            conns: Vec::new(),
+            warm: false,
+        }
+    }
+
+    pub fn warm_up(&mut self) {
+        self.warm = true;
+    }
+
+    pub fn connect(&self) -> u32 {
+        if self.warm {
+            self.conns.len() as u32
+        } else {
+            0
+        }
+    }
+}
+
+fn cold_connect(n: u32) -> u32 {
+    let pool = Pool::new();
+    let mut sum = 0;
+    for _ in 0..n {
+        sum += pool.connect();
+    }
+    sum
+}
+
+fn warm_connect(n: u32) -> u32 {
+    let mut pool = Pool::new();
+    pool.warm_up();
+    let mut sum = 0;
+    for _ in 0..n {
+        sum += pool.connect();
+    }
+    sum
+}
+
+fn late_warm_connect(n: u32) -> u32 {
+    let mut pool = Pool::new();
+    let mut sum = 0;
+    for _ in 0..n {
+        sum += pool.connect();
+    }
+    pool.warm_up();
+    sum
+}
+
+fn single_connect() -> u32 {
+    let pool = Pool::new();
+    pool.connect()
+}
+
+fn main() {
+    println!("{}", cold_connect(3) + warm_connect(3) + late_warm_connect(3) + single_connect());
+}
@@ -0,0 +1,20 @@
+#![feature(explicit_tail_calls)]
+#![allow(incomplete_features)]
+
+fn countdown(n: u32) -> u32 {
+    if n == 0 {
+        return 0;
+    }
+    become countdown(n - 1)
+}
+
+fn make_adder(base: i32) -> i32 {
+    let add = async move |x: i32| x + base;
+    drop(add);
+    base
+}
+
+fn main() {
+    countdown(3);
+    make_adder(1);
+}
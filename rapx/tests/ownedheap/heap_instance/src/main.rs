@@ -0,0 +1,35 @@
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+use std::mem::ManuallyDrop;
+
+struct Wrapper<T> {
+    inner: T,
+    len: usize,
+}
+
+struct Pair<A, B> {
+    left: A,
+    right: B,
+}
+
+// The enum field stops the per-ADT propagation.
+struct Holder {
+    items: Option<Vec<u8>>,
+}
+
+fn main() {
+    let a: Option<Vec<u8>> = Some(vec![1]);
+    let b: Option<u32> = None;
+    let c = Wrapper {
+        inner: Box::new(1u8),
+        len: 1,
+    };
+    let d = Wrapper { inner: 1u32, len: 0 };
+    let e = Pair {
+        left: 1u8,
+        right: String::new(),
+    };
+    let f = Holder { items: None };
+    let g = ManuallyDrop::new(vec![1u8]);
+}
@@ -0,0 +1,34 @@
+fn twice(x: Option<u32>) -> u32 {
+    let mut total = 0;
+    if let Some(v) = x {
+        total += v;
+    }
+    if let Some(v) = x {
+        total += v;
+    }
+    total
+}
+
+fn bounds(a: u8) -> u8 {
+    if a > 200 {
+        if a < 100 {
+            return 1;
+        }
+        return 2;
+    }
+    3
+}
+
+fn count(n: u32) -> u32 {
+    let mut i = 0;
+    while i < 10 {
+        i += n;
+    }
+    i
+}
+
+fn main() {
+    println!("{}", twice(Some(1)));
+    println!("{}", bounds(250));
+    println!("{}", count(3));
+}
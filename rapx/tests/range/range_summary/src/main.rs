@@ -0,0 +1,24 @@
+fn main() {
+    let mut i = 0;
+    while i < 10 {
+        let idx = slot(i);
+        let _next = slot(idx);
+        i += 1;
+    }
+    let _low = descend(9);
+}
+// The return range is a function of the argument range.
+fn slot(i: usize) -> usize {
+    offset(i) * 2
+}
+fn offset(i: usize) -> usize {
+    i + 3
+}
+// A recursive helper, whose return range is bounded by its base case.
+fn descend(n: usize) -> usize {
+    if n < 5 {
+        n
+    } else {
+        descend(n - 1)
+    }
+}
@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+// Unsound: `get` hands out `&T` from `&self` but `T: Sync` is missing.
+struct Shared<T> {
+    data: T,
+}
+
+impl<T> Shared<T> {
+    fn get(&self) -> &T {
+        &self.data
+    }
+}
+
+unsafe impl<T> Sync for Shared<T> {}
+
+// Unsound: the owned `T` is sent without `T: Send`.
+struct Owner<T> {
+    ptr: *mut T,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T> Send for Owner<T> {}
+
+// Unsound: `Rc` can never be sent to another thread.
+struct Counter {
+    rc: Rc<Cell<usize>>,
+}
+
+unsafe impl Send for Counter {}
+
+// Sound: the bounds match the usage.
+struct Wrapper<T> {
+    data: T,
+}
+
+impl<T> Wrapper<T> {
+    fn get(&self) -> &T {
+        &self.data
+    }
+}
+
+unsafe impl<T: Send> Send for Wrapper<T> {}
+unsafe impl<T: Sync> Sync for Wrapper<T> {}
+
+// Sound: no `Rc` is held, only named by the marker and the fn pointer.
+struct Callback {
+    f: fn(Rc<u8>) -> usize,
+    _marker: PhantomData<Rc<u8>>,
+}
+
+unsafe impl Send for Callback {}
+
+fn main() {
+    let shared = Shared { data: 1 };
+    let _ = shared.get();
+}
@@ -0,0 +1,18 @@
+//! The SSA form is built for every function, and the crate is analyzed unchanged.
+#![deny(missing_docs)]
+
+/// Clamp `x` into `[lo, hi]`.
+pub fn clamp(x: i32, lo: i32, hi: i32) -> i32 {
+    let mut y = x;
+    if y < lo {
+        y = lo;
+    }
+    if y > hi {
+        y = hi;
+    }
+    y
+}
+
+fn main() {
+    println!("{}", clamp(5, 0, 3));
+}
@@ -0,0 +1,108 @@
+use std::alloc::{alloc, Layout};
+use std::env;
+use std::io::Read;
+use std::net::TcpStream;
+
+fn read_len() -> usize {
+    env::var("LEN").unwrap().parse().unwrap()
+}
+
+// The offset is read from the environment and used without a check.
+fn direct(buf: &[u8]) -> u8 {
+    let n: usize = env::var("OFFSET").unwrap().parse().unwrap();
+    unsafe { *buf.as_ptr().add(n) }
+}
+
+// The length is returned by a function reading it from the environment.
+fn via_return(buf: &[u8]) -> &[u8] {
+    let n = read_len();
+    unsafe { std::slice::from_raw_parts(buf.as_ptr(), n) }
+}
+
+// The index is compared against the length first.
+fn checked(buf: &[u8]) -> u8 {
+    let n = read_len();
+    if n < buf.len() {
+        unsafe { *buf.get_unchecked(n) }
+    } else {
+        0
+    }
+}
+
+// The index is only bounded from below.
+fn lower_checked(buf: &[u8]) -> u8 {
+    let n = read_len();
+    if n > 0 {
+        unsafe { *buf.as_ptr().add(n) }
+    } else {
+        0
+    }
+}
+
+// The index is bounded after it is used.
+fn checked_late(buf: &[u8]) -> u8 {
+    let n = read_len();
+    let byte = unsafe { *buf.get_unchecked(n) };
+    if n < buf.len() {
+        byte
+    } else {
+        0
+    }
+}
+
+// The index is clamped by `min`.
+fn clamped(buf: &[u8]) -> u8 {
+    let n = read_len().min(buf.len() - 1);
+    unsafe { *buf.get_unchecked(n) }
+}
+
+fn alloc_buffer(size: usize) -> *mut u8 {
+    unsafe { alloc(Layout::from_size_align_unchecked(size, 8)) }
+}
+
+// The size read from the network into the header reaches the allocation in `alloc_buffer`.
+fn via_param() -> *mut u8 {
+    let mut stream = TcpStream::connect("127.0.0.1:8080").unwrap();
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).unwrap();
+    let size = u32::from_le_bytes(header) as usize;
+    alloc_buffer(size * 1024)
+}
+
+// The number of bytes read is trusted, unlike the bytes.
+fn read_count(buf: &[u8]) -> u8 {
+    let mut stream = TcpStream::connect("127.0.0.1:8080").unwrap();
+    let mut header = [0u8; 4];
+    let n = stream.read(&mut header).unwrap();
+    unsafe { *buf.get_unchecked(n) }
+}
+
+fn constant(_n: usize) -> usize {
+    16
+}
+
+// The untrusted value does not flow through `constant`.
+fn not_returned(buf: &[u8]) -> u8 {
+    let n = constant(read_len());
+    unsafe { *buf.as_ptr().add(n) }
+}
+
+// The length is declared as untrusted in the configuration.
+fn handle_request(buf: &mut Vec<u8>, len: usize) {
+    unsafe { buf.set_len(len) }
+}
+
+fn main() {
+    let buf = vec![0u8; 64];
+    println!("{}", direct(&buf));
+    println!("{}", via_return(&buf).len());
+    println!("{}", checked(&buf));
+    println!("{}", lower_checked(&buf));
+    println!("{}", checked_late(&buf));
+    println!("{}", read_count(&buf));
+    println!("{}", clamped(&buf));
+    println!("{:?}", via_param());
+    println!("{}", not_returned(&buf));
+    let mut request = Vec::with_capacity(64);
+    handle_request(&mut request, 16);
+}
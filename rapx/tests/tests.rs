@@ -3,17 +3,66 @@ use std::process::Command;
 
 #[inline(always)]
 fn running_tests_with_arg(dir: &str, arg: &str) -> String {
+    running_tests_with_arg_and_env(dir, arg, &[], false)
+}
+
+// Run with the environment variables `envs`. The reports of -opt and -lock are printed to stdout,
+// which is returned instead of stderr if `stdout` is set.
+#[inline(always)]
+fn running_tests_with_arg_and_env(
+    dir: &str,
+    arg: &str,
+    envs: &[(&str, &str)],
+    stdout: bool,
+) -> String {
     let raw_path = "./tests/".to_owned() + dir;
     let project_path = Path::new(&raw_path);
 
     let output = Command::new("cargo")
         .arg("rapx")
         .arg(arg)
+        .envs(envs.iter().copied())
         .current_dir(project_path)
         .output()
         .expect("Failed to execute cargo rapx");
 
-    String::from_utf8_lossy(&output.stderr).into_owned()
+    match stdout {
+        true => String::from_utf8_lossy(&output.stdout).into_owned(),
+        false => String::from_utf8_lossy(&output.stderr).into_owned(),
+    }
+}
+
+// Assert that each of the lines appears in the output.
+fn assert_contains_all(output: &str, lines: &[&str]) {
+    for line in lines {
+        assert!(
+            output.contains(line),
+            "Missing {}\nFull output:\n{}",
+            line,
+            output
+        );
+    }
+}
+
+// Assert that the warning `<warning> in function "<name>"` is printed for the reported functions
+// and not for the others.
+fn assert_reported_fns(output: &str, warning: &str, reported: &[&str], unreported: &[&str]) {
+    for name in reported {
+        assert!(
+            output.contains(&format!("{} in function \"{}\"", warning, name)),
+            "Missing report for {}\nFull output:\n{}",
+            name,
+            output
+        );
+    }
+    for name in unreported {
+        assert!(
+            !output.contains(&format!("{} in function \"{}\"", warning, name)),
+            "Unexpected report for {}\nFull output:\n{}",
+            name,
+            output
+        );
+    }
 }
 
 #[test]
@@ -106,6 +155,82 @@ fn test_uaf_swithint_diffbranch() {
     );
 }
 
+#[test]
+fn test_dp_async_cancel() {
+    let output = running_tests_with_arg("uaf/dp_async_cancel", "-F");
+    assert_eq!(
+        output.contains(
+            "Dangling pointer detected in function \"register\" if the future is cancelled."
+        ),
+        true
+    );
+    assert_eq!(
+        output.contains("Dangling pointer detected in function \"borrow_local\""),
+        false
+    );
+}
+
+#[test]
+fn test_uaf_infeasible_path() {
+    let output = running_tests_with_arg("uaf/uaf_infeasible_path", "-F");
+    assert_eq!(
+        output.contains("Use after free detected in function \"guarded\""),
+        true
+    );
+    let output = running_tests_with_arg_and_env(
+        "uaf/uaf_infeasible_path",
+        "-F",
+        &[("PRUNE_PATHS", "true")],
+        false,
+    );
+    assert_eq!(
+        output.contains("Use after free detected in function \"guarded\""),
+        false
+    );
+}
+
+#[test]
+fn test_uaf_multi_level() {
+    let output = running_tests_with_arg("uaf/uaf_multi_level", "-F");
+    assert_eq!(
+        output.contains("Use after free detected in function \"inner_after_free\""),
+        true
+    );
+    assert_eq!(
+        output.contains("Use after free detected in function \"move_inner\""),
+        false
+    );
+    assert_eq!(
+        output.contains("Use after free detected in function \"write_through\""),
+        false
+    );
+    assert_eq!(
+        output.contains("Use after free detected in function \"vec_elem\""),
+        false
+    );
+}
+
+#[test]
+fn test_panic_safety() {
+    let output = running_tests_with_arg("uaf/panic_safety", "-F");
+    assert_eq!(
+        output.contains("Panic safety bug detected in function \"map_in_place\""),
+        true
+    );
+    assert_eq!(
+        output.contains("Panic safety bug detected in function \"clone_all\""),
+        true
+    );
+    assert_eq!(
+        output.contains("Panic safety bug detected in function \"fill_pair\""),
+        true
+    );
+    assert_eq!(
+        output.contains("Panic safety bug detected in function \"map_in_place_safe\""),
+        false
+    );
+}
+
 #[test]
 fn test_alias_not_alias_iter() {
     let output = running_tests_with_arg("alias/not_alias_iter", "-alias");
@@ -152,6 +277,117 @@ fn test_alias_indirect() {
     assert_eq!(output.contains("iter_prop\": (0,1.0)"), true);
 }
 
+#[test]
+fn test_alias_resolve() {
+    let output = running_tests_with_arg("alias/alias_resolve", "-alias");
+    assert_eq!(
+        output.contains("via_trait\": (0,1)")
+            && output.contains("via_trait_second\": (0,2)")
+            && output.contains("via_generic\": (0,2)")
+            && output.contains("via_fn_ptr\": (0,1)"),
+        true
+    );
+    // the facts of a function are printed in no particular order.
+    let via_closure = output
+        .lines()
+        .find(|line| line.contains("via_closure\":"))
+        .unwrap_or_default();
+    assert_eq!(via_closure.contains("(0,1)"), true);
+}
+
+#[test]
+fn test_alias_multi_level() {
+    let output = running_tests_with_arg("alias/alias_multi_level", "-alias");
+    assert_eq!(
+        output.contains("load\": (0,1.*)")
+            && output.contains("deref_twice\": (0,1.*)")
+            && output.contains("inner\": (0.*,1.*.0.0.*)")
+            && output.contains("same\": (0.*,1.*)"),
+        true
+    );
+}
+
+#[test]
+fn test_alias_mutual_recursion() {
+    let output = running_tests_with_arg("alias/alias_mutual_recursion", "-alias");
+    assert_eq!(
+        output
+            .lines()
+            .any(|line| line.contains("rotate\": ") && line.contains("(0,3)")),
+        true
+    );
+}
+
+#[test]
+fn test_alias_steensgaard() {
+    let output = running_tests_with_arg("alias/alias_steensgaard", "-alias=steensgaard");
+    let has_line = |name: &str, facts: &[&str]| {
+        output
+            .lines()
+            .any(|line| line.contains(name) && facts.iter().all(|fact| line.contains(fact)))
+    };
+    assert_eq!(
+        has_line("machine\": ", &["(0,1)", "(0,2)"])
+            && has_line("field\": ", &["(0,1.0)", "(0,1.1)"])
+            && has_line("via_fn_ptr\": ", &["(0,1)"]),
+        true
+    );
+}
+
+#[test]
+fn test_alias_program_point() {
+    let output = running_tests_with_arg("alias/alias_program_point", "-test");
+    assert_eq!(
+        output.contains("choose\") at bb1: _1\n")
+            && output.contains("choose\") at bb2: _2\n")
+            && output.contains("choose\") at bb3: _1, _2\n"),
+        true
+    );
+}
+
+#[test]
+fn test_dataflow_interproc() {
+    let output = running_tests_with_arg("dataflow/dataflow_interproc", "-test");
+    let flows = |name: &str| {
+        output
+            .lines()
+            .find(|line| {
+                line.contains(&format!(
+                    "Inter-procedural dataflow to _0 in Some(\"::{}\")",
+                    name
+                ))
+            })
+            .and_then(|line| line.split(": ").last())
+            .map(str::to_owned)
+    };
+    assert_eq!(flows("through_identity").as_deref(), Some("_1"));
+    assert_eq!(flows("through_constant"), None);
+    assert_eq!(flows("len_of").as_deref(), Some("_1"));
+    assert_eq!(flows("pick").as_deref(), Some("_1"));
+    assert_eq!(flows("echo_size").as_deref(), Some("_2"));
+    assert_eq!(flows("fixed_size"), None);
+    assert_eq!(flows("apply").as_deref(), Some("_2"));
+
+    // the arguments equal to the return value, through the helpers returning them unchanged.
+    let equivalents = |name: &str| {
+        output
+            .lines()
+            .find(|line| {
+                line.contains(&format!(
+                    "Inter-procedural equivalents of _0 in Some(\"::{}\")",
+                    name
+                ))
+            })
+            .and_then(|line| line.split(": ").last())
+            .map(str::to_owned)
+    };
+    assert_eq!(equivalents("through_identity").as_deref(), Some("_1"));
+    assert_eq!(equivalents("through_constant"), None);
+    assert_eq!(equivalents("pick").as_deref(), Some("_1"));
+    assert_eq!(equivalents("echo_size").as_deref(), Some("_2"));
+    assert_eq!(equivalents("len_of"), None);
+}
+
 #[test]
 fn test_leak_ctor() {
     let output = running_tests_with_arg("leak/leak_ctor", "-M");
@@ -234,6 +470,23 @@ fn test_heap_proxy() {
     );
 }
 
+#[test]
+fn test_heap_instance() {
+    let output = running_tests_with_arg("ownedheap/heap_instance", "-ownedheap");
+    assert_contains_all(
+        &output,
+        &[
+            "Instance: std::option::Option<std::vec::Vec<u8, std::alloc::Global>>: True, [Some.0.buf.inner.ptr: ptr::unique::Unique]",
+            "Instance: std::option::Option<u32>: False, []",
+            "Instance: Wrapper<std::boxed::Box<u8, std::alloc::Global>>: True, [inner.0: ptr::unique::Unique]",
+            "Instance: Wrapper<u32>: False, []",
+            "Instance: Pair<u8, std::string::String>: True, [right.vec.buf.inner.ptr: ptr::unique::Unique]",
+            "Instance: Holder: True, [items.Some.0.buf.inner.ptr: ptr::unique::Unique]",
+            "Instance: std::mem::ManuallyDrop<std::vec::Vec<u8, std::alloc::Global>>: False, []",
+        ],
+    );
+}
+
 #[test]
 fn test_test_cons_merge() {
     let output = running_tests_with_arg("safety_check/test_cons_merge", "-verify");
@@ -257,6 +510,54 @@ fn test_ssa_transform() {
     let output = running_tests_with_arg("ssa/ssa_transform", "-ssa");
     assert_eq!(output.contains("ssa lvalue check true"), true);
 }
+
+#[test]
+fn test_ssa_functions() {
+    let output =
+        running_tests_with_arg_and_env("ssa/ssa_functions", "-ssa", &[("RAP_LOG", "debug")], false);
+    let nodes: Vec<&str> = output
+        .lines()
+        .skip_while(|line| !line.contains("SSA form of \"clamp\""))
+        .skip(1)
+        .take_while(|line| line.contains(" = phi(") || line.contains(" = sigma("))
+        .collect();
+    // `y` (_4) is merged after each `if`, and bounded by `lo` (_2) and `hi` (_3) in the branches.
+    let has = |kind: &str, part: &str| {
+        nodes
+            .iter()
+            .any(|line| line.contains(kind) && line.contains(part) && line.ends_with("for _4"))
+    };
+    assert_eq!(
+        nodes
+            .iter()
+            .filter(|line| line.contains(" = phi(") && line.ends_with("for _4"))
+            .count(),
+        2,
+        "Full output:\n{}",
+        output
+    );
+    assert!(
+        has(" = sigma(", "Some(Lt) copy _2"),
+        "Full output:\n{}",
+        output
+    );
+    assert!(
+        has(" = sigma(", "Some(Ge) copy _2"),
+        "Full output:\n{}",
+        output
+    );
+    assert!(
+        has(" = sigma(", "Some(Gt) copy _3"),
+        "Full output:\n{}",
+        output
+    );
+    assert!(
+        has(" = sigma(", "Some(Le) copy _3"),
+        "Full output:\n{}",
+        output
+    );
+    assert_eq!(output.contains("clamp: ssa lvalue check true"), true);
+}
 #[test]
 fn test_range_analysis() {
     let output = running_tests_with_arg("range/range_1", "-range");
@@ -281,6 +582,50 @@ fn test_range_analysis() {
     }
 }
 #[test]
+fn test_range_summary() {
+    let output = running_tests_with_arg("range/range_summary", "-range");
+
+    let expected_ranges = vec![
+        "_6 => Regular [6, 24]",
+        "_8 => Regular [18, 54]",
+        "_14 => Regular [4, 4]",
+        "Function: \"descend\" => return SSA(ESSA(arg_1, arg_1 < 5_usize), descend((ESSA(arg_1, arg_1 >= 5_usize) - 1_usize))) in Regular [0, 4]",
+        "Function: \"slot\" => return (offset(arg_1) * 2_usize)",
+    ];
+
+    for expected in expected_ranges {
+        assert!(
+            output.contains(expected),
+            "Missing expected range: '{}'\nFull output:\n{}",
+            expected,
+            output
+        );
+    }
+}
+
+#[test]
+fn test_range_feasibility() {
+    let output = running_tests_with_arg("range/range_feasibility", "-pathcond");
+
+    let expected = vec![
+        "Path [0, 1, 2, 5, 6]: infeasible",
+        "Path [0, 1, 3, 5, 6]: feasible",
+        "Path [0, 1, 2, 3, 4]: feasible",
+        "Path [0, 1, 2, 4, 7, 8]: infeasible",
+        "Path [0, 3, 4, 5, 6, 8]: infeasible",
+        "Path [0, 3, 4, 7, 8]: feasible",
+    ];
+
+    for verdict in expected {
+        assert!(
+            output.contains(verdict),
+            "Missing expected verdict: '{}'\nFull output:\n{}",
+            verdict,
+            output
+        );
+    }
+}
+#[test]
 
 fn test_interprocedual_range_analysis() {
     let output = running_tests_with_arg("range/range_2", "-range");
@@ -301,3 +646,405 @@ fn test_interprocedual_range_analysis() {
         );
     }
 }
+
+#[test]
+fn test_sendsync_bounds() {
+    let output = running_tests_with_arg("sendsync/sendsync_bounds", "-sendsync");
+    assert_eq!(
+        output.contains("Unsound Sync implementation detected for type Shared<T>")
+            && output.contains("Unsound Send implementation detected for type Owner<T>")
+            && output.contains("Unsound Send implementation detected for type Counter")
+            && !output.contains("type Wrapper<T>")
+            && !output.contains("type Callback"),
+        true
+    );
+
+    let output = running_tests_with_arg_and_env("sendsync/sendsync_bounds", "-sendsync", &[], true);
+    assert_contains_all(
+        &output,
+        &[
+            "Missing bound `T: Sync`",
+            "Add the bound `T: Send` to the impl.",
+            "Field `rc` has a non-thread-safe type",
+        ],
+    );
+}
+
+#[test]
+fn test_uninit_exposure() {
+    let output = running_tests_with_arg("uninit/uninit_exposure", "-uninit");
+    assert_reported_fns(
+        &output,
+        "Possibly uninitialized memory accessed",
+        &[
+            "exposed_buffer",
+            "partial_pair",
+            "split_init_missing",
+            "read_before_init",
+            "alloc_ref",
+            "alloc_slice",
+            "legacy_uninit",
+        ],
+        &[
+            "zeroed_buffer",
+            "full_pair",
+            "split_init",
+            "own_uninitialized",
+        ],
+    );
+}
+
+#[test]
+fn test_transmute_validity() {
+    let output = running_tests_with_arg("transmute/transmute_validity", "-transmute");
+    assert_reported_fns(
+        &output,
+        "Invalid transmute or pointer cast detected",
+        &[
+            "invalid_enum",
+            "byte_to_bool",
+            "int_to_char",
+            "null_ref",
+            "int_to_nonzero",
+            "wider_copy",
+            "misaligned_read",
+            "misaligned_cast",
+            "bytes_to_bools",
+            "bytes_to_ref",
+        ],
+        &[
+            "bool_to_byte",
+            "valid_enum",
+            "float_bits",
+            "narrower_copy",
+            "aligned_read",
+            "words_to_bytes",
+            "own_helpers",
+        ],
+    );
+}
+
+#[test]
+fn test_ffi_ownership() {
+    let config = Path::new("./tests/ffi/ffi_ownership/ffi_spec.toml")
+        .canonicalize()
+        .unwrap();
+    let output = running_tests_with_arg_and_env(
+        "ffi/ffi_ownership",
+        "-ffi",
+        &[("RAP_FFI_CONFIG", config.to_str().unwrap())],
+        false,
+    );
+    assert_reported_fns(
+        &output,
+        "FFI ownership bug detected",
+        &[
+            "leak_to_c",
+            "mismatched_reclaim",
+            "double_reclaim",
+            "rust_freed_by_c",
+            "c_freed_by_rust",
+            "vec_leak",
+            "reclaim_after_consume",
+        ],
+        &[
+            "roundtrip_cstring",
+            "roundtrip_vec",
+            "consumed_by_c",
+            "c_freed_by_c",
+            "returned_to_caller",
+            "own_into_raw",
+        ],
+    );
+}
+
+#[test]
+fn test_unsupported_mir_skipped() {
+    // the dataflow graph does not support async closures; the tail call is supported.
+    let output = running_tests_with_arg("others/support/unsupported_mir", "-dataflow");
+    assert_contains_all(
+        &output,
+        &[
+            "DataFlow Analysis: \"make_adder\" skipped: not yet implemented",
+            "DataFlow Analysis: 2 of 3 functions analyzed, 1 skipped",
+        ],
+    );
+    assert!(!output.contains("\"countdown\" skipped"));
+}
+
+#[test]
+fn test_opt_rules() {
+    let rules = Path::new("./tests/opt/opt_rules/rules.toml")
+        .canonicalize()
+        .unwrap();
+    let output = running_tests_with_arg_and_env(
+        "opt/opt_rules",
+        "-O",
+        &[("RAP_OPT_RULES", rules.to_str().unwrap())],
+        true,
+    );
+    assert_eq!(
+        output.contains("Connections of a cold pool are made in a loop"),
+        true
+    );
+    // the loops in `cold_connect` and `late_warm_connect` match; `warm_connect` calls `warm_up`
+    // before the loop.
+    assert_eq!(output.contains("  Pool: 2"), true);
+}
+
+#[test]
+fn test_map_double_lookup() {
+    let output = running_tests_with_arg_and_env("opt/map_double_lookup", "-O", &[], true);
+    assert_contains_all(
+        &output,
+        &[
+            "Use the entry API, e.g., `map.entry(k).or_insert(v)`.",
+            "Use the entry API, e.g., `map.entry(k).or_insert_with(f)`.",
+            "Use the old value returned by `insert` instead of removing it first.",
+            "Use the bool returned by `insert`, which tells whether the value is new.",
+        ],
+    );
+    // `count_with_entry` and `update_other_key` are not reported.
+    assert_eq!(output.contains("  Suboptimal: 4"), true);
+}
+
+#[test]
+fn test_loop_invariant_alloc() {
+    let output = running_tests_with_arg_and_env("opt/loop_invariant_alloc", "-O", &[], true);
+    assert_eq!(output.contains("let name = \"header\".to_string();"), true);
+    assert_eq!(
+        output.contains("let key = format!(\"{}-key\", prefix);"),
+        true
+    );
+    assert_eq!(output.contains("let mut buf = Vec::new();"), true);
+    // the key changes in each iteration, and the names are kept after the loop.
+    assert_eq!(output.contains("let key = format!(\"{}-key\", i);"), false);
+    assert_eq!(
+        output.contains("Allocate it once before the loop and reuse it with `clear()`."),
+        true
+    );
+    assert_eq!(
+        output.matches("Loop-invariant allocation detected").count(),
+        4
+    );
+}
+
+#[test]
+fn test_needless_collect() {
+    let output = running_tests_with_arg_and_env("opt/needless_collect", "-O", &[], true);
+    assert_contains_all(
+        &output,
+        &[
+            "Use `count()` instead of collecting the iterator.",
+            "Iterate the iterator directly instead of collecting it.",
+            "Extend the string with the iterator, e.g., `s.extend(iter)`.",
+            "Pass the iterator directly instead of collecting it.",
+        ],
+    );
+    // the set removes duplicates, and the other collections are used more than once.
+    assert_eq!(
+        output
+            .matches("Needless intermediate collection detected")
+            .count(),
+        4
+    );
+}
+
+// Run -O -fix on a copy of the opt_fix crate, whose main.rs is transformed by `convert`, and
+// return the fixed main.rs and the suggestions.
+fn run_opt_fix(name: &str, convert: impl Fn(&str) -> String) -> (String, String) {
+    // the fixes are applied to a copy of the crate to keep the test crate unchanged.
+    let project_path = std::env::temp_dir().join(name);
+    let _ = std::fs::remove_dir_all(&project_path);
+    std::fs::create_dir_all(project_path.join("src")).unwrap();
+    std::fs::copy(
+        "./tests/opt/opt_fix/Cargo.toml",
+        project_path.join("Cargo.toml"),
+    )
+    .unwrap();
+    let source = std::fs::read_to_string("./tests/opt/opt_fix/src/main.rs").unwrap();
+    std::fs::write(project_path.join("src/main.rs"), convert(&source)).unwrap();
+    let suggestions = project_path.join("suggestions.json");
+
+    Command::new("cargo")
+        .arg("rapx")
+        .arg("-O")
+        .arg("-fix")
+        .env("RAP_OPT_SUGGESTIONS", &suggestions)
+        .current_dir(&project_path)
+        .output()
+        .expect("Failed to execute cargo rapx");
+
+    (
+        std::fs::read_to_string(project_path.join("src/main.rs")).unwrap(),
+        std::fs::read_to_string(suggestions).unwrap(),
+    )
+}
+
+#[test]
+fn test_opt_fix() {
+    let (source, suggestions) = run_opt_fix("rapx_opt_fix", |source| source.to_string());
+    assert_contains_all(
+        &source,
+        &[
+            "let mut v = Vec::with_capacity(n);",
+            "out.reserve(words.len());\n    for word in words.iter() {",
+            "a.eq_ignore_ascii_case(b)",
+            "!a.eq_ignore_ascii_case(\"yes\")",
+            "let mut m = HashMap::with_capacity(words.len());",
+            "words.iter().filter(|w| w.len() > 3).count()",
+            // lowercasing a string used elsewhere is only suggested.
+            "a.to_lowercase()\n}",
+        ],
+    );
+    assert_eq!(
+        suggestions
+            .matches("\"suggestion_applicability\":\"MachineApplicable\"")
+            .count(),
+        6
+    );
+    assert_eq!(
+        suggestions
+            .matches("\"suggestion_applicability\":\"MaybeIncorrect\"")
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_opt_fix_crlf() {
+    // a source with a BOM and CRLF line endings, which rustc normalizes before computing spans.
+    let (source, _) = run_opt_fix("rapx_opt_fix_crlf", |source| {
+        format!("\u{feff}{}", source.replace('\n', "\r\n"))
+    });
+    assert!(source.starts_with('\u{feff}'));
+    assert_contains_all(
+        &source,
+        &[
+            "let mut v = Vec::with_capacity(n);\r\n",
+            "out.reserve(words.len());\r\n    for word in words.iter() {\r\n",
+        ],
+    );
+    assert!(!source.replace("\r\n", "").contains('\n'));
+}
+
+#[test]
+fn test_lock_guard() {
+    let output = running_tests_with_arg("lock/lock_guard", "-lock");
+    assert_reported_fns(
+        &output,
+        "Lock guard held too long",
+        &[
+            "Store::flush",
+            "Store::fill",
+            "Store::for_each_line",
+            "Store::transfer",
+            "slow_push",
+            "update",
+        ],
+        &[
+            "Store::flush_copy",
+            "Store::flush_len",
+            "Store::record",
+            "Store::wait_log",
+            "Store::buffer_log",
+            "Store::check_log",
+        ],
+    );
+    assert!(output.contains(
+        "Potential deadlock: locks `Store.accounts` and `Store.audit` are acquired in inconsistent orders"
+    ));
+
+    let output = running_tests_with_arg_and_env("lock/lock_guard", "-lock", &[], true);
+    assert_contains_all(
+        &output,
+        &[
+            "Lock guard held across the blocking call `std::io::Write::write_all`",
+            "Lock guard held across an allocating loop",
+            "Lock guard held across a call to an unknown closure",
+            "Lock `Store.audit` acquired while holding another guard",
+            "Lock guard held across the blocking call `std::thread::sleep`",
+            "Lock guard held across an await point",
+        ],
+    );
+}
+
+#[test]
+fn test_taint_flow() {
+    let config = std::fs::canonicalize("./tests/taint/taint_flow/taint.toml").unwrap();
+    let config = config.to_str().unwrap();
+    let output = running_tests_with_arg_and_env(
+        "taint/taint_flow",
+        "-taint",
+        &[("RAP_TAINT_CONFIG", config)],
+        false,
+    );
+    assert_reported_fns(
+        &output,
+        "Untrusted value reaches unsafe code",
+        &[
+            "direct",
+            "via_return",
+            "via_param",
+            "handle_request",
+            "lower_checked",
+            "checked_late",
+        ],
+        &[
+            "checked",
+            "clamped",
+            "alloc_buffer",
+            "not_returned",
+            "read_count",
+        ],
+    );
+
+    let output = running_tests_with_arg_and_env(
+        "taint/taint_flow",
+        "-taint",
+        &[("RAP_TAINT_CONFIG", config)],
+        true,
+    );
+    assert_contains_all(
+        &output,
+        &[
+        "Untrusted value from `std::env::var` reaches `std::ptr::const_ptr::add`",
+        "Untrusted value from `std::env::var` reaches `std::slice::from_raw_parts`",
+        "Untrusted value from `std::io::Read::read_exact` reaches `std::alloc::Layout::from_size_align_unchecked`",
+        "Untrusted value written by `std::io::Read::read_exact` to argument 1.",
+        "Untrusted value from `taint_flow::handle_request` reaches `std::vec::Vec::set_len`",
+        "`read_len` returns an untrusted value from `std::env::var`.",
+        "Passed to `alloc_buffer`, where it reaches",
+        ],
+    );
+}
+
+#[test]
+fn test_explore() {
+    let dir = std::env::temp_dir().join("rapx_explore_test");
+    let _ = std::fs::remove_dir_all(&dir);
+    Command::new("cargo")
+        .args(["rapx", "-explore", "-O"])
+        .env("RAP_EXPLORE_DIR", &dir)
+        .current_dir("./tests/explore/explore_site")
+        .output()
+        .expect("Failed to execute cargo rapx");
+    let page = std::fs::read_to_string(dir.join("explore_site-bin.html")).unwrap();
+    for fragment in [
+        "sum_below",
+        "first_or_zero",
+        "<svg",
+        "id=\"f0-bb0\"",
+        "id=\"f0-L5\"",
+        "id=\"f0-n0\"",
+        "call to <a href=\"#f0\">sum_below</a>",
+    ] {
+        assert!(page.contains(fragment), "Missing {} in the page", fragment);
+    }
+    // The lib and bin targets have their own pages, with the findings of the opt checks.
+    let page = std::fs::read_to_string(dir.join("explore_site-rlib.html")).unwrap();
+    assert!(page.contains("<li>Improper data collection detected"));
+    let index = std::fs::read_to_string(dir.join("index.html")).unwrap();
+    assert!(index.contains("explore_site-bin.html"));
+    assert!(index.contains("explore_site-rlib.html"));
+}
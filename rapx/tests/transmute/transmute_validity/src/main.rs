@@ -0,0 +1,123 @@
+#![allow(unnecessary_transmutes, invalid_value)]
+
+use std::mem;
+use std::num::NonZeroU32;
+use std::slice;
+
+#[derive(Clone, Copy, Debug)]
+#[allow(dead_code)]
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+// 3 is not a valid discriminant of `Color`.
+fn invalid_enum() -> Color {
+    unsafe { mem::transmute::<u8, Color>(3) }
+}
+
+// Any byte other than 0 and 1 is an invalid `bool`.
+fn byte_to_bool(b: u8) -> bool {
+    unsafe { mem::transmute::<u8, bool>(b) }
+}
+
+// Surrogates and values above `char::MAX` are invalid.
+fn int_to_char(x: u32) -> char {
+    unsafe { mem::transmute::<u32, char>(x) }
+}
+
+// A reference must never be null.
+fn null_ref() -> &'static u32 {
+    unsafe { mem::transmute::<usize, &'static u32>(0) }
+}
+
+fn int_to_nonzero(x: u32) -> NonZeroU32 {
+    unsafe { mem::transmute::<u32, NonZeroU32>(x) }
+}
+
+// Reading a `u64` out of a `u32` reads past the source.
+fn wider_copy(x: u32) -> u64 {
+    unsafe { mem::transmute_copy::<u32, u64>(&x) }
+}
+
+// A `u32` read through a pointer into a byte buffer may be misaligned.
+fn misaligned_read(buf: &[u8; 8]) -> u32 {
+    let p = buf.as_ptr() as *const u32;
+    unsafe { *p }
+}
+
+fn misaligned_cast(buf: &[u8]) -> u16 {
+    let p = buf.as_ptr().cast::<u16>();
+    unsafe { *p }
+}
+
+fn bytes_to_bools(buf: &[u8]) -> &[bool] {
+    unsafe { slice::from_raw_parts(buf.as_ptr() as *const bool, buf.len()) }
+}
+
+fn bytes_to_ref(x: &[u8; 4]) -> &u32 {
+    unsafe { mem::transmute::<&[u8; 4], &u32>(x) }
+}
+
+// The following conversions are sound.
+fn bool_to_byte(b: bool) -> u8 {
+    unsafe { mem::transmute::<bool, u8>(b) }
+}
+
+fn valid_enum() -> Color {
+    unsafe { mem::transmute::<u8, Color>(2) }
+}
+
+fn float_bits(f: f32) -> u32 {
+    unsafe { mem::transmute::<f32, u32>(f) }
+}
+
+fn narrower_copy(x: u64) -> u32 {
+    unsafe { mem::transmute_copy::<u64, u32>(&x) }
+}
+
+fn aligned_read(buf: &[u32; 2]) -> u8 {
+    let p = buf.as_ptr() as *const u8;
+    unsafe { *p }
+}
+
+fn words_to_bytes(buf: &[u32]) -> &[u8] {
+    unsafe { slice::from_raw_parts(buf.as_ptr() as *const u8, buf.len() * 4) }
+}
+
+mod ptr {
+    pub fn read(p: *const u16) -> u16 {
+        p as usize as u16
+    }
+}
+
+fn transmute_copy<T: Copy>(x: &T) -> T {
+    *x
+}
+
+// The functions of the crate named like `ptr::read` and `transmute_copy` are not the std ones.
+fn own_helpers(buf: &[u8; 8]) -> u16 {
+    let p = buf.as_ptr() as *const u16;
+    ptr::read(p) + transmute_copy(&1u16)
+}
+
+fn main() {
+    println!("{:?}", invalid_enum());
+    println!("{}", byte_to_bool(1));
+    println!("{}", int_to_char(97));
+    println!("{:?}", null_ref() as *const u32);
+    println!("{}", int_to_nonzero(1));
+    println!("{}", wider_copy(1));
+    println!("{}", misaligned_read(&[0; 8]));
+    println!("{}", misaligned_cast(&[0; 8]));
+    println!("{:?}", bytes_to_bools(&[0, 1]));
+    println!("{}", bytes_to_ref(&[0; 4]));
+    println!("{}", bool_to_byte(true));
+    println!("{:?}", valid_enum());
+    println!("{}", float_bits(1.0));
+    println!("{}", narrower_copy(1));
+    println!("{}", aligned_read(&[0; 2]));
+    println!("{:?}", words_to_bytes(&[0]));
+    println!("{}", own_helpers(&[0; 8]));
+}
@@ -0,0 +1,51 @@
+/*
+ * This is a buggy case: register() leaves a dangling pointer in the registry if the future is
+ * dropped while it waits, because the buffer lives in the future and is dropped with it.
+ */
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+struct Registry {
+    slot: *const Vec<u8>,
+}
+
+async fn register(reg: &mut Registry) {
+    let buf = vec![0u8; 16];
+    reg.slot = &buf;
+    YieldNow(false).await;
+    // only reached if the future is polled to completion.
+    reg.slot = std::ptr::null();
+}
+
+// A reference into the future held across the await is dropped together with the future.
+async fn borrow_local() -> usize {
+    let buf = vec![0u8; 16];
+    let first = &buf[0];
+    YieldNow(false).await;
+    *first as usize + buf.len()
+}
+
+fn main() {
+    let mut reg = Registry {
+        slot: std::ptr::null(),
+    };
+    let fut = register(&mut reg);
+    drop(fut);
+    let _ = borrow_local();
+}
@@ -0,0 +1,61 @@
+use std::mem::MaybeUninit;
+use std::ptr;
+
+// The element is duplicated by `ptr::read` while `f` runs.
+// If `f` panics, the caller drops the original element again.
+pub fn map_in_place<T, F: FnMut(T) -> T>(v: &mut Vec<T>, mut f: F) {
+    for i in 0..v.len() {
+        unsafe {
+            let p = v.as_mut_ptr().add(i);
+            let x = ptr::read(p);
+            ptr::write(p, f(x));
+        }
+    }
+}
+
+// The length covers uninitialized slots while `clone` runs.
+// If `clone` panics, `v` is dropped during unwinding and reads uninitialized memory.
+pub fn clone_all<T: Clone>(src: &[T]) -> Vec<T> {
+    let n = src.len();
+    let mut v: Vec<T> = Vec::with_capacity(n);
+    unsafe {
+        v.set_len(n);
+        for i in 0..n {
+            ptr::write(v.as_mut_ptr().add(i), src[i].clone());
+        }
+    }
+    v
+}
+
+// The length is shrunk before calling user code, which only leaks on panic.
+pub fn map_in_place_safe<T, F: FnMut(&T)>(v: &mut Vec<T>, mut f: F) {
+    let len = v.len();
+    unsafe {
+        v.set_len(0);
+        for i in 0..len {
+            f(&*v.as_ptr().add(i));
+        }
+        v.set_len(len);
+    }
+}
+
+// The array is assumed initialized before its elements are produced by `f`.
+// If `f` panics, the uninitialized elements are dropped during unwinding.
+#[allow(invalid_value)]
+pub fn fill_pair<T, F: FnMut() -> T>(mut f: F) -> [T; 2] {
+    unsafe {
+        let mut pair: [T; 2] = MaybeUninit::uninit().assume_init();
+        ptr::write(&mut pair[0], f());
+        ptr::write(&mut pair[1], f());
+        pair
+    }
+}
+
+fn main() {
+    let mut v = vec![1, 2, 3];
+    map_in_place(&mut v, |x| x + 1);
+    let w = clone_all(&v);
+    map_in_place_safe(&mut v, |x| println!("{}", x));
+    let pair = fill_pair(|| 0);
+    println!("{:?} {:?}", w, pair);
+}
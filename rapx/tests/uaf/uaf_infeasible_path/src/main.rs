@@ -0,0 +1,19 @@
+use std::mem::ManuallyDrop;
+
+fn guarded(n: u32) {
+    let mut slot = ManuallyDrop::new(Box::new(1u8));
+    if n > 10 {
+        unsafe {
+            ManuallyDrop::drop(&mut slot);
+        }
+    }
+    // `slot` is only used when it has not been dropped.
+    if n < 5 {
+        println!("{:?}", slot);
+    }
+}
+
+fn main() {
+    guarded(1);
+    guarded(20);
+}
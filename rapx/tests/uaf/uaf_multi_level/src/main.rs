@@ -0,0 +1,47 @@
+/*
+ * Cases on multi-level pointers: the pointer, its pointee and the pointee's pointee are
+ * different objects.
+ */
+#![allow(dead_code)]
+#![allow(unused_variables)]
+
+// Buggy: the inner box is freed together with the outer box.
+fn inner_after_free() {
+    let outer = Box::new(Box::new(String::from("inner")));
+    let inner: *const String = &**outer;
+    drop(outer);
+    unsafe {
+        println!("{}", *inner);
+    }
+}
+
+// Sound: the inner box is moved out before the outer box is freed.
+fn move_inner() {
+    let outer = Box::new(Box::new(String::from("inner")));
+    let inner: Box<String> = *outer;
+    println!("{}", inner);
+}
+
+// Sound: the slot behind `pp` is updated, `pp` itself is not.
+fn write_through(slot: &mut &mut String, other: &mut String) {
+    let pp: &mut &mut String = slot;
+    **pp = other.clone();
+    println!("{}", pp);
+}
+
+// Sound: an element is popped out of a vector of boxes before the vector is dropped.
+fn vec_elem() {
+    let mut v: Vec<Box<String>> = vec![Box::new(String::from("elem"))];
+    let first = v.pop().unwrap();
+    drop(v);
+    println!("{}", first);
+}
+
+fn main() {
+    inner_after_free();
+    move_inner();
+    let mut a = String::from("a");
+    let mut b = String::from("b");
+    write_through(&mut &mut a, &mut b);
+    vec_elem();
+}
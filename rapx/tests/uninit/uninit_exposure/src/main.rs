@@ -0,0 +1,139 @@
+#![allow(deprecated, invalid_value)]
+use std::alloc::{alloc, dealloc, Layout};
+use std::mem::{self, MaybeUninit};
+use std::ptr::{self, addr_of_mut};
+use std::slice;
+
+pub struct Pair {
+    pub a: u32,
+    pub b: u64,
+}
+
+// The buffer is returned with uninitialized elements.
+pub fn exposed_buffer(n: usize) -> Vec<u8> {
+    let mut v = Vec::with_capacity(n);
+    unsafe {
+        v.set_len(n);
+    }
+    v
+}
+
+// The buffer is zeroed before its length is set.
+pub fn zeroed_buffer(n: usize) -> Vec<u8> {
+    let mut v: Vec<u8> = Vec::with_capacity(n);
+    unsafe {
+        ptr::write_bytes(v.as_mut_ptr(), 0, n);
+        v.set_len(n);
+    }
+    v
+}
+
+// Only one field is written before `assume_init`.
+pub fn partial_pair() -> Pair {
+    let mut pair = MaybeUninit::<Pair>::uninit();
+    let p = pair.as_mut_ptr();
+    unsafe {
+        addr_of_mut!((*p).a).write(1);
+        pair.assume_init()
+    }
+}
+
+// Both fields are written before `assume_init`; the padding does not matter.
+pub fn full_pair() -> Pair {
+    let mut pair = MaybeUninit::<Pair>::uninit();
+    let p = pair.as_mut_ptr();
+    unsafe {
+        addr_of_mut!((*p).a).write(1);
+        addr_of_mut!((*p).b).write(2);
+        pair.assume_init()
+    }
+}
+
+unsafe fn init_a(p: *mut Pair) {
+    (*p).a = 1;
+}
+
+unsafe fn init_b(p: *mut Pair) {
+    (*p).b = 2;
+}
+
+unsafe fn read_a(p: *const Pair) -> u32 {
+    (*p).a
+}
+
+// Each field is initialized by a different callee.
+pub fn split_init() -> Pair {
+    let mut pair = MaybeUninit::<Pair>::uninit();
+    unsafe {
+        init_a(pair.as_mut_ptr());
+        init_b(pair.as_mut_ptr());
+        pair.assume_init()
+    }
+}
+
+// The callee that initializes `b` is never called.
+pub fn split_init_missing() -> Pair {
+    let mut pair = MaybeUninit::<Pair>::uninit();
+    unsafe {
+        init_a(pair.as_mut_ptr());
+        pair.assume_init()
+    }
+}
+
+// The callee reads the field before anyone writes it.
+pub fn read_before_init() -> u32 {
+    let pair = MaybeUninit::<Pair>::uninit();
+    unsafe { read_a(pair.as_ptr()) }
+}
+
+// A reference is created to freshly allocated memory.
+pub fn alloc_ref() -> u64 {
+    unsafe {
+        let layout = Layout::new::<u64>();
+        let p = alloc(layout) as *mut u64;
+        let r = &*p;
+        let v = *r;
+        dealloc(p as *mut u8, layout);
+        v
+    }
+}
+
+// A slice is created over freshly allocated memory.
+pub fn alloc_slice() -> &'static [u8] {
+    unsafe {
+        let p = alloc(Layout::new::<[u8; 16]>());
+        slice::from_raw_parts(p, 16)
+    }
+}
+
+// The value is read before it is written.
+pub fn legacy_uninit() -> u32 {
+    let x: u32 = unsafe { mem::uninitialized() };
+    x + 1
+}
+
+mod memo {
+    pub fn uninitialized() -> u32 {
+        0
+    }
+}
+
+// The function of the crate is not `mem::uninitialized`.
+pub fn own_uninitialized() -> u32 {
+    let x = memo::uninitialized();
+    x + 1
+}
+
+fn main() {
+    let _ = exposed_buffer(4);
+    let _ = zeroed_buffer(4);
+    let _ = partial_pair();
+    let _ = full_pair();
+    let _ = split_init();
+    let _ = split_init_missing();
+    let _ = read_before_init();
+    let _ = alloc_ref();
+    let _ = alloc_slice();
+    let _ = legacy_uninit();
+    let _ = own_uninitialized();
+}